@@ -0,0 +1,92 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use std::collections::HashMap;
+
+/// Alerts sharing one rule ID, with a few triggering requests/flows
+#[derive(Debug, Clone)]
+pub struct RuleAlerts {
+    pub rule_id: String,
+    pub message: String,
+    pub count: usize,
+    pub samples: Vec<String>,
+}
+
+pub struct AlertReport {
+    rules: Vec<RuleAlerts>,
+    sources: HashMap<String, usize>,
+    sample_limit: usize,
+}
+
+impl AlertReport {
+    /// Group WAF/IDS entries by the "rule.<id>" fields their parsers extract
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut report = Self {
+            rules: Vec::new(),
+            sources: HashMap::new(),
+            sample_limit: 3,
+        };
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let mut alerted = false;
+            for (key, message) in &entry.attrs {
+                let rule_id = match key.strip_prefix("rule.") {
+                    Some(rule_id) => rule_id,
+                    None => continue,
+                };
+                alerted = true;
+
+                let idx = *index.entry(rule_id.to_string()).or_insert_with(|| {
+                    report.rules.push(RuleAlerts {
+                        rule_id: rule_id.to_string(),
+                        message: message.clone(),
+                        count: 0,
+                        samples: Vec::new(),
+                    });
+                    report.rules.len() - 1
+                });
+
+                let rule = &mut report.rules[idx];
+                rule.count += 1;
+                if let Some(sample) = entry.attrs.get("sample") {
+                    let sample = format!("{} ({})", sample, entry.host);
+                    if rule.samples.len() < report.sample_limit && !rule.samples.contains(&sample) {
+                        rule.samples.push(sample);
+                    }
+                }
+            }
+            if alerted {
+                *report.sources.entry(entry.host.clone()).or_insert(0) += 1;
+            }
+        }
+
+        report.rules.sort_by(|a, b| b.count.cmp(&a.count).then(a.rule_id.cmp(&b.rule_id)));
+        report
+    }
+
+    pub fn rules(&self) -> &[RuleAlerts] {
+        &self.rules
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.rules.is_empty() {
+            println!("No WAF/IDS alerts found");
+            return;
+        }
+
+        let alerts: usize = self.rules.iter().map(|r| r.count).sum();
+        println!("Alerts: {}\tRules: {}", alerts, self.rules.len());
+        println!();
+
+        println!("Top rules:");
+        for rule in self.rules.iter().take(limit) {
+            println!("{}:\t[{}] {}", rule.count, rule.rule_id, rule.message);
+            for sample in &rule.samples {
+                println!("\t{}", sample);
+            }
+        }
+        println!();
+
+        print_top("Top sources", self.sources.clone(), limit);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// All lines of one Asterisk call, correlated by the "[C-0000abcd]" call ID
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub call_id: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub lines: usize,
+    pub channels: Vec<String>,
+    pub extension: Option<String>,
+    pub outcome: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Call {
+    /// Seconds between the first and last line of the call
+    pub fn duration(&self) -> i64 {
+        (self.end - self.start).num_seconds()
+    }
+}
+
+pub struct CallReport {
+    calls: Vec<Call>,
+}
+
+impl CallReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // "Executing [100@from-internal:1] Dial("SIP/alice-00000001", ...)"
+        let exec_re = Regex::new(r"Executing \[([^@\]]+@[^:\]]+):\d+\]").unwrap();
+        let channel_re = Regex::new(r"\b((?:SIP|PJSIP|IAX2|DAHDI|Local|Motif)/[\w@.;+-]+)").unwrap();
+        let outcome_re = Regex::new(r"\b(answered|is busy|is ringing|is circuit-busy|Everyone is busy/congested|hungup|exited non-zero)\b").unwrap();
+
+        let mut calls: Vec<Call> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let call_id = match entry.attrs.get("call_id") {
+                Some(call_id) => call_id,
+                None => continue,
+            };
+            let ts = entry.naive_datetime();
+            let idx = *seen.entry(call_id.clone()).or_insert_with(|| {
+                calls.push(Call {
+                    call_id: call_id.clone(),
+                    start: ts,
+                    end: ts,
+                    lines: 0,
+                    channels: Vec::new(),
+                    extension: None,
+                    outcome: None,
+                    warnings: Vec::new(),
+                });
+                calls.len() - 1
+            });
+
+            let call = &mut calls[idx];
+            call.end = call.end.max(ts);
+            call.lines += 1;
+            if call.extension.is_none() {
+                call.extension = exec_re.captures(&entry.log_entry).map(|caps| caps[1].to_string());
+            }
+            for caps in channel_re.captures_iter(&entry.log_entry) {
+                let channel = caps[1].trim_end_matches(['"', ',', '\'']).to_string();
+                if !call.channels.contains(&channel) {
+                    call.channels.push(channel);
+                }
+            }
+            // The first answer or failure decides the outcome; hangups only fill in
+            if let Some(caps) = outcome_re.captures(&entry.log_entry) {
+                let outcome = &caps[1];
+                if call.outcome.is_none() || call.outcome.as_deref() == Some("is ringing") {
+                    call.outcome = Some(outcome.to_string());
+                }
+            }
+            if entry.severity.is_some_and(|s| s <= 4) {
+                call.warnings.push(entry.log_entry.clone());
+            }
+        }
+
+        calls.sort_by_key(|c| c.start);
+        Self { calls }
+    }
+
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// "SIP/alice-00000001" -> "SIP/alice"
+    fn peer(channel: &str) -> &str {
+        match channel.rsplit_once('-') {
+            Some((peer, suffix)) if suffix.chars().all(|c| c.is_ascii_hexdigit()) => peer,
+            _ => channel,
+        }
+    }
+
+    fn format_duration(seconds: i64) -> String {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.calls.is_empty() {
+            println!("No Asterisk calls found");
+            return;
+        }
+
+        let answered = self.calls.iter().filter(|c| c.outcome.as_deref() == Some("answered")).count();
+        let with_warnings = self.calls.iter().filter(|c| !c.warnings.is_empty()).count();
+        println!("Calls: {}\tAnswered: {}\tWith warnings or errors: {}", self.calls.len(), answered, with_warnings);
+        println!();
+
+        let mut extensions: HashMap<String, usize> = HashMap::new();
+        let mut peers: HashMap<String, usize> = HashMap::new();
+        let mut outcomes: HashMap<String, usize> = HashMap::new();
+        let mut warnings: HashMap<String, usize> = HashMap::new();
+        for call in &self.calls {
+            if let Some(extension) = &call.extension {
+                *extensions.entry(extension.clone()).or_insert(0) += 1;
+            }
+            let mut call_peers: Vec<&str> = call.channels.iter().map(|c| Self::peer(c)).collect();
+            call_peers.sort();
+            call_peers.dedup();
+            for peer in call_peers {
+                *peers.entry(peer.to_string()).or_insert(0) += 1;
+            }
+            *outcomes.entry(call.outcome.clone().unwrap_or_else(|| "-".to_string())).or_insert(0) += 1;
+            for warning in &call.warnings {
+                *warnings.entry(warning.clone()).or_insert(0) += 1;
+            }
+        }
+        print_top("Outcomes", outcomes, limit);
+        print_top("Dialed extensions", extensions, limit);
+        print_top("Calls per peer", peers, limit);
+        print_top("Warnings and errors in calls", warnings, limit);
+
+        println!("Calls:");
+        for call in &self.calls {
+            println!("{}\t{}\t{}\t{} lines\t{}\t{}\t{}",
+                call.start.format("%Y-%m-%dT%H:%M:%S"), Self::format_duration(call.duration()),
+                call.call_id, call.lines, call.extension.as_deref().unwrap_or("-"),
+                call.outcome.as_deref().unwrap_or("-"), call.channels.join(","));
+        }
+        println!();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use crate::report::write_top;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// A regex recognizing an authentication event; captures `user` and/or `ip`, and
+/// `nas` when a network access server relays the request (RADIUS)
+pub struct AuthPattern {
+    pub source: &'static str,
+    pub regex: Regex,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthEvent {
+    pub source: &'static str,
+    pub user: String,
+    pub ip: String,
+    pub nas: Option<String>,
+    pub success: bool,
+    pub entry: LogEntry,
+}
+
+pub struct AuthReport {
+    events: Vec<AuthEvent>,
+}
+
+impl AuthReport {
+    pub fn default_patterns() -> Vec<AuthPattern> {
+        let pattern = |source, re: &str, success| AuthPattern {
+            source,
+            regex: Regex::new(re).unwrap(),
+            success,
+        };
+
+        vec![
+            // OpenSSH
+            pattern("sshd", r"Failed \S+ for (?:invalid user )?(?P<user>\S+) from (?P<ip>\S+)", false),
+            pattern("sshd", r"Accepted \S+ for (?P<user>\S+) from (?P<ip>\S+)", true),
+            // Dovecot login processes
+            pattern("dovecot", r"\(auth failed[^)]*\): user=<(?P<user>[^>]*)>.*?rip=(?P<ip>[^,\s]+)", false),
+            pattern("dovecot", r"Login: user=<(?P<user>[^>]*)>.*?rip=(?P<ip>[^,\s]+)", true),
+            // Exim SMTP AUTH
+            pattern("exim", r"authenticator failed for .*?\[(?P<ip>[0-9a-fA-F.:]+)\].*\(set_id=(?P<user>[^)]+)\)", false),
+            pattern("exim", r"<= .*?\[(?P<ip>[0-9a-fA-F.:]+)\].* A=\w+:(?P<user>\S+)", true),
+            // OpenVPN username/password authentication
+            pattern("openvpn", r"(?P<ip>[0-9a-fA-F.:]+):\d+ TLS Auth Error: Auth Username/Password verification failed", false),
+            pattern("openvpn", r"(?P<ip>[0-9a-fA-F.:]+):\d+ TLS: Username/Password authentication succeeded for username '(?P<user>[^']*)'", true),
+            // FreeRADIUS: "Login incorrect (reason): [user/<via ...>] (from client ap01 port 0 cli aa-bb-cc-dd-ee-ff)"
+            pattern("radius", r"(?:Login incorrect|Invalid user)[^\[]*: \[(?P<user>[^/\]]*)[^\]]*\] \(from client (?P<nas>\S+) port \S+(?: cli (?P<ip>[^)\s]+))?", false),
+            pattern("radius", r"Login OK: \[(?P<user>[^/\]]*)[^\]]*\] \(from client (?P<nas>\S+) port \S+(?: cli (?P<ip>[^)\s]+))?", true),
+            // Proxmox VE web UI/API logins (pvedaemon)
+            pattern("pve", r"authentication failure; rhost=(?:::ffff:)?(?P<ip>\S+) user=(?P<user>\S+)", false),
+            pattern("pve", r"<(?P<user>[^>]+)> successful auth for user", true),
+            // Keycloak login events; values are quoted since Keycloak 23
+            pattern("keycloak", r#"type="?LOGIN_ERROR"?,.*?ipAddress="?(?P<ip>[^",\s]+)(?:.*?username="?(?P<user>[^",\s]+))?"#, false),
+            pattern("keycloak", r#"type="?LOGIN"?,.*?ipAddress="?(?P<ip>[^",\s]+)(?:.*?username="?(?P<user>[^",\s]+))?"#, true),
+        ]
+    }
+
+    pub fn from_log(log: &CrunchLog) -> Self {
+        Self::from_log_with_patterns(log, &Self::default_patterns())
+    }
+
+    pub fn from_log_with_patterns(log: &CrunchLog, patterns: &[AuthPattern]) -> Self {
+        let mut events = Vec::new();
+
+        for entry in &log.entries {
+            for pattern in patterns {
+                if let Some(caps) = pattern.regex.captures(&entry.log_entry) {
+                    events.push(AuthEvent {
+                        source: pattern.source,
+                        user: caps.name("user").map(|m| m.as_str()).filter(|u| !u.is_empty()).unwrap_or("-").to_string(),
+                        ip: caps.name("ip").map(|m| m.as_str()).unwrap_or("-").to_string(),
+                        nas: caps.name("nas").map(|m| m.as_str().to_string()),
+                        success: pattern.success,
+                        entry: entry.clone(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[AuthEvent] {
+        &self.events
+    }
+
+    pub fn display(&self, limit: usize) {
+        let _ = self.write_to(&mut std::io::stdout().lock(), limit);
+    }
+
+    /// The report into any writer, e.g. a digest being assembled
+    pub fn write_to<W: Write>(&self, out: &mut W, limit: usize) -> std::io::Result<()> {
+        if self.events.is_empty() {
+            writeln!(out, "No authentication events found")?;
+            return Ok(());
+        }
+
+        let failures = self.events.iter().filter(|e| !e.success).count();
+        writeln!(out, "Authentication events: {}\tSucceeded: {}\tFailed: {}",
+            self.events.len(), self.events.len() - failures, failures)?;
+        writeln!(out)?;
+
+        let mut failed_users: HashMap<String, usize> = HashMap::new();
+        let mut failed_ips: HashMap<String, usize> = HashMap::new();
+        let mut logins: HashMap<String, usize> = HashMap::new();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        let mut nas_results: HashMap<String, usize> = HashMap::new();
+
+        for event in &self.events {
+            let result = if event.success { "ok" } else { "failed" };
+            *sources.entry(format!("{} {}", event.source, result)).or_insert(0) += 1;
+            if let Some(nas) = &event.nas {
+                *nas_results.entry(format!("{} {}", nas, result)).or_insert(0) += 1;
+            }
+            if event.success {
+                *logins.entry(format!("{} from {}", event.user, event.ip)).or_insert(0) += 1;
+            } else {
+                *failed_users.entry(event.user.clone()).or_insert(0) += 1;
+                *failed_ips.entry(event.ip.clone()).or_insert(0) += 1;
+            }
+        }
+
+        write_top(out, "By source", sources, limit)?;
+        write_top(out, "By NAS", nas_results, limit)?;
+        write_top(out, "Failed users", failed_users, limit)?;
+        write_top(out, "Failed source IPs", failed_ips, limit)?;
+        write_top(out, "Successful logins", logins, limit)?;
+
+        // A success from an address that previously failed is worth a closer look
+        let mut failed_before: HashSet<&str> = HashSet::new();
+        let mut suspicious: Vec<&AuthEvent> = Vec::new();
+        for event in &self.events {
+            if !event.success {
+                failed_before.insert(event.ip.as_str());
+            } else if event.ip != "-" && failed_before.contains(event.ip.as_str()) {
+                suspicious.push(event);
+            }
+        }
+
+        if !suspicious.is_empty() {
+            writeln!(out, "Successful logins after failures from the same IP:")?;
+            for event in suspicious.iter().take(limit) {
+                writeln!(out, "{}", event.entry.to_normalized())?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
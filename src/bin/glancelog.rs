@@ -1,7 +1,14 @@
-use clap::Parser;
-use glancelog::{CrunchLog, Filter, GraphHash, GraphType, HashMode, SuperHash};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use glancelog::{AlertReport, AuthReport, BanReport, CallReport, ClockSkew, ContextMatches, CorrelationReport, CrunchLog, CsvMap, CsvParser, DhcpReport, Digest, DigestConfig, DiskReport, DnsReport, DuplicateReport, EntropyReport, EntropyTarget, FailPolicy, Filter, FilterOptions, FilterPack, FirewallReport, FlowReport, FollowEvent, FollowState, Follower, GcReport, GraphHash, GraphType, GraphValues, HLine, HashMode, HostTimeline, HourlyProfile, KeyTemplate, JobReport, KernelReport, LogEntry, LogFormat, LogFormatParser, LogSplitter, MailReport, Matrix, MetricsEmitter, MetricsTarget, PatternDb, PidTrace, PveTaskReport, RecordAssembler, Rollup, RuleSet, SambaReport, SeverityTrend, SourceReport, SourceSummary, SplitKey, SuperHash, SuricataReport, SystemdReport, TagReport, TransferReport, UalReport, UserExtractor, VpnReport};
 use glancelog::hash::SampleMode;
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use glancelog::log_entry::{LogParser, DETECT_LINES};
+use glancelog::packs::pack_dir;
+use glancelog::rules::TAG_ATTR;
+use glancelog::sources::INPUT_ATTR;
+use regex::Regex;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use std::time::{Duration, Instant};
+use log::{info, warn, Level, LevelFilter, Log, Metadata, Record};
 
 #[derive(Parser)]
 #[command(name = "glancelog")]
@@ -9,13 +16,20 @@ use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(about = "Log analysis tool for systems administrators", long_about = None)]
 struct Cli {
-    /// Input file (or use stdin if not provided)
-    file: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Verbose output
-    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
+    /// Input files (or use stdin if not provided)
+    files: Vec<String>,
+
+    /// Verbose output (-v progress, -vv filter file lookups, -vvv everything)
+    #[arg(short = 'v', long, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
 
+    /// Suppress warnings, only print errors
+    #[arg(short = 'q', long, conflicts_with = "verbose", global = true)]
+    quiet: bool,
+
     /// Show sample output for small numbered entries
     #[arg(long)]
     sample: bool,
@@ -28,6 +42,14 @@ struct Cli {
     #[arg(long)]
     allsample: bool,
 
+    /// Record the hash patterns of this run in a pattern database (JSON file, created if missing); hash mode only
+    #[arg(long, value_name = "DB")]
+    learn: Option<String>,
+
+    /// Show only hash patterns not yet in the pattern database
+    #[arg(long, value_name = "DB")]
+    only_new: Option<String>,
+
     /// Use filter files during processing
     #[arg(long)]
     filter: bool,
@@ -37,9 +59,17 @@ struct Cli {
     nofilter: bool,
 
     /// Custom directory for filter files (overrides default paths and GLANCELOG_FILTERDIR)
-    #[arg(long)]
+    #[arg(long, global = true)]
     filter_dir: Option<String>,
 
+    /// Make filter patterns case-insensitive (a filter file can turn this off with "@ignorecase off")
+    #[arg(long, global = true)]
+    filter_ignore_case: bool,
+
+    /// Make filter patterns match only the whole line ("daemon message" in hash mode, the daemon or host name) (a filter file can turn this off with "@anchored off")
+    #[arg(long, global = true)]
+    filter_anchored: bool,
+
     /// Export embedded default filters to a directory (defaults to ~/.glancelog/filters)
     #[arg(long)]
     export_filters: Option<Option<String>>,
@@ -48,6 +78,34 @@ struct Cli {
     #[arg(long)]
     wide: bool,
 
+    /// Plot the running total on graphs instead of the count per bucket
+    #[arg(long, conflicts_with = "delta")]
+    cumulative: bool,
+
+    /// Plot the change from the previous bucket on graphs
+    #[arg(long)]
+    delta: bool,
+
+    /// Draw one graph per input file on the same buckets (one column per file with --graph-data)
+    #[arg(long, conflicts_with = "compare")]
+    per_file: bool,
+
+    /// Print graph buckets as "start<TAB>count" rows instead of drawing the graph
+    #[arg(long)]
+    graph_data: bool,
+
+    /// Graph only the entries of one hash pattern, given as its scrubbed key from the hash report or as a regex
+    #[arg(long, value_name = "PATTERN")]
+    graph_pattern: Option<String>,
+
+    /// Overlay a baseline on graphs: another log file, or the same range an offset earlier such as "7d" or "24h"
+    #[arg(long, value_name = "FILE|OFFSET", conflicts_with_all = ["delta", "hline"])]
+    compare: Option<String>,
+
+    /// Draw a marker line on graphs at "mean", a percentile such as "p95", or a count such as 1000 (repeatable)
+    #[arg(long, value_name = "LINE")]
+    hline: Vec<String>,
+
     /// Change tick character from default
     #[arg(long, default_value = "#")]
     tick: String,
@@ -60,26 +118,353 @@ struct Cli {
     #[arg(long)]
     from: Option<String>,
 
-    /// Filter logs to this datetime (format: "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD")
+    /// Filter logs to this datetime, inclusive (format: "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD"); a date alone covers the whole day
     #[arg(long)]
     to: Option<String>,
 
+    /// Round --from down and --to up to whole units; "auto" rounds each to the precision it is written in, "none" takes both exactly as written
+    #[arg(long, default_value = "auto", value_parser = ["auto", "none", "minute", "hour", "day"])]
+    time_round: String,
+
+    /// Keep entries without a timestamp (unparsed lines) when --from/--to filter by time
+    #[arg(long, conflicts_with = "only_undated")]
+    include_undated: bool,
+
+    /// Keep only entries without a timestamp, to see what the parser could not read
+    #[arg(long)]
+    only_undated: bool,
+
+    /// Shift parsed timestamps by an offset (e.g. "+02:00", "-00:30", "+90"); repeat once per input file for per-file offsets
+    #[arg(long, allow_hyphen_values = true)]
+    time_offset: Vec<String>,
+
+    /// Read input as CSV with the given column mapping, counted from 0 (e.g. "time=0,host=2,msg=5"; "time=1+2" joins date and time columns)
+    #[arg(long)]
+    csv_map: Option<String>,
+
+    /// Field delimiter for --csv-map ("tab" for tab separated input)
+    #[arg(long, default_value = ",")]
+    csv_delimiter: String,
+
+    /// The first line of each --csv-map input is a header row
+    #[arg(long)]
+    csv_header: bool,
+
+    /// Read input as an access log in this Apache LogFormat or nginx log_format (e.g. '%h %l %u %t "%r" %>s %b %D')
+    #[arg(long, value_name = "FORMAT", conflicts_with = "csv_map")]
+    logformat: Option<String>,
+
     /// Print log lines as-is (respects --from/--to filters)
     #[arg(short = 'p', long, group = "mode")]
     print: bool,
 
+    /// Print each entry as one JSON object per line (timestamp, host, daemon, severity, message, attrs), e.g. for jq
+    #[arg(long, group = "mode")]
+    print_json: bool,
+
+    /// Send entries to an OpenTelemetry endpoint as OTLP/HTTP log records, e.g. http://localhost:4318
+    #[cfg(feature = "otlp")]
+    #[arg(long, value_name = "ENDPOINT", group = "mode")]
+    otlp_export: Option<String>,
+
+    /// Header for --otlp-export requests, e.g. 'Authorization=Bearer TOKEN' (repeatable)
+    #[cfg(feature = "otlp")]
+    #[arg(long, value_name = "NAME=VALUE", requires = "otlp_export")]
+    otlp_header: Vec<String>,
+
+    /// Entries per --otlp-export request
+    #[cfg(feature = "otlp")]
+    #[arg(long, default_value = "1000", requires = "otlp_export")]
+    otlp_batch: usize,
+
+    /// Push entries to Grafana Loki, e.g. http://loki:3100, labeled by host, daemon and severity
+    #[cfg(feature = "loki")]
+    #[arg(long, value_name = "URL", group = "mode")]
+    export_loki: Option<String>,
+
+    /// Extra label for every --export-loki stream, e.g. 'env=prod'; 'job=...' replaces job="glancelog" (repeatable)
+    #[cfg(feature = "loki")]
+    #[arg(long, value_name = "NAME=VALUE", requires = "export_loki")]
+    loki_label: Vec<String>,
+
+    /// Tenant for multi-tenant Loki (X-Scope-OrgID header)
+    #[cfg(feature = "loki")]
+    #[arg(long, requires = "export_loki")]
+    loki_tenant: Option<String>,
+
+    /// Entries per --export-loki push
+    #[cfg(feature = "loki")]
+    #[arg(long, default_value = "1000", requires = "export_loki")]
+    loki_batch: usize,
+
+    /// Produce entries to Kafka as JSON messages keyed by host: BROKER:PORT/TOPIC (comma-separate several brokers)
+    #[cfg(feature = "kafka")]
+    #[arg(long, value_name = "BROKER/TOPIC", group = "mode")]
+    export_kafka: Option<String>,
+
+    /// librdkafka producer setting for --export-kafka, e.g. 'compression.type=zstd' (repeatable)
+    #[cfg(feature = "kafka")]
+    #[arg(long, value_name = "NAME=VALUE", requires = "export_kafka")]
+    kafka_option: Vec<String>,
+
+    /// Write the daemon, host, authentication and hash report as a PDF, e.g. for archiving incident evidence
+    #[cfg(feature = "pdf")]
+    #[arg(long, value_name = "FILE", group = "mode")]
+    report: Option<String>,
+
+    /// Print entries whose normalized line matches a regex (respects --from/--to filters)
+    #[arg(long, group = "mode")]
+    grep: Option<String>,
+
+    /// Entries of the original stream to show before and after each --grep match
+    #[arg(short = 'C', long, default_value = "0")]
+    context: usize,
+
+    /// Print the entries of one process as a timeline: a PID, or a regex matched against "name[pid]" (e.g. "^sshd")
+    #[arg(long, value_name = "PID|REGEX", group = "mode")]
+    pid_trace: Option<String>,
+
+    /// With --pid-trace, also follow the children a traced process reports forking or spawning
+    #[arg(long, requires = "pid_trace")]
+    pid_children: bool,
+
+    /// Write entries of each daemon or host to a separate file (normalized format)
+    #[arg(long, group = "mode", value_parser = ["daemon", "host"])]
+    split_by: Option<String>,
+
+    /// Output directory for --split-by (default: ./split)
+    #[arg(long, default_value = "./split")]
+    out_dir: String,
+
+    /// Estimate per-host clock offsets in a merged log
+    #[arg(long, group = "mode")]
+    skew: bool,
+
+    /// Offset in seconds above which hosts are reported as skewed (default: 5)
+    #[arg(long, default_value = "5")]
+    skew_threshold: i64,
+
+    /// Correct timestamps of skewed hosts before filtering and graphing
+    #[arg(long)]
+    skew_correct: bool,
+
+    /// Report the most duplicated lines (forwarding loops, double shipping)
+    #[arg(long, group = "mode")]
+    duplicates: bool,
+
+    /// Collapse identical consecutive lines before analysis
+    #[arg(long)]
+    dedupe: bool,
+
+    /// Keep daemons as the parser wrote them ("sshd[1234]:") instead of the bare program name with the PID in the "pid" field (always so with --print)
+    #[arg(long)]
+    keep_pid: bool,
+
+    /// Correlate postfix/sendmail lines by queue ID into a delivery report
+    #[arg(long, group = "mode")]
+    mail_report: bool,
+
+    /// Summarize authentication successes and failures by user and source IP
+    #[arg(long, group = "mode")]
+    auth_report: bool,
+
+    /// Summarize fail2ban bans/unbans per jail and per IP over time
+    #[arg(long, group = "mode")]
+    ban_report: bool,
+
+    /// Report top queried domains, top clients, rare domains and NXDOMAIN rates
+    #[arg(long, group = "mode")]
+    dns_report: bool,
+
+    /// Report DHCP message counts and MAC to IP assignments over time
+    #[arg(long, group = "mode")]
+    dhcp_report: bool,
+
+    /// Report OpenVPN/WireGuard sessions with user, source IP, duration and bytes
+    #[arg(long, group = "mode")]
+    vpn_report: bool,
+
+    /// Summarize blocked firewall traffic by rule, source, port and interface
+    #[arg(long, group = "mode")]
+    firewall_report: bool,
+
+    /// Summarize network flows (VPC Flow Logs): top talkers by bytes, rejected flows and destination ports
+    #[arg(long, group = "mode")]
+    flow_report: bool,
+
+    /// Summarize Microsoft 365 Unified Audit Log records: operations per user with client addresses and time span
+    #[arg(long, group = "mode")]
+    ual_report: bool,
+
+    /// Summarize Suricata eve.json alerts by signature, severity, source and destination
+    #[arg(long, group = "mode")]
+    suricata_report: bool,
+
+    /// Group Snort/ModSecurity alerts by rule ID with sample requests
+    #[arg(long, group = "mode")]
+    alert_report: bool,
+
+    /// Pair Proxmox VE task start/end by UPID and report durations and failures
+    #[arg(long, group = "mode")]
+    pve_report: bool,
+
+    /// Summarize FTP xferlog transfers by file, user and bytes per client
+    #[arg(long, group = "mode")]
+    xfer_report: bool,
+
+    /// Summarize Samba full_audit file operations by user, share and path
+    #[arg(long, group = "mode")]
+    samba_report: bool,
+
+    /// Correlate Asterisk full log lines by call ID into a per-call report
+    #[arg(long, group = "mode")]
+    call_report: bool,
+
+    /// Count SLURM/PBS job submits, starts, ends and failures per user and partition
+    #[arg(long, group = "mode")]
+    job_report: bool,
+
+    /// Report JVM GC pause counts and percentiles by type and over time
+    #[arg(long, group = "mode")]
+    gc_report: bool,
+
+    /// Summarize systemd units: failed units, restart loops, OOM kills and segfaults over time
+    #[arg(long, group = "mode")]
+    systemd_report: bool,
+
+    /// Tabulate kernel OOM kills (victim, scores, invoking process), hardware errors (MCE, EDAC, I/O errors, segfaults) and link flaps
+    #[arg(long, group = "mode")]
+    kernel_report: bool,
+
+    /// Group storage errors by disk: ATA, SCSI and NVMe command failures, I/O errors, EXT4/XFS/Btrfs errors and smartd warnings
+    #[arg(long, group = "mode")]
+    disk_report: bool,
+
+    /// Cross-tab entry counts: rows by the first key, columns by the second
+    #[arg(long, group = "mode", value_parser = ["host,daemon", "daemon,host"])]
+    matrix: Option<String>,
+
+    /// Show when each of the top daemons logs, as a 24-hour distribution
+    #[arg(long, group = "mode")]
+    daemon_hours: bool,
+
+    /// Show entries per host over the time span of the log, one row of columns per host
+    #[arg(long, group = "mode")]
+    host_timeline: bool,
+
+    /// Number of columns in --host-timeline
+    #[arg(long, default_value = "60", requires = "host_timeline", value_parser = clap::value_parser!(u16).range(1..=1000))]
+    timeline_width: u16,
+
+    /// Find hash patterns that occur in the same time buckets, ranked by lift
+    #[arg(long, group = "mode")]
+    correlate: bool,
+
+    /// Bucket size in seconds for --correlate (default: 60)
+    #[arg(long, default_value = "60")]
+    correlate_window: i64,
+
+    /// Show error and warning counts next to the total per hour (or =minutes, =days...), with the error share of each
+    #[arg(long, group = "mode", num_args = 0..=1, require_equals = true, default_missing_value = "hours",
+        value_parser = ["seconds", "minutes", "hours", "days", "months", "years"])]
+    severity_trend: Option<String>,
+
+    /// Report the highest-entropy messages; --entropy=tokens, =domains or =FIELD scores those instead
+    #[arg(long, group = "mode", num_args = 0..=1, require_equals = true, default_missing_value = "message")]
+    entropy: Option<String>,
+
+    /// Summarize each input file: entries, share, unparsed lines, time span and detected format
+    #[arg(long, group = "mode")]
+    sources: bool,
+
+    /// Tag entries with the first matching rule of a YAML rules file (daemon, host, pattern, severity)
+    #[arg(long, value_name = "FILE")]
+    rules: Option<String>,
+
+    /// Report entries per tag and how often each rule matched (needs --rules)
+    #[arg(long, group = "mode", requires = "rules")]
+    tag_report: bool,
+
+    /// Leave out entries with this tag, e.g. noise (repeatable, needs --rules)
+    #[arg(long, value_name = "TAG", requires = "rules")]
+    hide_tag: Vec<String>,
+
+    /// Build a digest of the last hours of the logs named in a config file, and mail or write it (for cron)
+    #[arg(long, group = "mode", value_name = "CONFIG")]
+    digest: Option<String>,
+
+    /// Keep reading stdin or a file as it grows and refresh the hash report (or --daemon/--host)
+    #[arg(short = 'f', long)]
+    follow: bool,
+
+    /// Save the followed file's position and counts here at every refresh, and resume from them on restart
+    #[arg(long, value_name = "FILE", requires = "follow")]
+    state: Option<String>,
+
+    /// Seconds between report refreshes with --follow (default: 5)
+    #[arg(long, default_value = "5", requires = "follow")]
+    interval: u64,
+
+    /// Send per-daemon and per-severity entry counts every --interval while following: statsd://HOST:PORT or graphite://HOST:PORT
+    #[arg(long, value_name = "URL", requires = "follow")]
+    metrics: Option<String>,
+
+    /// Prefix of the --metrics names
+    #[arg(long, default_value = "glancelog", requires = "metrics")]
+    metrics_prefix: String,
+
+    /// Exit with status 3 if any entry matches this regex (repeatable)
+    #[arg(long, value_name = "REGEX", conflicts_with_all = ["digest", "follow"])]
+    fail_on: Vec<String>,
+
+    /// Exit with status 3 if a count condition holds, e.g. "errors>0", "daemon:sshd>=100", "pattern:oom>2" (repeatable)
+    #[arg(long, value_name = "KEY>N", conflicts_with_all = ["digest", "follow"])]
+    fail_if_count: Vec<String>,
+
+    /// Number of rows shown in each section of summary reports (default: 10)
+    #[arg(long, default_value = "10")]
+    top: usize,
+
     /// Show hashes of log files with numbers removed
     #[arg(long, group = "mode")]
     hash: bool,
 
+    /// Hash mode key built from fields, e.g. '{host}/{daemon}', '{daemon} {severity}' or '{daemon} {message}'
+    #[arg(long, value_name = "TEMPLATE")]
+    key: Option<String>,
+
     /// Show word count for given word
     #[arg(long, group = "mode")]
     wordcount: bool,
 
+    /// Count phrases of this many consecutive words in --wordcount (e.g. 2 for bigrams, 3 for trigrams)
+    #[arg(long, default_value = "1", requires = "wordcount", value_parser = clap::value_parser!(u8).range(1..=5))]
+    ngrams: u8,
+
     /// Show a report of entries from each daemon
     #[arg(long, group = "mode")]
     daemon: bool,
 
+    /// Count entries by event ID (Windows event IDs, Cisco message IDs, MySQL error codes) with the event name
+    #[arg(long, group = "mode")]
+    event_id: bool,
+
+    /// Count entries by client or source IP address (web, load balancer, firewall and IDS logs)
+    #[arg(long, group = "mode")]
+    source_ip: bool,
+
+    /// Roll --source-ip addresses up to networks, e.g. '/24' or '/24,/48' for IPv4 and IPv6 (default /64)
+    #[arg(long, value_name = "/N", requires = "source_ip")]
+    rollup: Option<String>,
+
+    /// Count entries per user (sshd, PAM, sudo, su, logind, web auth, EVTX) with first and last seen
+    #[arg(long, group = "mode")]
+    user: bool,
+
+    /// Extra regex finding the user in --user messages, with a (?P<user>...) group or just one group (repeatable)
+    #[arg(long, value_name = "REGEX", requires = "user")]
+    user_pattern: Vec<String>,
+
     /// Show a report of entries from each host
     #[arg(long, group = "mode")]
     host: bool,
@@ -109,8 +494,90 @@ struct Cli {
     ygraph: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Install community filter packs into the filter directory (--filter-dir or ~/.glancelog/filters)
+    Filters {
+        #[command(subcommand)]
+        action: FiltersAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum FiltersAction {
+    /// List the curated filter packs and the installed ones
+    List,
+    /// Download a curated pack by name, or a pack file from a URL, and verify its checksum
+    Install {
+        /// Pack name from 'filters list', or the URL of a .stopwords file
+        pack: String,
+
+        /// Expected SHA-256 of a pack given by URL
+        #[arg(long)]
+        sha256: Option<String>,
+
+        /// Filter file the pack adds to: hash, daemon, host or words (default: the pack's own, or hash)
+        #[arg(long = "for", value_name = "FILTER")]
+        filter: Option<String>,
+    },
+}
+
+/// Prints glancelog's log records to stderr; warnings from dependencies are
+/// passed through, their chatter below that is not
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn || metadata.target().starts_with("glancelog")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Error => eprintln!("Error: {}", record.args()),
+            Level::Warn => eprintln!("Warning: {}", record.args()),
+            _ => eprintln!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+fn init_logging(cli: &Cli) {
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // The pattern database is only read and written by hash mode, which is
+    // --hash or no mode at all
+    if cli.learn.is_some() || cli.only_new.is_some() {
+        let mode = matches.get_one::<clap::Id>("mode").filter(|mode| mode.as_str() != "hash");
+        if let Some(mode) = mode.map(|mode| mode.as_str().replace('_', "-")).or_else(|| cli.follow.then(|| "follow".to_string())) {
+            Cli::command().error(clap::error::ErrorKind::ArgumentConflict,
+                format!("--learn and --only-new only work in hash mode, not with --{}", mode)).exit();
+        }
+    }
+    init_logging(&cli);
+
+    if let Some(Command::Filters { action }) = &cli.command {
+        mode_filters(&cli, action);
+        return;
+    }
 
     // Handle filter export if requested
     if let Some(export_path) = &cli.export_filters {
@@ -129,55 +596,200 @@ fn main() {
         }
     }
 
-    // Load log
-    let log = if let Some(filename) = &cli.file {
-        match CrunchLog::from_file(filename) {
-            Ok(log) => log,
-            Err(e) => {
-                eprintln!("Error reading file: {}", e);
-                std::process::exit(1);
-            }
-        }
-    } else {
-        match CrunchLog::from_stdin() {
-            Ok(log) => log,
-            Err(e) => {
-                eprintln!("Error reading stdin: {}", e);
-                std::process::exit(1);
-            }
+    let policy = match FailPolicy::new(&cli.fail_on, &cli.fail_if_count) {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("Error parsing --fail-on/--fail-if-count: {}", e);
+            std::process::exit(1);
         }
     };
 
-    if cli.verbose > 0 {
-        eprintln!("Detected log format: {}", log.parser_type);
-        eprintln!("Loaded {} entries", log.entries.len());
+    // The digest reads the logs named in its config
+    if let Some(config) = &cli.digest {
+        mode_digest(&cli, config);
+        return;
     }
 
-    // Apply time filters if specified
-    let log = apply_time_filters(log, &cli);
+    // Followed input never ends, so it is counted as it arrives instead of loaded
+    if cli.follow {
+        mode_follow(&cli);
+        return;
+    }
+
+    // Load log
+    let (mut log, sources) = load_log(&cli);
+
+    info!("Detected log format: {}", log.parser_type);
+    info!("Loaded {} entries", log.entries.len());
+
+    // --print shows the lines as the parser read them
+    if !cli.keep_pid && !cli.print {
+        log.normalize_daemons();
+    }
 
-    if cli.verbose > 0 && (cli.from.is_some() || cli.to.is_some()) {
-        eprintln!("After filtering: {} entries", log.entries.len());
+    if cli.dedupe {
+        let removed = log.dedupe();
+        info!("Removed {} duplicate consecutive entries", removed);
     }
 
-    // Parse from/to datetimes for use in graph modes
-    let from_dt = cli.from.as_ref().and_then(|s| {
-        match parse_datetime(s) {
-            Ok(dt) => Some(dt),
-            Err(_) => None,  // Already handled in apply_time_filters
+    // Correct host clock skew before any time-based processing
+    let mut log = apply_skew_correction(log, &cli);
+
+    // Tag before anything else looks at the entries, so hidden tags are gone
+    // from every report
+    let rules = cli.rules.as_ref().map(|path| {
+        let rules = RuleSet::from_file(path).unwrap_or_else(|e| {
+            eprintln!("Error reading rules {}: {}", path, e);
+            std::process::exit(1);
+        });
+        rules.apply(&mut log);
+        if !cli.hide_tag.is_empty() {
+            let before = log.entries.len();
+            log.entries.retain(|e| e.attrs.get(TAG_ATTR).map(|tag| !cli.hide_tag.contains(tag)).unwrap_or(true));
+            info!("Hid {} entries tagged {}", before - log.entries.len(), cli.hide_tag.join(", "));
         }
+        rules
     });
 
-    let to_dt = cli.to.as_ref().and_then(|s| {
-        match parse_datetime(s) {
-            Ok(dt) => Some(dt),
-            Err(_) => None,  // Already handled in apply_time_filters
+    // Context comes from the stream as loaded, so matches are picked before the
+    // time filters drop anything
+    if let Some(pattern) = &cli.grep {
+        mode_context(&cli, &log, pattern);
+        if !policy.is_empty() {
+            enforce_policy(&policy, &apply_time_filters(log, &cli));
         }
-    });
+        return;
+    }
+
+    // An earlier range to compare against lies outside --from/--to, so it is
+    // taken, and shifted onto the same clock, before the time filters drop it
+    let baseline = cli.compare.as_ref().map(|spec| load_baseline(&cli, &log, spec));
+
+    // Apply time filters if specified
+    let log = apply_time_filters(log, &cli);
+    let baseline = baseline.map(|b| Baseline { log: apply_time_filters(b.log, &cli), ..b });
+
+    if cli.from.is_some() || cli.to.is_some() {
+        info!("After filtering: {} entries", log.entries.len());
+    }
+
+    // Graph modes take the range the filters used; the graph ends once the
+    // last second --to takes in is over
+    let (from_dt, to_dt) = parse_time_range(&cli);
+    let to_dt = to_dt.map(|dt| dt + TimeDelta::seconds(1));
+
+    // Exporting goes through the same loading, tagging and time filters as
+    // the reports
+    #[cfg(feature = "loki")]
+    if let Some(endpoint) = &cli.export_loki {
+        mode_export_loki(&cli, &log, endpoint);
+        enforce_policy(&policy, &log);
+        return;
+    }
+    #[cfg(feature = "otlp")]
+    if let Some(endpoint) = &cli.otlp_export {
+        mode_otlp_export(&cli, &log, endpoint);
+        enforce_policy(&policy, &log);
+        return;
+    }
+    #[cfg(feature = "kafka")]
+    if let Some(target) = &cli.export_kafka {
+        mode_export_kafka(&cli, &log, target);
+        enforce_policy(&policy, &log);
+        return;
+    }
+    #[cfg(feature = "pdf")]
+    if let Some(path) = &cli.report {
+        mode_report_pdf(&cli, &log, path);
+        enforce_policy(&policy, &log);
+        return;
+    }
 
     // Determine mode and execute
     if cli.print {
         mode_print(&log);
+    } else if cli.print_json {
+        mode_print_json(&log);
+    } else if let Some(split_by) = &cli.split_by {
+        mode_split(&cli, &log, split_by);
+    } else if cli.skew {
+        mode_skew(&cli, &log);
+    } else if cli.duplicates {
+        DuplicateReport::from_log(&log).display();
+    } else if cli.mail_report {
+        MailReport::from_log(&log).display(cli.top);
+    } else if cli.auth_report {
+        AuthReport::from_log(&log).display(cli.top);
+    } else if cli.ban_report {
+        BanReport::from_log(&log).display(cli.top);
+    } else if cli.dns_report {
+        DnsReport::from_log(&log).display(cli.top);
+    } else if cli.dhcp_report {
+        DhcpReport::from_log(&log).display(cli.top);
+    } else if cli.vpn_report {
+        VpnReport::from_log(&log).display(cli.top);
+    } else if cli.firewall_report {
+        FirewallReport::from_log(&log).display(cli.top);
+    } else if cli.flow_report {
+        FlowReport::from_log(&log).display(cli.top);
+    } else if cli.ual_report {
+        UalReport::from_log(&log).display(cli.top);
+    } else if cli.suricata_report {
+        SuricataReport::from_log(&log).display(cli.top);
+    } else if cli.alert_report {
+        AlertReport::from_log(&log).display(cli.top);
+    } else if cli.pve_report {
+        PveTaskReport::from_log(&log).display(cli.top);
+    } else if cli.xfer_report {
+        TransferReport::from_log(&log).display(cli.top);
+    } else if cli.samba_report {
+        SambaReport::from_log(&log).display(cli.top);
+    } else if cli.call_report {
+        CallReport::from_log(&log).display(cli.top);
+    } else if cli.job_report {
+        JobReport::from_log(&log).display(cli.top);
+    } else if cli.gc_report {
+        GcReport::from_log(&log).display(cli.top);
+    } else if cli.systemd_report {
+        SystemdReport::from_log(&log).display(cli.top);
+    } else if cli.kernel_report {
+        KernelReport::from_log(&log).display(cli.top);
+    } else if cli.disk_report {
+        DiskReport::from_log(&log).display(cli.top);
+    } else if let Some(axes) = &cli.matrix {
+        mode_matrix(&cli, &log, axes);
+    } else if cli.daemon_hours {
+        mode_daemon_hours(&cli, &log);
+    } else if cli.host_timeline {
+        mode_host_timeline(&cli, &log);
+    } else if cli.correlate {
+        mode_correlate(&cli, &log);
+    } else if let Some(spec) = &cli.pid_trace {
+        match PidTrace::from_log(&log, spec, cli.pid_children) {
+            Ok(trace) => trace.display(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(unit) = &cli.severity_trend {
+        let graph_type = match unit.as_str() {
+            "seconds" => GraphType::Seconds,
+            "minutes" => GraphType::Minutes,
+            "days" => GraphType::Days,
+            "months" => GraphType::Months,
+            "years" => GraphType::Years,
+            _ => GraphType::Hours,
+        };
+        SeverityTrend::from_log(&log, graph_type).display();
+    } else if let Some(target) = &cli.entropy {
+        EntropyReport::from_log(&log, EntropyTarget::parse(target)).display(cli.top);
+    } else if cli.tag_report {
+        if let Some(rules) = &rules {
+            TagReport::from_log(&log, rules).display(cli.top);
+        }
+    } else if cli.sources {
+        SourceReport::new(sources).display();
     } else if cli.hash {
         mode_hash(&cli, &log);
     } else if cli.wordcount {
@@ -186,169 +798,998 @@ fn main() {
         mode_daemon(&cli, &log);
     } else if cli.host {
         mode_host(&cli, &log);
+    } else if cli.event_id {
+        mode_event_id(&cli, &log);
+    } else if cli.source_ip {
+        mode_source_ip(&cli, &log);
+    } else if cli.user {
+        mode_user(&cli, &log);
     } else if cli.sgraph {
-        mode_graph(&cli, &log, GraphType::Seconds, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Seconds, from_dt, to_dt);
     } else if cli.mgraph {
-        mode_graph(&cli, &log, GraphType::Minutes, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Minutes, from_dt, to_dt);
     } else if cli.hgraph {
-        mode_graph(&cli, &log, GraphType::Hours, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Hours, from_dt, to_dt);
     } else if cli.dgraph {
-        mode_graph(&cli, &log, GraphType::Days, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Days, from_dt, to_dt);
     } else if cli.mograph {
-        mode_graph(&cli, &log, GraphType::Months, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Months, from_dt, to_dt);
     } else if cli.ygraph {
-        mode_graph(&cli, &log, GraphType::Years, from_dt, to_dt);
+        mode_graph(&cli, &log, baseline.as_ref(), GraphType::Years, from_dt, to_dt);
     } else {
         // Default to hash mode
         mode_hash(&cli, &log);
     }
+
+    enforce_policy(&policy, &log);
 }
 
-fn mode_print(log: &CrunchLog) {
-    for entry in &log.entries {
-        // Format: YYYY-MM-DDTHH:MM:SS host daemon: message
-        // Some parsers include trailing ":" in daemon field, some don't
-        let daemon_separator = if entry.daemon.ends_with(':') { "" } else { ":" };
+/// Exit with status 3, naming each condition met, if the entries fail the
+/// --fail-on/--fail-if-count policy
+fn enforce_policy(policy: &FailPolicy, log: &CrunchLog) {
+    let failures = policy.check(log);
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("Failing: {}", failure);
+        }
+        std::process::exit(3);
+    }
+}
 
-        // Strip leading ": " or " " from log_entry if present (added by some parsers)
-        let message = entry.log_entry
-            .strip_prefix(": ")
-            .or_else(|| entry.log_entry.strip_prefix(" "))
-            .unwrap_or(&entry.log_entry);
+/// The merged log and a summary of each input as loaded. With more than one
+/// file every entry carries its file name, see `CrunchLog::set_input`
+fn load_log(cli: &Cli) -> (CrunchLog, Vec<SourceSummary>) {
+    let offsets: Vec<i64> = cli.time_offset.iter().map(|s| {
+        match parse_offset(s) {
+            Ok(offset) => offset,
+            Err(e) => {
+                eprintln!("Error parsing --time-offset: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }).collect();
 
-        println!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02} {} {}{} {}",
-            entry.year, entry.month, entry.day,
-            entry.hour, entry.minute, entry.second,
-            entry.host,
-            entry.daemon,
-            daemon_separator,
-            message
-        );
+    let inputs = cli.files.len().max(1);
+    if offsets.len() > 1 && offsets.len() != inputs {
+        eprintln!("Error: got {} --time-offset values for {} inputs (give one, or one per input file)", offsets.len(), inputs);
+        std::process::exit(1);
     }
-}
+    let offset_for = |idx: usize| if offsets.len() > 1 { offsets[idx] } else { offsets.first().copied().unwrap_or(0) };
 
-fn mode_hash(cli: &Cli, log: &CrunchLog) {
-    let filter = if cli.nofilter {
-        Filter::new()
-    } else {
-        Filter::from_file_with_dir("hash.stopwords", cli.filter_dir.as_deref())
-            .unwrap_or_else(|_| Filter::new())
+    let csv_map = cli.csv_map.as_ref().map(|spec| {
+        let delimiter = match cli.csv_delimiter.as_str() {
+            "tab" | "\\t" => '\t',
+            d if d.chars().count() == 1 => d.chars().next().unwrap(),
+            d => {
+                eprintln!("Error: --csv-delimiter must be a single character, got '{}'", d);
+                std::process::exit(1);
+            }
+        };
+        match CsvMap::parse(spec, delimiter, cli.csv_header) {
+            Ok(map) => map,
+            Err(e) => {
+                eprintln!("Error parsing --csv-map: {}", e);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    let log_format = cli.logformat.as_ref().map(|spec| match LogFormat::parse(spec) {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("Error parsing --logformat: {}", e);
+            std::process::exit(1);
+        }
+    });
+    // A fresh parser per input, as a CSV header belongs to its file
+    let custom_parser = || -> Option<Box<dyn LogParser>> {
+        match (&csv_map, &log_format) {
+            (Some(map), _) => Some(Box::new(CsvParser::new(map.clone()))),
+            (_, Some(format)) => Some(Box::new(LogFormatParser::new(format.clone()))),
+            _ => None,
+        }
     };
 
-    let mut hash = SuperHash::from_log(log, HashMode::Hash, filter);
+    if cli.files.is_empty() {
+        let result = match custom_parser() {
+            Some(parser) => CrunchLog::from_stdin_with_parser(parser.as_ref()),
+            None => CrunchLog::from_stdin(),
+        };
+        let mut log = match result {
+            Ok(log) => log,
+            Err(e) => {
+                eprintln!("Error reading stdin: {}", e);
+                std::process::exit(1);
+            }
+        };
+        log.shift_time(offset_for(0));
+        let sources = vec![SourceSummary::from_log("-", &log)];
+        return (log, sources);
+    }
 
-    // Set sample threshold
-    hash.set_sample_threshold(cli.lowcount);
+    let mut merged: Option<CrunchLog> = None;
+    let mut sources = Vec::new();
+    for (idx, filename) in cli.files.iter().enumerate() {
+        let result = match custom_parser() {
+            Some(parser) => CrunchLog::from_file_with_parser(filename, parser.as_ref()),
+            None => CrunchLog::from_file(filename),
+        };
+        let mut log = match result {
+            Ok(log) => log,
+            Err(e) => {
+                eprintln!("Error reading file {}: {}", filename, e);
+                std::process::exit(1);
+            }
+        };
+        log.shift_time(offset_for(idx));
+        sources.push(SourceSummary::from_log(filename, &log));
+        if cli.files.len() > 1 {
+            log.set_input(filename);
+        }
 
-    // Set sample mode
-    if cli.allsample {
-        hash.set_sample_mode(SampleMode::All);
-    } else if cli.nosample {
-        hash.set_sample_mode(SampleMode::None);
-    } else {
-        hash.set_sample_mode(SampleMode::Threshold);
+        match merged.as_mut() {
+            Some(m) => m.append(log),
+            None => merged = Some(log),
+        }
     }
 
-    hash.display();
+    (merged.unwrap(), sources)
 }
 
-fn mode_wordcount(cli: &Cli, log: &CrunchLog) {
-    let filter = if cli.nofilter {
-        Filter::new()
-    } else {
-        Filter::from_file_with_dir("words.stopwords", cli.filter_dir.as_deref())
-            .unwrap_or_else(|_| Filter::new())
-    };
+/// --from and --to rounded as --time-round says: the first and the last
+/// second of the range, both inclusive
+fn parse_time_range(cli: &Cli) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+    let parse = |name: &str, value: &Option<String>, end: bool| value.as_ref().map(|s| {
+        parse_time_bound(s, end, &cli.time_round).unwrap_or_else(|e| {
+            eprintln!("Error parsing --{}: {}", name, e);
+            std::process::exit(1);
+        })
+    });
+    (parse("from", &cli.from, false), parse("to", &cli.to, true))
+}
+
+/// --from/--to together with --include-undated and --only-undated. Entries
+/// without a timestamp have no place in a time range, so they pass only when
+/// asked for or when there is no range at all
+fn time_filter(cli: &Cli) -> impl Fn(&LogEntry) -> bool {
+    let (from, to) = parse_time_range(cli);
+    let (include_undated, only_undated) = (cli.include_undated, cli.only_undated);
+    move |entry| {
+        if entry.is_undated() {
+            only_undated || include_undated || (from.is_none() && to.is_none())
+        } else {
+            !only_undated && CrunchLog::in_time_range(entry, from, to)
+        }
+    }
+}
+
+fn mode_print(log: &CrunchLog) {
+    for entry in &log.entries {
+        println!("{}", entry.to_normalized());
+    }
+}
+
+fn mode_print_json(log: &CrunchLog) {
+    for entry in &log.entries {
+        println!("{}", entry.to_json());
+    }
+}
+
+#[cfg(feature = "loki")]
+fn mode_export_loki(cli: &Cli, log: &CrunchLog, endpoint: &str) {
+    let mut exporter = glancelog::LokiExporter::new(endpoint);
+    exporter.set_tenant(cli.loki_tenant.clone());
+    exporter.set_batch_size(cli.loki_batch);
+    for label in &cli.loki_label {
+        if let Err(e) = exporter.add_label(label) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    match exporter.export(log) {
+        Ok(sent) => println!("Exported {} entries to {}", sent, exporter.url()),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "otlp")]
+fn mode_otlp_export(cli: &Cli, log: &CrunchLog, endpoint: &str) {
+    let mut exporter = glancelog::OtlpExporter::new(endpoint);
+    exporter.set_batch_size(cli.otlp_batch);
+    for header in &cli.otlp_header {
+        if let Err(e) = exporter.add_header(header) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    match exporter.export(log) {
+        Ok(sent) => println!("Exported {} entries to {}", sent, exporter.url()),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+fn mode_export_kafka(cli: &Cli, log: &CrunchLog, target: &str) {
+    let exporter = glancelog::KafkaExporter::parse(target).and_then(|mut exporter| {
+        for option in &cli.kafka_option {
+            exporter.add_option(option)?;
+        }
+        Ok(exporter)
+    });
+    let exporter = exporter.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    match exporter.export(log) {
+        Ok(sent) => println!("Exported {} entries to Kafka topic {}", sent, exporter.topic()),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+fn mode_report_pdf(cli: &Cli, log: &CrunchLog, path: &str) {
+    let mut config = DigestConfig::default();
+    for section in &mut config.sections {
+        section.top = cli.top;
+    }
+    let title = if cli.files.is_empty() {
+        "glancelog report".to_string()
+    } else {
+        format!("glancelog report: {}", cli.files.join(", "))
+    };
+
+    let result = Digest::from_log(log, &config, cli.filter_dir.as_deref(), !cli.nofilter, filter_options(cli))
+        .and_then(|digest| glancelog::PdfReport::new(&title, digest.text()).write(path));
+    match result {
+        Ok(()) => println!("Wrote report to {}", path),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn mode_context(cli: &Cli, log: &CrunchLog, pattern: &str) {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            eprintln!("Error parsing --grep: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let keep = time_filter(cli);
+
+    let matches = ContextMatches::from_log(log, &regex, cli.context, keep);
+    info!("{} matching entries", matches.matches().len());
+    matches.display(log);
+}
+
+fn mode_split(cli: &Cli, log: &CrunchLog, split_by: &str) {
+    let (key, filter_file) = match split_by {
+        "host" => (SplitKey::Host, "host.stopwords"),
+        _ => (SplitKey::Daemon, "daemon.stopwords"),
+    };
+
+    let filter = load_filter(cli, filter_file);
+
+    let splitter = LogSplitter::new(key, filter);
+    match splitter.write_to_dir(log, std::path::Path::new(&cli.out_dir)) {
+        Ok(written) => {
+            for (path, count) in written {
+                eprintln!("Wrote {} entries to {}", count, path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("Error splitting log: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn mode_matrix(cli: &Cli, log: &CrunchLog, axes: &str) {
+    let host = (SplitKey::Host, load_filter(cli, "host.stopwords"));
+    let daemon = (SplitKey::Daemon, load_filter(cli, "daemon.stopwords"));
+
+    let (rows, columns) = match axes {
+        "daemon,host" => (&daemon, &host),
+        _ => (&host, &daemon),
+    };
+    Matrix::from_log(log, (rows.0, &rows.1), (columns.0, &columns.1)).display(cli.top);
+}
+
+fn mode_daemon_hours(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "daemon.stopwords");
+
+    HourlyProfile::from_log(log, SplitKey::Daemon, &filter).display(cli.top);
+}
+
+fn mode_host_timeline(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "host.stopwords");
+
+    HostTimeline::from_log(log, cli.timeline_width as usize, &filter).display(cli.top);
+}
+
+fn mode_correlate(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "hash.stopwords");
+
+    CorrelationReport::from_log(log, filter, cli.correlate_window, cli.lowcount).display(cli.top);
+}
+
+fn mode_skew(cli: &Cli, log: &CrunchLog) {
+    let mut skew = ClockSkew::from_log(log);
+    skew.set_threshold(cli.skew_threshold);
+    skew.display();
+}
+
+fn mode_hash(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "hash.stopwords");
+
+    let template = cli.key.as_ref().map(|spec| KeyTemplate::parse(spec).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }));
+    let mut hash = match &template {
+        Some(template) => SuperHash::from_log_with_key(log, template, filter),
+        None => SuperHash::from_log(log, HashMode::Hash, filter),
+    };
+
+    // Compare against the database as it was before this run learns anything
+    let known = cli.only_new.as_ref().map(|path| load_pattern_db(path));
+    if let Some(path) = &cli.learn {
+        let mut db = load_pattern_db(path);
+        let added = db.learn(&hash);
+        if let Err(e) = db.save() {
+            eprintln!("Error writing pattern database {}: {}", path, e);
+            std::process::exit(1);
+        }
+        info!("Learned {} new patterns ({} in {})", added, db.len(), path);
+    }
+    if let Some(known) = known {
+        hash.retain(|key| !known.contains(key));
+    }
+
+    // Set sample threshold
+    hash.set_sample_threshold(cli.lowcount);
+
+    // Set sample mode; a sample line says little about a key without the message
+    if cli.allsample {
+        hash.set_sample_mode(SampleMode::All);
+    } else if cli.nosample || template.as_ref().map(|t| !t.uses_message()).unwrap_or(false) {
+        hash.set_sample_mode(SampleMode::None);
+    } else {
+        hash.set_sample_mode(SampleMode::Threshold);
+    }
 
-    let mut hash = SuperHash::from_log(log, HashMode::WordCount, filter);
-    hash.set_sample_mode(SampleMode::None);
     hash.display();
 }
 
-fn mode_daemon(cli: &Cli, log: &CrunchLog) {
-    let filter = if cli.nofilter {
+fn filter_options(cli: &Cli) -> FilterOptions {
+    FilterOptions {
+        ignore_case: cli.filter_ignore_case,
+        anchored: cli.filter_anchored,
+    }
+}
+
+/// The named filter file with the filter options, or an empty filter with
+/// --nofilter or when the file cannot be read
+fn load_filter(cli: &Cli, filter_file: &str) -> Filter {
+    if cli.nofilter {
         Filter::new()
     } else {
-        Filter::from_file_with_dir("daemon.stopwords", cli.filter_dir.as_deref())
+        Filter::from_file_with_options(filter_file, cli.filter_dir.as_deref(), filter_options(cli))
             .unwrap_or_else(|_| Filter::new())
+    }
+}
+
+fn mode_filters(cli: &Cli, action: &FiltersAction) {
+    let dir = pack_dir(cli.filter_dir.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    match action {
+        FiltersAction::List => {
+            let installed = FilterPack::installed(&dir);
+            println!("Available filter packs:");
+            for pack in FilterPack::available() {
+                let mark = if installed.contains(&(pack.filter.clone(), pack.name.clone())) { "installed" } else { "" };
+                println!("{}	{}	{}	{}", pack.name, pack.filter, pack.description, mark);
+            }
+            println!();
+
+            println!("Installed in {}:", dir.display());
+            if installed.is_empty() {
+                println!("none");
+            }
+            for (filter, name) in installed {
+                println!("{}	{}", name, filter);
+            }
+        }
+        FiltersAction::Install { pack, sha256, filter } => {
+            let result = FilterPack::resolve(pack, filter.as_deref(), sha256.as_deref())
+                .and_then(|pack| pack.install(&dir));
+            match result {
+                Ok(path) => println!("Installed {}", path.display()),
+                Err(e) => {
+                    eprintln!("Error installing filter pack {}: {}", pack, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn mode_digest(cli: &Cli, config_path: &str) {
+    let mut config = match DigestConfig::from_file(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading digest config: {}", e);
+            std::process::exit(1);
+        }
     };
+    // Files on the command line replace the configured logs
+    if !cli.files.is_empty() {
+        config.logs = cli.files.clone();
+    }
 
-    let mut hash = SuperHash::from_log(log, HashMode::Daemon, filter);
+    let result = Digest::build(&config, cli.filter_dir.as_deref(), !cli.nofilter, filter_options(cli))
+        .and_then(|digest| digest.deliver(&config));
+    if let Err(e) = result {
+        eprintln!("Error building digest: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn load_pattern_db(path: &str) -> PatternDb {
+    match PatternDb::load(std::path::Path::new(path)) {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("Error reading pattern database {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn mode_wordcount(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "words.stopwords");
+
+    let mode = match cli.ngrams {
+        1 => HashMode::WordCount,
+        n => HashMode::NGrams(n as usize),
+    };
+    let mut hash = SuperHash::from_log(log, mode, filter);
     hash.set_sample_mode(SampleMode::None);
     hash.display();
 }
 
-fn mode_host(cli: &Cli, log: &CrunchLog) {
-    let filter = if cli.nofilter {
-        Filter::new()
+fn mode_follow(cli: &Cli) {
+    // Other modes need the whole log; followed input gets the hash report
+    let (mode, mode_name, filter_file) = if cli.daemon {
+        (HashMode::Daemon, "daemon", "daemon.stopwords")
+    } else if cli.host {
+        (HashMode::Host, "host", "host.stopwords")
     } else {
-        Filter::from_file_with_dir("host.stopwords", cli.filter_dir.as_deref())
-            .unwrap_or_else(|_| Filter::new())
+        (HashMode::Hash, "hash", "hash.stopwords")
+    };
+
+    if cli.files.len() > 1 {
+        eprintln!("Error: --follow reads stdin or a single file");
+        std::process::exit(1);
+    }
+    let file = cli.files.first();
+    if cli.state.is_some() && file.is_none() {
+        eprintln!("Error: --state needs a file to follow, stdin can't be resumed");
+        std::process::exit(1);
+    }
+
+    // A state for another file or report is not resumed
+    let saved = cli.state.as_ref().and_then(|path| {
+        let saved = FollowState::load(std::path::Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("Error reading follow state {}: {}", path, e);
+            std::process::exit(1);
+        })?;
+        if Some(&saved.path) != file || saved.mode != mode_name {
+            warn!("{} is the state of --{} on {}, starting over", path, saved.mode, saved.path);
+            return None;
+        }
+        Some(saved)
+    });
+
+    let mut follower = match file {
+        None => Follower::stdin(),
+        Some(file) => Follower::file_from(file, saved.as_ref().map(|s| s.position)).unwrap_or_else(|e| {
+            eprintln!("Error reading file {}: {}", file, e);
+            std::process::exit(1);
+        }),
+    };
+    if let Some(saved) = &saved {
+        if follower.position() == saved.position {
+            info!("Resuming at offset {} with {} entries counted", saved.position.offset, saved.entries);
+        } else {
+            info!("{} was rotated or truncated, reading it from the start", saved.path);
+        }
+    }
+
+    // Detect the format from the first lines, or from whatever arrived before
+    // the input went quiet; a resumed run keeps the format it detected before
+    let known_parser = saved.as_ref()
+        .and_then(|saved| CrunchLog::parsers().into_iter().find(|p| p.name() == saved.format));
+    let mut head = Vec::new();
+    let mut closed = false;
+    while known_parser.is_none() && head.len() < DETECT_LINES {
+        match follower.next(Duration::from_secs(1)) {
+            FollowEvent::Line(line) => head.push(line),
+            FollowEvent::Idle if head.is_empty() => continue,
+            FollowEvent::Idle => break,
+            FollowEvent::Closed => {
+                closed = true;
+                break;
+            }
+        }
+    }
+    let parser = match known_parser {
+        Some(parser) => parser,
+        None if head.is_empty() => {
+            eprintln!("Error reading stdin: No data found");
+            std::process::exit(1);
+        }
+        None => CrunchLog::detect_format(&head),
     };
+    info!("Detected log format: {}", parser.name());
+
+    let filter = load_filter(cli, filter_file);
+    let mut hash = SuperHash::new(filter);
+    hash.set_sample_threshold(cli.lowcount);
+    if cli.allsample {
+        hash.set_sample_mode(SampleMode::All);
+    } else if cli.nosample || !matches!(mode, HashMode::Hash) {
+        hash.set_sample_mode(SampleMode::None);
+    }
+    let mut entries = 0;
+    if let Some(saved) = &saved {
+        saved.restore(&mut hash);
+        entries = saved.entries;
+    }
+
+    let save = |hash: &SuperHash, entries: usize, position| {
+        if let (Some(path), Some(file)) = (&cli.state, file) {
+            let state = FollowState::from_hash(file, position, parser.name(), mode_name, entries, hash);
+            if let Err(e) = state.save(std::path::Path::new(path)) {
+                warn!("Could not write follow state {}: {}", path, e);
+            }
+        }
+    };
+
+    let mut metrics = cli.metrics.as_ref().map(|spec| {
+        MetricsTarget::parse(spec)
+            .and_then(|target| MetricsEmitter::new(target, &cli.metrics_prefix))
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            })
+    });
+    let send_metrics = |metrics: &mut Option<MetricsEmitter>| {
+        if let Some(metrics) = metrics {
+            if let Err(e) = metrics.flush() {
+                warn!("Could not send metrics: {}", e);
+            }
+        }
+    };
+
+    let keep = time_filter(cli);
+    let mut assembler = RecordAssembler::new(parser.as_ref());
+    let count = |hash: &mut SuperHash, metrics: &mut Option<MetricsEmitter>, entry: Option<LogEntry>| {
+        match entry.filter(&keep) {
+            Some(mut entry) => {
+                if !cli.keep_pid {
+                    entry.normalize_daemon();
+                }
+                hash.add_entry(mode, &entry);
+                if let Some(metrics) = metrics {
+                    metrics.count(&entry);
+                }
+                1
+            }
+            None => 0,
+        }
+    };
+    for line in head {
+        entries += count(&mut hash, &mut metrics, assembler.push(line));
+    }
+
+    let interval = Duration::from_secs(cli.interval.max(1));
+    let mut shown = Instant::now();
+    let mut sent = Instant::now();
+    let mut changed = true;
+    while !closed {
+        match follower.next(Duration::from_millis(500)) {
+            FollowEvent::Line(line) => {
+                entries += count(&mut hash, &mut metrics, assembler.push(line));
+                changed = true;
+            }
+            FollowEvent::Idle => {}
+            FollowEvent::Closed => closed = true,
+        }
+        // Counters go out every interval, whether or not the report is refreshed
+        if sent.elapsed() >= interval {
+            send_metrics(&mut metrics);
+            sent = Instant::now();
+        }
+        if changed && shown.elapsed() >= interval {
+            // A record still open when the input pauses is complete enough to
+            // count, which also makes the position safe to save
+            entries += count(&mut hash, &mut metrics, assembler.finish());
+            save(&hash, entries, follower.position());
+            println!("==> {} entries at {} <==", entries, Local::now().format("%H:%M:%S"));
+            hash.display();
+            println!();
+            shown = Instant::now();
+            changed = false;
+        }
+    }
+
+    entries += count(&mut hash, &mut metrics, assembler.finish());
+    send_metrics(&mut metrics);
+    save(&hash, entries, follower.position());
+    println!("==> {} entries <==", entries);
+    hash.display();
+}
+
+fn mode_daemon(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "daemon.stopwords");
+
+    let mut hash = SuperHash::from_log(log, HashMode::Daemon, filter);
+    hash.set_sample_mode(SampleMode::None);
+    hash.display();
+}
+
+fn mode_event_id(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "hash.stopwords");
+
+    let mut hash = SuperHash::from_log(log, HashMode::EventId, filter);
+    if hash.is_empty() {
+        println!("No event IDs found");
+        return;
+    }
+    hash.set_sample_mode(SampleMode::None);
+    hash.display();
+}
+
+fn mode_source_ip(cli: &Cli, log: &CrunchLog) {
+    let rollup = cli.rollup.as_ref().map(|spec| Rollup::parse(spec).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }));
+
+    // Addresses are the keys, so there is nothing to filter
+    let mut hash = SuperHash::from_log(log, HashMode::SourceIp(rollup), Filter::new());
+    if hash.is_empty() {
+        println!("No source IPs found");
+        return;
+    }
+    hash.set_sample_mode(SampleMode::None);
+    hash.display();
+}
+
+fn mode_user(cli: &Cli, log: &CrunchLog) {
+    let mut users = UserExtractor::new();
+    for pattern in &cli.user_pattern {
+        if let Err(e) = users.add_pattern(pattern) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut hash = SuperHash::from_log_with_users(log, &users, Filter::new());
+    if hash.is_empty() {
+        println!("No users found");
+        return;
+    }
+    hash.set_sample_mode(SampleMode::None);
+    hash.display();
+}
+
+fn mode_host(cli: &Cli, log: &CrunchLog) {
+    let filter = load_filter(cli, "host.stopwords");
 
     let mut hash = SuperHash::from_log(log, HashMode::Host, filter);
     hash.set_sample_mode(SampleMode::None);
     hash.display();
 }
 
-fn mode_graph(cli: &Cli, log: &CrunchLog, graph_type: GraphType, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>) {
-    let mut graph = GraphHash::new_with_range(log, graph_type, from, to);
+/// The log a graph is compared against with --compare
+struct Baseline {
+    log: CrunchLog,
+    label: String,
+    /// Shifted onto the clock of the main log, so it shares its buckets;
+    /// otherwise it is graphed from its own start
+    same_clock: bool,
+}
+
+fn load_baseline(cli: &Cli, log: &CrunchLog, spec: &str) -> Baseline {
+    if std::path::Path::new(spec).exists() {
+        return match CrunchLog::from_file(spec) {
+            Ok(mut log) => {
+                if !cli.keep_pid {
+                    log.normalize_daemons();
+                }
+                Baseline { log, label: spec.to_string(), same_clock: false }
+            }
+            Err(e) => {
+                eprintln!("Error reading file {}: {}", spec, e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    let seconds = match parse_compare_offset(spec) {
+        Ok(seconds) => seconds,
+        Err(e) => {
+            eprintln!("Error parsing --compare: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut baseline = CrunchLog {
+        entries: log.entries.clone(),
+        parser_type: log.parser_type.clone(),
+    };
+    baseline.shift_time(seconds);
+    Baseline { log: baseline, label: format!("{} earlier", spec), same_clock: true }
+}
+
+/// Longest --compare offset in seconds, a century; longer ones would shift
+/// baseline times out of range
+const MAX_COMPARE_OFFSET: i64 = 100 * 366 * 86400;
+
+fn parse_compare_offset(spec: &str) -> Result<i64, String> {
+    let re = Regex::new(r"^(\d+)([smhdw])$").unwrap();
+    let caps = re.captures(spec.trim_start_matches('-'))
+        .ok_or_else(|| format!("'{}' is neither a file nor an offset such as '30m', '24h', '7d' or '1w'", spec))?;
+    let unit = match &caps[2] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => 7 * 86400,
+    };
+    caps[1].parse::<i64>().ok()
+        .and_then(|n| n.checked_mul(unit))
+        .filter(|seconds| *seconds <= MAX_COMPARE_OFFSET)
+        .ok_or_else(|| format!("Offset '{}' is out of range, at most 100 years", spec))
+}
+
+/// The entries of the hash pattern with exactly this scrubbed key, or else of
+/// every pattern whose key or message matches it as a regex
+fn select_pattern(cli: &Cli, log: &CrunchLog, spec: &str) -> CrunchLog {
+    let filter = load_filter(cli, "hash.stopwords");
+    let hash = SuperHash::from_log(log, HashMode::Hash, filter);
+
+    let mut entries: Vec<LogEntry> = hash.patterns()
+        .filter(|(key, _)| *key == spec)
+        .flat_map(|(_, entries)| entries.iter().cloned())
+        .collect();
+    if entries.is_empty() {
+        let re = match Regex::new(spec) {
+            Ok(re) => re,
+            Err(e) => {
+                eprintln!("Error parsing --graph-pattern: {}", e);
+                std::process::exit(1);
+            }
+        };
+        for (key, pattern_entries) in hash.patterns() {
+            let key_matches = re.is_match(key);
+            entries.extend(pattern_entries.iter().filter(|e| key_matches || re.is_match(&e.log_entry)).cloned());
+        }
+    }
+    entries.sort_by_key(|e| e.naive_datetime());
+
+    info!("{} entries match --graph-pattern", entries.len());
+    CrunchLog { entries, parser_type: log.parser_type.clone() }
+}
+
+fn mode_graph(cli: &Cli, log: &CrunchLog, baseline: Option<&Baseline>, graph_type: GraphType, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>) {
+    let (selected, selected_baseline);
+    let (log, baseline_log) = match &cli.graph_pattern {
+        Some(spec) => {
+            selected = select_pattern(cli, log, spec);
+            selected_baseline = baseline.map(|b| select_pattern(cli, &b.log, spec));
+            if selected.entries.is_empty() {
+                warn!("No entries match --graph-pattern '{}'", spec);
+            }
+            (&selected, selected_baseline.as_ref())
+        }
+        None => (log, baseline.map(|b| &b.log)),
+    };
+
+    let values = if cli.cumulative {
+        GraphValues::Cumulative
+    } else if cli.delta {
+        GraphValues::Delta
+    } else {
+        GraphValues::Count
+    };
+    let hlines: Vec<HLine> = cli.hline.iter().map(|spec| {
+        HLine::parse(spec).unwrap_or_else(|e| {
+            eprintln!("Error parsing --hline: {}", e);
+            std::process::exit(1);
+        })
+    }).collect();
+    let new_graph = |log: &CrunchLog, from: Option<DateTime<Local>>| {
+        let mut graph = GraphHash::new_with_range(log, graph_type, from, to);
+
+        // Set tick character
+        if let Some(tick_char) = cli.tick.chars().next() {
+            graph.set_tick(tick_char);
+        }
+
+        graph.set_wide(cli.wide);
+        graph.set_values(values);
+        for hline in &hlines {
+            graph.add_hline(*hline);
+        }
+        graph
+    };
+
+    let graph = new_graph(log, from);
+
+    if let (Some(baseline), Some(baseline_log)) = (baseline, baseline_log) {
+        let from = if baseline.same_clock { Some(from.unwrap_or(graph.start())) } else { from };
+        let base = new_graph(baseline_log, from);
+        if cli.graph_data {
+            let _ = graph.write_data(&mut std::io::stdout().lock(), &[&base]);
+        } else {
+            graph.display_compare(&base, &baseline.label);
+        }
+        return;
+    }
 
-    // Set tick character
-    if let Some(tick_char) = cli.tick.chars().next() {
-        graph.set_tick(tick_char);
+    // Each file's graph starts where the merged one does, so buckets line up
+    if cli.per_file && cli.files.len() > 1 {
+        let start = Some(from.unwrap_or(graph.start()));
+        let files: Vec<(&String, GraphHash)> = cli.files.iter().map(|name| {
+            let entries = log.entries.iter()
+                .filter(|e| e.attrs.get(INPUT_ATTR) == Some(name))
+                .cloned()
+                .collect();
+            (name, new_graph(&CrunchLog { entries, parser_type: log.parser_type.clone() }, start))
+        }).collect();
+
+        if cli.graph_data {
+            let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+            println!("# start\tall\t{}", names.join("\t"));
+            let columns: Vec<&GraphHash> = files.iter().map(|(_, g)| g).collect();
+            let _ = graph.write_data(&mut std::io::stdout().lock(), &columns);
+        } else {
+            for (name, file_graph) in &files {
+                println!("==> {} <==", name);
+                file_graph.display();
+            }
+        }
+        return;
     }
 
-    graph.set_wide(cli.wide);
+    if cli.graph_data {
+        let _ = graph.write_data(&mut std::io::stdout().lock(), &[]);
+        return;
+    }
     graph.display();
 }
 
-fn parse_datetime(datetime_str: &str) -> Result<DateTime<Local>, String> {
+fn parse_datetime(datetime_str: &str) -> Result<(NaiveDateTime, &'static str), String> {
     // Try parsing "YYYY-MM-DD HH:MM:SS"
     if let Ok(naive_dt) = NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%d %H:%M:%S") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, *Local::now().offset()));
+        return Ok((naive_dt, "second"));
     }
 
-    // Try parsing "YYYY-MM-DD" (assume start of day)
+    // Try parsing "YYYY-MM-DD" (start of day)
     if let Ok(naive_date) = NaiveDate::parse_from_str(datetime_str, "%Y-%m-%d") {
         let naive_time = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
-        let naive_dt = NaiveDateTime::new(naive_date, naive_time);
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, *Local::now().offset()));
+        return Ok((NaiveDateTime::new(naive_date, naive_time), "day"));
     }
 
     // Try parsing "YYYY-MM-DD HH:MM"
     if let Ok(naive_dt) = NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%d %H:%M") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, *Local::now().offset()));
+        return Ok((naive_dt, "minute"));
     }
 
     Err(format!("Invalid datetime format: '{}'. Expected 'YYYY-MM-DD HH:MM:SS', 'YYYY-MM-DD HH:MM', or 'YYYY-MM-DD'", datetime_str))
 }
 
-fn apply_time_filters(mut log: CrunchLog, cli: &Cli) -> CrunchLog {
-    if cli.from.is_none() && cli.to.is_none() {
+/// A --from (`end` false) or --to (`end` true) value rounded to a whole unit:
+/// the first second of it for --from, the last for --to, so "--to 2024-01-05"
+/// takes in all of that day. "auto" rounds to the precision the value is
+/// written in, "none" leaves it as written
+fn parse_time_bound(datetime_str: &str, end: bool, round: &str) -> Result<DateTime<Local>, String> {
+    let (naive_dt, precision) = parse_datetime(datetime_str)?;
+    let unit = match round {
+        "auto" => precision,
+        "none" => "second",
+        unit => unit,
+    };
+
+    let time = naive_dt.time();
+    let (start, length) = match unit {
+        "minute" => (time.with_second(0), TimeDelta::minutes(1)),
+        "hour" => (time.with_minute(0).and_then(|t| t.with_second(0)), TimeDelta::hours(1)),
+        "day" => (NaiveTime::from_hms_opt(0, 0, 0), TimeDelta::days(1)),
+        _ => (Some(time), TimeDelta::seconds(1)),
+    };
+    let start = NaiveDateTime::new(naive_dt.date(), start.unwrap_or(time));
+    let bound = if end { start + length - TimeDelta::seconds(1) } else { start };
+    Ok(DateTime::from_naive_utc_and_offset(bound, *Local::now().offset()))
+}
+
+/// Longest --time-offset in seconds, a century, as for --compare
+const MAX_TIME_OFFSET: i64 = MAX_COMPARE_OFFSET;
+
+fn parse_offset(offset_str: &str) -> Result<i64, String> {
+    let err = || format!("Invalid time offset: '{}'. Expected '+HH:MM', '-HH:MM:SS' or seconds like '+90'", offset_str);
+
+    let (sign, rest) = match offset_str.chars().next() {
+        Some('-') => (-1, &offset_str[1..]),
+        Some('+') => (1, &offset_str[1..]),
+        _ => (1, offset_str),
+    };
+
+    let parts: Vec<&str> = rest.split(':').collect();
+    let values: Vec<i64> = parts.iter()
+        .map(|p| p.parse::<i64>().map_err(|_| err()))
+        .collect::<Result<_, _>>()?;
+
+    let seconds = match values.as_slice() {
+        [secs] => Some(*secs),
+        [hours, minutes] => hours.checked_mul(3600).zip(minutes.checked_mul(60)).and_then(|(h, m)| h.checked_add(m)),
+        [hours, minutes, secs] => hours.checked_mul(3600).zip(minutes.checked_mul(60))
+            .and_then(|(h, m)| h.checked_add(m)).and_then(|hm| hm.checked_add(*secs)),
+        _ => return Err(err()),
+    };
+
+    seconds.filter(|seconds| seconds.unsigned_abs() <= MAX_TIME_OFFSET as u64)
+        .map(|seconds| sign * seconds)
+        .ok_or_else(|| format!("Time offset '{}' is out of range, at most 100 years", offset_str))
+}
+
+fn apply_skew_correction(mut log: CrunchLog, cli: &Cli) -> CrunchLog {
+    if !cli.skew_correct {
         return log;
     }
 
-    let from_dt = cli.from.as_ref().and_then(|s| {
-        match parse_datetime(s) {
-            Ok(dt) => Some(dt),
-            Err(e) => {
-                eprintln!("Error parsing --from: {}", e);
-                std::process::exit(1);
-            }
-        }
-    });
+    let mut skew = ClockSkew::from_log(&log);
+    skew.set_threshold(cli.skew_threshold);
 
-    let to_dt = cli.to.as_ref().and_then(|s| {
-        match parse_datetime(s) {
-            Ok(dt) => Some(dt),
-            Err(e) => {
-                eprintln!("Error parsing --to: {}", e);
-                std::process::exit(1);
-            }
-        }
-    });
+    for host in skew.skewed_hosts() {
+        info!("Correcting clock of {} by {:+}s", host, -skew.offsets()[host].offset);
+    }
 
-    log.filter_by_time(from_dt, to_dt);
+    skew.apply(&mut log);
+    log
+}
+
+fn apply_time_filters(mut log: CrunchLog, cli: &Cli) -> CrunchLog {
+    if cli.from.is_none() && cli.to.is_none() && !cli.only_undated {
+        return log;
+    }
+
+    let undated = log.undated_count();
+    let keep = time_filter(cli);
+    log.entries.retain(|entry| keep(entry));
+
+    if cli.only_undated {
+        info!("Kept {} undated entries", undated);
+    } else if !cli.include_undated && undated > 0 {
+        warn!("Left out {} undated entries, use --include-undated to keep them", undated);
+    }
     log
 }
@@ -0,0 +1,119 @@
+use crate::follow::FilePosition;
+use crate::hash::SuperHash;
+use crate::log_entry::LogEntry;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// A pattern count carried over between runs, with one sample message
+#[derive(Debug, Clone)]
+pub struct SavedPattern {
+    pub key: String,
+    pub count: usize,
+    pub sample: String,
+}
+
+/// Where `--follow` left off and what it had counted, so a restarted run
+/// resumes instead of counting the file again. Stored as JSON:
+/// {"version": 1, "path": ..., "inode": n, "offset": n, "format": ..., "mode": ...,
+///  "entries": n, "patterns": {"<key>": {"count": n, "sample": ...}}}
+#[derive(Debug, Clone)]
+pub struct FollowState {
+    pub path: String,
+    pub position: FilePosition,
+    /// Name of the detected parser, so the format need not be detected again
+    pub format: String,
+    /// Report the counts belong to: "hash", "daemon" or "host"
+    pub mode: String,
+    pub entries: usize,
+    pub patterns: Vec<SavedPattern>,
+}
+
+impl FollowState {
+    pub fn from_hash(path: &str, position: FilePosition, format: &str, mode: &str, entries: usize, hash: &SuperHash) -> Self {
+        let patterns = hash.patterns()
+            .map(|(key, samples)| SavedPattern {
+                key: key.to_string(),
+                count: hash.count(key),
+                sample: samples.first().map(|e| e.log_entry.clone()).unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            path: path.to_string(),
+            position,
+            format: format.to_string(),
+            mode: mode.to_string(),
+            entries,
+            patterns,
+        }
+    }
+
+    /// The saved state, or None when the file does not exist yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let not_state = || anyhow!("{} is not a follow state file", path.display());
+        let text = |name: &str| json.get(name).and_then(|v| v.as_str()).map(str::to_string).ok_or_else(not_state);
+        let number = |name: &str| json.get(name).and_then(|v| v.as_u64()).ok_or_else(not_state);
+
+        let patterns = json.get("patterns")
+            .and_then(|p| p.as_object())
+            .ok_or_else(not_state)?
+            .iter()
+            .map(|(key, value)| SavedPattern {
+                key: key.clone(),
+                count: value.get("count").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+                sample: value.get("sample").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            })
+            .collect();
+
+        Ok(Some(Self {
+            path: text("path")?,
+            position: FilePosition {
+                inode: number("inode")?,
+                offset: number("offset")?,
+            },
+            format: text("format")?,
+            mode: text("mode")?,
+            entries: number("entries")? as usize,
+            patterns,
+        }))
+    }
+
+    /// Written to a temporary file first, so an interrupted run never leaves half a state
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let patterns: serde_json::Map<String, serde_json::Value> = self.patterns.iter()
+            .map(|p| (p.key.clone(), serde_json::json!({
+                "count": p.count,
+                "sample": p.sample,
+            })))
+            .collect();
+        let json = serde_json::json!({
+            "version": 1,
+            "path": self.path,
+            "inode": self.position.inode,
+            "offset": self.position.offset,
+            "format": self.format,
+            "mode": self.mode,
+            "entries": self.entries,
+            "patterns": patterns,
+        });
+
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&json)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Put the saved counts back into a hash
+    pub fn restore(&self, hash: &mut SuperHash) {
+        for pattern in &self.patterns {
+            let mut sample = LogEntry::new();
+            sample.log_entry = pattern.sample.clone();
+            hash.set_count(pattern.key.clone(), pattern.count, sample);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use regex::Regex;
+use std::ops::Range;
+
+/// Entries matching a pattern together with the entries around them, like
+/// `grep -C`. Positions are indices into the log as loaded, so the context
+/// comes from the original stream even when the matches are restricted further
+pub struct ContextMatches {
+    ranges: Vec<Range<usize>>,
+    matches: Vec<usize>,
+}
+
+impl ContextMatches {
+    /// Match `pattern` against the normalized line of each entry accepted by
+    /// `keep`, taking `context` entries before and after each match.
+    /// Overlapping or adjacent ranges are merged
+    pub fn from_log<F>(log: &CrunchLog, pattern: &Regex, context: usize, keep: F) -> Self
+    where
+        F: Fn(&LogEntry) -> bool,
+    {
+        let matches: Vec<usize> = log.entries.iter()
+            .enumerate()
+            .filter(|(_, entry)| keep(entry) && pattern.is_match(&entry.to_normalized()))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for &idx in &matches {
+            let range = idx.saturating_sub(context)..(idx + context + 1).min(log.entries.len());
+            match ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+
+        Self { ranges, matches }
+    }
+
+    /// Positions of the matching entries
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// Runs of positions to print, in stream order
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Print each run in normalized format, separated by "--" as grep does
+    pub fn display(&self, log: &CrunchLog) {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                println!("--");
+            }
+            for entry in &log.entries[range.clone()] {
+                println!("{}", entry.to_normalized());
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::log_entry::CrunchLog;
+use crate::filter::Filter;
+use crate::hash::{HashMode, SuperHash};
+use std::collections::HashSet;
+
+/// Patterns considered, by number of buckets they occur in; keeps the pairwise
+/// comparison bounded on logs with many distinct patterns
+const MAX_PATTERNS: usize = 300;
+
+/// Two hash patterns that tend to occur in the same time buckets
+#[derive(Debug, Clone)]
+pub struct Correlation {
+    pub first: String,
+    pub second: String,
+    /// Buckets containing the first, the second, and both patterns
+    pub first_buckets: usize,
+    pub second_buckets: usize,
+    pub together: usize,
+    /// How much more often the two occur together than if they were independent
+    pub lift: f64,
+}
+
+pub struct CorrelationReport {
+    correlations: Vec<Correlation>,
+    window: i64,
+    buckets: usize,
+}
+
+impl CorrelationReport {
+    /// Bucket the entries of each hash pattern into `window` second slots and
+    /// score each pair of patterns by lift: P(both) / (P(first) * P(second)),
+    /// over the buckets that contain any entry. Pairs seen together in fewer
+    /// than `min_together` buckets are left out
+    pub fn from_log(log: &CrunchLog, filter: Filter, window: i64, min_together: usize) -> Self {
+        let window = window.max(1);
+        let hash = SuperHash::from_log(log, HashMode::Hash, filter);
+
+        let mut all_buckets: HashSet<i64> = HashSet::new();
+        let mut patterns: Vec<(&str, HashSet<i64>)> = hash.patterns()
+            .map(|(key, entries)| {
+                let buckets: HashSet<i64> = entries.iter()
+                    .filter(|e| !e.is_undated())
+                    .map(|e| e.naive_datetime().and_utc().timestamp().div_euclid(window))
+                    .collect();
+                all_buckets.extend(&buckets);
+                (key, buckets)
+            })
+            .filter(|(_, buckets)| buckets.len() >= min_together.max(1))
+            .collect();
+        patterns.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        patterns.truncate(MAX_PATTERNS);
+
+        let total = all_buckets.len() as f64;
+        let mut correlations = Vec::new();
+        for (i, (first, first_buckets)) in patterns.iter().enumerate() {
+            for (second, second_buckets) in &patterns[i + 1..] {
+                let together = first_buckets.intersection(second_buckets).count();
+                if together < min_together.max(1) {
+                    continue;
+                }
+                let lift = together as f64 * total / (first_buckets.len() as f64 * second_buckets.len() as f64);
+                if lift <= 1.0 {
+                    continue;
+                }
+                correlations.push(Correlation {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    first_buckets: first_buckets.len(),
+                    second_buckets: second_buckets.len(),
+                    together,
+                    lift,
+                });
+            }
+        }
+        correlations.sort_by(|a, b| b.lift.total_cmp(&a.lift)
+            .then(b.together.cmp(&a.together))
+            .then(a.first.cmp(&b.first)));
+
+        Self {
+            correlations,
+            window,
+            buckets: all_buckets.len(),
+        }
+    }
+
+    pub fn correlations(&self) -> &[Correlation] {
+        &self.correlations
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.correlations.is_empty() {
+            println!("No correlated patterns found");
+            return;
+        }
+
+        println!("Patterns occurring in the same {}s buckets ({} buckets with entries):", self.window, self.buckets);
+        println!();
+        for correlation in self.correlations.iter().take(limit) {
+            println!("lift {:.1}\ttogether in {} buckets ({} and {} in all)",
+                correlation.lift, correlation.together, correlation.first_buckets, correlation.second_buckets);
+            println!("\t{}", correlation.first);
+            println!("\t{}", correlation.second);
+            println!();
+        }
+    }
+}
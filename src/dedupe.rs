@@ -0,0 +1,78 @@
+use crate::log_entry::CrunchLog;
+use std::collections::HashMap;
+
+pub struct DuplicateLine {
+    pub line: String,
+    /// Total number of occurrences
+    pub count: usize,
+    /// Longest run of consecutive identical occurrences
+    pub longest_run: usize,
+}
+
+pub struct DuplicateReport {
+    lines: Vec<DuplicateLine>,
+    total: usize,
+    consecutive: usize,
+}
+
+impl DuplicateReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut consecutive = 0;
+        let mut run = 0;
+        let mut previous: Option<String> = None;
+
+        for entry in &log.entries {
+            let line = entry.to_normalized();
+
+            if previous.as_ref() == Some(&line) {
+                run += 1;
+                consecutive += 1;
+            } else {
+                run = 1;
+            }
+
+            let stats = counts.entry(line.clone()).or_insert((0, 0));
+            stats.0 += 1;
+            stats.1 = stats.1.max(run);
+            previous = Some(line);
+        }
+
+        let mut lines: Vec<DuplicateLine> = counts
+            .into_iter()
+            .filter(|(_, (count, _))| *count > 1)
+            .map(|(line, (count, longest_run))| DuplicateLine { line, count, longest_run })
+            .collect();
+        lines.sort_by(|a, b| b.count.cmp(&a.count).then(a.line.cmp(&b.line)));
+
+        Self {
+            lines,
+            total: log.entries.len(),
+            consecutive,
+        }
+    }
+
+    pub fn lines(&self) -> &[DuplicateLine] {
+        &self.lines
+    }
+
+    /// Number of entries that repeat the entry right before them
+    pub fn consecutive(&self) -> usize {
+        self.consecutive
+    }
+
+    pub fn display(&self) {
+        for dup in &self.lines {
+            println!("{}:\t(run {})\t{}", dup.count, dup.longest_run, dup.line);
+        }
+
+        println!();
+        let percent = if self.total > 0 {
+            self.consecutive as f64 * 100.0 / self.total as f64
+        } else {
+            0.0
+        };
+        println!("Duplicated lines: {}\tConsecutive repeats: {} of {} entries ({:.1}%)",
+            self.lines.len(), self.consecutive, self.total, percent);
+    }
+}
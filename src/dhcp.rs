@@ -0,0 +1,121 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+/// One MAC/IP pairing with the time range it was seen in
+#[derive(Debug, Clone)]
+pub struct LeaseAssignment {
+    pub ip: String,
+    pub hostname: Option<String>,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub count: usize,
+}
+
+pub struct DhcpReport {
+    leases: BTreeMap<String, Vec<LeaseAssignment>>,
+    messages: HashMap<String, usize>,
+}
+
+impl DhcpReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // dhcpd: DHCPACK on 192.168.1.50 to 00:11:22:33:44:55 (laptop) via eth0
+        let dhcpd_re = Regex::new(r"DHCPACK on (?P<ip>\S+) to (?P<mac>[0-9a-fA-F:]{17})(?: \((?P<name>[^)]*)\))?").unwrap();
+        // Kea: DHCP4_LEASE_ALLOC [hwtype=1 00:11:22:33:44:55], cid=..., tid=...: lease 192.168.1.50 has been allocated
+        let kea_re = Regex::new(r"DHCP4_(?:LEASE_ALLOC|INIT_REALLOC|LEASE_REUSE) \[hwtype=\d+ (?P<mac>[0-9a-fA-F:]{17})\].*?lease (?P<ip>\S+) ").unwrap();
+        let message_re = Regex::new(r"^(DHCP[A-Z]+|DHCP[46]_[A-Z0-9_]+)").unwrap();
+
+        let mut leases: BTreeMap<String, Vec<LeaseAssignment>> = BTreeMap::new();
+        let mut messages: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            if let Some(caps) = message_re.captures(&entry.log_entry) {
+                *messages.entry(caps[1].to_string()).or_insert(0) += 1;
+            }
+
+            let caps = match dhcpd_re.captures(&entry.log_entry).or_else(|| kea_re.captures(&entry.log_entry)) {
+                Some(caps) => caps,
+                None => continue,
+            };
+
+            let mac = caps["mac"].to_lowercase();
+            let ip = caps["ip"].to_string();
+            let hostname = caps.name("name").map(|m| m.as_str().to_string());
+            let when = format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                entry.year, entry.month, entry.day, entry.hour, entry.minute, entry.second);
+
+            let assignments = leases.entry(mac).or_default();
+            // A new row whenever the device moves to a different address
+            match assignments.last_mut() {
+                Some(last) if last.ip == ip => {
+                    last.last_seen = when;
+                    last.count += 1;
+                    if hostname.is_some() {
+                        last.hostname = hostname;
+                    }
+                }
+                _ => assignments.push(LeaseAssignment {
+                    ip,
+                    hostname,
+                    first_seen: when.clone(),
+                    last_seen: when,
+                    count: 1,
+                }),
+            }
+        }
+
+        Self { leases, messages }
+    }
+
+    pub fn leases(&self) -> &BTreeMap<String, Vec<LeaseAssignment>> {
+        &self.leases
+    }
+
+    /// MAC addresses that held the given IP, in order of first assignment
+    pub fn macs_for_ip(&self, ip: &str) -> Vec<&str> {
+        self.leases
+            .iter()
+            .filter(|(_, assignments)| assignments.iter().any(|a| a.ip == ip))
+            .map(|(mac, _)| mac.as_str())
+            .collect()
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.leases.is_empty() && self.messages.is_empty() {
+            println!("No DHCP activity found");
+            return;
+        }
+
+        print_top("Messages", self.messages.clone(), limit);
+
+        println!("MAC to IP assignments:");
+        for (mac, assignments) in &self.leases {
+            for assignment in assignments {
+                let name = assignment.hostname.as_deref().map(|n| format!(" ({})", n)).unwrap_or_default();
+                println!("{}{}\t{}\t{} .. {}\t({} acks)",
+                    mac, name, assignment.ip, assignment.first_seen, assignment.last_seen, assignment.count);
+            }
+        }
+        println!();
+
+        // IPs handed to more than one device
+        let mut by_ip: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (mac, assignments) in &self.leases {
+            for assignment in assignments {
+                let macs = by_ip.entry(assignment.ip.as_str()).or_default();
+                if !macs.contains(&mac.as_str()) {
+                    macs.push(mac.as_str());
+                }
+            }
+        }
+        let shared: Vec<_> = by_ip.iter().filter(|(_, macs)| macs.len() > 1).collect();
+        if !shared.is_empty() {
+            println!("IPs assigned to multiple devices:");
+            for (ip, macs) in shared {
+                println!("{}\t{}", ip, macs.join(", "));
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::auth::AuthReport;
+use crate::filter::{Filter, FilterOptions};
+use crate::hash::{HashMode, SampleMode, SuperHash};
+use crate::log_entry::CrunchLog;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Digest sections, in the order they appear
+pub const SECTIONS: [&str; 4] = ["daemon", "host", "auth", "hash"];
+
+/// Limits for one digest section
+#[derive(Debug, Clone)]
+pub struct SectionConfig {
+    pub enabled: bool,
+    /// Rows shown
+    pub top: usize,
+    /// Rows counted fewer times than this are left out (daemon, host, hash)
+    pub min_count: usize,
+    /// Hash patterns seen this often or less are shown as a sample line
+    pub lowcount: usize,
+}
+
+impl Default for SectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            top: 20,
+            min_count: 0,
+            lowcount: 3,
+        }
+    }
+}
+
+/// Digest settings, from an INI style file:
+///
+/// ```text
+/// logs = /var/log/messages /var/log/secure
+/// hours = 24
+/// mail_to = root
+/// [hash]
+/// top = 50
+/// [host]
+/// enabled = no
+/// ```
+#[derive(Debug, Clone)]
+pub struct DigestConfig {
+    pub logs: Vec<String>,
+    /// How far back from now entries are included
+    pub hours: i64,
+    pub mail_to: Option<String>,
+    pub subject: String,
+    /// File the digest is written to instead of standard output
+    pub output: Option<String>,
+    pub sendmail: String,
+    /// Settings of each of `SECTIONS`, in the same order
+    pub sections: Vec<SectionConfig>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            logs: Vec::new(),
+            hours: 24,
+            mail_to: None,
+            subject: "glancelog digest".to_string(),
+            output: None,
+            sendmail: "/usr/sbin/sendmail".to_string(),
+            sections: SECTIONS.iter().map(|_| SectionConfig::default()).collect(),
+        }
+    }
+}
+
+impl DigestConfig {
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read {}: {}", path, e))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config = Self::default();
+        let mut section: Option<usize> = None;
+
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                section = Some(SECTIONS.iter().position(|s| *s == name)
+                    .ok_or_else(|| anyhow!("line {}: unknown section [{}], expected one of {}", idx + 1, name, SECTIONS.join(", ")))?);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected key = value", idx + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let number = |value: &str| value.parse::<usize>()
+                .map_err(|_| anyhow!("line {}: {} must be a number", idx + 1, key));
+
+            match section {
+                None => match key {
+                    "logs" => config.logs.extend(value.split_whitespace().map(|s| s.to_string())),
+                    "hours" => config.hours = number(value)? as i64,
+                    "mail_to" => config.mail_to = Some(value.to_string()),
+                    "subject" => config.subject = value.to_string(),
+                    "output" => config.output = Some(value.to_string()),
+                    "sendmail" => config.sendmail = value.to_string(),
+                    _ => return Err(anyhow!("line {}: unknown setting '{}'", idx + 1, key)),
+                },
+                Some(s) => {
+                    let settings = &mut config.sections[s];
+                    match key {
+                        "enabled" => settings.enabled = matches!(value.to_lowercase().as_str(), "yes" | "true" | "on" | "1"),
+                        "top" => settings.top = number(value)?,
+                        "min_count" => settings.min_count = number(value)?,
+                        "lowcount" => settings.lowcount = number(value)?,
+                        _ => return Err(anyhow!("line {}: unknown section setting '{}'", idx + 1, key)),
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// One text report of the last hours of the configured logs, with a section
+/// per report, for mailing from cron like logwatch
+pub struct Digest {
+    text: String,
+}
+
+impl Digest {
+    /// Load the logs, keep the entries of the configured window and render
+    /// the enabled sections. Unreadable logs are noted in the digest rather
+    /// than failing the whole run
+    pub fn build(config: &DigestConfig, filter_dir: Option<&str>, use_filters: bool, filter_options: FilterOptions) -> Result<Self> {
+        let to = Local::now();
+        let from = to - Duration::hours(config.hours);
+
+        let mut out: Vec<u8> = Vec::new();
+        writeln!(out, "glancelog digest: {} to {}", from.format("%Y-%m-%d %H:%M"), to.format("%Y-%m-%d %H:%M"))?;
+        writeln!(out)?;
+
+        let mut merged: Option<CrunchLog> = None;
+        for path in &config.logs {
+            match CrunchLog::from_file(path) {
+                Ok(log) => {
+                    writeln!(out, "{}: {} entries ({})", path, log.entries.len(), log.parser_type)?;
+                    match merged.as_mut() {
+                        Some(m) => m.append(log),
+                        None => merged = Some(log),
+                    }
+                }
+                Err(e) => writeln!(out, "{}: not read: {}", path, e)?,
+            }
+        }
+        let mut log = merged.ok_or_else(|| anyhow!("None of the digest logs could be read"))?;
+        log.normalize_daemons();
+        log.filter_by_time(Some(from), Some(to));
+        writeln!(out, "Entries in the last {} hours: {}", config.hours, log.entries.len())?;
+        writeln!(out)?;
+        Self::write_sections(&mut out, &log, config, filter_dir, use_filters, filter_options)?;
+
+        Ok(Self {
+            text: String::from_utf8_lossy(&out).into_owned(),
+        })
+    }
+
+    /// The enabled sections for a log that is already loaded, under a
+    /// summary of its entries and time span instead of the digest window
+    pub fn from_log(log: &CrunchLog, config: &DigestConfig, filter_dir: Option<&str>, use_filters: bool, filter_options: FilterOptions) -> Result<Self> {
+        let mut out: Vec<u8> = Vec::new();
+        let timed: Vec<_> = log.entries.iter().filter(|e| !e.is_undated()).map(|e| e.naive_datetime()).collect();
+        match (timed.iter().min(), timed.iter().max()) {
+            (Some(first), Some(last)) => writeln!(out, "Entries: {} ({}), {} to {}", log.entries.len(), log.parser_type,
+                first.format("%Y-%m-%d %H:%M:%S"), last.format("%Y-%m-%d %H:%M:%S"))?,
+            _ => writeln!(out, "Entries: {} ({})", log.entries.len(), log.parser_type)?,
+        }
+        if timed.len() < log.entries.len() {
+            writeln!(out, "Undated: {}", log.entries.len() - timed.len())?;
+        }
+        writeln!(out)?;
+        Self::write_sections(&mut out, log, config, filter_dir, use_filters, filter_options)?;
+
+        Ok(Self {
+            text: String::from_utf8_lossy(&out).into_owned(),
+        })
+    }
+
+    fn write_sections<W: Write>(out: &mut W, log: &CrunchLog, config: &DigestConfig, filter_dir: Option<&str>, use_filters: bool, filter_options: FilterOptions) -> Result<()> {
+        let filter = |name: &str| if use_filters {
+            Filter::from_file_with_options(name, filter_dir, filter_options).unwrap_or_else(|_| Filter::new())
+        } else {
+            Filter::new()
+        };
+
+        for (name, settings) in SECTIONS.iter().zip(&config.sections) {
+            if !settings.enabled {
+                continue;
+            }
+            let (title, mode, filter_file) = match *name {
+                "daemon" => ("Entries by daemon", HashMode::Daemon, "daemon.stopwords"),
+                "host" => ("Entries by host", HashMode::Host, "host.stopwords"),
+                "hash" => ("Log patterns", HashMode::Hash, "hash.stopwords"),
+                _ => {
+                    writeln!(out, "=== Authentication ===")?;
+                    AuthReport::from_log(log).write_to(out, settings.top)?;
+                    continue;
+                }
+            };
+
+            writeln!(out, "=== {} ===", title)?;
+            let mut hash = SuperHash::from_log(log, mode, filter(filter_file));
+            hash.set_limit(Some(settings.top));
+            hash.set_min_count(settings.min_count);
+            hash.set_sample_threshold(settings.lowcount);
+            hash.set_sample_mode(match mode {
+                HashMode::Hash => SampleMode::Threshold,
+                _ => SampleMode::None,
+            });
+            hash.write_to(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Mail the digest when `mail_to` is set, write it to `output` when set,
+    /// and print it otherwise (cron mails standard output itself)
+    pub fn deliver(&self, config: &DigestConfig) -> Result<()> {
+        if let Some(path) = &config.output {
+            std::fs::write(path, &self.text)?;
+        }
+        if let Some(to) = &config.mail_to {
+            self.mail(config, to)?;
+        }
+        if config.output.is_none() && config.mail_to.is_none() {
+            print!("{}", self.text);
+        }
+        Ok(())
+    }
+
+    fn mail(&self, config: &DigestConfig, to: &str) -> Result<()> {
+        let mut child = Command::new(&config.sendmail)
+            .arg("-t")
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Cannot run {}: {}", config.sendmail, e))?;
+        {
+            let stdin = child.stdin.as_mut().ok_or_else(|| anyhow!("Cannot write to {}", config.sendmail))?;
+            write!(stdin, "To: {}\nSubject: {}\nDate: {}\nContent-Type: text/plain; charset=utf-8\n\n{}",
+                to, config.subject, Local::now().to_rfc2822(), self.text)?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", config.sendmail, status));
+        }
+        Ok(())
+    }
+}
+
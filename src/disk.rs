@@ -0,0 +1,150 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// A storage error or warning: a failed ATA, SCSI or NVMe command, a
+/// filesystem error, or a smartd finding
+#[derive(Debug, Clone)]
+pub struct DiskEvent {
+    pub timestamp: NaiveDateTime,
+    pub host: String,
+    /// The disk as logged, without partition numbers ("sda" for "sda1"),
+    /// or the ATA port or NVMe controller when no disk is named
+    pub device: String,
+    /// "ata", "scsi", "nvme", "block", "ext4", "xfs", "btrfs" or "smart"
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// Storage errors grouped by device, from kernel and smartd entries, so a
+/// failing disk stands out among everything else the log holds
+pub struct DiskReport {
+    events: Vec<DiskEvent>,
+}
+
+impl DiskReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // "ata1.00: failed command: READ FPDMA QUEUED", "ata1: hard resetting link", "ata1.00: error: { UNC }"
+        let ata = Regex::new(r"^(ata\d+)(?:\.\d+)?: (.*(?:exception|failed|error|ERR|resetting link|link down|timeout|limiting SATA link speed).*)$").unwrap();
+        // "sd 0:0:0:0: [sda] tag#0 FAILED Result: hostbyte=DID_OK driverbyte=DRIVER_SENSE",
+        // "sd 0:0:0:0: [sda] tag#0 Sense Key : Medium Error [current]"
+        let scsi = Regex::new(r"^sd \S+ \[(\w+)\] (.*(?:FAILED|Sense Key|Add\. Sense|error|timing out|rejecting I/O).*)$").unwrap();
+        // "nvme nvme0: I/O 123 QID 4 timeout, aborting", "nvme0n1: I/O Cmd(0x2) @ LBA 1234, 8 blocks, I/O Error (sct 0x2 / sc 0x81)"
+        let nvme = Regex::new(r"^(?:nvme )?(nvme\d+(?:n\d+)?): (.*(?:timeout|abort|reset|error|Error|down|not ready|failed).*)$").unwrap();
+        // "blk_update_request: I/O error, dev sda, sector 2048", "critical medium error, dev sda, sector 2048",
+        // "Buffer I/O error on dev sda1, logical block 0"
+        let block = Regex::new(r"(?:I/O|medium|target) error,? (?:on )?dev(?:ice)? ([\w-]+)").unwrap();
+        // "EXT4-fs error (device sda1): ext4_find_entry:1455: ...", "EXT4-fs (sda1): Remounting filesystem read-only"
+        let ext = Regex::new(r"^EXT[234]-fs (?:(error|warning|critical) \(device ([\w-]+)\)|\(([\w-]+)\): (?:.*(?:error|read-only|corrupt).*))").unwrap();
+        // "XFS (sdb1): Metadata corruption detected at xfs_dinode_verify+0x1a0/0x6f0", "XFS (dm-0): log I/O error -5"
+        let xfs = Regex::new(r"^XFS \(([\w-]+)\): .*(?:[Cc]orruption|error|[Ss]hut(?:ting)? down|failed|I/O)").unwrap();
+        let btrfs = Regex::new(r"^BTRFS (?:error|warning|critical) \(device ([\w-]+)").unwrap();
+        // "Device: /dev/sda [SAT], 8 Currently unreadable (pending) sectors", "Device: /dev/sda [SAT], FAILED SMART self-check. BACK UP DATA NOW!"
+        let smart = Regex::new(r"^Device: /dev/(\w+)(?: \[[^\]]*\])?, (.*(?:pending|[Uu]ncorrectable|FAILED|Prefailure|Critical Warning|error count increased|Reallocated|[Ss]elf-[Tt]est.*(?:failed|error)).*)$").unwrap();
+        let partition = Regex::new(r"^((?:[shv]|xv)d[a-z]+)\d+$|^((?:nvme\d+n|mmcblk)\d+)p\d+$").unwrap();
+
+        let mut events = Vec::new();
+        for entry in &log.entries {
+            let message = entry.log_entry.strip_prefix(": ").or_else(|| entry.log_entry.strip_prefix(" ")).unwrap_or(&entry.log_entry).trim_end();
+            // Kernel lines in syslog may still carry the time since boot
+            let message = match message.strip_prefix('[').and_then(|m| m.split_once("] ")) {
+                Some((uptime, rest)) if uptime.trim().parse::<f64>().is_ok() => rest,
+                _ => message,
+            };
+
+            let found = match entry.program() {
+                "kernel" => {
+                    if let Some(caps) = ata.captures(message) {
+                        Some(("ata", caps[1].to_string()))
+                    } else if let Some(caps) = scsi.captures(message) {
+                        Some(("scsi", caps[1].to_string()))
+                    } else if let Some(caps) = nvme.captures(message) {
+                        Some(("nvme", caps[1].to_string()))
+                    } else if let Some(caps) = block.captures(message) {
+                        Some(("block", caps[1].to_string()))
+                    } else if let Some(caps) = ext.captures(message) {
+                        caps.get(2).or_else(|| caps.get(3)).map(|device| ("ext4", device.as_str().to_string()))
+                    } else if let Some(caps) = xfs.captures(message) {
+                        Some(("xfs", caps[1].to_string()))
+                    } else {
+                        btrfs.captures(message).map(|caps| ("btrfs", caps[1].to_string()))
+                    }
+                }
+                "smartd" => smart.captures(message).map(|caps| ("smart", caps[1].to_string())),
+                _ => None,
+            };
+
+            if let Some((source, device)) = found {
+                events.push(DiskEvent {
+                    timestamp: entry.naive_datetime(),
+                    host: entry.host.clone(),
+                    device: disk_of(&partition, &device),
+                    source,
+                    message: message.to_string(),
+                });
+            }
+        }
+        events.sort_by_key(|e| e.timestamp);
+
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[DiskEvent] {
+        &self.events
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.events.is_empty() {
+            println!("No disk or filesystem errors found");
+            return;
+        }
+
+        // Hosts are only named when there is more than one
+        let many_hosts = self.events.iter().any(|e| e.host != self.events[0].host);
+        let device_name = |event: &DiskEvent| if many_hosts { format!("{} {}", event.host, event.device) } else { event.device.clone() };
+
+        let mut devices: HashMap<String, Vec<&DiskEvent>> = HashMap::new();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        for event in &self.events {
+            devices.entry(device_name(event)).or_default().push(event);
+            *sources.entry(event.source.to_string()).or_insert(0) += 1;
+        }
+
+        println!("Disk errors: {}\tDevices: {}", self.events.len(), devices.len());
+        println!();
+
+        print_top("Errors by device", devices.iter().map(|(device, events)| (device.clone(), events.len())).collect(), limit);
+        print_top("Errors by source", sources, limit);
+
+        let mut devices: Vec<(String, Vec<&DiskEvent>)> = devices.into_iter().collect();
+        devices.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+        for (device, events) in devices.iter().take(limit) {
+            let mut by_source: HashMap<&str, usize> = HashMap::new();
+            for event in events {
+                *by_source.entry(event.source).or_insert(0) += 1;
+            }
+            let mut by_source: Vec<(&str, usize)> = by_source.into_iter().collect();
+            by_source.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let by_source: Vec<String> = by_source.iter().map(|(source, count)| format!("{} {}", source, count)).collect();
+
+            let (first, last) = (events[0].timestamp, events[events.len() - 1].timestamp);
+            println!("{}: {} errors ({}), {} .. {}", device, events.len(), by_source.join(", "),
+                first.format("%Y-%m-%d %H:%M:%S"), last.format("%Y-%m-%d %H:%M:%S"));
+            for event in &events[events.len().saturating_sub(limit)..] {
+                println!("\t{}\t{}\t{}", event.timestamp.format("%Y-%m-%d %H:%M:%S"), event.source, event.message);
+            }
+            println!();
+        }
+    }
+}
+
+/// The whole disk for a partition: "sda1" is "sda", "nvme0n1p2" is "nvme0n1",
+/// "mmcblk0p1" is "mmcblk0"; anything else (dm-0, md0, ata1) as it is
+fn disk_of(partition: &Regex, device: &str) -> String {
+    partition.captures(device)
+        .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|disk| disk.as_str().to_string())
+        .unwrap_or_else(|| device.to_string())
+}
@@ -0,0 +1,151 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// A regex recognizing a DNS query or response line.
+/// Captures `client` and `domain`, optionally `qtype` and `rcode` (response lines).
+pub struct DnsPattern {
+    pub source: &'static str,
+    pub regex: Regex,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsEvent {
+    pub client: String,
+    pub domain: String,
+    pub qtype: String,
+    /// Response code for response/failure lines, None for plain queries
+    pub rcode: Option<String>,
+}
+
+pub struct DnsReport {
+    events: Vec<DnsEvent>,
+}
+
+impl DnsReport {
+    pub fn default_patterns() -> Vec<DnsPattern> {
+        let pattern = |source, re: &str| DnsPattern {
+            source,
+            regex: Regex::new(re).unwrap(),
+        };
+
+        vec![
+            // BIND: client @0x... 1.2.3.4#5353 (example.com): query: example.com IN A +E(0)K (10.0.0.1)
+            pattern("bind", r"client (?:@0x[0-9a-f]+ )?(?P<client>[^#\s]+)#\d+ \([^)]*\): query: (?P<domain>\S+) IN (?P<qtype>\S+)"),
+            // BIND query-errors: ... (bad.example): query failed (NXDOMAIN) for bad.example/IN/A at ...
+            pattern("bind", r"client (?:@0x[0-9a-f]+ )?(?P<client>[^#\s]+)#\d+ \([^)]*\): query failed \((?P<rcode>[A-Z]+)\) for (?P<domain>[^/\s]+)/IN/(?P<qtype>\w+)"),
+            // dnsmasq: query[A] example.com from 192.168.1.10 / reply bad.example is NXDOMAIN
+            pattern("dnsmasq", r"^query\[(?P<qtype>\w+)\] (?P<domain>\S+) from (?P<client>\S+)"),
+            pattern("dnsmasq", r"^(?:reply|cached|config) (?P<domain>\S+) is (?P<rcode>NXDOMAIN|NODATA\S*)"),
+            // Windows DNS debug log (client address is the entry host)
+            pattern("windows", r"^Rcv Q \[[^\]]*\] (?P<qtype>\w+) (?P<domain>\S+)"),
+            pattern("windows", r"^Snd R Q \[[^\]]*?(?P<rcode>[A-Z]+)\] (?P<qtype>\w+) (?P<domain>\S+)"),
+        ]
+    }
+
+    pub fn from_log(log: &CrunchLog) -> Self {
+        Self::from_log_with_patterns(log, &Self::default_patterns())
+    }
+
+    pub fn from_log_with_patterns(log: &CrunchLog, patterns: &[DnsPattern]) -> Self {
+        let mut events = Vec::new();
+        // Responses that don't name the client are attributed to the last client asking for the name
+        let mut last_client: HashMap<String, String> = HashMap::new();
+
+        for entry in &log.entries {
+            for pattern in patterns {
+                if let Some(caps) = pattern.regex.captures(&entry.log_entry) {
+                    let domain = caps.name("domain").map(|m| m.as_str()).unwrap_or("-")
+                        .trim_end_matches('.')
+                        .to_lowercase();
+                    let rcode = caps.name("rcode").map(|m| m.as_str().to_uppercase());
+                    let client = match (caps.name("client"), &rcode) {
+                        (Some(client), _) => client.as_str().to_string(),
+                        (None, Some(_)) => last_client.get(&domain).cloned().unwrap_or_else(|| entry.host.clone()),
+                        (None, None) => entry.host.clone(),
+                    };
+                    if rcode.is_none() {
+                        last_client.insert(domain.clone(), client.clone());
+                    }
+
+                    events.push(DnsEvent {
+                        client,
+                        domain,
+                        qtype: caps.name("qtype").map(|m| m.as_str()).unwrap_or("-").to_string(),
+                        rcode,
+                    });
+                    break;
+                }
+            }
+        }
+
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[DnsEvent] {
+        &self.events
+    }
+
+    /// NXDOMAIN responses relative to queries, in percent
+    pub fn nxdomain_rate(&self) -> f64 {
+        let queries = self.events.iter().filter(|e| e.rcode.is_none()).count();
+        if queries == 0 {
+            return 0.0;
+        }
+        let nxdomain = self.events.iter().filter(|e| e.rcode.as_deref() == Some("NXDOMAIN")).count();
+        nxdomain as f64 * 100.0 / queries as f64
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.events.is_empty() {
+            println!("No DNS queries found");
+            return;
+        }
+
+        let mut domains: HashMap<String, usize> = HashMap::new();
+        let mut clients: HashMap<String, usize> = HashMap::new();
+        let mut qtypes: HashMap<String, usize> = HashMap::new();
+        let mut rcodes: HashMap<String, usize> = HashMap::new();
+        let mut nx_clients: HashMap<String, usize> = HashMap::new();
+
+        for event in &self.events {
+            match &event.rcode {
+                None => {
+                    *domains.entry(event.domain.clone()).or_insert(0) += 1;
+                    *clients.entry(event.client.clone()).or_insert(0) += 1;
+                    *qtypes.entry(event.qtype.clone()).or_insert(0) += 1;
+                }
+                Some(rcode) => {
+                    *rcodes.entry(rcode.clone()).or_insert(0) += 1;
+                    if rcode == "NXDOMAIN" {
+                        *nx_clients.entry(event.client.clone()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let queries: usize = clients.values().sum();
+        println!("Queries: {}\tDomains: {}\tClients: {}\tNXDOMAIN rate: {:.1}%",
+            queries, domains.len(), clients.len(), self.nxdomain_rate());
+        println!();
+
+        // Rarely queried names are where DGA and beaconing domains hide
+        let mut rare: Vec<_> = domains.iter().filter(|(_, c)| **c == 1).map(|(d, _)| d.clone()).collect();
+        rare.sort();
+
+        print_top("Top queried domains", domains, limit);
+        print_top("Top clients", clients, limit);
+        print_top("Query types", qtypes, limit);
+        print_top("Response codes", rcodes, limit);
+        print_top("NXDOMAIN by client", nx_clients, limit);
+
+        if !rare.is_empty() {
+            println!("Rare domains ({} queried once):", rare.len());
+            for domain in rare.iter().take(limit) {
+                println!("1:\t{}", domain);
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::log_entry::CrunchLog;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Tokens shorter than this are too short for their entropy to mean much
+const MIN_TOKEN_LEN: usize = 16;
+/// Shortest domain label scored
+const MIN_LABEL_LEN: usize = 6;
+
+/// Shannon entropy in bits per character
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    let mut total = 0;
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    counts.values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// What gets scored
+#[derive(Debug, Clone)]
+pub enum EntropyTarget {
+    /// Message payloads
+    Message,
+    /// Long whitespace separated tokens (base64 blobs, hex strings, encoded paths)
+    Tokens,
+    /// Domain names in messages, scored on their longest label below the TLD (DGA, DNS tunnels)
+    Domains,
+    /// A structured field such as "file", "path" or "rrname"
+    Field(String),
+}
+
+impl EntropyTarget {
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "message" => EntropyTarget::Message,
+            "tokens" => EntropyTarget::Tokens,
+            "domains" => EntropyTarget::Domains,
+            field => EntropyTarget::Field(field.to_string()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            EntropyTarget::Message => "messages".to_string(),
+            EntropyTarget::Tokens => format!("tokens of {} or more characters", MIN_TOKEN_LEN),
+            EntropyTarget::Domains => "domain names".to_string(),
+            EntropyTarget::Field(name) => format!("'{}' fields", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoredValue {
+    pub value: String,
+    /// Bits per character
+    pub entropy: f64,
+    pub count: usize,
+}
+
+pub struct EntropyReport {
+    target: EntropyTarget,
+    values: Vec<ScoredValue>,
+    mean: f64,
+    stddev: f64,
+}
+
+impl EntropyReport {
+    pub fn from_log(log: &CrunchLog, target: EntropyTarget) -> Self {
+        let domain_re = Regex::new(r"(?i)\b(?:[a-z0-9-]{1,63}\.)+[a-z]{2,24}\b").unwrap();
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for entry in &log.entries {
+            let found: Vec<String> = match &target {
+                EntropyTarget::Message => vec![entry.log_entry.clone()],
+                EntropyTarget::Tokens => entry.log_entry.split_whitespace()
+                    .map(|t| t.trim_matches(|c: char| "\"'()[]{}<>,;".contains(c)))
+                    .filter(|t| t.chars().count() >= MIN_TOKEN_LEN)
+                    .map(|t| t.to_string())
+                    .collect(),
+                EntropyTarget::Domains => domain_re.find_iter(&entry.log_entry)
+                    .map(|m| m.as_str().to_lowercase())
+                    // Dotted numbers (addresses, versions) are not names
+                    .filter(|d| d.chars().any(|c| c.is_ascii_alphabetic()) && Self::domain_label(d).len() >= MIN_LABEL_LEN)
+                    .collect(),
+                EntropyTarget::Field(name) => entry.attrs.get(name).cloned().into_iter().collect(),
+            };
+            for value in found {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+
+        let mut values: Vec<ScoredValue> = counts.into_iter()
+            .map(|(value, count)| {
+                let scored = match target {
+                    EntropyTarget::Domains => Self::domain_label(&value),
+                    _ => value.as_str(),
+                };
+                ScoredValue {
+                    entropy: shannon_entropy(scored),
+                    value,
+                    count,
+                }
+            })
+            .collect();
+        values.sort_by(|a, b| b.entropy.total_cmp(&a.entropy).then(a.value.cmp(&b.value)));
+
+        // Spread over distinct values, so one chatty line does not set the baseline
+        let n = values.len().max(1) as f64;
+        let mean = values.iter().map(|v| v.entropy).sum::<f64>() / n;
+        let stddev = (values.iter().map(|v| (v.entropy - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+        Self {
+            target,
+            values,
+            mean,
+            stddev,
+        }
+    }
+
+    /// The longest label left of the top-level domain
+    fn domain_label(domain: &str) -> &str {
+        let labels: Vec<&str> = domain.split('.').collect();
+        labels[..labels.len().saturating_sub(1)].iter()
+            .copied()
+            .max_by_key(|l| l.len())
+            .unwrap_or(domain)
+    }
+
+    pub fn values(&self) -> &[ScoredValue] {
+        &self.values
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.values.is_empty() {
+            println!("No {} found", self.target.describe());
+            return;
+        }
+
+        println!("Entropy of {} ({} distinct): mean {:.2}, standard deviation {:.2} bits per character",
+            self.target.describe(), self.values.len(), self.mean, self.stddev);
+        println!();
+        println!("Highest entropy (bits/char, standard deviations above the mean, count):");
+        for value in self.values.iter().take(limit) {
+            let z = if self.stddev > 0.0 { (value.entropy - self.mean) / self.stddev } else { 0.0 };
+            println!("{:.2}\t{:+.1}\t{}:\t{}", value.entropy, z, value.count, value.value);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,124 @@
+use crate::log_entry::LogEntry;
+
+// Windows events worth knowing by number: (provider, event ID, name)
+const WINDOWS_EVENTS: &[(&str, &str, &str)] = &[
+    ("Security-Auditing", "1102", "The audit log was cleared"),
+    ("Security-Auditing", "4624", "An account was successfully logged on"),
+    ("Security-Auditing", "4625", "An account failed to log on"),
+    ("Security-Auditing", "4634", "An account was logged off"),
+    ("Security-Auditing", "4647", "User initiated logoff"),
+    ("Security-Auditing", "4648", "A logon was attempted using explicit credentials"),
+    ("Security-Auditing", "4672", "Special privileges assigned to new logon"),
+    ("Security-Auditing", "4688", "A new process has been created"),
+    ("Security-Auditing", "4697", "A service was installed in the system"),
+    ("Security-Auditing", "4698", "A scheduled task was created"),
+    ("Security-Auditing", "4719", "System audit policy was changed"),
+    ("Security-Auditing", "4720", "A user account was created"),
+    ("Security-Auditing", "4722", "A user account was enabled"),
+    ("Security-Auditing", "4723", "An attempt was made to change an account's password"),
+    ("Security-Auditing", "4724", "An attempt was made to reset an account's password"),
+    ("Security-Auditing", "4725", "A user account was disabled"),
+    ("Security-Auditing", "4726", "A user account was deleted"),
+    ("Security-Auditing", "4728", "A member was added to a security-enabled global group"),
+    ("Security-Auditing", "4732", "A member was added to a security-enabled local group"),
+    ("Security-Auditing", "4738", "A user account was changed"),
+    ("Security-Auditing", "4740", "A user account was locked out"),
+    ("Security-Auditing", "4756", "A member was added to a security-enabled universal group"),
+    ("Security-Auditing", "4767", "A user account was unlocked"),
+    ("Security-Auditing", "4768", "A Kerberos authentication ticket (TGT) was requested"),
+    ("Security-Auditing", "4769", "A Kerberos service ticket was requested"),
+    ("Security-Auditing", "4771", "Kerberos pre-authentication failed"),
+    ("Security-Auditing", "4776", "The computer attempted to validate the credentials for an account"),
+    ("Security-Auditing", "4798", "A user's local group membership was enumerated"),
+    ("Security-Auditing", "5140", "A network share object was accessed"),
+    ("Security-Auditing", "5145", "A network share object was checked for access"),
+    ("Security-Auditing", "5156", "The Windows Filtering Platform has permitted a connection"),
+    ("Security-Auditing", "5157", "The Windows Filtering Platform has blocked a connection"),
+    ("Eventlog", "1102", "The audit log was cleared"),
+    ("Eventlog", "6005", "The Event log service was started"),
+    ("Eventlog", "6006", "The Event log service was stopped"),
+    ("EventLog", "6005", "The Event log service was started"),
+    ("EventLog", "6006", "The Event log service was stopped"),
+    ("EventLog", "6008", "The previous system shutdown was unexpected"),
+    ("Service Control Manager", "7034", "A service terminated unexpectedly"),
+    ("Service Control Manager", "7036", "A service entered the running or stopped state"),
+    ("Service Control Manager", "7040", "The start type of a service was changed"),
+    ("Service Control Manager", "7045", "A service was installed in the system"),
+    ("Kernel-Power", "41", "The system rebooted without cleanly shutting down first"),
+    ("Kernel-General", "12", "The operating system started"),
+    ("Kernel-General", "13", "The operating system is shutting down"),
+    ("User32", "1074", "A process initiated a restart or shutdown"),
+    ("Application Error", "1000", "Application crashed"),
+    ("Windows Error Reporting", "1001", "Windows Error Reporting fault bucket"),
+    ("Sysmon", "1", "Process creation"),
+    ("Sysmon", "3", "Network connection"),
+    ("Sysmon", "11", "File created"),
+    ("Sysmon", "22", "DNS query"),
+];
+
+// Cisco ASA/FTD messages worth knowing by number
+const ASA_MESSAGES: &[(&str, &str)] = &[
+    ("106001", "Inbound TCP connection denied"),
+    ("106006", "Deny inbound UDP"),
+    ("106007", "Deny inbound UDP due to DNS query"),
+    ("106014", "Deny inbound ICMP"),
+    ("106015", "Deny TCP (no connection)"),
+    ("106021", "Deny protocol reverse path check"),
+    ("106023", "Deny by access-group"),
+    ("106100", "Access list hit"),
+    ("113004", "AAA user authentication successful"),
+    ("113005", "AAA user authentication rejected"),
+    ("113008", "AAA transaction status accepted"),
+    ("113015", "AAA user authentication rejected (invalid password)"),
+    ("113019", "AnyConnect/VPN session disconnected"),
+    ("302013", "Built TCP connection"),
+    ("302014", "Teardown TCP connection"),
+    ("302015", "Built UDP connection"),
+    ("302016", "Teardown UDP connection"),
+    ("302020", "Built ICMP connection"),
+    ("302021", "Teardown ICMP connection"),
+    ("305011", "Built dynamic translation"),
+    ("305012", "Teardown dynamic translation"),
+    ("313001", "Denied ICMP"),
+    ("313005", "No matching connection for ICMP error message"),
+    ("410001", "Dropped UDP DNS reply"),
+    ("419002", "Duplicate TCP SYN"),
+    ("605004", "Login denied"),
+    ("605005", "Login permitted"),
+    ("611101", "User authentication succeeded"),
+    ("611102", "User authentication failed"),
+    ("710003", "TCP access denied by management access rule"),
+    ("710005", "TCP request discarded"),
+    ("722022", "AnyConnect connection established"),
+    ("722023", "AnyConnect connection terminated"),
+    ("722051", "AnyConnect address assigned"),
+    ("733100", "Threat detection rate exceeded"),
+];
+
+/// The event identifier of an entry and its name, if a parser extracted one:
+/// Windows event IDs ("Security-Auditing/4625"), Cisco message IDs
+/// ("ASA-6-302013"), MySQL error codes ("MY-010119") and IDS signature IDs
+/// ("sid 2010935"). The name is None when the ID is not a well-known one
+pub fn event_id(entry: &LogEntry) -> Option<(String, Option<String>)> {
+    let attr = |name: &str| entry.attrs.get(name).map(|v| v.as_str());
+
+    if let Some(message_id) = attr("message_id") {
+        let id = entry.daemon.trim_start_matches('%').trim_end_matches(':').to_string();
+        let name = ASA_MESSAGES.iter().find(|(m, _)| *m == message_id).map(|(_, name)| name.to_string());
+        return Some((id, name));
+    }
+    if let Some(event_id) = attr("event_id") {
+        let provider = entry.daemon.trim_start_matches("Microsoft-Windows-");
+        let name = WINDOWS_EVENTS.iter()
+            .find(|(p, id, _)| *p == provider && *id == event_id)
+            .map(|(_, _, name)| name.to_string());
+        return Some((format!("{}/{}", provider, event_id), name));
+    }
+    if let Some(code) = attr("error_code") {
+        return Some((code.to_string(), None));
+    }
+    if let Some(sid) = attr("signature_id") {
+        return Some((format!("sid {}", sid), attr("signature").map(|s| s.to_string())));
+    }
+    None
+}
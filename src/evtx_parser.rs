@@ -1,7 +1,9 @@
 use crate::log_entry::LogEntry;
 use anyhow::{Result, anyhow};
+use log::warn;
 use chrono::{DateTime, Datelike, Local, Timelike};
 use evtx::{EvtxParser, SerializedEvtxRecord};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 pub struct EvtxLogParser;
@@ -29,18 +31,15 @@ impl EvtxLogParser {
                     }
                 }
                 Err(e) => {
-                    eprintln!("Warning: Failed to read EVTX record {}: {}", total_records, e);
+                    warn!("Failed to read EVTX record {}: {}", total_records, e);
                 }
             }
         }
 
         if entries.is_empty() {
-            eprintln!("Processed {} records but couldn't parse any successfully.", total_records);
-            if !parse_errors.is_empty() {
-                eprintln!("Sample errors:");
-                for err in parse_errors {
-                    eprintln!("  - {}", err);
-                }
+            warn!("Processed {} records but couldn't parse any successfully", total_records);
+            for err in parse_errors {
+                warn!("EVTX {}", err);
             }
             return Err(anyhow!("No valid EVTX records found"));
         }
@@ -166,6 +165,17 @@ impl EvtxLogParser {
         // Prepend level to log message
         log_message = format!("[{}] {}", level_str, log_message);
 
+        let mut attrs = BTreeMap::new();
+        attrs.insert("event_id".to_string(), event_id.to_string());
+        // The account acted on (logons, lockouts), else the one acting;
+        // "-" stands for none
+        let user = ["TargetUserName", "SubjectUserName"].iter()
+            .filter_map(|field| event.get("EventData").and_then(|d| d.get(*field)).and_then(|u| u.as_str()))
+            .find(|user| !user.is_empty() && *user != "-");
+        if let Some(user) = user {
+            attrs.insert("user".to_string(), user.to_string());
+        }
+
         Ok(LogEntry {
             year: local_time.year(),
             month: local_time.month(),
@@ -176,6 +186,8 @@ impl EvtxLogParser {
             host: computer,
             daemon: provider,
             log_entry: log_message,
+            severity: None,
+            attrs,
         })
     }
 
@@ -0,0 +1,107 @@
+use crate::log_entry::CrunchLog;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct JailStats {
+    pub found: usize,
+    pub bans: usize,
+    pub unbans: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IpBans {
+    pub bans: usize,
+    pub jails: Vec<String>,
+    pub first_ban: String,
+    pub last_ban: String,
+}
+
+pub struct BanReport {
+    jails: BTreeMap<String, JailStats>,
+    ips: BTreeMap<String, IpBans>,
+    per_day: BTreeMap<String, usize>,
+}
+
+impl BanReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // "NOTICE [sshd] Ban 1.2.3.4", "INFO [sshd] Found 1.2.3.4 - 2023-11-14 10:00:00"
+        let re = Regex::new(r"\[([^\]]+)\] (Found|Ban|Unban|Restore Ban) (\S+)").unwrap();
+
+        let mut report = Self {
+            jails: BTreeMap::new(),
+            ips: BTreeMap::new(),
+            per_day: BTreeMap::new(),
+        };
+
+        for entry in &log.entries {
+            let caps = match re.captures(&entry.log_entry) {
+                Some(caps) => caps,
+                None => continue,
+            };
+            let jail = caps[1].to_string();
+            let ip = caps[3].to_string();
+            let stats = report.jails.entry(jail.clone()).or_default();
+
+            match &caps[2] {
+                "Found" => stats.found += 1,
+                "Unban" => stats.unbans += 1,
+                _ => {
+                    stats.bans += 1;
+
+                    let when = format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                        entry.year, entry.month, entry.day, entry.hour, entry.minute, entry.second);
+                    *report.per_day.entry(when[..10].to_string()).or_insert(0) += 1;
+
+                    let ip_bans = report.ips.entry(ip).or_default();
+                    ip_bans.bans += 1;
+                    if !ip_bans.jails.contains(&jail) {
+                        ip_bans.jails.push(jail);
+                    }
+                    if ip_bans.first_ban.is_empty() {
+                        ip_bans.first_ban = when.clone();
+                    }
+                    ip_bans.last_ban = when;
+                }
+            }
+        }
+
+        report
+    }
+
+    pub fn jails(&self) -> &BTreeMap<String, JailStats> {
+        &self.jails
+    }
+
+    pub fn ips(&self) -> &BTreeMap<String, IpBans> {
+        &self.ips
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.jails.is_empty() {
+            println!("No fail2ban activity found");
+            return;
+        }
+
+        println!("Jail\tFound\tBans\tUnbans");
+        for (jail, stats) in &self.jails {
+            println!("{}\t{}\t{}\t{}", jail, stats.found, stats.bans, stats.unbans);
+        }
+        println!();
+
+        let mut ips: Vec<_> = self.ips.iter().collect();
+        ips.sort_by(|a, b| b.1.bans.cmp(&a.1.bans).then(a.0.cmp(b.0)));
+
+        println!("Top banned IPs:");
+        for (ip, bans) in ips.into_iter().take(limit) {
+            println!("{}:\t{}\t[{}]\t{} .. {}", bans.bans, ip, bans.jails.join(","), bans.first_ban, bans.last_ban);
+        }
+        println!();
+
+        println!("Bans per day:");
+        for (day, count) in &self.per_day {
+            println!("{}:\t{}", count, day);
+        }
+        println!();
+    }
+}
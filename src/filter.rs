@@ -1,8 +1,10 @@
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 use std::fs::{File, create_dir_all};
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use anyhow::Result;
+use log::{debug, warn};
+use crate::packs::pack_files;
 
 // Embedded default filter files
 const EMBEDDED_HASH_STOPWORDS: &str = include_str!("../filters/hash.stopwords");
@@ -10,6 +12,28 @@ const EMBEDDED_WORDS_STOPWORDS: &str = include_str!("../filters/words.stopwords"
 const EMBEDDED_DAEMON_STOPWORDS: &str = include_str!("../filters/daemon.stopwords");
 const EMBEDDED_HOST_STOPWORDS: &str = include_str!("../filters/host.stopwords");
 
+// Texts a filter pattern should never consume whole
+const PROBE_TEXTS: [&str; 2] = ["Accepted password for alice from 192.0.2.10 port 52144 ssh2", "x"];
+
+/// How filter file patterns are compiled. These are the defaults for every
+/// file; `@ignorecase` and `@anchored` lines change them for the rest of a file
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilterOptions {
+    /// Match regardless of case, as if every pattern started with (?i)
+    pub ignore_case: bool,
+    /// A pattern must match the whole text it is applied to (the daemon and
+    /// message line in hash mode, the daemon or host name), not a part of it
+    pub anchored: bool,
+}
+
+impl FilterOptions {
+    /// Compile a pattern with these options
+    pub fn compile(&self, pattern: &str) -> std::result::Result<Regex, regex::Error> {
+        let pattern = if self.anchored { format!("^(?:{})$", pattern) } else { pattern.to_string() };
+        RegexBuilder::new(&pattern).case_insensitive(self.ignore_case).build()
+    }
+}
+
 pub struct Filter {
     stopwords: Vec<Regex>,
 }
@@ -21,28 +45,97 @@ impl Filter {
         }
     }
 
+    /// A filter built in code from regex patterns, applied in the given order
+    pub fn from_patterns<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut filter = Self::new();
+        for pattern in patterns {
+            filter.add_pattern(pattern.as_ref())?;
+        }
+        Ok(filter)
+    }
+
+    /// Append a pattern, applied after those already loaded
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
+        let re = Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid regex '{}': {}", pattern, e))?;
+        self.stopwords.push(re);
+        Ok(())
+    }
+
+    /// The loaded patterns, in the order they are applied
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.stopwords.iter().map(|re| re.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.stopwords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stopwords.is_empty()
+    }
+
     pub fn from_file(filename: &str) -> Result<Self> {
         Self::from_file_with_dir(filename, None)
     }
 
     pub fn from_file_with_dir(filename: &str, custom_dir: Option<&str>) -> Result<Self> {
-        let mut paths = Vec::new();
+        Self::from_file_with_options(filename, custom_dir, FilterOptions::default())
+    }
+
+    /// Like `from_file_with_dir`, compiling the patterns with the given options
+    /// unless a file sets its own
+    pub fn from_file_with_options(filename: &str, custom_dir: Option<&str>, options: FilterOptions) -> Result<Self> {
+        let base = Self::load_base(filename, custom_dir, options)?;
+
+        // Installed filter packs add to whichever file was loaded. Their patterns
+        // are more specific, so they go first, before the generic ones turn the
+        // numbers and addresses they look for into '#'
+        let pack_dir = format!("{}.d", filename.trim_end_matches(".stopwords"));
+        let mut stopwords = Vec::new();
+        for dir in Self::user_dirs(custom_dir) {
+            for path in pack_files(&dir.join(&pack_dir)) {
+                debug!("Adding filter pack {}", path.display());
+                stopwords.extend(Self::load_from_path(&path, custom_dir, options, &mut Vec::new())?.stopwords);
+            }
+        }
+        stopwords.extend(base.stopwords);
+
+        Ok(Self { stopwords })
+    }
+
+    /// Filter directories set by the user, highest priority first
+    fn user_dirs(custom_dir: Option<&str>) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
 
         // Priority 1: Custom directory from parameter (highest priority)
         if let Some(dir) = custom_dir {
-            paths.push(PathBuf::from(dir).join(filename));
+            dirs.push(PathBuf::from(dir));
         }
 
         // Priority 2: Environment variable GLANCELOG_FILTERDIR
         if let Ok(env_dir) = std::env::var("GLANCELOG_FILTERDIR") {
-            paths.push(PathBuf::from(env_dir).join(filename));
+            dirs.push(PathBuf::from(env_dir));
         }
 
         // Priority 3: User home directory ~/.glancelog/filters
         if let Some(home_dir) = dirs::home_dir() {
-            paths.push(home_dir.join(".glancelog").join("filters").join(filename));
+            let home = home_dir.join(".glancelog").join("filters");
+            if !dirs.contains(&home) {
+                dirs.push(home);
+            }
         }
 
+        dirs
+    }
+
+    /// Where a filter file is looked for, highest priority first
+    fn search_paths(filename: &str, custom_dir: Option<&str>) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Self::user_dirs(custom_dir).iter().map(|dir| dir.join(filename)).collect();
+
         // Priority 4: Default search paths
         paths.extend(vec![
             PathBuf::from(format!("./filters/{}", filename)),
@@ -51,15 +144,21 @@ impl Filter {
             PathBuf::from(format!("/opt/glancelog/var/lib/filters/{}", filename)),
         ]);
 
-        for path in paths {
+        paths
+    }
+
+    fn load_base(filename: &str, custom_dir: Option<&str>, options: FilterOptions) -> Result<Self> {
+        for path in Self::search_paths(filename, custom_dir) {
             if path.exists() {
-                return Self::load_from_path(&path);
+                debug!("Loading {} from {}", filename, path.display());
+                return Self::load_from_path(&path, custom_dir, options, &mut Vec::new());
             }
         }
 
         // Priority 5: Use embedded default filters as fallback
         if let Some(embedded_content) = Self::get_embedded_filter(filename) {
-            return Self::load_from_string(embedded_content);
+            debug!("Using embedded {}", filename);
+            return Self::load_from_string(embedded_content, filename, None, custom_dir, options, &mut vec![Self::embedded_path(filename)]);
         }
 
         // Return empty filter if no embedded filter exists
@@ -76,39 +175,130 @@ impl Filter {
         }
     }
 
-    fn load_from_string(content: &str) -> Result<Self> {
+    /// Stands for an embedded filter in the include stack
+    fn embedded_path(filename: &str) -> PathBuf {
+        PathBuf::from("<embedded>").join(filename)
+    }
+
+    /// Filter file lines: a regex per line, `#` comment lines (a `#` alone or
+    /// followed by a space, as patterns such as `#+` start with `#` too),
+    /// `@include other.stopwords` and `@ignorecase on|off` / `@anchored on|off`
+    /// for the lines after them. `loading` holds the files being read, so a
+    /// file that includes itself resolves to the next one in the search order
+    fn load_from_string(content: &str, source: &str, dir: Option<&Path>, custom_dir: Option<&str>, defaults: FilterOptions, loading: &mut Vec<PathBuf>) -> Result<Self> {
         let mut stopwords = Vec::new();
+        let mut options = defaults;
 
-        for line in content.lines() {
+        for (idx, line) in content.lines().enumerate() {
             let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                match Regex::new(trimmed) {
-                    Ok(re) => stopwords.push(re),
-                    Err(e) => eprintln!("Warning: Invalid regex '{}': {}", trimmed, e),
+            if trimmed.is_empty() || trimmed == "#" || trimmed.starts_with("# ") || trimmed.starts_with("#\t") {
+                continue;
+            }
+
+            if let Some(name) = trimmed.strip_prefix("@include").filter(|rest| rest.starts_with(char::is_whitespace)) {
+                let name = name.trim();
+                match Self::load_include(name, dir, custom_dir, defaults, loading)? {
+                    Some(filter) => stopwords.extend(filter.stopwords),
+                    None => warn!("{}:{}: cannot find included filter '{}'", source, idx + 1, name),
                 }
+                continue;
+            }
+
+            if let Some((flag, value)) = Self::option_line(trimmed) {
+                match value {
+                    Some(value) if flag == "ignorecase" => options.ignore_case = value,
+                    Some(value) => options.anchored = value,
+                    None => warn!("{}:{}: expected @{} on or off", source, idx + 1, flag),
+                }
+                continue;
+            }
+
+            match options.compile(trimmed) {
+                Ok(re) => {
+                    if let Some(problem) = Self::check_pattern(&re, options) {
+                        warn!("Pattern '{}' in {}:{} {}", trimmed, source, idx + 1, problem);
+                    }
+                    stopwords.push(re);
+                }
+                Err(e) => warn!("Invalid regex '{}' in {}:{}: {}", trimmed, source, idx + 1, e),
             }
         }
 
         Ok(Self { stopwords })
     }
 
-    fn load_from_path(path: &Path) -> Result<Self> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut stopwords = Vec::new();
+    /// An `@ignorecase` or `@anchored` line: the flag and whether it is on,
+    /// None for a value that is neither
+    fn option_line(line: &str) -> Option<(&str, Option<bool>)> {
+        let (flag, value) = line.strip_prefix('@')?.split_once(char::is_whitespace).unwrap_or((&line[1..], ""));
+        if flag != "ignorecase" && flag != "anchored" {
+            return None;
+        }
+        let value = match value.trim().to_lowercase().as_str() {
+            "on" | "yes" | "true" | "1" => Some(true),
+            "off" | "no" | "false" | "0" => Some(false),
+            _ => None,
+        };
+        Some((flag, value))
+    }
 
-        for line in reader.lines() {
-            let line = line?;
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                match Regex::new(trimmed) {
-                    Ok(re) => stopwords.push(re),
-                    Err(e) => eprintln!("Warning: Invalid regex '{}': {}", trimmed, e),
-                }
+    /// Why a pattern would wreck the text it is applied to, if it would.
+    /// The regex engine runs in linear time, so there is no catastrophic
+    /// backtracking to fear, but a pattern can still match everywhere
+    fn check_pattern(re: &Regex, options: FilterOptions) -> Option<&'static str> {
+        if !options.anchored && re.is_match("") {
+            return Some("matches the empty string, so '#' is inserted between every character");
+        }
+        let whole = PROBE_TEXTS.iter().all(|text| re.find(text).map(|m| m.as_str() == *text).unwrap_or(false));
+        if whole {
+            return Some("matches any text, so every line is reduced to '#'");
+        }
+        None
+    }
+
+    /// An included file, looked for next to the including file and then in
+    /// the filter search paths and embedded filters, skipping files being read
+    fn load_include(name: &str, dir: Option<&Path>, custom_dir: Option<&str>, options: FilterOptions, loading: &mut Vec<PathBuf>) -> Result<Option<Self>> {
+        let mut paths = Vec::new();
+        if Path::new(name).is_absolute() {
+            paths.push(PathBuf::from(name));
+        } else {
+            if let Some(dir) = dir {
+                paths.push(dir.join(name));
             }
+            paths.extend(Self::search_paths(name, custom_dir));
         }
 
-        Ok(Self { stopwords })
+        for path in paths {
+            if path.exists() && !loading.contains(&Self::canonical(&path)) {
+                debug!("Including {} from {}", name, path.display());
+                return Self::load_from_path(&path, custom_dir, options, loading).map(Some);
+            }
+        }
+
+        let embedded = Self::embedded_path(name);
+        match Self::get_embedded_filter(name) {
+            Some(content) if !loading.contains(&embedded) => {
+                debug!("Including embedded {}", name);
+                loading.push(embedded);
+                let filter = Self::load_from_string(content, name, None, custom_dir, options, loading);
+                loading.pop();
+                filter.map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn canonical(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn load_from_path(path: &Path, custom_dir: Option<&str>, options: FilterOptions, loading: &mut Vec<PathBuf>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        loading.push(Self::canonical(path));
+        let filter = Self::load_from_string(&content, &path.display().to_string(), path.parent(), custom_dir, options, loading);
+        loading.pop();
+        filter
     }
 
     pub fn scrub(&self, input: &str) -> String {
@@ -0,0 +1,104 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use std::collections::HashMap;
+
+/// Firewall actions that mean the traffic did not get through
+const BLOCK_ACTIONS: &[&str] = &[
+    "block", "deny", "drop", "reject", "reset-client", "reset-server", "reset-both", "blocked", "dropped",
+];
+
+/// One firewall decision, taken from the structured fields the firewall parsers extract
+#[derive(Debug, Clone)]
+pub struct FirewallEvent {
+    pub action: String,
+    pub rule: String,
+    pub interface: String,
+    pub proto: String,
+    pub src: String,
+    pub dst: String,
+    pub dst_port: String,
+}
+
+impl FirewallEvent {
+    pub fn is_blocked(&self) -> bool {
+        BLOCK_ACTIONS.contains(&self.action.as_str())
+    }
+}
+
+pub struct FirewallReport {
+    events: Vec<FirewallEvent>,
+}
+
+impl FirewallReport {
+    /// Collect every entry that carries an action and a source address
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut events = Vec::new();
+
+        for entry in &log.entries {
+            let (action, src) = match (entry.attrs.get("action"), entry.attrs.get("src")) {
+                (Some(action), Some(src)) => (action, src),
+                _ => continue,
+            };
+            let attr = |key: &str| entry.attrs.get(key).cloned().unwrap_or_else(|| "-".to_string());
+
+            events.push(FirewallEvent {
+                action: action.to_lowercase(),
+                rule: attr("rule"),
+                // FortiGate and PAN-OS name interfaces per direction; the ingress side is what matters here
+                interface: entry.attrs.get("interface")
+                    .or_else(|| entry.attrs.get("src_intf"))
+                    .or_else(|| entry.attrs.get("src_zone"))
+                    .cloned()
+                    .unwrap_or_else(|| "-".to_string()),
+                proto: attr("proto"),
+                src: src.clone(),
+                dst: attr("dst"),
+                dst_port: attr("dst_port"),
+            });
+        }
+
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[FirewallEvent] {
+        &self.events
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.events.is_empty() {
+            println!("No firewall events found");
+            return;
+        }
+
+        let blocked: Vec<&FirewallEvent> = self.events.iter().filter(|e| e.is_blocked()).collect();
+        println!("Firewall events: {}\tBlocked: {}\tPassed: {}",
+            self.events.len(), blocked.len(), self.events.len() - blocked.len());
+        println!();
+
+        let mut actions: HashMap<String, usize> = HashMap::new();
+        for event in &self.events {
+            *actions.entry(event.action.clone()).or_insert(0) += 1;
+        }
+        print_top("Actions", actions, limit);
+
+        let mut rules: HashMap<String, usize> = HashMap::new();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        let mut ports: HashMap<String, usize> = HashMap::new();
+        let mut interfaces: HashMap<String, usize> = HashMap::new();
+        let mut pairs: HashMap<String, usize> = HashMap::new();
+
+        for event in &blocked {
+            *rules.entry(event.rule.clone()).or_insert(0) += 1;
+            *sources.entry(event.src.clone()).or_insert(0) += 1;
+            *ports.entry(format!("{}/{}", event.proto, event.dst_port)).or_insert(0) += 1;
+            *interfaces.entry(event.interface.clone()).or_insert(0) += 1;
+            *pairs.entry(format!("{} (rule {})", event.src, event.rule)).or_insert(0) += 1;
+        }
+
+        print_top("Blocked by rule", rules, limit);
+        print_top("Blocked sources", sources, limit);
+        print_top("Blocked sources by rule", pairs, limit);
+        print_top("Blocked destination ports", ports, limit);
+        print_top("Blocked by interface", interfaces, limit);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use std::collections::HashMap;
+
+/// One network flow with its volume, from entries that carry addresses and
+/// packet or byte counts (VPC Flow Logs)
+#[derive(Debug, Clone)]
+pub struct Flow {
+    pub src: String,
+    pub dst: String,
+    pub proto: String,
+    pub dst_port: String,
+    pub action: String,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+impl Flow {
+    pub fn is_rejected(&self) -> bool {
+        self.action == "reject"
+    }
+}
+
+/// Top talkers by volume, rejected flows and the destination port
+/// distribution of flow records
+pub struct FlowReport {
+    flows: Vec<Flow>,
+}
+
+impl FlowReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut flows = Vec::new();
+
+        for entry in &log.entries {
+            let (src, dst) = match (entry.attrs.get("src"), entry.attrs.get("dst")) {
+                (Some(src), Some(dst)) => (src, dst),
+                _ => continue,
+            };
+            if !entry.attrs.contains_key("bytes") && !entry.attrs.contains_key("packets") {
+                continue;
+            }
+            let attr = |key: &str| entry.attrs.get(key).cloned().unwrap_or_else(|| "-".to_string());
+            let count = |key: &str| entry.attrs.get(key).and_then(|v| v.parse().ok()).unwrap_or(0);
+
+            flows.push(Flow {
+                src: src.clone(),
+                dst: dst.clone(),
+                proto: attr("proto"),
+                dst_port: attr("dst_port"),
+                action: attr("action"),
+                packets: count("packets"),
+                bytes: count("bytes"),
+            });
+        }
+
+        Self { flows }
+    }
+
+    pub fn flows(&self) -> &[Flow] {
+        &self.flows
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.flows.is_empty() {
+            println!("No flow records found");
+            return;
+        }
+
+        let rejected: Vec<&Flow> = self.flows.iter().filter(|f| f.is_rejected()).collect();
+        let packets: u64 = self.flows.iter().map(|f| f.packets).sum();
+        let bytes: u64 = self.flows.iter().map(|f| f.bytes).sum();
+        println!("Flows: {}\tAccepted: {}\tRejected: {}\tPackets: {}\tBytes: {}",
+            self.flows.len(), self.flows.len() - rejected.len(), rejected.len(), packets, bytes);
+        println!();
+
+        let mut talkers: HashMap<String, usize> = HashMap::new();
+        let mut destinations: HashMap<String, usize> = HashMap::new();
+        let mut conversations: HashMap<String, usize> = HashMap::new();
+        let mut ports: HashMap<String, usize> = HashMap::new();
+        for flow in &self.flows {
+            *talkers.entry(flow.src.clone()).or_insert(0) += flow.bytes as usize;
+            *destinations.entry(flow.dst.clone()).or_insert(0) += flow.bytes as usize;
+            *conversations.entry(format!("{} -> {}", flow.src, flow.dst)).or_insert(0) += flow.bytes as usize;
+            *ports.entry(format!("{}/{}", flow.proto, flow.dst_port)).or_insert(0) += 1;
+        }
+        talkers.retain(|_, bytes| *bytes > 0);
+        destinations.retain(|_, bytes| *bytes > 0);
+        conversations.retain(|_, bytes| *bytes > 0);
+
+        print_top("Top talkers (bytes sent)", talkers, limit);
+        print_top("Top destinations (bytes received)", destinations, limit);
+        print_top("Top conversations (bytes)", conversations, limit);
+        print_top("Flows by destination port", ports, limit);
+
+        let mut rejected_sources: HashMap<String, usize> = HashMap::new();
+        let mut rejected_ports: HashMap<String, usize> = HashMap::new();
+        let mut rejected_pairs: HashMap<String, usize> = HashMap::new();
+        for flow in &rejected {
+            *rejected_sources.entry(flow.src.clone()).or_insert(0) += 1;
+            *rejected_ports.entry(format!("{}/{}", flow.proto, flow.dst_port)).or_insert(0) += 1;
+            *rejected_pairs.entry(format!("{} -> {} {}/{}", flow.src, flow.dst, flow.proto, flow.dst_port)).or_insert(0) += 1;
+        }
+
+        print_top("Rejected flows by source", rejected_sources, limit);
+        print_top("Rejected flows by destination port", rejected_ports, limit);
+        print_top("Rejected flows", rejected_pairs, limit);
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How often a followed file is checked for new data
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub enum FollowEvent {
+    Line(String),
+    /// Nothing arrived within the timeout
+    Idle,
+    /// The input ended (stdin closed)
+    Closed,
+}
+
+/// Where a followed file has been read up to: its inode (0 where there is
+/// none) and the byte offset just past the last complete line
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilePosition {
+    pub inode: u64,
+    pub offset: u64,
+}
+
+/// Lines of an input that keeps growing, read on a background thread so the
+/// caller can refresh its output while waiting
+pub struct Follower {
+    lines: Receiver<(String, FilePosition)>,
+    position: FilePosition,
+}
+
+impl Follower {
+    /// Standard input until it is closed, e.g. `journalctl -f | glancelog --follow`
+    pub fn stdin() -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let stdin = std::io::stdin();
+            Self::send_lines(stdin.lock(), &tx);
+        });
+        Self { lines: rx, position: FilePosition::default() }
+    }
+
+    /// A file from its start, then whatever is appended, like `tail -f`
+    pub fn file(path: &str) -> Result<Self> {
+        Self::file_from(path, None)
+    }
+
+    /// A file from a position saved earlier, as long as it is still the same
+    /// file (same inode, not shorter); otherwise from its start. A file that
+    /// shrinks or is replaced by a new one (rotation) is read from the start
+    pub fn file_from(path: &str, resume: Option<FilePosition>) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let inode = Self::inode(path);
+        let mut position = FilePosition { inode, offset: 0 };
+        if let Some(resume) = resume {
+            if resume.inode == inode && file.metadata()?.len() >= resume.offset {
+                file.seek(SeekFrom::Start(resume.offset))?;
+                position = resume;
+            }
+        }
+
+        let path = path.to_string();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || Self::follow_file(&path, file, position, &tx));
+        Ok(Self { lines: rx, position })
+    }
+
+    pub fn next(&mut self, timeout: Duration) -> FollowEvent {
+        match self.lines.recv_timeout(timeout) {
+            Ok((line, position)) => {
+                self.position = position;
+                FollowEvent::Line(line)
+            }
+            Err(RecvTimeoutError::Timeout) => FollowEvent::Idle,
+            Err(RecvTimeoutError::Disconnected) => FollowEvent::Closed,
+        }
+    }
+
+    /// Position after the last line returned by `next`
+    pub fn position(&self) -> FilePosition {
+        self.position
+    }
+
+    #[cfg(unix)]
+    fn inode(path: &str) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).map(|m| m.ino()).unwrap_or(0)
+    }
+
+    #[cfg(not(unix))]
+    fn inode(_path: &str) -> u64 {
+        0
+    }
+
+    fn send_lines<R: BufRead>(mut reader: R, tx: &Sender<(String, FilePosition)>) {
+        let mut position = FilePosition::default();
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => {
+                    position.offset += n as u64;
+                    if tx.send((Self::decode(&buf), position)).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn follow_file(path: &str, file: File, mut position: FilePosition, tx: &Sender<(String, FilePosition)>) {
+        let mut reader = BufReader::new(file);
+        let mut read = position.offset;
+        // A line is only sent once its newline is written
+        let mut partial = Vec::new();
+
+        loop {
+            match reader.read_until(b'\n', &mut partial) {
+                Ok(0) => {
+                    thread::sleep(POLL_INTERVAL);
+                    let inode = Self::inode(path);
+                    let shrunk = std::fs::metadata(path).map(|m| m.len() < read).unwrap_or(false);
+                    if shrunk || (inode != 0 && inode != position.inode) {
+                        match File::open(path) {
+                            Ok(file) => reader = BufReader::new(file),
+                            Err(_) => continue,
+                        }
+                        position = FilePosition { inode, offset: 0 };
+                        read = 0;
+                        partial.clear();
+                    }
+                }
+                Ok(n) => {
+                    read += n as u64;
+                    if partial.ends_with(b"\n") {
+                        position.offset = read;
+                        if tx.send((Self::decode(&partial), position)).is_err() {
+                            return;
+                        }
+                        partial.clear();
+                    }
+                }
+                Err(_) => return,
+            }
+        }
+    }
+
+    fn decode(line: &[u8]) -> String {
+        String::from_utf8_lossy(line).trim_end_matches(['\n', '\r']).to_string()
+    }
+}
@@ -0,0 +1,128 @@
+use crate::log_entry::CrunchLog;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use std::collections::BTreeMap;
+
+/// One stop-the-world pause from a JVM unified GC log
+#[derive(Debug, Clone)]
+pub struct GcPause {
+    pub timestamp: NaiveDateTime,
+    pub kind: String,
+    pub millis: f64,
+}
+
+pub struct GcReport {
+    pauses: Vec<GcPause>,
+}
+
+impl GcReport {
+    /// Collect the pauses JVM-GC entries carry in their "pause" and "pause_ms" fields
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut pauses: Vec<GcPause> = log.entries.iter()
+            .filter_map(|entry| {
+                Some(GcPause {
+                    timestamp: entry.naive_datetime(),
+                    kind: entry.attrs.get("pause")?.clone(),
+                    millis: entry.attrs.get("pause_ms")?.parse().ok()?,
+                })
+            })
+            .collect();
+        pauses.sort_by_key(|p| p.timestamp);
+
+        Self { pauses }
+    }
+
+    pub fn pauses(&self) -> &[GcPause] {
+        &self.pauses
+    }
+
+    /// Nearest-rank percentile of sorted values
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    fn stats_row(label: &str, pauses: &[&GcPause]) -> String {
+        let mut millis: Vec<f64> = pauses.iter().map(|p| p.millis).collect();
+        millis.sort_by(|a, b| a.total_cmp(b));
+        let total: f64 = millis.iter().sum();
+        format!("{}\t{:.1}\t{:.3}\t{:.3}\t{:.3}\t{:.3}\t{}",
+            pauses.len(), total, Self::percentile(&millis, 50.0), Self::percentile(&millis, 90.0),
+            Self::percentile(&millis, 99.0), millis.last().copied().unwrap_or(0.0), label)
+    }
+
+    /// Buckets of a minute, an hour or a day, whichever keeps the table readable
+    fn bucket_size(&self) -> Duration {
+        let span = match (self.pauses.first(), self.pauses.last()) {
+            (Some(first), Some(last)) => last.timestamp - first.timestamp,
+            _ => Duration::zero(),
+        };
+        if span <= Duration::hours(2) {
+            Duration::minutes(1)
+        } else if span <= Duration::days(2) {
+            Duration::hours(1)
+        } else {
+            Duration::days(1)
+        }
+    }
+
+    fn bucket_start(timestamp: NaiveDateTime, size: Duration) -> NaiveDateTime {
+        let truncated = timestamp.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(timestamp);
+        if size >= Duration::days(1) {
+            truncated.date().and_hms_opt(0, 0, 0).unwrap_or(truncated)
+        } else if size >= Duration::hours(1) {
+            truncated.with_minute(0).unwrap_or(truncated)
+        } else {
+            truncated
+        }
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.pauses.is_empty() {
+            println!("No GC pauses found");
+            return;
+        }
+
+        let header = "count\ttotal ms\tp50 ms\tp90 ms\tp99 ms\tmax ms";
+        let all: Vec<&GcPause> = self.pauses.iter().collect();
+        println!("GC pauses:");
+        println!("{}", header);
+        println!("{}", Self::stats_row("all", &all));
+        println!();
+
+        let mut kinds: BTreeMap<&str, Vec<&GcPause>> = BTreeMap::new();
+        for pause in &self.pauses {
+            kinds.entry(pause.kind.as_str()).or_default().push(pause);
+        }
+        let mut kinds: Vec<_> = kinds.into_iter().collect();
+        kinds.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        println!("Pauses by type:");
+        println!("{}\ttype", header);
+        for (kind, pauses) in kinds.iter().take(limit) {
+            println!("{}", Self::stats_row(kind, pauses));
+        }
+        println!();
+
+        let size = self.bucket_size();
+        let mut buckets: BTreeMap<NaiveDateTime, Vec<&GcPause>> = BTreeMap::new();
+        for pause in &self.pauses {
+            buckets.entry(Self::bucket_start(pause.timestamp, size)).or_default().push(pause);
+        }
+        println!("Pauses over time:");
+        println!("{}\tperiod", header);
+        for (start, pauses) in &buckets {
+            println!("{}", Self::stats_row(&start.format("%Y-%m-%dT%H:%M").to_string(), pauses));
+        }
+        println!();
+
+        let mut longest: Vec<&GcPause> = self.pauses.iter().collect();
+        longest.sort_by(|a, b| b.millis.total_cmp(&a.millis));
+        println!("Longest pauses:");
+        for pause in longest.iter().take(limit) {
+            println!("{:.3} ms\t{}\t{}", pause.millis, pause.timestamp.format("%Y-%m-%dT%H:%M:%S"), pause.kind);
+        }
+        println!();
+    }
+}
@@ -1,6 +1,8 @@
-use crate::log_entry::CrunchLog;
-use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use crate::log_entry::{CrunchLog, LogEntry};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use anyhow::{Result, anyhow};
 use std::collections::HashMap;
+use std::io::Write;
 
 #[derive(Debug, Clone, Copy)]
 pub enum GraphType {
@@ -12,17 +14,69 @@ pub enum GraphType {
     Years,
 }
 
+/// A horizontal marker line drawn across the graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HLine {
+    /// Mean of the bucket counts
+    Mean,
+    /// Percentile of the bucket counts, 0 to 100
+    Percentile(f64),
+    /// Absolute count per bucket, e.g. an alert threshold
+    Value(usize),
+}
+
+impl HLine {
+    /// "mean", "p95" or an absolute count such as "1000"
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec == "mean" {
+            return Ok(HLine::Mean);
+        }
+        if let Some(p) = spec.strip_prefix('p') {
+            let p: f64 = p.parse().map_err(|_| anyhow!("Invalid percentile '{}'", spec))?;
+            if !(0.0..=100.0).contains(&p) {
+                return Err(anyhow!("Percentile must be between 0 and 100, got {}", p));
+            }
+            return Ok(HLine::Percentile(p));
+        }
+        spec.parse().map(HLine::Value)
+            .map_err(|_| anyhow!("Invalid line '{}', expected mean, pNN or a count", spec))
+    }
+
+    fn label(&self) -> String {
+        match self {
+            HLine::Mean => "mean".to_string(),
+            HLine::Percentile(p) => format!("p{}", p),
+            HLine::Value(_) => "threshold".to_string(),
+        }
+    }
+}
+
+/// What is plotted for each bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphValues {
+    /// Entries in the bucket
+    Count,
+    /// Running total up to and including the bucket
+    Cumulative,
+    /// Change from the previous bucket; the first bucket is compared to zero
+    Delta,
+}
+
 pub struct GraphHash {
     data: HashMap<String, usize>,
     start_date: DateTime<Local>,
     middle_date: DateTime<Local>,
     end_date: DateTime<Local>,
-    max_value: usize,
-    min_value: usize,
+    before: usize,
+    after: usize,
+    undated: usize,
     duration: i64,
     unit: &'static str,
     tick: char,
     wide: bool,
+    hlines: Vec<HLine>,
+    values: GraphValues,
 }
 
 impl GraphHash {
@@ -41,26 +95,29 @@ impl GraphHash {
             start_date: Local::now(),
             middle_date: Local::now(),
             end_date: Local::now(),
-            max_value: 0,
-            min_value: 0,
+            before: 0,
+            after: 0,
+            undated: 0,
             duration: 0,
             unit: "",
             tick: '#',
             wide: false,
+            hlines: Vec::new(),
+            values: GraphValues::Count,
         };
 
-        if log.entries.is_empty() {
-            return graph;
-        }
-
-        // Determine start date: use --from if provided, otherwise first entry
-        let start_date = if let Some(from_dt) = from {
-            from_dt
-        } else {
-            let first_entry = &log.entries[0];
-            Self::entry_to_datetime(first_entry)
+        // Entries without a timestamp have no place on the time axis
+        let first_entry = match log.entries.iter().find(|e| !e.is_undated()) {
+            Some(entry) => entry,
+            None => {
+                graph.undated = log.entries.len();
+                return graph;
+            }
         };
 
+        // Determine start date: use --from if provided, otherwise first dated entry
+        let start_date = from.unwrap_or_else(|| Self::entry_to_datetime(first_entry));
+
         // Determine if we have a custom range
         let custom_range = from.is_some() || to.is_some();
 
@@ -73,11 +130,10 @@ impl GraphHash {
             GraphType::Years => graph.fill_years(log, start_date, to, custom_range),
         }
 
-        graph.calculate_stats();
         graph
     }
 
-    fn entry_to_datetime(entry: &crate::log_entry::LogEntry) -> DateTime<Local> {
+    fn entry_to_datetime(entry: &LogEntry) -> DateTime<Local> {
         let naive_date = NaiveDate::from_ymd_opt(entry.year, entry.month, entry.day)
             .unwrap_or_else(|| NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
         let naive_time = NaiveTime::from_hms_opt(entry.hour, entry.minute, entry.second)
@@ -111,15 +167,9 @@ impl GraphHash {
         self.middle_date = start_date + Duration::seconds(self.duration / 2);
         self.end_date = start_date + Duration::seconds(self.duration - 1);
 
-        // Fill with actual data
-        for entry in &log.entries {
-            let key = format!("{}{:02}{:02}{:02}{:02}{:02}",
+        self.fill_counts(log, |entry| format!("{}{:02}{:02}{:02}{:02}{:02}",
                 entry.year, entry.month, entry.day,
-                entry.hour, entry.minute, entry.second);
-            if let Some(count) = self.data.get_mut(&key) {
-                *count += 1;
-            }
-        }
+                entry.hour, entry.minute, entry.second));
     }
 
     fn fill_minutes(&mut self, log: &CrunchLog, start_date: DateTime<Local>, to: Option<DateTime<Local>>, custom_range: bool) {
@@ -146,14 +196,9 @@ impl GraphHash {
         self.middle_date = start_date + Duration::minutes(self.duration / 2);
         self.end_date = start_date + Duration::minutes(self.duration - 1);
 
-        for entry in &log.entries {
-            let key = format!("{}{:02}{:02}{:02}{:02}",
+        self.fill_counts(log, |entry| format!("{}{:02}{:02}{:02}{:02}",
                 entry.year, entry.month, entry.day,
-                entry.hour, entry.minute);
-            if let Some(count) = self.data.get_mut(&key) {
-                *count += 1;
-            }
-        }
+                entry.hour, entry.minute));
     }
 
     fn fill_hours(&mut self, log: &CrunchLog, start_date: DateTime<Local>, to: Option<DateTime<Local>>, custom_range: bool) {
@@ -179,13 +224,8 @@ impl GraphHash {
         self.middle_date = start_date + Duration::hours(self.duration / 2);
         self.end_date = start_date + Duration::hours(self.duration - 1);
 
-        for entry in &log.entries {
-            let key = format!("{}{:02}{:02}{:02}",
-                entry.year, entry.month, entry.day, entry.hour);
-            if let Some(count) = self.data.get_mut(&key) {
-                *count += 1;
-            }
-        }
+        self.fill_counts(log, |entry| format!("{}{:02}{:02}{:02}",
+                entry.year, entry.month, entry.day, entry.hour));
     }
 
     fn fill_days(&mut self, log: &CrunchLog, start_date: DateTime<Local>, to: Option<DateTime<Local>>, custom_range: bool) {
@@ -211,13 +251,26 @@ impl GraphHash {
         self.middle_date = start_date + Duration::days(self.duration / 2);
         self.end_date = start_date + Duration::days(self.duration - 1);
 
-        for entry in &log.entries {
-            let key = format!("{}{:02}{:02}",
-                entry.year, entry.month, entry.day);
-            if let Some(count) = self.data.get_mut(&key) {
-                *count += 1;
-            }
-        }
+        self.fill_counts(log, |entry| format!("{}{:02}{:02}",
+                entry.year, entry.month, entry.day));
+    }
+
+    /// First day of the month `months` calendar months after the month of `date`
+    fn month_start(date: DateTime<Local>, months: i64) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(date.year(), date.month(), 1)?
+            .checked_add_months(Months::new(u32::try_from(months).ok()?))
+    }
+
+    fn local_midnight(date: Option<NaiveDate>) -> Option<DateTime<Local>> {
+        date?.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()
+    }
+
+    /// Calendar months from the month of `start` to the month of `end`, counting
+    /// the last one if `end` is past its first instant
+    fn months_between(start: DateTime<Local>, end: DateTime<Local>) -> i64 {
+        let months = (end.year() - start.year()) as i64 * 12 + end.month() as i64 - start.month() as i64;
+        let partial = end.day() > 1 || end.num_seconds_from_midnight() > 0;
+        months + partial as i64
     }
 
     fn fill_months(&mut self, log: &CrunchLog, start_date: DateTime<Local>, to: Option<DateTime<Local>>, custom_range: bool) {
@@ -226,29 +279,21 @@ impl GraphHash {
 
         // Calculate duration
         if custom_range && to.is_some() {
-            let end_dt = to.unwrap();
-            let diff = end_dt.signed_duration_since(start_date);
-            self.duration = (diff.num_days() / 30).max(1);
+            self.duration = Self::months_between(start_date, to.unwrap()).max(1);
         } else {
             self.duration = 12;
         }
 
         for i in 0..self.duration {
-            let days_offset = (i * 365) / 12 + 1;
-            let date = start_date + Duration::days(days_offset);
-            let key = format!("{}{:02}", date.year(), date.month());
-            self.data.insert(key, 0);
+            if let Some(date) = Self::month_start(start_date, i) {
+                self.data.insert(format!("{}{:02}", date.year(), date.month()), 0);
+            }
         }
 
-        self.middle_date = start_date + Duration::days((self.duration * 365) / 24);
-        self.end_date = start_date + Duration::days((self.duration * 365) / 12);
+        self.middle_date = Self::local_midnight(Self::month_start(start_date, self.duration / 2)).unwrap_or(start_date);
+        self.end_date = Self::local_midnight(Self::month_start(start_date, self.duration - 1)).unwrap_or(start_date);
 
-        for entry in &log.entries {
-            let key = format!("{}{:02}", entry.year, entry.month);
-            if let Some(count) = self.data.get_mut(&key) {
-                *count += 1;
-            }
-        }
+        self.fill_counts(log, |entry| format!("{}{:02}", entry.year, entry.month));
     }
 
     fn fill_years(&mut self, log: &CrunchLog, start_date: DateTime<Local>, to: Option<DateTime<Local>>, custom_range: bool) {
@@ -258,32 +303,126 @@ impl GraphHash {
         // Calculate duration
         if custom_range && to.is_some() {
             let end_dt = to.unwrap();
-            let diff = end_dt.signed_duration_since(start_date);
-            self.duration = (diff.num_days() / 365).max(1);
+            let partial = end_dt.ordinal() > 1 || end_dt.num_seconds_from_midnight() > 0;
+            self.duration = ((end_dt.year() - start_date.year()) as i64 + partial as i64).max(1);
         } else {
             self.duration = 10;
         }
 
         for i in 0..self.duration {
-            let date = start_date + Duration::days(i * 365);
-            let key = format!("{}", date.year());
-            self.data.insert(key, 0);
+            self.data.insert(format!("{}", start_date.year() as i64 + i), 0);
         }
 
-        self.middle_date = start_date + Duration::days((self.duration * 365) / 2);
-        self.end_date = start_date + Duration::days(self.duration * 365);
+        let year_start = |offset: i64| Self::local_midnight(NaiveDate::from_ymd_opt(start_date.year() + offset as i32, 1, 1))
+            .unwrap_or(start_date);
+        self.middle_date = year_start(self.duration / 2);
+        self.end_date = year_start(self.duration - 1);
+
+        self.fill_counts(log, |entry| format!("{}", entry.year));
+    }
+
+    /// Count each entry into its bucket. Keys are fixed width digits, so an
+    /// entry without a bucket sorts before the first or after the last one
+    fn fill_counts<F: Fn(&LogEntry) -> String>(&mut self, log: &CrunchLog, key_of: F) {
+        let first = self.data.keys().min().cloned().unwrap_or_default();
 
         for entry in &log.entries {
-            let key = format!("{}", entry.year);
+            if entry.is_undated() {
+                self.undated += 1;
+                continue;
+            }
+            let key = key_of(entry);
             if let Some(count) = self.data.get_mut(&key) {
                 *count += 1;
+            } else if key < first {
+                self.before += 1;
+            } else {
+                self.after += 1;
             }
         }
     }
 
-    fn calculate_stats(&mut self) {
-        self.max_value = *self.data.values().max().unwrap_or(&0);
-        self.min_value = *self.data.values().min().unwrap_or(&0);
+    /// Bucket keys in time order with their counts; keys are the bucket start
+    /// as digits, e.g. "202402" for February 2024 in a monthly graph
+    pub fn buckets(&self) -> Vec<(String, usize)> {
+        let mut buckets: Vec<(String, usize)> = self.data.iter().map(|(k, &v)| (k.clone(), v)).collect();
+        buckets.sort();
+        buckets
+    }
+
+    /// Bucket keys in time order with the plotted value, see `set_values`
+    pub fn series(&self) -> Vec<(String, i64)> {
+        let mut total = 0i64;
+        let mut previous = 0i64;
+        self.buckets().into_iter().map(|(key, count)| {
+            let count = count as i64;
+            let value = match self.values {
+                GraphValues::Count => count,
+                GraphValues::Cumulative => {
+                    total += count;
+                    total
+                }
+                GraphValues::Delta => count - previous,
+            };
+            previous = count;
+            (key, value)
+        }).collect()
+    }
+
+    /// Keys of the first and last plotted bucket
+    pub fn bucket_range(&self) -> Option<(String, String)> {
+        Some((self.data.keys().min()?.clone(), self.data.keys().max()?.clone()))
+    }
+
+    /// A bucket key as a readable time: "2024021514" becomes "2024-02-15 14:00"
+    pub fn key_label(key: &str) -> String {
+        let part = |range: std::ops::Range<usize>| key.get(range).unwrap_or("");
+        match key.len() {
+            6 => format!("{}-{}", part(0..4), part(4..6)),
+            8 => format!("{}-{}-{}", part(0..4), part(4..6), part(6..8)),
+            10 => format!("{}-{}-{} {}:00", part(0..4), part(4..6), part(6..8), part(8..10)),
+            12 => format!("{}-{}-{} {}:{}", part(0..4), part(4..6), part(6..8), part(8..10), part(10..12)),
+            14 => format!("{}-{}-{} {}:{}:{}", part(0..4), part(4..6), part(6..8), part(8..10), part(10..12), part(12..14)),
+            _ => key.to_string(),
+        }
+    }
+
+    /// Start of the bucket a key stands for: "202402" is 2024-02-01 00:00:00
+    pub fn key_start(key: &str) -> Option<NaiveDateTime> {
+        let padded = format!("{}{}", key, "00000101000000".get(key.len()..)?);
+        NaiveDateTime::parse_from_str(&padded, "%Y%m%d%H%M%S").ok()
+    }
+
+    /// One "bucket start<TAB>value" row per bucket, followed by a column for
+    /// each other graph paired bucket by bucket (a baseline, or one per file)
+    pub fn write_data<W: Write>(&self, out: &mut W, others: &[&GraphHash]) -> std::io::Result<()> {
+        let columns: Vec<Vec<(String, i64)>> = others.iter().map(|g| g.series()).collect();
+        for (i, (key, value)) in self.series().iter().enumerate() {
+            let start = Self::key_start(key)
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+                .unwrap_or_else(|| key.clone());
+            write!(out, "{}\t{}", start, value)?;
+            for column in &columns {
+                write!(out, "\t{}", column.get(i).map(|(_, v)| *v).unwrap_or(0))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Entries older than the first plotted bucket
+    pub fn before(&self) -> usize {
+        self.before
+    }
+
+    /// Entries newer than the last plotted bucket
+    pub fn after(&self) -> usize {
+        self.after
+    }
+
+    /// Entries without a timestamp, left out of the buckets
+    pub fn undated(&self) -> usize {
+        self.undated
     }
 
     pub fn set_tick(&mut self, tick: char) {
@@ -294,12 +433,43 @@ impl GraphHash {
         self.wide = wide;
     }
 
+    pub fn set_values(&mut self, values: GraphValues) {
+        self.values = values;
+    }
+
+    pub fn add_hline(&mut self, hline: HLine) {
+        self.hlines.push(hline);
+    }
+
+    /// The value a marker line stands for on this graph
+    pub fn hline_value(&self, hline: &HLine) -> f64 {
+        let mut values: Vec<i64> = self.series().into_iter().map(|(_, v)| v).collect();
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.sort_unstable();
+
+        match hline {
+            HLine::Mean => values.iter().sum::<i64>() as f64 / values.len() as f64,
+            // Nearest rank
+            HLine::Percentile(p) => {
+                let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+                values[rank.clamp(1, values.len()) - 1] as f64
+            }
+            HLine::Value(v) => *v as f64,
+        }
+    }
+
     pub fn display(&self) {
         let graph_height = 6;
         let graph_width = self.data.len();
 
         if graph_width == 0 {
-            println!("No data to graph");
+            if self.undated > 0 {
+                println!("No data to graph, {} entries without a timestamp", self.undated);
+            } else {
+                println!("No data to graph");
+            }
             return;
         }
 
@@ -308,50 +478,193 @@ impl GraphHash {
         } else {
             (self.tick.to_string(), " ".to_string())
         };
+        let char_line = if self.wide { "- " } else { "-" };
+
+        let series = self.series();
+        let graph_min = series.iter().map(|(_, v)| *v).min().unwrap_or(0);
+        let graph_max = series.iter().map(|(_, v)| *v).max().unwrap_or(0);
+
+        // Marker lines sit on the row a bucket of that value would reach; lines
+        // above the top row get a row of their own, lines at the minimum only
+        // show up in the summary
+        let markers: Vec<(usize, String, f64)> = self.hlines.iter().map(|hline| {
+            let value = self.hline_value(hline);
+            let level = if value <= graph_min as f64 {
+                0
+            } else if graph_max > graph_min {
+                ((value - graph_min as f64) / (graph_max - graph_min) as f64 * graph_height as f64).ceil() as usize
+            } else {
+                graph_height
+            };
+            (level, hline.label(), value)
+        }).collect();
 
-        // Get sorted keys
-        let mut keys: Vec<_> = self.data.keys().cloned().collect();
-        keys.sort();
-
-        // Normalize data for display
-        let mut normalized: HashMap<String, usize> = HashMap::new();
-        let graph_min = self.min_value;
-        let graph_max = self.max_value;
-
-        for key in &keys {
-            let value = self.data[key];
-            if value > 0 {
-                let normalized_value = if graph_max > graph_min {
-                    ((value - graph_min) as f64 / (graph_max - graph_min) as f64 * graph_height as f64).ceil() as usize
+        println!();
+        if graph_min < 0 {
+            // Changes go up and down from a zero line, half the height each way
+            let half = graph_height / 2;
+            let scale = graph_max.max(-graph_min) as f64;
+            let level = |v: i64| (v.abs() as f64 / scale * half as f64).ceil() as usize;
+            for i in (1..=half).rev() {
+                for (_, value) in &series {
+                    print!("{}", if *value > 0 && level(*value) >= i { &char_fill } else { &char_blank });
+                }
+                println!();
+            }
+            println!("{}", char_line.repeat(series.len()));
+            for i in 1..=half {
+                for (_, value) in &series {
+                    print!("{}", if *value < 0 && level(*value) >= i { &char_fill } else { &char_blank });
+                }
+                println!();
+            }
+        } else {
+            // Normalize data for display
+            let normalized: Vec<usize> = series.iter().map(|(_, value)| {
+                if *value > 0 {
+                    if graph_max > graph_min {
+                        ((value - graph_min) as f64 / (graph_max - graph_min) as f64 * graph_height as f64).ceil() as usize
+                    } else {
+                        (*value as f64 / graph_max as f64 * graph_height as f64).ceil() as usize
+                    }
                 } else {
-                    (value as f64 / graph_max as f64 * graph_height as f64).ceil() as usize
-                };
-                normalized.insert(key.clone(), normalized_value);
-            } else {
-                normalized.insert(key.clone(), 0);
+                    0
+                }
+            }).collect();
+
+            if let Some((_, label, value)) = markers.iter().find(|(level, _, _)| *level >= graph_height) {
+                println!("{} {} {:.0}", char_line.repeat(series.len()), label, value);
             }
+            for i in (1..graph_height).rev() {
+                let marker = markers.iter().find(|(level, _, _)| *level == i);
+                for height in &normalized {
+                    if *height >= i {
+                        print!("{}", char_fill);
+                    } else if marker.is_some() {
+                        print!("{}", char_line);
+                    } else {
+                        print!("{}", char_blank);
+                    }
+                }
+                match marker {
+                    Some((_, label, value)) => println!(" {} {:.0}", label, value),
+                    None => println!(),
+                }
+            }
+
+            // Bottom line
+            println!("{}", char_fill.repeat(series.len()));
+        }
+
+        self.print_time_markers();
+
+        // Summary
+        println!();
+        println!("Start Time:\t{}\t\tMinimum Value: {}", self.start_date.format("%Y-%m-%d %H:%M:%S"), graph_min);
+        println!("End Time:\t{}\t\tMaximum Value: {}", self.end_date.format("%Y-%m-%d %H:%M:%S"), graph_max);
+        let scale = if graph_height > 0 {
+            (graph_max - graph_min) as f64 / graph_height as f64
+        } else {
+            0.0
+        };
+        println!("Duration:\t{} {}s\t\t\tScale: {:.2}", self.duration, self.unit, scale);
+        match self.values {
+            GraphValues::Count => {}
+            GraphValues::Cumulative => println!("Plotted:\trunning total"),
+            GraphValues::Delta => println!("Plotted:\tchange from the previous {}", self.unit),
+        }
+        for (hline, (_, label, value)) in self.hlines.iter().zip(&markers) {
+            let above = series.iter().filter(|(_, v)| *v as f64 >= *value).count();
+            let first = series.iter().find(|(_, v)| *v as f64 >= *value);
+            let first = match (first, hline) {
+                (Some((key, _)), HLine::Value(_)) => format!("\tfirst reached {}", Self::key_label(key)),
+                _ => String::new(),
+            };
+            println!("Line {}:\t{:.2}\t{} of {} {}s at or above{}", label, value, above, series.len(), self.unit, first);
+        }
+        if self.before > 0 || self.after > 0 {
+            println!("Outside range:\t<before> {}\t<after> {}\t(not plotted)", self.before, self.after);
+        }
+        if self.undated > 0 {
+            println!("Undated:\t{}\t(not plotted)", self.undated);
+        }
+        println!();
+    }
+
+    /// Overlay a baseline graph on this one, bucket by bucket: `#` (the tick)
+    /// where this graph reaches a row, `.` where only the baseline does. Both
+    /// are drawn from zero on one scale so their shapes can be compared
+    pub fn display_compare(&self, baseline: &GraphHash, label: &str) {
+        let graph_height = 6;
+        let current = self.series();
+        let previous = baseline.series();
+
+        if current.is_empty() {
+            println!("No data to graph");
+            return;
         }
 
-        // Print graph
+        let (char_fill, char_base, char_blank) = if self.wide {
+            (format!("{} ", self.tick), ". ".to_string(), "  ".to_string())
+        } else {
+            (self.tick.to_string(), ".".to_string(), " ".to_string())
+        };
+
+        // Buckets are paired by position; a baseline with fewer buckets counts as zero
+        let pairs: Vec<(&str, i64, i64)> = current.iter().enumerate()
+            .map(|(i, (key, value))| (key.as_str(), *value, previous.get(i).map(|(_, v)| *v).unwrap_or(0)))
+            .collect();
+        let graph_max = pairs.iter().map(|(_, a, b)| *a.max(b)).max().unwrap_or(0).max(1);
+        let level = |v: i64| if v > 0 { (v as f64 / graph_max as f64 * graph_height as f64).ceil() as usize } else { 0 };
+
         println!();
         for i in (1..graph_height).rev() {
-            for key in &keys {
-                if normalized[key] >= i {
+            for (_, value, base) in &pairs {
+                if level(*value) >= i {
                     print!("{}", char_fill);
+                } else if level(*base) >= i {
+                    print!("{}", char_base);
                 } else {
                     print!("{}", char_blank);
                 }
             }
             println!();
         }
+        println!("{}", char_fill.repeat(pairs.len()));
+        self.print_time_markers();
 
-        // Bottom line
-        for _ in &keys {
-            print!("{}", char_fill);
+        let total: i64 = pairs.iter().map(|(_, v, _)| v).sum();
+        let base_total: i64 = pairs.iter().map(|(_, _, b)| b).sum();
+        let change = if base_total != 0 {
+            format!("{:+.1}%", (total - base_total) as f64 / base_total as f64 * 100.0)
+        } else {
+            "n/a".to_string()
+        };
+
+        println!();
+        println!("Start Time:\t{}\t\tTotal: {}", self.start_date.format("%Y-%m-%d %H:%M:%S"), total);
+        println!("Baseline:\t{}\t\tTotal: {}\t({})", baseline.start_date.format("%Y-%m-%d %H:%M:%S"), base_total, label);
+        println!("Duration:\t{} {}s\t\t\tChange: {}", self.duration, self.unit, change);
+
+        let mut differences: Vec<&(&str, i64, i64)> = pairs.iter().filter(|(_, v, b)| v != b).collect();
+        differences.sort_by_key(|(_, v, b)| std::cmp::Reverse((v - b).abs()));
+        if !differences.is_empty() {
+            println!();
+            println!("Largest differences:");
+            for (key, value, base) in differences.iter().take(5) {
+                println!("{}\t{} vs {}\t{:+}", Self::key_label(key), value, base, value - base);
+            }
         }
         println!();
+    }
 
-        // Print time markers
+    /// First instant of the plotted range
+    pub fn start(&self) -> DateTime<Local> {
+        self.start_date
+    }
+
+    fn print_time_markers(&self) {
+        let graph_width = self.data.len();
         let display_width = if self.wide { graph_width * 2 } else { graph_width };
         let pos_begin = 1;
         let pos_middle = display_width / 2;
@@ -373,18 +686,6 @@ impl GraphHash {
             }
         }
         println!();
-
-        // Summary
-        println!();
-        println!("Start Time:\t{}\t\tMinimum Value: {}", self.start_date.format("%Y-%m-%d %H:%M:%S"), self.min_value);
-        println!("End Time:\t{}\t\tMaximum Value: {}", self.end_date.format("%Y-%m-%d %H:%M:%S"), self.max_value);
-        let scale = if graph_height > 0 {
-            (self.max_value - self.min_value) as f64 / graph_height as f64
-        } else {
-            0.0
-        };
-        println!("Duration:\t{} {}s\t\t\tScale: {:.2}", self.duration, self.unit, scale);
-        println!();
     }
 
     fn start_date_value(&self) -> i64 {
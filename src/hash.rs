@@ -1,6 +1,12 @@
+use crate::eventid::event_id;
 use crate::filter::Filter;
 use crate::log_entry::{CrunchLog, LogEntry};
-use std::collections::HashMap;
+use crate::rollup::{source_ip, Rollup};
+use crate::users::UserExtractor;
+use anyhow::{Result, anyhow};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::IpAddr;
 use rand::seq::SliceRandom;
 
 #[derive(Debug, Clone, Copy)]
@@ -9,6 +15,96 @@ pub enum HashMode {
     Daemon,
     Host,
     WordCount,
+    /// Runs of this many consecutive words ("connection reset"), counted like words
+    NGrams(usize),
+    /// Event identifiers (Windows event IDs, Cisco message IDs, MySQL error
+    /// codes) with their names; entries without one are not counted
+    EventId,
+    /// Client and source addresses from web, load balancer and firewall logs,
+    /// optionally rolled up to their networks; entries without one are not counted
+    SourceIp(Option<Rollup>),
+    /// Users from sshd, PAM, sudo, su and logind messages and the user field of
+    /// web, EVTX and other parsers, with when each was first and last seen
+    User,
+}
+
+#[derive(Debug, Clone)]
+enum KeyPart {
+    Text(String),
+    Field(String),
+}
+
+/// A hash key built from entry fields, such as "{host}/{daemon}" or
+/// "{daemon} {severity}". `{daemon}` is the name without the PID, `{message}`
+/// the message with the filter applied, `{severity}` the syslog keyword, and
+/// any other name a structured field of the entry ("-" when missing)
+#[derive(Debug, Clone)]
+pub struct KeyTemplate {
+    parts: Vec<KeyPart>,
+}
+
+impl KeyTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    let name = name.trim();
+                    if name.is_empty() || name.contains('{') {
+                        return Err(anyhow!("Invalid field '{{{}}}' in key template '{}'", name, template));
+                    }
+                    if !text.is_empty() {
+                        parts.push(KeyPart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(KeyPart::Field(name.to_string()));
+                }
+                '}' => return Err(anyhow!("Unmatched '}}' in key template '{}', write '}}}}' for a brace", template)),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(KeyPart::Text(text));
+        }
+        if !parts.iter().any(|p| matches!(p, KeyPart::Field(_))) {
+            return Err(anyhow!("Key template '{}' has no {{field}}", template));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Whether the key includes the message, so a sample line still makes sense for it
+    pub fn uses_message(&self) -> bool {
+        self.parts.iter().any(|p| matches!(p, KeyPart::Field(name) if name == "message"))
+    }
+
+    pub fn render(&self, entry: &LogEntry, filter: &Filter) -> String {
+        let mut key = String::new();
+        for part in &self.parts {
+            match part {
+                KeyPart::Text(text) => key.push_str(text),
+                KeyPart::Field(name) => match name.as_str() {
+                    "host" => key.push_str(&entry.host),
+                    "daemon" => key.push_str(entry.program()),
+                    "message" => key.push_str(filter.scrub(entry.log_entry.trim_start_matches([':', ' '])).trim()),
+                    "severity" => key.push_str(entry.severity_name().unwrap_or("-")),
+                    field => key.push_str(entry.attrs.get(field).map(|v| v.as_str()).unwrap_or("-")),
+                },
+            }
+        }
+        key
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -23,6 +119,8 @@ pub struct SuperHash {
     filter: Filter,
     sample_mode: SampleMode,
     sample_threshold: usize,
+    limit: Option<usize>,
+    min_count: usize,
 }
 
 impl SuperHash {
@@ -32,6 +130,8 @@ impl SuperHash {
             filter,
             sample_mode: SampleMode::Threshold,
             sample_threshold: 3,
+            limit: None,
+            min_count: 0,
         }
     }
 
@@ -43,6 +143,16 @@ impl SuperHash {
         self.sample_mode = mode;
     }
 
+    /// Show at most this many keys
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// Leave out keys counted fewer times than this
+    pub fn set_min_count(&mut self, min_count: usize) {
+        self.min_count = min_count;
+    }
+
     pub fn increment(&mut self, key: String, entry: LogEntry) {
         self.data
             .entry(key)
@@ -54,6 +164,11 @@ impl SuperHash {
     }
 
     pub fn display(&self) {
+        let _ = self.write_to(&mut std::io::stdout().lock());
+    }
+
+    /// The "count:\tkey" listing into any writer, e.g. a digest being assembled
+    pub fn write_to<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
         // Sort by count (descending) and then alphabetically
         let mut items: Vec<_> = self.data.iter().collect();
         items.sort_by(|a, b| {
@@ -65,33 +180,33 @@ impl SuperHash {
             }
         });
 
+        let items = items.into_iter()
+            .filter(|(key, (count, _))| *key != "#" && *count >= self.min_count)
+            .take(self.limit.unwrap_or(usize::MAX));
         for (key, (count, entries)) in items {
-            if key == "#" {
-                continue;
-            }
-
             match self.sample_mode {
                 SampleMode::All => {
                     // Show random sample
                     if let Some(entry) = entries.choose(&mut rand::thread_rng()) {
-                        println!("{}:\t{}", count, entry.log_entry);
+                        writeln!(out, "{}:\t{}", count, entry.log_entry)?;
                     }
                 }
                 SampleMode::None => {
-                    println!("{}:\t{}", count, key);
+                    writeln!(out, "{}:\t{}", count, key)?;
                 }
                 SampleMode::Threshold => {
                     if *count <= self.sample_threshold {
                         // Show first entry for small counts
                         if let Some(entry) = entries.first() {
-                            println!("{}:\t{}", count, entry.log_entry);
+                            writeln!(out, "{}:\t{}", count, entry.log_entry)?;
                         }
                     } else {
-                        println!("{}:\t{}", count, key);
+                        writeln!(out, "{}:\t{}", count, key)?;
                     }
                 }
             }
         }
+        Ok(())
     }
 
     pub fn from_log(log: &CrunchLog, mode: HashMode, filter: Filter) -> Self {
@@ -102,6 +217,11 @@ impl SuperHash {
             HashMode::Daemon => hash.fill_daemon(log),
             HashMode::Host => hash.fill_host(log),
             HashMode::WordCount => hash.fill_wordcount(log),
+            HashMode::NGrams(n) if n > 1 => hash.fill_ngrams(log, n),
+            HashMode::NGrams(_) => hash.fill_wordcount(log),
+            HashMode::EventId => hash.fill_event_id(log),
+            HashMode::SourceIp(rollup) => hash.fill_source_ip(log, rollup),
+            HashMode::User => hash.fill_user(log, &UserExtractor::new()),
         }
 
         // Remove valueless entries
@@ -110,17 +230,80 @@ impl SuperHash {
         hash
     }
 
+    /// Hash mode with keys from a template instead of the filtered line
+    pub fn from_log_with_key(log: &CrunchLog, template: &KeyTemplate, filter: Filter) -> Self {
+        let mut hash = Self::new(filter);
+
+        for entry in &log.entries {
+            let key = template.render(entry, &hash.filter);
+            hash.increment(key, entry.clone());
+        }
+
+        hash
+    }
+
+    /// User mode with extra patterns for finding users
+    pub fn from_log_with_users(log: &CrunchLog, users: &UserExtractor, filter: Filter) -> Self {
+        let mut hash = Self::new(filter);
+        hash.fill_user(log, users);
+        hash
+    }
+
+    /// Times a key was counted
+    pub fn count(&self, key: &str) -> usize {
+        self.data.get(key).map(|(count, _)| *count).unwrap_or(0)
+    }
+
+    /// Start a key at a count carried over from an earlier run, with one sample
+    pub fn set_count(&mut self, key: String, count: usize, sample: LogEntry) {
+        self.data.insert(key, (count, vec![sample]));
+    }
+
+    /// Count one more entry, for input that arrives as a stream. Word and
+    /// phrase counts need the whole log, so only the hash, daemon and host
+    /// modes count anything here
+    pub fn add_entry(&mut self, mode: HashMode, entry: &LogEntry) {
+        let key = match mode {
+            HashMode::Hash => self.hash_key(entry),
+            HashMode::Daemon => self.daemon_key(entry),
+            HashMode::Host => self.filter.scrub(&entry.host),
+            HashMode::WordCount | HashMode::NGrams(_) | HashMode::EventId | HashMode::SourceIp(_) | HashMode::User => return,
+        };
+        if key != "#" {
+            self.increment(key, entry.clone());
+        }
+    }
+
+    fn hash_key(&self, entry: &LogEntry) -> String {
+        // Message IDs (e.g. %ASA-6-302013) are the grouping key, so keep them intact;
+        // query fingerprints are already normalized
+        if let Some(fingerprint) = entry.attrs.get("fingerprint") {
+            format!("{} {}", entry.daemon, fingerprint)
+        } else if entry.attrs.contains_key("message_id") {
+            format!("{} {}", entry.daemon, self.filter.scrub(&entry.log_entry))
+        } else {
+            self.filter.scrub(&format!("{} {}", entry.daemon, entry.log_entry))
+        }
+    }
+
+    fn daemon_key(&self, entry: &LogEntry) -> String {
+        if entry.attrs.contains_key("message_id") {
+            entry.daemon.clone()
+        } else {
+            self.filter.scrub(&entry.daemon)
+        }
+    }
+
     fn fill_hash(&mut self, log: &CrunchLog) {
         for entry in &log.entries {
-            let key = format!("{} {}", entry.daemon, entry.log_entry);
-            let key = self.filter.scrub(&key);
+            let key = self.hash_key(entry);
             self.increment(key, entry.clone());
         }
     }
 
     fn fill_daemon(&mut self, log: &CrunchLog) {
         for entry in &log.entries {
-            let key = self.filter.scrub(&entry.daemon);
+            let key = self.daemon_key(entry);
             self.increment(key, entry.clone());
         }
     }
@@ -132,6 +315,80 @@ impl SuperHash {
         }
     }
 
+    /// "ID: name" keys. IDs without a well-known name are named after the
+    /// filtered message of their first entry
+    fn fill_event_id(&mut self, log: &CrunchLog) {
+        let mut names: HashMap<String, String> = HashMap::new();
+
+        for entry in &log.entries {
+            let (id, name) = match event_id(entry) {
+                Some(found) => found,
+                None => continue,
+            };
+            let name = names.entry(id.clone())
+                .or_insert_with(|| name.unwrap_or_else(|| self.filter.scrub(entry.log_entry.trim_start_matches([':', ' ']))));
+            let key = format!("{}: {}", id, name);
+            self.increment(key, entry.clone());
+        }
+    }
+
+    /// Addresses, or "203.0.113.0/24 (57 addresses)" keys when rolled up
+    fn fill_source_ip(&mut self, log: &CrunchLog, rollup: Option<Rollup>) {
+        let rollup = match rollup {
+            Some(rollup) => rollup,
+            None => {
+                for entry in &log.entries {
+                    if let Some(ip) = source_ip(entry) {
+                        self.increment(ip.to_string(), entry.clone());
+                    }
+                }
+                return;
+            }
+        };
+
+        let mut networks: HashMap<String, (Vec<&LogEntry>, HashSet<IpAddr>)> = HashMap::new();
+        for entry in &log.entries {
+            if let Some(ip) = source_ip(entry) {
+                let (entries, addresses) = networks.entry(rollup.network(ip)).or_default();
+                entries.push(entry);
+                addresses.insert(ip);
+            }
+        }
+
+        for (network, (entries, addresses)) in networks {
+            let key = match addresses.len() {
+                1 => format!("{} (1 address)", network),
+                n => format!("{} ({} addresses)", network, n),
+            };
+            for entry in entries {
+                self.increment(key.clone(), entry.clone());
+            }
+        }
+    }
+
+    /// "user<TAB>first .. last" keys
+    fn fill_user(&mut self, log: &CrunchLog, users: &UserExtractor) {
+        let mut by_user: HashMap<String, Vec<&LogEntry>> = HashMap::new();
+        for entry in &log.entries {
+            if let Some(user) = users.user(entry) {
+                by_user.entry(user).or_default().push(entry);
+            }
+        }
+
+        for (user, entries) in by_user {
+            let first = entries.iter().map(|e| e.naive_datetime()).min();
+            let last = entries.iter().map(|e| e.naive_datetime()).max();
+            let key = match (first, last) {
+                (Some(first), Some(last)) => format!("{}\t{} .. {}", user,
+                    first.format("%Y-%m-%d %H:%M:%S"), last.format("%Y-%m-%d %H:%M:%S")),
+                _ => user,
+            };
+            for entry in entries {
+                self.increment(key.clone(), entry.clone());
+            }
+        }
+    }
+
     fn fill_wordcount(&mut self, log: &CrunchLog) {
         let mut word_map: HashMap<String, Vec<String>> = HashMap::new();
 
@@ -164,6 +421,56 @@ impl SuperHash {
         }
     }
 
+    /// Count phrases of `n` words. Words the filter bleaches (stopwords, numbers)
+    /// only count when most of the phrase carries value, so "authentication
+    /// failure for" is kept while "for the #" is not; stopwords are shown as
+    /// written and numbers as "#"
+    fn fill_ngrams(&mut self, log: &CrunchLog, n: usize) {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let words: Vec<(String, bool)> = entry.log_entry.split_whitespace()
+                .map(|word| {
+                    let scrubbed = self.filter.scrub(word);
+                    if scrubbed != "#" {
+                        (scrubbed, true)
+                    } else if word.chars().any(|c| c.is_ascii_digit()) {
+                        ("#".to_string(), false)
+                    } else {
+                        (word.to_string(), false)
+                    }
+                })
+                .collect();
+
+            for window in words.windows(n) {
+                let valued = window.iter().filter(|(_, valued)| *valued).count();
+                if valued * 2 <= n {
+                    continue;
+                }
+                let phrase = window.iter().map(|(word, _)| word.as_str()).collect::<Vec<_>>().join(" ");
+                *counts.entry(phrase).or_insert(0) += 1;
+            }
+        }
+
+        for (phrase, count) in counts {
+            let mut entry = LogEntry::new();
+            entry.log_entry = phrase.clone();
+            for _ in 0..count {
+                self.increment(phrase.clone(), entry.clone());
+            }
+        }
+    }
+
+    /// Keep only the keys for which `keep` returns true
+    pub fn retain<F: FnMut(&str) -> bool>(&mut self, mut keep: F) {
+        self.data.retain(|key, _| keep(key));
+    }
+
+    /// Each key with the entries counted under it
+    pub fn patterns(&self) -> impl Iterator<Item = (&str, &[LogEntry])> {
+        self.data.iter().map(|(key, (_, entries))| (key.as_str(), entries.as_slice()))
+    }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
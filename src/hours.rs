@@ -0,0 +1,70 @@
+use crate::filter::Filter;
+use crate::log_entry::CrunchLog;
+use crate::split::SplitKey;
+use std::collections::HashMap;
+
+/// Shades from a quiet hour to the busiest hour of a row
+const LEVELS: [char; 7] = ['.', ':', '-', '=', '+', '*', '#'];
+
+/// One character per count, scaled to the largest count; blank when zero
+pub(crate) fn shade(counts: &[usize]) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    counts.iter()
+        .map(|&count| match count {
+            0 => ' ',
+            _ => LEVELS[((count * LEVELS.len()).div_ceil(max) - 1).min(LEVELS.len() - 1)],
+        })
+        .collect()
+}
+
+/// When each daemon (or host) logs: entry counts per hour of the day, summed
+/// over all days in the log
+pub struct HourlyProfile {
+    key: SplitKey,
+    hours: HashMap<String, [usize; 24]>,
+}
+
+impl HourlyProfile {
+    pub fn from_log(log: &CrunchLog, key: SplitKey, filter: &Filter) -> Self {
+        let mut hours: HashMap<String, [usize; 24]> = HashMap::new();
+        for entry in &log.entries {
+            if entry.is_undated() || entry.hour > 23 {
+                continue;
+            }
+            let name = filter.scrub(key.value(entry));
+            hours.entry(name).or_insert([0; 24])[entry.hour as usize] += 1;
+        }
+
+        Self { key, hours }
+    }
+
+    pub fn hours(&self, name: &str) -> Option<&[usize; 24]> {
+        self.hours.get(name)
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.hours.is_empty() {
+            println!("No timestamped entries found");
+            return;
+        }
+
+        let mut rows: Vec<(&String, &[usize; 24], usize)> = self.hours.iter()
+            .map(|(name, hours)| (name, hours, hours.iter().sum()))
+            .collect();
+        rows.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)));
+
+        let name = match self.key {
+            SplitKey::Daemon => "daemon",
+            SplitKey::Host => "host",
+        };
+        println!("Entries by hour of day for the top {} {}s (each row scaled to its busiest hour):", limit.min(rows.len()), name);
+        println!("  total   peak  active  |0         1         2   |");
+        println!("                        |012345678901234567890123|");
+        for (name, hours, total) in rows.into_iter().take(limit) {
+            let peak = (0..24).max_by_key(|&h| (hours[h], std::cmp::Reverse(h))).unwrap_or(0);
+            let active = hours.iter().filter(|&&count| count > 0).count();
+            println!("{:>7}  {:02}:00  {:>6}  |{}|  {}", total, peak, active, shade(hours), name);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,160 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+/// A batch job pieced together from its scheduler events
+#[derive(Debug, Clone, Default)]
+pub struct Job {
+    pub id: String,
+    pub user: Option<String>,
+    pub partition: Option<String>,
+    pub nodes: Option<String>,
+    pub submitted: Option<NaiveDateTime>,
+    pub started: Option<NaiveDateTime>,
+    pub ended: Option<NaiveDateTime>,
+    /// Final state: "end", "fail", "cancel" or "timeout"
+    pub state: Option<String>,
+    pub exit: Option<String>,
+}
+
+impl Job {
+    /// Seconds from submission to start, if both were seen
+    pub fn wait(&self) -> Option<i64> {
+        Some((self.started? - self.submitted?).num_seconds())
+    }
+}
+
+/// Submitted/started/completed/failed/canceled/timed out counts for one user or partition
+#[derive(Default)]
+struct JobCounts {
+    submitted: usize,
+    started: usize,
+    completed: usize,
+    failed: usize,
+    canceled: usize,
+    timeout: usize,
+}
+
+pub struct JobReport {
+    jobs: Vec<Job>,
+}
+
+impl JobReport {
+    /// Collect the submit/start/end/fail/cancel/timeout events SLURM and PBS
+    /// entries carry in their "event" field, by job ID
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut jobs: Vec<Job> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let (event, id) = match (entry.attrs.get("event"), entry.attrs.get("job")) {
+                (Some(event), Some(id)) => (event.as_str(), id),
+                _ => continue,
+            };
+            let idx = *seen.entry(id.clone()).or_insert_with(|| {
+                jobs.push(Job { id: id.clone(), ..Default::default() });
+                jobs.len() - 1
+            });
+
+            let job = &mut jobs[idx];
+            let ts = entry.naive_datetime();
+            for (field, key) in [(&mut job.user, "user"), (&mut job.partition, "partition"), (&mut job.nodes, "nodes"), (&mut job.exit, "exit")] {
+                if field.is_none() {
+                    *field = entry.attrs.get(key).cloned();
+                }
+            }
+            match event {
+                "submit" => { job.submitted.get_or_insert(ts); }
+                "start" => { job.started.get_or_insert(ts); }
+                _ => {
+                    // A timeout or cancel is followed by a non-zero exit; keep the cause
+                    if job.state.is_none() || job.state.as_deref() == Some("end") {
+                        job.state = Some(event.to_string());
+                    }
+                    job.ended = Some(ts);
+                }
+            }
+        }
+
+        Self { jobs }
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    fn count(counts: &mut HashMap<String, JobCounts>, key: &str, job: &Job) {
+        let counts = counts.entry(key.to_string()).or_default();
+        if job.submitted.is_some() {
+            counts.submitted += 1;
+        }
+        if job.started.is_some() {
+            counts.started += 1;
+        }
+        match job.state.as_deref() {
+            Some("end") => counts.completed += 1,
+            Some("fail") => counts.failed += 1,
+            Some("cancel") => counts.canceled += 1,
+            Some("timeout") => counts.timeout += 1,
+            _ => {}
+        }
+    }
+
+    fn print_counts(title: &str, counts: HashMap<String, JobCounts>, limit: usize) {
+        if counts.is_empty() {
+            return;
+        }
+
+        let mut items: Vec<_> = counts.into_iter().collect();
+        items.sort_by(|a, b| {
+            let total = |c: &JobCounts| c.submitted.max(c.started);
+            total(&b.1).cmp(&total(&a.1)).then(a.0.cmp(&b.0))
+        });
+
+        println!("{}:", title);
+        println!("submitted\tstarted\tcompleted\tfailed\tcanceled\ttimeout\tname");
+        for (key, c) in items.into_iter().take(limit) {
+            println!("{}\t{}\t{}\t{}\t{}\t{}\t{}", c.submitted, c.started, c.completed, c.failed, c.canceled, c.timeout, key);
+        }
+        println!();
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.jobs.is_empty() {
+            println!("No scheduler job events found");
+            return;
+        }
+
+        let state = |s: &str| self.jobs.iter().filter(|j| j.state.as_deref() == Some(s)).count();
+        let running = self.jobs.iter().filter(|j| j.started.is_some() && j.state.is_none()).count();
+        println!("Jobs: {}\tCompleted: {}\tFailed: {}\tCanceled: {}\tTimed out: {}\tRunning or unfinished: {}",
+            self.jobs.len(), state("end"), state("fail"), state("cancel"), state("timeout"), running);
+        println!();
+
+        let mut users: HashMap<String, JobCounts> = HashMap::new();
+        let mut partitions: HashMap<String, JobCounts> = HashMap::new();
+        let mut failed_nodes: HashMap<String, usize> = HashMap::new();
+        for job in &self.jobs {
+            Self::count(&mut users, job.user.as_deref().unwrap_or("-"), job);
+            Self::count(&mut partitions, job.partition.as_deref().unwrap_or("-"), job);
+            if matches!(job.state.as_deref(), Some("fail") | Some("timeout")) {
+                *failed_nodes.entry(job.nodes.clone().unwrap_or_else(|| "-".to_string())).or_insert(0) += 1;
+            }
+        }
+        Self::print_counts("Jobs per user", users, limit);
+        Self::print_counts("Jobs per partition", partitions, limit);
+        print_top("Failed and timed out jobs per node", failed_nodes, limit);
+
+        let mut waits: Vec<&Job> = self.jobs.iter().filter(|j| j.wait().is_some()).collect();
+        waits.sort_by_key(|j| std::cmp::Reverse(j.wait()));
+        if !waits.is_empty() {
+            println!("Longest queue waits:");
+            for job in waits.iter().take(limit) {
+                println!("{}s\t{}\t{}\t{}", job.wait().unwrap_or(0), job.id,
+                    job.user.as_deref().unwrap_or("-"), job.partition.as_deref().unwrap_or("-"));
+            }
+            println!();
+        }
+    }
+}
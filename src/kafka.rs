@@ -0,0 +1,103 @@
+use crate::log_entry::CrunchLog;
+use anyhow::{Result, anyhow};
+use log::info;
+use rdkafka::client::ClientContext;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for the brokers to acknowledge what is still queued at the end
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Counts messages the brokers did not take, from the delivery reports
+#[derive(Default)]
+struct Deliveries {
+    failed: Mutex<(usize, Option<String>)>,
+}
+
+impl ClientContext for Deliveries {}
+
+impl ProducerContext for Deliveries {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        if let Err((e, _)) = result {
+            let mut failed = self.failed.lock().unwrap();
+            failed.0 += 1;
+            failed.1.get_or_insert_with(|| e.to_string());
+        }
+    }
+}
+
+/// Produces each entry to a Kafka topic as a JSON message (see
+/// `LogEntry::to_json`), keyed by host so a host's entries stay in order
+pub struct KafkaExporter {
+    brokers: String,
+    topic: String,
+    options: Vec<(String, String)>,
+}
+
+impl KafkaExporter {
+    /// "broker:9092/topic", or "b1:9092,b2:9092/topic" for several brokers
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (brokers, topic) = spec.rsplit_once('/')
+            .filter(|(brokers, topic)| !brokers.is_empty() && !topic.is_empty())
+            .ok_or_else(|| anyhow!("Invalid Kafka target '{}', expected broker:port/topic", spec))?;
+        Ok(Self { brokers: brokers.to_string(), topic: topic.to_string(), options: Vec::new() })
+    }
+
+    /// A librdkafka producer setting as "name=value", e.g.
+    /// "security.protocol=SASL_SSL" or "compression.type=zstd"
+    pub fn add_option(&mut self, spec: &str) -> Result<()> {
+        let (name, value) = spec.split_once('=')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(|| anyhow!("Invalid Kafka option '{}', expected name=value", spec))?;
+        self.options.push((name.trim().to_string(), value.trim().to_string()));
+        Ok(())
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Produce all entries of the log, returning how many the brokers acknowledged
+    pub fn export(&self, log: &CrunchLog) -> Result<usize> {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", &self.brokers);
+        for (name, value) in &self.options {
+            config.set(name, value);
+        }
+        let producer: BaseProducer<Deliveries> = config.create_with_context(Deliveries::default())
+            .map_err(|e| anyhow!("Cannot create Kafka producer for {}: {}", self.brokers, e))?;
+
+        for entry in &log.entries {
+            let payload = entry.to_json().to_string();
+            let mut record = BaseRecord::to(&self.topic).key(&entry.host).payload(&payload);
+            // A full queue drains as delivery reports are polled
+            loop {
+                match producer.send(record) {
+                    Ok(()) => break,
+                    Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                        record = returned;
+                        producer.poll(Duration::from_millis(100));
+                    }
+                    Err((e, _)) => return Err(anyhow!("Cannot produce to {}: {}", self.topic, e)),
+                }
+            }
+            producer.poll(Duration::ZERO);
+        }
+
+        producer.flush(FLUSH_TIMEOUT)
+            .map_err(|e| anyhow!("Kafka brokers {} did not acknowledge all entries: {}", self.brokers, e))?;
+
+        let (failed, error) = producer.context().failed.lock().unwrap().clone();
+        if failed > 0 {
+            return Err(anyhow!("{} of {} entries were not delivered to {}: {}",
+                failed, log.entries.len(), self.topic, error.unwrap_or_default()));
+        }
+        info!("Produced {} entries to {}", log.entries.len(), self.topic);
+        Ok(log.entries.len())
+    }
+}
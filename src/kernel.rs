@@ -0,0 +1,223 @@
+use crate::log_entry::CrunchLog;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+/// A process killed by the OOM killer, with the process that ran out of
+/// memory when the kernel logged it
+#[derive(Debug, Clone)]
+pub struct OomKill {
+    pub timestamp: NaiveDateTime,
+    pub host: String,
+    pub victim: String,
+    pub pid: String,
+    /// Badness score (kernels before 4.19) and oom_score_adj, when logged
+    pub score: Option<String>,
+    pub score_adj: Option<String>,
+    pub anon_rss: Option<String>,
+    pub invoked_by: Option<String>,
+    /// CONSTRAINT_NONE for the whole system, CONSTRAINT_MEMCG for a cgroup limit
+    pub constraint: Option<String>,
+}
+
+/// A machine check, memory (EDAC), I/O error or segfault
+#[derive(Debug, Clone)]
+pub struct HardwareError {
+    pub timestamp: NaiveDateTime,
+    pub host: String,
+    /// "MCE", "EDAC", "I/O" or "segfault"
+    pub kind: &'static str,
+    /// CPU, memory controller, block device or program, "-" when not named
+    pub device: String,
+    pub message: String,
+}
+
+/// A network interface going down or coming up
+#[derive(Debug, Clone)]
+pub struct LinkChange {
+    pub timestamp: NaiveDateTime,
+    pub host: String,
+    pub interface: String,
+    pub up: bool,
+}
+
+/// OOM killer runs, hardware errors and link flaps from kernel entries, in
+/// syslog, journalctl or dmesg output
+pub struct KernelReport {
+    ooms: Vec<OomKill>,
+    errors: Vec<HardwareError>,
+    links: Vec<LinkChange>,
+}
+
+impl KernelReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // "java invoked oom-killer: gfp_mask=0x100cca(GFP_HIGHUSER_MOVABLE), order=0, oom_score_adj=0"
+        let invoked = Regex::new(r"(\S+) invoked oom-killer:").unwrap();
+        // "oom-kill:constraint=CONSTRAINT_NONE,nodemask=(null),...,task=java,pid=4242,uid=0"
+        let constraint = Regex::new(r"oom-kill:constraint=(\w+)").unwrap();
+        // "Out of memory: Killed process 4242 (java) total-vm:..kB, anon-rss:..kB, ... oom_score_adj:0"
+        // "Out of memory: Kill process 4242 (java) score 912 or sacrifice child"
+        let killed = Regex::new(r"[Oo]ut of memory: Kill(?:ed)? process (\d+) \(([^)]+)\)(.*)").unwrap();
+        let score = Regex::new(r"\bscore (\d+)").unwrap();
+        let score_adj = Regex::new(r"oom_score_adj[:=](-?\d+)").unwrap();
+        let anon_rss = Regex::new(r"anon-rss:(\d+\w*)").unwrap();
+
+        let mce = Regex::new(r"\[Hardware Error\]|[Mm]achine [Cc]heck").unwrap();
+        let mce_cpu = Regex::new(r"\bCPU:? ?(\d+)").unwrap();
+        // "EDAC MC0: 1 CE memory read error on CPU_SrcID#0_Ha#0_Chan#1_DIMM#0"
+        let edac = Regex::new(r"EDAC (\w+)").unwrap();
+        // "blk_update_request: I/O error, dev sda, sector 2048 op 0x0:(READ)", "Buffer I/O error on dev sda1, logical block 0"
+        let io_error = Regex::new(r"I/O error,? (?:on )?(?:dev(?:ice)? )?(\w[\w-]*)").unwrap();
+        let segfault = Regex::new(r"(?:^|\s)([^\s\[]+)\[\d+\]:? (?:segfault at|general protection)").unwrap();
+
+        // "e1000e: eth0 NIC Link is Down", "igb 0000:01:00.0 eno1: igb: eno1 NIC Link is Up 1000 Mbps",
+        // "bond0: link status definitely down for interface eth1, disabling it"
+        let link = Regex::new(r"(?i)\blink (?:is |status definitely |becomes )?(up|down)\b").unwrap();
+        let bond_slave = Regex::new(r"for interface (\S+?),?(?:\s|$)").unwrap();
+        let interface = Regex::new(r"\b((?:eth|en[ospx]|em|eno|bond|wl|ib|br|vlan|team)[\w.-]*)\b").unwrap();
+
+        let mut ooms = Vec::new();
+        let mut errors = Vec::new();
+        let mut links = Vec::new();
+        // An OOM killer run spans several lines; the invoking process and
+        // the constraint come before the kill, per host
+        let mut pending: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+
+        for entry in &log.entries {
+            if entry.program() != "kernel" {
+                continue;
+            }
+            let message = entry.log_entry.strip_prefix(": ").or_else(|| entry.log_entry.strip_prefix(" ")).unwrap_or(&entry.log_entry).trim_end();
+            let timestamp = entry.naive_datetime();
+            let host = entry.host.clone();
+
+            if let Some(caps) = invoked.captures(message) {
+                pending.insert(host, (Some(caps[1].to_string()), None));
+            } else if let Some(caps) = constraint.captures(message) {
+                pending.entry(host).or_default().1 = Some(caps[1].to_string());
+            } else if let Some(caps) = killed.captures(message) {
+                let (invoked_by, constraint) = pending.remove(&host).unwrap_or_default();
+                let rest = &caps[3];
+                ooms.push(OomKill {
+                    timestamp,
+                    host,
+                    victim: caps[2].to_string(),
+                    pid: caps[1].to_string(),
+                    score: score.captures(rest).map(|c| c[1].to_string()),
+                    score_adj: score_adj.captures(rest).map(|c| c[1].to_string()),
+                    anon_rss: anon_rss.captures(rest).map(|c| c[1].to_string()),
+                    invoked_by,
+                    constraint,
+                });
+            } else if let Some(caps) = link.captures(message) {
+                let name = bond_slave.captures(message).or_else(|| interface.captures(message)).map(|c| c[1].to_string());
+                if let Some(name) = name {
+                    links.push(LinkChange { timestamp, host, interface: name, up: caps[1].eq_ignore_ascii_case("up") });
+                }
+            } else {
+                let error = |kind: &'static str, device: Option<&str>| HardwareError {
+                    timestamp,
+                    host: host.clone(),
+                    kind,
+                    device: device.unwrap_or("-").to_string(),
+                    message: message.to_string(),
+                };
+                if let Some(caps) = edac.captures(message) {
+                    errors.push(error("EDAC", Some(&caps[1])));
+                } else if mce.is_match(message) {
+                    let cpu = mce_cpu.captures(message).map(|c| format!("cpu{}", &c[1]));
+                    errors.push(error("MCE", cpu.as_deref()));
+                } else if let Some(caps) = io_error.captures(message) {
+                    errors.push(error("I/O", Some(&caps[1])));
+                } else if let Some(caps) = segfault.captures(message) {
+                    errors.push(error("segfault", Some(&caps[1])));
+                }
+            }
+        }
+
+        ooms.sort_by_key(|o| o.timestamp);
+        errors.sort_by_key(|e| e.timestamp);
+        links.sort_by_key(|l| l.timestamp);
+        Self { ooms, errors, links }
+    }
+
+    pub fn ooms(&self) -> &[OomKill] {
+        &self.ooms
+    }
+
+    pub fn errors(&self) -> &[HardwareError] {
+        &self.errors
+    }
+
+    pub fn links(&self) -> &[LinkChange] {
+        &self.links
+    }
+
+    /// The most recent `limit` rows of a table, under its title
+    fn recent<'a, T>(title: &str, rows: &'a [T], limit: usize) -> &'a [T] {
+        if rows.len() > limit {
+            println!("{} (last {} of {}):", title, limit, rows.len());
+        } else {
+            println!("{}:", title);
+        }
+        &rows[rows.len().saturating_sub(limit)..]
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.ooms.is_empty() && self.errors.is_empty() && self.links.is_empty() {
+            println!("No OOM kills, hardware errors or link changes found");
+            return;
+        }
+
+        let downs = self.links.iter().filter(|l| !l.up).count();
+        println!("OOM kills: {}\tHardware errors: {}\tLink downs: {}", self.ooms.len(), self.errors.len(), downs);
+        println!();
+
+        let time = |t: &NaiveDateTime| t.format("%Y-%m-%d %H:%M:%S").to_string();
+        let or_dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+
+        if !self.ooms.is_empty() {
+            let ooms = Self::recent("OOM kills", &self.ooms, limit);
+            println!("time\t\t\thost\tvictim\tpid\tscore\tadj\tanon-rss\tinvoked by\tconstraint");
+            for oom in ooms {
+                println!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}", time(&oom.timestamp), oom.host, oom.victim, oom.pid,
+                    or_dash(&oom.score), or_dash(&oom.score_adj), or_dash(&oom.anon_rss), or_dash(&oom.invoked_by), or_dash(&oom.constraint));
+            }
+            println!();
+        }
+
+        if !self.errors.is_empty() {
+            let errors = Self::recent("Hardware errors", &self.errors, limit);
+            println!("time\t\t\thost\ttype\tdevice\tmessage");
+            for error in errors {
+                println!("{}\t{}\t{}\t{}\t{}", time(&error.timestamp), error.host, error.kind, error.device, error.message);
+            }
+            println!();
+        }
+
+        if !self.links.is_empty() {
+            // Down and up counts per interface; more than one down is a flap
+            let mut interfaces: BTreeMap<(&str, &str), (usize, usize)> = BTreeMap::new();
+            for change in &self.links {
+                let counts = interfaces.entry((change.host.as_str(), change.interface.as_str())).or_default();
+                if change.up { counts.1 += 1 } else { counts.0 += 1 }
+            }
+            println!("Links:");
+            println!("downs\tups\thost\tinterface");
+            let mut interfaces: Vec<_> = interfaces.into_iter().collect();
+            interfaces.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(a.0.cmp(&b.0)));
+            for ((host, interface), (downs, ups)) in interfaces.iter().take(limit) {
+                let flapping = if *downs > 1 { "\tflapping" } else { "" };
+                println!("{}\t{}\t{}\t{}{}", downs, ups, host, interface, flapping);
+            }
+            println!();
+
+            let changes = Self::recent("Link changes", &self.links, limit);
+            println!("time\t\t\thost\tinterface\tstate");
+            for change in changes {
+                println!("{}\t{}\t{}\t{}", time(&change.timestamp), change.host, change.interface, if change.up { "up" } else { "down" });
+            }
+            println!();
+        }
+    }
+}
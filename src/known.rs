@@ -0,0 +1,99 @@
+use crate::hash::SuperHash;
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A pattern recorded in the database
+#[derive(Debug, Clone)]
+pub struct KnownPattern {
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Occurrences over all learning runs
+    pub count: usize,
+}
+
+/// Persistent set of scrubbed hash keys seen in earlier runs, stored as JSON:
+/// {"version": 1, "patterns": {"<key>": {"first_seen": ..., "last_seen": ..., "count": n}}}
+pub struct PatternDb {
+    path: PathBuf,
+    patterns: BTreeMap<String, KnownPattern>,
+}
+
+impl PatternDb {
+    /// Load the database, or start an empty one when the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut db = Self {
+            path: path.to_path_buf(),
+            patterns: BTreeMap::new(),
+        };
+        if !path.exists() {
+            return Ok(db);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let patterns = json.get("patterns")
+            .and_then(|p| p.as_object())
+            .ok_or_else(|| anyhow!("{} is not a pattern database", path.display()))?;
+        for (key, value) in patterns {
+            let text = |name: &str| value.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            db.patterns.insert(key.clone(), KnownPattern {
+                first_seen: text("first_seen"),
+                last_seen: text("last_seen"),
+                count: value.get("count").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+            });
+        }
+        Ok(db)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.patterns.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&KnownPattern> {
+        self.patterns.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Record every key of a hash, returning how many were not known before
+    pub fn learn(&mut self, hash: &SuperHash) -> usize {
+        let now = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        let mut added = 0;
+        for (key, entries) in hash.patterns() {
+            let known = self.patterns.entry(key.to_string()).or_insert_with(|| {
+                added += 1;
+                KnownPattern {
+                    first_seen: now.clone(),
+                    last_seen: now.clone(),
+                    count: 0,
+                }
+            });
+            known.last_seen = now.clone();
+            known.count += entries.len();
+        }
+        added
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let patterns: serde_json::Map<String, serde_json::Value> = self.patterns.iter()
+            .map(|(key, known)| (key.clone(), serde_json::json!({
+                "first_seen": known.first_seen,
+                "last_seen": known.last_seen,
+                "count": known.count,
+            })))
+            .collect();
+        let json = serde_json::json!({
+            "version": 1,
+            "patterns": patterns,
+        });
+        std::fs::write(&self.path, serde_json::to_string_pretty(&json)?)?;
+        Ok(())
+    }
+}
@@ -3,9 +3,111 @@ pub mod filter;
 pub mod hash;
 pub mod graph;
 pub mod evtx_parser;
+pub mod report;
+pub mod split;
+pub mod skew;
+pub mod dedupe;
+pub mod mail;
+pub mod auth;
+pub mod fail2ban;
+pub mod dns;
+pub mod dhcp;
+pub mod vpn;
+pub mod firewall;
+pub mod flows;
+pub mod ual;
+pub mod suricata;
+pub mod alerts;
+pub mod pve;
+pub mod xfer;
+pub mod samba;
+pub mod asterisk;
+pub mod hpc;
+pub mod gc;
+pub mod systemd;
+pub mod kernel;
+pub mod disk;
+pub mod matrix;
+pub mod hours;
+pub mod correlate;
+pub mod context;
+pub mod pidtrace;
+pub mod entropy;
+pub mod known;
+pub mod digest;
+pub mod policy;
+pub mod sources;
+pub mod follow;
+pub mod checkpoint;
+pub mod rules;
+pub mod packs;
+pub mod eventid;
+pub mod rollup;
+pub mod users;
+pub mod timeline;
+pub mod trend;
+#[cfg(feature = "loki")]
+pub mod loki;
+pub mod metrics;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "pdf")]
+pub mod pdf;
 
-pub use log_entry::{LogEntry, CrunchLog};
-pub use filter::Filter;
-pub use hash::{SuperHash, HashMode, SampleMode};
-pub use graph::{GraphHash, GraphType};
+pub use log_entry::{LogEntry, CrunchLog, CsvMap, CsvParser, LogFormat, LogFormatParser, RecordAssembler};
+pub use filter::{Filter, FilterOptions};
+pub use hash::{SuperHash, HashMode, KeyTemplate, SampleMode};
+pub use graph::{GraphHash, GraphType, GraphValues, HLine};
 pub use evtx_parser::EvtxLogParser;
+pub use split::{LogSplitter, SplitKey};
+pub use skew::{ClockSkew, HostOffset};
+pub use dedupe::{DuplicateLine, DuplicateReport};
+pub use mail::{DeliveryAttempt, MailReport};
+pub use auth::{AuthEvent, AuthPattern, AuthReport};
+pub use fail2ban::{BanReport, IpBans, JailStats};
+pub use dns::{DnsEvent, DnsPattern, DnsReport};
+pub use dhcp::{DhcpReport, LeaseAssignment};
+pub use vpn::{VpnReport, VpnSession};
+pub use firewall::{FirewallEvent, FirewallReport};
+pub use flows::{Flow, FlowReport};
+pub use ual::{UalReport, UserActivity};
+pub use suricata::{SuricataAlert, SuricataReport};
+pub use alerts::{AlertReport, RuleAlerts};
+pub use pve::{PveTask, PveTaskReport, Upid};
+pub use xfer::{Transfer, TransferReport};
+pub use samba::{FileAccess, SambaReport};
+pub use asterisk::{Call, CallReport};
+pub use hpc::{Job, JobReport};
+pub use gc::{GcPause, GcReport};
+pub use systemd::{RestartLoop, SystemdReport, UnitEvent, UnitEventKind};
+pub use kernel::{HardwareError, KernelReport, LinkChange, OomKill};
+pub use disk::{DiskEvent, DiskReport};
+pub use matrix::Matrix;
+pub use hours::HourlyProfile;
+pub use correlate::{Correlation, CorrelationReport};
+pub use context::ContextMatches;
+pub use pidtrace::{PidTrace, TracedProcess};
+pub use entropy::{EntropyReport, EntropyTarget, ScoredValue};
+pub use known::{KnownPattern, PatternDb};
+pub use digest::{Digest, DigestConfig, SectionConfig};
+pub use policy::{Comparison, CountCondition, CountKey, FailPolicy};
+pub use sources::{SourceReport, SourceSummary};
+pub use follow::{FilePosition, FollowEvent, Follower};
+pub use checkpoint::{FollowState, SavedPattern};
+pub use rules::{Rule, RuleSet, TagReport};
+pub use packs::FilterPack;
+pub use rollup::Rollup;
+pub use users::UserExtractor;
+pub use timeline::HostTimeline;
+pub use trend::{SeverityTrend, TrendBucket};
+#[cfg(feature = "loki")]
+pub use loki::LokiExporter;
+pub use metrics::{MetricsEmitter, MetricsTarget};
+#[cfg(feature = "otlp")]
+pub use otlp::OtlpExporter;
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaExporter;
+#[cfg(feature = "pdf")]
+pub use pdf::PdfReport;
@@ -1,10 +1,14 @@
-use chrono::{Datelike, Local, DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{Datelike, Duration, Local, DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
 use regex::Regex;
 use anyhow::{Result, anyhow};
 use std::io::{BufRead, BufReader};
 use std::fs::File;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use crate::pve::Upid;
+use crate::sources::INPUT_ATTR;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LogEntry {
     pub year: i32,
     pub month: u32,
@@ -15,6 +19,10 @@ pub struct LogEntry {
     pub host: String,
     pub daemon: String,
     pub log_entry: String,
+    /// Syslog severity (0 = emerg .. 7 = debug), when the format carries one
+    pub severity: Option<u8>,
+    /// Structured fields extracted by the parser (message IDs, src/dst, ...)
+    pub attrs: BTreeMap<String, String>,
 }
 
 impl LogEntry {
@@ -29,6 +37,8 @@ impl LogEntry {
             host: "#".to_string(),
             daemon: "#".to_string(),
             log_entry: "#".to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
         }
     }
 
@@ -42,6 +52,137 @@ impl LogEntry {
         self.host = "#".to_string();
         self.daemon = "#".to_string();
         self.log_entry = value.to_string();
+        self.severity = None;
+        self.attrs.clear();
+    }
+
+    /// Whether the entry has no timestamp of its own: an abnormal line the
+    /// parser could not read, dated 1900-01-01
+    pub fn is_undated(&self) -> bool {
+        self.year == 1900
+    }
+
+    /// The bare program name as the daemon: "sshd[1234]:", "sshd[1234]" and
+    /// "sshd:" all become "sshd", so one program is not split up by the way
+    /// its parser writes it. The PID moves to the "pid" attr, unless the
+    /// parser already set one
+    pub fn normalize_daemon(&mut self) {
+        let daemon = self.daemon.trim_end_matches(':');
+        let (name, pid) = match daemon.strip_suffix(']').and_then(|d| d.rsplit_once('[')) {
+            Some((name, pid)) if !name.is_empty() && !pid.is_empty() && pid.chars().all(|c| c.is_ascii_digit()) => (name, Some(pid)),
+            _ => (daemon, None),
+        };
+        if let Some(pid) = pid {
+            self.attrs.entry("pid".to_string()).or_insert_with(|| pid.to_string());
+        }
+        if name != self.daemon && !name.is_empty() {
+            self.daemon = name.to_string();
+        }
+    }
+
+    /// The process ID, from the "pid" attr or a daemon still written as
+    /// "name[pid]"
+    pub fn pid(&self) -> Option<&str> {
+        self.attrs.get("pid").map(|p| p.as_str()).or_else(|| {
+            self.daemon.trim_end_matches(':').strip_suffix(']')
+                .and_then(|d| d.rsplit_once('['))
+                .map(|(_, pid)| pid)
+                .filter(|pid| !pid.is_empty() && pid.chars().all(|c| c.is_ascii_digit()))
+        })
+    }
+
+    /// The daemon without a PID or trailing colon, for entries whose daemon
+    /// was not normalized: "sshd[1234]:" is "sshd"
+    pub fn program(&self) -> &str {
+        let daemon = self.daemon.trim_end_matches(':');
+        daemon.split('[').next().unwrap_or(daemon)
+    }
+
+    /// Timestamp of the entry (falls back to 1900-01-01 00:00:00 for invalid fields)
+    pub fn naive_datetime(&self) -> NaiveDateTime {
+        let naive_date = NaiveDate::from_ymd_opt(self.year, self.month, self.day)
+            .unwrap_or_else(|| NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
+        let naive_time = NaiveTime::from_hms_opt(self.hour, self.minute, self.second)
+            .unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+        NaiveDateTime::new(naive_date, naive_time)
+    }
+
+    /// Move the entry timestamp by the given number of seconds; an entry the
+    /// shift would take out of the representable range is left as it is
+    pub fn shift_seconds(&mut self, seconds: i64) {
+        let shifted = match Duration::try_seconds(seconds).and_then(|d| self.naive_datetime().checked_add_signed(d)) {
+            Some(shifted) => shifted,
+            None => return,
+        };
+        self.year = shifted.year();
+        self.month = shifted.month();
+        self.day = shifted.day();
+        self.hour = shifted.hour();
+        self.minute = shifted.minute();
+        self.second = shifted.second();
+    }
+
+    /// Syslog keyword for the severity ("err", "warning", ...)
+    pub fn severity_name(&self) -> Option<&'static str> {
+        const NAMES: [&str; 8] = ["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+        self.severity.and_then(|s| NAMES.get(s as usize).copied())
+    }
+
+    /// Format the entry as a normalized line: YYYY-MM-DDTHH:MM:SS host daemon: message
+    pub fn to_normalized(&self) -> String {
+        // Some parsers include trailing ":" in daemon field, some don't
+        let daemon_separator = if self.daemon.ends_with(':') { "" } else { ":" };
+
+        // Strip leading ": " or " " from log_entry if present (added by some parsers)
+        let message = self.log_entry
+            .strip_prefix(": ")
+            .or_else(|| self.log_entry.strip_prefix(" "))
+            .unwrap_or(&self.log_entry);
+
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02} {} {}{} {}",
+            self.year, self.month, self.day,
+            self.hour, self.minute, self.second,
+            self.host,
+            self.daemon,
+            daemon_separator,
+            message
+        )
+    }
+
+    /// The entry as a JSON object with the fields of the normalized line,
+    /// the severity name and the structured fields:
+    /// {"timestamp": "2025-11-14T10:00:00+01:00", "host": ..., "daemon": ...,
+    /// "severity": "err", "message": ..., "attrs": {...}}
+    ///
+    /// The timestamp is RFC 3339 in local time, as entries are read; it is
+    /// null for abnormal entries, which carry none
+    pub fn to_json(&self) -> serde_json::Value {
+        let message = self.log_entry
+            .strip_prefix(": ")
+            .or_else(|| self.log_entry.strip_prefix(" "))
+            .unwrap_or(&self.log_entry);
+
+        let timestamp = (!self.is_undated()).then(|| {
+            let time = self.naive_datetime();
+            match Local.from_local_datetime(&time).earliest() {
+                Some(time) => time.to_rfc3339(),
+                // A local time skipped by a DST change has no offset
+                None => time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            }
+        });
+        let mut json = serde_json::json!({
+            "timestamp": timestamp,
+            "host": self.host,
+            "daemon": self.daemon.trim_end_matches(':'),
+            "message": message,
+        });
+        if let Some(name) = self.severity_name() {
+            json["severity"] = serde_json::json!(name);
+        }
+        if !self.attrs.is_empty() {
+            json["attrs"] = serde_json::json!(self.attrs);
+        }
+        json
     }
 }
 
@@ -49,6 +190,207 @@ pub trait LogParser: Send + Sync {
     fn is_type(&self, line: &str) -> bool;
     fn parse(&self, line: &str) -> Result<LogEntry>;
     fn name(&self) -> &'static str;
+
+    /// Header lines describing the file rather than an event (e.g. Zeek "#fields").
+    /// Returns true when the line was consumed and should not become an entry.
+    fn consume_header(&self, _line: &str) -> bool {
+        false
+    }
+
+    /// Lines continuing the previous record (multi-part audit logs, stack traces);
+    /// `previous` is the line before. Continuations are appended to that record,
+    /// newline separated, before it is parsed.
+    fn is_continuation(&self, _previous: &str, _line: &str) -> bool {
+        false
+    }
+}
+
+// How far detection looks back from a continuation line for the start of its record
+const MAX_RECORD_LINES: usize = 200;
+
+/// Lines of a stream read before its format is detected
+pub const DETECT_LINES: usize = 1000;
+
+/// Whether a line starts with a "YYYY-MM-DD"-shaped date using the given
+/// separator; a cheap record start test for continuation checks, which detection
+/// calls while walking back through whole records
+fn starts_with_date(line: &str, separator: u8) -> bool {
+    let b = line.as_bytes();
+    b.len() >= 10 && b[4] == separator && b[7] == separator && b[..4].iter().all(u8::is_ascii_digit)
+}
+
+// Abbreviated month names: English plus common European LC_TIME locales
+// (de, fr, es, it, nl, pt, pl, sv). Matched case-insensitively, trailing '.' ignored.
+const MONTH_NAMES: &[(&str, u32)] = &[
+    ("jan", 1), ("feb", 2), ("mar", 3), ("apr", 4), ("may", 5), ("jun", 6),
+    ("jul", 7), ("aug", 8), ("sep", 9), ("oct", 10), ("nov", 11), ("dec", 12),
+    // German
+    ("mär", 3), ("mrz", 3), ("mai", 5), ("okt", 10), ("dez", 12),
+    // French
+    ("janv", 1), ("févr", 2), ("fév", 2), ("mars", 3), ("avr", 4), ("juin", 6),
+    ("juil", 7), ("août", 8), ("sept", 9), ("déc", 12),
+    // Spanish
+    ("ene", 1), ("abr", 4), ("ago", 8), ("dic", 12),
+    // Italian
+    ("gen", 1), ("mag", 5), ("giu", 6), ("lug", 7), ("set", 9), ("ott", 10),
+    // Dutch
+    ("mrt", 3), ("mei", 5),
+    // Portuguese
+    ("fev", 2), ("out", 10),
+    // Polish
+    ("sty", 1), ("lut", 2), ("kwi", 4), ("maj", 5), ("cze", 6), ("lip", 7),
+    ("sie", 8), ("wrz", 9), ("paź", 10), ("lis", 11), ("gru", 12),
+];
+
+/// Parse an abbreviated month name in any of the supported locales
+pub fn parse_month(name: &str) -> Option<u32> {
+    let name = name.trim_end_matches('.').to_lowercase();
+    MONTH_NAMES.iter().find(|(n, _)| *n == name).map(|(_, m)| *m)
+}
+
+/// Split a CSV record, honouring double quotes ("" inside quotes is a literal quote)
+pub fn split_csv(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse space separated key=value pairs; values may be double quoted
+pub fn parse_key_values(line: &str) -> BTreeMap<String, String> {
+    let re = Regex::new(r#"([A-Za-z_][\w.-]*)=("(?:[^"\\]|\\.)*"|\S*)"#).unwrap();
+    re.captures_iter(line)
+        .map(|caps| {
+            let value = caps[2].strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(&caps[2]);
+            (caps[1].to_string(), value.replace("\\\"", "\""))
+        })
+        .collect()
+}
+
+/// Add the leaves of a JSON value to `attrs` under dotted names
+/// ("properties.statusCode"); arrays are kept as JSON text and nulls left out
+fn flatten_json(prefix: &str, value: &serde_json::Value, attrs: &mut BTreeMap<String, String>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_json(&name, value, attrs);
+            }
+        }
+        serde_json::Value::Null => {}
+        serde_json::Value::String(s) => {
+            attrs.insert(prefix.to_string(), s.clone());
+        }
+        other => {
+            attrs.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Local wall-clock time for a Unix epoch value in seconds
+pub fn epoch_to_local(seconds: f64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp(seconds.trunc() as i64, (seconds.fract() * 1e9) as u32)
+        .map(|dt| dt.with_timezone(&Local).naive_local())
+}
+
+/// Timestamps as spreadsheets and appliance exports write them: RFC 3339,
+/// "2023-11-14 10:00:00", "2023/11/14 10:00:00", "14.11.2023 10:00:00" and Unix
+/// epochs in seconds or milliseconds
+pub fn parse_loose_timestamp(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+        let value: f64 = text.parse().ok()?;
+        return match text.len() {
+            10 => epoch_to_local(value),
+            13 => epoch_to_local(value / 1000.0),
+            _ => None,
+        };
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Local).naive_local());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y/%m/%d %H:%M:%S", "%d.%m.%Y %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+}
+
+/// `attrs` with the client address as "src", as firewalls record it, for
+/// web and load balancer formats whose host is the client
+fn with_src(mut attrs: BTreeMap<String, String>, client: &str) -> BTreeMap<String, String> {
+    if client.parse::<std::net::IpAddr>().is_ok() {
+        attrs.insert("src".to_string(), client.to_string());
+    }
+    attrs
+}
+
+/// `attrs` with the authenticated user, unless the log shows none ("-")
+fn with_user(mut attrs: BTreeMap<String, String>, user: &str) -> BTreeMap<String, String> {
+    if !user.is_empty() && user != "-" {
+        attrs.insert("user".to_string(), user.to_string());
+    }
+    attrs
+}
+
+/// Syslog severity for the level keywords used by firewalls and appliances
+pub fn severity_from_name(name: &str) -> Option<u8> {
+    match name.to_lowercase().as_str() {
+        "emerg" | "emergency" | "panic" => Some(0),
+        "alert" => Some(1),
+        "crit" | "critical" | "fatal" => Some(2),
+        "err" | "error" | "high" => Some(3),
+        "warn" | "warning" | "medium" => Some(4),
+        "notice" | "low" => Some(5),
+        "info" | "information" | "informational" => Some(6),
+        "debug" | "trace" => Some(7),
+        _ => None,
+    }
+}
+
+/// Normalized shape of an SQL statement: literals become `?` and value lists
+/// collapse, so queries differing only in their parameters compare equal
+pub fn fingerprint_query(query: &str) -> String {
+    let comment_re = Regex::new(r"(?s)/\*.*?\*/|--[^\n]*").unwrap();
+    let string_re = Regex::new(r#"'(?:[^'\\]|\\.|'')*'|"(?:[^"\\]|\\.)*""#).unwrap();
+    let number_re = Regex::new(r"\b(?:0x[0-9a-fA-F]+|-?\d+(?:\.\d+)?(?:[eE][-+]?\d+)?)\b").unwrap();
+    let list_re = Regex::new(r"\(\s*\?(?:\s*,\s*\?)*\s*\)").unwrap();
+    let rows_re = Regex::new(r"\(\?\+\)(?:\s*,\s*\(\?\+\))+").unwrap();
+
+    let query = comment_re.replace_all(query, " ");
+    let query = string_re.replace_all(&query, "?");
+    let query = number_re.replace_all(&query, "?");
+    let query = list_re.replace_all(&query, "(?+)");
+    let query = rows_re.replace_all(&query, "(?+)");
+    query.split_whitespace().collect::<Vec<_>>().join(" ").trim_end_matches(';').trim_end().to_string()
+}
+
+/// Common IP protocol numbers, as logged numerically by some firewalls
+fn protocol_name(proto: &str) -> String {
+    match proto {
+        "1" => "icmp",
+        "6" => "tcp",
+        "17" => "udp",
+        "47" => "gre",
+        "50" => "esp",
+        "58" => "ipv6-icmp",
+        other => other,
+    }
+    .to_string()
 }
 
 pub struct SyslogParser;
@@ -60,12 +402,11 @@ impl LogParser for SyslogParser {
             return false;
         }
 
-        // Check for month pattern like "Feb", "Jan", etc.
-        let month_re = Regex::new(r"^[A-Z][a-z]{2}$").unwrap();
+        // Check for month name like "Feb", "Jan", "Mär", "Okt", etc.
         let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
         let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
 
-        month_re.is_match(parts[0]) &&
+        parse_month(parts[0]).is_some() &&
         day_re.is_match(parts[1]) &&
         time_re.is_match(parts[2]) &&
         !parts[4].starts_with("pam_") &&
@@ -99,12 +440,7 @@ impl LogParser for SyslogParser {
         let second: u32 = time_parts[2].parse()?;
 
         // Parse month
-        let month = match month_str {
-            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
-            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
-            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
-            _ => return Err(anyhow!("Invalid month")),
-        };
+        let month = parse_month(month_str).ok_or_else(|| anyhow!("Invalid month"))?;
 
         let day: u32 = day_str.parse()?;
         let year = Local::now().year();
@@ -119,6 +455,8 @@ impl LogParser for SyslogParser {
             host,
             daemon,
             log_entry,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
@@ -197,6 +535,8 @@ impl LogParser for RSyslogParser {
             host,
             daemon,
             log_entry,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
@@ -217,6 +557,7 @@ impl LogParser for SecureLogParser {
         let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
         let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
 
+        parse_month(parts[0]).is_some() &&
         day_re.is_match(parts[1]) &&
         time_re.is_match(parts[2]) &&
         (parts[5].starts_with("pam_") || parts[4].starts_with("sshd["))
@@ -249,12 +590,7 @@ impl LogParser for SecureLogParser {
         let second: u32 = time_parts[2].parse()?;
 
         // Parse month
-        let month = match month_str {
-            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
-            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
-            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
-            _ => return Err(anyhow!("Invalid month")),
-        };
+        let month = parse_month(month_str).ok_or_else(|| anyhow!("Invalid month"))?;
 
         let day: u32 = day_str.parse()?;
         let year = Local::now().year();
@@ -269,6 +605,8 @@ impl LogParser for SecureLogParser {
             host,
             daemon,
             log_entry,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
@@ -286,15 +624,14 @@ impl LogParser for JournalctlParser {
             return false;
         }
 
-        // Check for month pattern like "Feb", "Jan", etc.
-        let month_re = Regex::new(r"^[A-Z][a-z]{2}$").unwrap();
+        // Check for month name like "Feb", "Jan", "Mär", "Okt", etc.
         let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
         let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
 
         // Journalctl typically has daemon[pid] format or just daemon:
         let daemon_re = Regex::new(r"^[a-zA-Z0-9_\-\.]+(\[[0-9]+\])?:?$").unwrap();
 
-        month_re.is_match(parts[0]) &&
+        parse_month(parts[0]).is_some() &&
         day_re.is_match(parts[1]) &&
         time_re.is_match(parts[2]) &&
         parts.len() >= 4 &&
@@ -333,12 +670,7 @@ impl LogParser for JournalctlParser {
         let second: u32 = time_parts[2].parse()?;
 
         // Parse month
-        let month = match month_str {
-            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
-            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
-            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
-            _ => return Err(anyhow!("Invalid month")),
-        };
+        let month = parse_month(month_str).ok_or_else(|| anyhow!("Invalid month"))?;
 
         let day: u32 = day_str.parse()?;
         let year = Local::now().year();
@@ -353,6 +685,8 @@ impl LogParser for JournalctlParser {
             host,
             daemon,
             log_entry,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
@@ -361,42 +695,96 @@ impl LogParser for JournalctlParser {
     }
 }
 
-pub struct ApacheCommonParser;
+pub struct MailLogParser;
 
-impl LogParser for ApacheCommonParser {
+impl LogParser for MailLogParser {
     fn is_type(&self, line: &str) -> bool {
-        // Apache Common Log Format: IP - user [timestamp] "request" status bytes
-        let re = Regex::new(r#"^\S+ \S+ \S+ \[\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}\] "\S+ \S+ \S+" \d+ (?:\d+|-)$"#).unwrap();
-        re.is_match(line)
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            return false;
+        }
+
+        let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
+        let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
+        // postfix/smtpd[123]:, postfix-out/qmgr[45]:, sendmail[678]:, sm-mta[9]:
+        let daemon_re = Regex::new(r"^(postfix(-[\w]+)?/[\w/-]+|sendmail|sm-mta)\[[0-9]+\]:$").unwrap();
+
+        parse_month(parts[0]).is_some() &&
+        day_re.is_match(parts[1]) &&
+        time_re.is_match(parts[2]) &&
+        daemon_re.is_match(parts[4])
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        // Format: IP ident authuser [timestamp] "request" status bytes
-        let re = Regex::new(r#"^(\S+) (\S+) (\S+) \[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2}) ([+-]\d{4})\] "([^"]+)" (\d+) (\S+)"#).unwrap();
+        let mut entry = SyslogParser.parse(line)?;
 
-        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Apache Common Log"))?;
+        // Group by mail service rather than by process: "postfix/smtp[123]:" -> "postfix/smtp"
+        if let Some(pos) = entry.daemon.find('[') {
+            entry.daemon.truncate(pos);
+        }
 
-        let ip = caps.get(1).unwrap().as_str();
-        let day: u32 = caps.get(4).unwrap().as_str().parse()?;
-        let month_str = caps.get(5).unwrap().as_str();
-        let year: i32 = caps.get(6).unwrap().as_str().parse()?;
-        let hour: u32 = caps.get(7).unwrap().as_str().parse()?;
-        let minute: u32 = caps.get(8).unwrap().as_str().parse()?;
-        let second: u32 = caps.get(9).unwrap().as_str().parse()?;
-        let request = caps.get(11).unwrap().as_str();
-        let status = caps.get(12).unwrap().as_str();
-        let bytes = caps.get(13).unwrap().as_str();
+        Ok(entry)
+    }
 
-        let month = match month_str {
-            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
-            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
-            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
-            _ => return Err(anyhow!("Invalid month")),
+    fn name(&self) -> &'static str {
+        "MailLog"
+    }
+}
+
+pub struct DovecotParser;
+
+impl LogParser for DovecotParser {
+    fn is_type(&self, line: &str) -> bool {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            return false;
+        }
+
+        let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
+        let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
+        let service_re = Regex::new(r"^[a-z0-9-]+(\([^)]*\))?(<[^>]*>)*:$").unwrap();
+        let level_re = Regex::new(r"^(Debug|Info|Warning|Error|Fatal|Panic):$").unwrap();
+
+        if !(parse_month(parts[0]).is_some() && day_re.is_match(parts[1]) && time_re.is_match(parts[2])) {
+            return false;
+        }
+
+        // Dovecot's own log file: "Nov 14 10:00:00 imap-login: Info: Login: ..."
+        // or via syslog: "Nov 14 10:00:00 host dovecot: imap-login: Login: ..."
+        (service_re.is_match(parts[3]) && level_re.is_match(parts[4])) ||
+        (parts[4].starts_with("dovecot") && service_re.is_match(parts[5]))
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            return Err(anyhow!("Invalid Dovecot log format"));
+        }
+
+        let (host, service, message) = if parts[4].starts_with("dovecot") {
+            (parts[3].to_string(), parts[5], parts[6..].join(" "))
+        } else {
+            ("localhost".to_string(), parts[3], parts[4..].join(" "))
         };
 
-        // Extract HTTP method from request
-        let daemon = request.split_whitespace().next().unwrap_or("HTTP").to_string();
-        let log_entry = format!("{} {} {}", request, status, bytes);
+        // "imap(bob)<123><session>:" -> "imap"
+        let daemon = service
+            .split(['(', '<', ':'])
+            .next()
+            .unwrap_or(service)
+            .to_string();
+
+        let time_parts: Vec<&str> = parts[2].split(':').collect();
+        if time_parts.len() != 3 {
+            return Err(anyhow!("Invalid time format"));
+        }
+        let hour: u32 = time_parts[0].parse()?;
+        let minute: u32 = time_parts[1].parse()?;
+        let second: u32 = time_parts[2].parse()?;
+
+        let month = parse_month(parts[0]).ok_or_else(|| anyhow!("Invalid month"))?;
+        let day: u32 = parts[1].parse()?;
+        let year = Local::now().year();
 
         Ok(LogEntry {
             year,
@@ -405,55 +793,57 @@ impl LogParser for ApacheCommonParser {
             hour,
             minute,
             second,
-            host: ip.to_string(),
+            host,
             daemon,
-            log_entry,
+            log_entry: message,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
     fn name(&self) -> &'static str {
-        "ApacheCommon"
+        "Dovecot"
     }
 }
 
-pub struct ApacheCombinedParser;
+pub struct EximParser;
 
-impl LogParser for ApacheCombinedParser {
+impl LogParser for EximParser {
     fn is_type(&self, line: &str) -> bool {
-        // Apache Combined Log Format: IP - user [timestamp] "request" status bytes "referer" "user-agent"
-        let re = Regex::new(r#"^\S+ \S+ \S+ \[\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}\] "\S+ \S+ \S+" \d+ (?:\d+|-) "[^"]*" "[^"]*"$"#).unwrap();
+        // Exim mainlog: YYYY-MM-DD HH:MM:SS [+TZ] [[pid]] [message-id flag] ...
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}( [+-]\d{4})?( \[\d+\])? (\w{6}-\w{6,11}-\w{2,4} (<=|=>|->|>>|\*>|\*\*|==|Completed)|.*( H=| authenticator failed |Start queue run|exim ))").unwrap();
         re.is_match(line)
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        // Format: IP ident authuser [timestamp] "request" status bytes "referer" "user-agent"
-        let re = Regex::new(r#"^(\S+) (\S+) (\S+) \[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2}) ([+-]\d{4})\] "([^"]+)" (\d+) (\S+) "([^"]*)" "([^"]*)"#).unwrap();
-
-        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Apache Combined Log"))?;
-
-        let ip = caps.get(1).unwrap().as_str();
-        let day: u32 = caps.get(4).unwrap().as_str().parse()?;
-        let month_str = caps.get(5).unwrap().as_str();
-        let year: i32 = caps.get(6).unwrap().as_str().parse()?;
-        let hour: u32 = caps.get(7).unwrap().as_str().parse()?;
-        let minute: u32 = caps.get(8).unwrap().as_str().parse()?;
-        let second: u32 = caps.get(9).unwrap().as_str().parse()?;
-        let request = caps.get(11).unwrap().as_str();
-        let status = caps.get(12).unwrap().as_str();
-        let bytes = caps.get(13).unwrap().as_str();
-        let referer = caps.get(14).unwrap().as_str();
-        let user_agent = caps.get(15).unwrap().as_str();
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})(?: [+-]\d{4})?(?: \[\d+\])? (.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Exim log"))?;
 
-        let month = match month_str {
-            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
-            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
-            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
-            _ => return Err(anyhow!("Invalid month")),
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+        let message = caps.get(7).unwrap().as_str();
+
+        // Message lines carry an event flag right after the message ID
+        let msg_re = Regex::new(r"^\w{6}-\w{6,11}-\w{2,4} (\S+)").unwrap();
+        let daemon = match msg_re.captures(message).map(|c| c.get(1).unwrap().as_str()) {
+            Some("<=") => "arrival",
+            Some("=>") | Some("->") | Some(">>") => "delivery",
+            Some("*>") => "suppressed",
+            Some("**") => "bounce",
+            Some("==") => "deferred",
+            Some("Completed") => "completed",
+            _ => "exim",
         };
 
-        // Extract HTTP method from request
-        let daemon = request.split_whitespace().next().unwrap_or("HTTP").to_string();
-        let log_entry = format!("{} {} {} \"{}\" \"{}\"", request, status, bytes, referer, user_agent);
+        // Remote host from H=name [ip]
+        let host_re = Regex::new(r"H=\S*(?: \([^)]*\))? \[([0-9a-fA-F.:]+)\]").unwrap();
+        let host = host_re.captures(message)
+            .map(|c| c.get(1).unwrap().as_str().to_string())
+            .unwrap_or_else(|| "localhost".to_string());
 
         Ok(LogEntry {
             year,
@@ -462,37 +852,31 @@ impl LogParser for ApacheCombinedParser {
             hour,
             minute,
             second,
-            host: ip.to_string(),
-            daemon,
-            log_entry,
+            host,
+            daemon: daemon.to_string(),
+            log_entry: message.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
     fn name(&self) -> &'static str {
-        "ApacheCombined"
+        "Exim"
     }
 }
 
-pub struct AwsElbParser;
+pub struct Fail2banParser;
 
-impl LogParser for AwsElbParser {
+impl LogParser for Fail2banParser {
     fn is_type(&self, line: &str) -> bool {
-        // AWS ELB format: timestamp elb client:port backend:port request_time backend_time response_time elb_status backend_status ...
-        // Backend field can be "-" when no backend connection
-        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z \S+ \d+\.\d+\.\d+\.\d+:\d+ (\d+\.\d+\.\d+\.\d+:\d+|-) [\d\.-]+ [\d\.-]+ [\d\.-]+ \d+ ").unwrap();
+        // fail2ban.log: YYYY-MM-DD HH:MM:SS,ms fail2ban.component [pid]: LEVEL message
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2},\d+ fail2ban\.\S+\s+\[\d+\]: [A-Z]+\s").unwrap();
         re.is_match(line)
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 13 {
-            return Err(anyhow!("Invalid AWS ELB log format"));
-        }
-
-        // Parse timestamp: 2015-05-24T19:21:39.218145Z
-        let timestamp_str = parts[0];
-        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})").unwrap();
-        let caps = re.captures(timestamp_str).ok_or_else(|| anyhow!("Failed to parse timestamp"))?;
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2}),\d+ (fail2ban\.\S+)\s+\[\d+\]: (.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse fail2ban log"))?;
 
         let year: i32 = caps.get(1).unwrap().as_str().parse()?;
         let month: u32 = caps.get(2).unwrap().as_str().parse()?;
@@ -500,23 +884,9 @@ impl LogParser for AwsElbParser {
         let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
         let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
         let second: u32 = caps.get(6).unwrap().as_str().parse()?;
-
-        let _elb_name = parts[1];
-        let client = parts[2].split(':').next().unwrap_or("unknown");
-        let elb_status = parts[7];
-        let backend_status = parts[8];
-
-        // Extract HTTP method from request (parts 11 onwards contain the quoted request)
-        let request_start = line.find('"').unwrap_or(0);
-        let request_end = line.rfind('"').unwrap_or(line.len());
-        let request = if request_start < request_end {
-            &line[request_start+1..request_end]
-        } else {
-            "-"
-        };
-
-        let daemon = request.split_whitespace().next().unwrap_or("HTTP").to_string();
-        let log_entry = format!("{} elb_status={} backend_status={}", request, elb_status, backend_status);
+        let component = caps.get(7).unwrap().as_str();
+        // Level and jail are padded with runs of spaces
+        let message = caps.get(8).unwrap().as_str().split_whitespace().collect::<Vec<_>>().join(" ");
 
         Ok(LogEntry {
             year,
@@ -525,163 +895,6362 @@ impl LogParser for AwsElbParser {
             hour,
             minute,
             second,
-            host: client.to_string(),
-            daemon,
-            log_entry,
+            host: "localhost".to_string(),
+            daemon: component.to_string(),
+            log_entry: message,
+            severity: None,
+            attrs: BTreeMap::new(),
         })
     }
 
     fn name(&self) -> &'static str {
-        "AWS-ELB"
+        "Fail2ban"
     }
 }
 
-pub struct AwsAlbParser;
+pub struct BindQueryParser;
 
-impl LogParser for AwsAlbParser {
+impl LogParser for BindQueryParser {
     fn is_type(&self, line: &str) -> bool {
-        // AWS ALB format starts with: http/https/h2/grpc/ws/wss timestamp
-        let re = Regex::new(r"^(http|https|h2|grpc|ws|wss) \d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z").unwrap();
+        // named query log: DD-Mon-YYYY HH:MM:SS.mmm [category: severity: ]client @0x... ip#port (name): ...
+        let re = Regex::new(r"^\d{2}-\w{3}-\d{4} \d{2}:\d{2}:\d{2}\.\d+ (\S+: \S+: )?client (@0x[0-9a-f]+ )?\S+#\d+").unwrap();
         re.is_match(line)
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 15 {
-            return Err(anyhow!("Invalid AWS ALB log format"));
-        }
+        let re = Regex::new(r"^(\d{2})-(\w{3})-(\d{4}) (\d{2}):(\d{2}):(\d{2})\.\d+ (?:(\S+): \S+: )?(client (?:@0x[0-9a-f]+ )?([^#\s]+)#\d+.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse BIND query log"))?;
 
-        // Parse timestamp: 2018-07-02T22:23:00.186641Z
-        let timestamp_str = parts[1];
-        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})").unwrap();
-        let caps = re.captures(timestamp_str).ok_or_else(|| anyhow!("Failed to parse timestamp"))?;
+        let day: u32 = caps.get(1).unwrap().as_str().parse()?;
+        let month = parse_month(caps.get(2).unwrap().as_str()).ok_or_else(|| anyhow!("Invalid month"))?;
+        let year: i32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+        let category = caps.get(7).map(|m| m.as_str()).unwrap_or("named");
+        let message = caps.get(8).unwrap().as_str();
+        let client = caps.get(9).unwrap().as_str();
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: client.to_string(),
+            daemon: category.to_string(),
+            log_entry: message.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "BIND"
+    }
+}
+
+pub struct DnsmasqParser;
+
+impl LogParser for DnsmasqParser {
+    fn is_type(&self, line: &str) -> bool {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 5 {
+            return false;
+        }
+
+        let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
+        let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
+        let daemon_re = Regex::new(r"^dnsmasq(-dhcp)?\[[0-9]+\]:$").unwrap();
+
+        // Via syslog (with host) or dnsmasq's own log-facility file (without host)
+        parse_month(parts[0]).is_some() &&
+        day_re.is_match(parts[1]) &&
+        time_re.is_match(parts[2]) &&
+        (daemon_re.is_match(parts[3]) || daemon_re.is_match(parts[4]))
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 5 {
+            return Err(anyhow!("Invalid dnsmasq log format"));
+        }
+
+        let (host, daemon, message) = if parts[3].starts_with("dnsmasq") {
+            ("localhost".to_string(), parts[3], parts[4..].join(" "))
+        } else {
+            (parts[3].to_string(), parts[4], parts[5..].join(" "))
+        };
+
+        let time_parts: Vec<&str> = parts[2].split(':').collect();
+        if time_parts.len() != 3 {
+            return Err(anyhow!("Invalid time format"));
+        }
+        let hour: u32 = time_parts[0].parse()?;
+        let minute: u32 = time_parts[1].parse()?;
+        let second: u32 = time_parts[2].parse()?;
+
+        let month = parse_month(parts[0]).ok_or_else(|| anyhow!("Invalid month"))?;
+        let day: u32 = parts[1].parse()?;
+        let year = Local::now().year();
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host,
+            daemon: daemon.to_string(),
+            log_entry: message,
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Dnsmasq"
+    }
+}
+
+pub struct WindowsDnsParser;
+
+impl WindowsDnsParser {
+    /// Decode the wire-style name "(7)example(3)com(0)" into "example.com"
+    fn decode_name(name: &str) -> String {
+        let re = Regex::new(r"\(\d+\)").unwrap();
+        re.replace_all(name, ".").trim_matches('.').to_string()
+    }
+}
+
+impl LogParser for WindowsDnsParser {
+    fn is_type(&self, line: &str) -> bool {
+        // DNS Server debug log: M/D/YYYY H:MM:SS AM 0A2C PACKET  000001D2B3F4C120 UDP Rcv 192.168.1.10    5a3b   Q [0001   D   NOERROR] A      (7)example(3)com(0)
+        let re = Regex::new(r"^\d{1,2}/\d{1,2}/\d{4} \d{1,2}:\d{2}:\d{2}( [AP]M)? \w+ PACKET\s+\w+ (UDP|TCP) (Rcv|Snd) ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^(\d{1,2})/(\d{1,2})/(\d{4}) (\d{1,2}):(\d{2}):(\d{2})(?: ([AP]M))? \w+ PACKET\s+\w+ (?:UDP|TCP) (Rcv|Snd) (\S+)\s+\w+ (R )?\s*Q \[([^\]]*)\]\s+(\w+)\s+(\S+)").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Windows DNS debug log"))?;
+
+        let month: u32 = caps.get(1).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let year: i32 = caps.get(3).unwrap().as_str().parse()?;
+        let mut hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+
+        // 12-hour clock
+        match caps.get(7).map(|m| m.as_str()) {
+            Some("PM") if hour < 12 => hour += 12,
+            Some("AM") if hour == 12 => hour = 0,
+            _ => {}
+        }
+
+        let direction = caps.get(8).unwrap().as_str();
+        let remote = caps.get(9).unwrap().as_str();
+        let response = caps.get(10).is_some();
+        let flags = caps.get(11).unwrap().as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+        let qtype = caps.get(12).unwrap().as_str();
+        let name = Self::decode_name(caps.get(13).unwrap().as_str());
+
+        let log_entry = format!("{} {}Q [{}] {} {}", direction, if response { "R " } else { "" }, flags, qtype, name);
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: remote.to_string(),
+            daemon: if response { "response" } else { "query" }.to_string(),
+            log_entry,
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "WindowsDNS"
+    }
+}
+
+pub struct DhcpdParser;
+
+impl LogParser for DhcpdParser {
+    fn is_type(&self, line: &str) -> bool {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            return false;
+        }
+
+        let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
+        let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
+        let daemon_re = Regex::new(r"^dhcpd\[[0-9]+\]:$").unwrap();
+
+        parse_month(parts[0]).is_some() &&
+        day_re.is_match(parts[1]) &&
+        time_re.is_match(parts[2]) &&
+        daemon_re.is_match(parts[4])
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut entry = SyslogParser.parse(line)?;
+        entry.daemon = "dhcpd".to_string();
+
+        // The client device is more useful than the server name: key by MAC when present
+        let mac_re = Regex::new(r"\b([0-9a-fA-F]{2}(?::[0-9a-fA-F]{2}){5})\b").unwrap();
+        if let Some(caps) = mac_re.captures(&entry.log_entry) {
+            entry.host = caps[1].to_lowercase();
+        }
+
+        Ok(entry)
+    }
+
+    fn name(&self) -> &'static str {
+        "DHCPD"
+    }
+}
+
+pub struct KeaParser;
+
+impl LogParser for KeaParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Kea: YYYY-MM-DD HH:MM:SS.mmm LEVEL [kea-dhcp4.leases/1234.140...] DHCP4_LEASE_ALLOC ...
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d+ +[A-Z]+ +\[kea-[\w.-]+/[\d.]+\] [A-Z0-9_]+ ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})\.\d+ +([A-Z]+) +\[(kea-[\w.-]+)/[\d.]+\] (.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Kea log"))?;
+
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+        let logger = caps.get(8).unwrap().as_str();
+        let message = caps.get(9).unwrap().as_str();
+
+        let mac_re = Regex::new(r"hwtype=\d+ ([0-9a-fA-F]{2}(?::[0-9a-fA-F]{2}){5})").unwrap();
+        let host = mac_re.captures(message)
+            .map(|c| c[1].to_lowercase())
+            .unwrap_or_else(|| "localhost".to_string());
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host,
+            daemon: logger.to_string(),
+            log_entry: message.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Kea"
+    }
+}
+
+pub struct OpenVpnParser;
+
+impl LogParser for OpenVpnParser {
+    fn is_type(&self, line: &str) -> bool {
+        // OpenVPN log file: "YYYY-MM-DD HH:MM:SS [user/]ip:port message" or ctime "Tue Nov 14 10:00:00 2023 ..."
+        let re = Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}|\w{3} \w{3} [ \d]\d \d{2}:\d{2}:\d{2} \d{4}) ((\S+/)?[0-9a-fA-F.:]+:\d+ |(OpenVPN|MULTI|TLS|Initialization Sequence|TUN/TAP|UDPv4|TCPv4)\b)").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let iso_re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2}) (.*)$").unwrap();
+        let ctime_re = Regex::new(r"^\w{3} (\w{3}) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2}) (\d{4}) (.*)$").unwrap();
+
+        let (year, month, day, hour, minute, second, message) = if let Some(caps) = iso_re.captures(line) {
+            (
+                caps[1].parse::<i32>()?, caps[2].parse::<u32>()?, caps[3].parse::<u32>()?,
+                caps[4].parse::<u32>()?, caps[5].parse::<u32>()?, caps[6].parse::<u32>()?,
+                caps.get(7).unwrap().as_str(),
+            )
+        } else if let Some(caps) = ctime_re.captures(line) {
+            (
+                caps[6].parse::<i32>()?,
+                parse_month(&caps[1]).ok_or_else(|| anyhow!("Invalid month"))?,
+                caps[2].parse::<u32>()?,
+                caps[3].parse::<u32>()?, caps[4].parse::<u32>()?, caps[5].parse::<u32>()?,
+                caps.get(7).unwrap().as_str(),
+            )
+        } else {
+            return Err(anyhow!("Failed to parse OpenVPN log"));
+        };
+
+        // Client lines are prefixed with "common_name/ip:port" or "ip:port"
+        let peer_re = Regex::new(r"^(?:[^/\s]+/)?([0-9a-fA-F.:]+):\d+ ").unwrap();
+        let host = peer_re.captures(message)
+            .map(|c| c[1].to_string())
+            .unwrap_or_else(|| "localhost".to_string());
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host,
+            daemon: "openvpn".to_string(),
+            log_entry: message.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "OpenVPN"
+    }
+}
+
+pub struct WireGuardParser;
+
+impl LogParser for WireGuardParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Kernel dynamic debug messages: "Nov 14 10:00:00 host kernel: wireguard: wg0: ..."
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 7 {
+            return false;
+        }
+
+        let day_re = Regex::new(r"^[0-9]{1,2}$").unwrap();
+        let time_re = Regex::new(r"^[0-9]{1,2}:[0-9]{2}:[0-9]{2}$").unwrap();
+
+        parse_month(parts[0]).is_some() &&
+        day_re.is_match(parts[1]) &&
+        time_re.is_match(parts[2]) &&
+        parts[4] == "kernel:" &&
+        line.contains(" wireguard: ")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut entry = SyslogParser.parse(line)?;
+
+        // "wireguard: wg0: Handshake for peer 3 (1.2.3.4:51820) ..." -> daemon "wireguard/wg0"
+        let re = Regex::new(r"^(?:\[[ \d.]+\] )?wireguard: (\w+): (.*)$").unwrap();
+        if let Some(caps) = re.captures(&entry.log_entry.clone()) {
+            entry.daemon = format!("wireguard/{}", &caps[1]);
+            entry.log_entry = caps[2].to_string();
+
+            let endpoint_re = Regex::new(r"peer \d+ \(([0-9a-fA-F.:\[\]]+):\d+\)").unwrap();
+            if let Some(endpoint) = endpoint_re.captures(&entry.log_entry) {
+                entry.host = endpoint[1].trim_matches(|c| c == '[' || c == ']').to_string();
+            }
+        }
+
+        Ok(entry)
+    }
+
+    fn name(&self) -> &'static str {
+        "WireGuard"
+    }
+}
+
+pub struct FreeRadiusParser;
+
+impl LogParser for FreeRadiusParser {
+    fn is_type(&self, line: &str) -> bool {
+        // radius.log: "Tue Nov 14 10:00:00 2023 : Auth: (12) Login OK: [alice] (from client ap01 port 0)"
+        let re = Regex::new(r"^\w{3} \w{3} +\d{1,2} \d{2}:\d{2}:\d{2} \d{4} : \w+: ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^\w{3} (\w{3}) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2}) (\d{4}) : (.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse FreeRADIUS log"))?;
+        let message = caps.get(7).unwrap().as_str();
+
+        // The NAS the request came from is the most useful "host" for triage
+        let nas_re = Regex::new(r"\(from client (\S+) port").unwrap();
+        let host = nas_re.captures(message)
+            .map(|c| c[1].to_string())
+            .unwrap_or_else(|| "localhost".to_string());
+
+        Ok(LogEntry {
+            year: caps[6].parse()?,
+            month: parse_month(&caps[1]).ok_or_else(|| anyhow!("Invalid month"))?,
+            day: caps[2].parse()?,
+            hour: caps[3].parse()?,
+            minute: caps[4].parse()?,
+            second: caps[5].parse()?,
+            host,
+            daemon: "radiusd".to_string(),
+            log_entry: message.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "FreeRADIUS"
+    }
+}
+
+pub struct CiscoAsaParser;
+
+impl LogParser for CiscoAsaParser {
+    fn is_type(&self, line: &str) -> bool {
+        // "Nov 14 10:00:00 fw01 %ASA-6-302013: Built outbound TCP connection ..."
+        // "Nov 14 2023 10:00:00 fw01 : %ASA-6-302013: ..." (logging timestamp enabled on the device)
+        let re = Regex::new(r"^\S+ +\d{1,2}(?: \d{4})? \d{2}:\d{2}:\d{2}:? .*%(?:ASA|FTD|PIX|FWSM)-\d-\d{6}: ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^(\S+) +(\d{1,2})(?: (\d{4}))? (\d{2}):(\d{2}):(\d{2}):? (?:(\S+) )?(?:.*?)(%(?:ASA|FTD|PIX|FWSM)-(\d)-(\d{6})): ?(.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Cisco ASA log"))?;
+
+        let year = match caps.get(3) {
+            Some(y) => y.as_str().parse()?,
+            None => Local::now().year(),
+        };
+        // Without a device hostname the header ends in " : %ASA-..."
+        let host = caps.get(7)
+            .map(|h| h.as_str())
+            .filter(|h| *h != ":" && !h.starts_with('%'))
+            .unwrap_or("localhost")
+            .to_string();
+        let severity: u8 = caps[9].parse()?;
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("message_id".to_string(), caps[10].to_string());
+        // "Deny tcp src outside:192.0.2.44/51000 dst ..." and "... denied from 192.0.2.44/51000 to ..."
+        let src_re = Regex::new(r"(?:\bsrc|\bfrom) (?:[\w-]+:)?([0-9a-fA-F.:]+?)/\d+").unwrap();
+        if let Some(src) = src_re.captures(&caps[11]) {
+            attrs = with_src(attrs, &src[1]);
+        }
+
+        Ok(LogEntry {
+            year,
+            month: parse_month(&caps[1]).ok_or_else(|| anyhow!("Invalid month"))?,
+            day: caps[2].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host,
+            daemon: caps[8].to_string(),
+            log_entry: caps[11].to_string(),
+            severity: Some(severity),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Cisco ASA"
+    }
+}
+
+pub struct PanOsParser;
+
+impl PanOsParser {
+    fn csv_start(line: &str) -> Option<usize> {
+        let re = Regex::new(r"(?:^|\s)(\d+,\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2},[^,]*,(?:TRAFFIC|THREAT|SYSTEM|CONFIG|GLOBALPROTECT|USERID|AUTHENTICATION|DECRYPTION),)").unwrap();
+        re.captures(line).map(|c| c.get(1).unwrap().start())
+    }
+}
+
+impl LogParser for PanOsParser {
+    fn is_type(&self, line: &str) -> bool {
+        // "1,2023/11/14 10:00:00,012345678901,TRAFFIC,end,..." optionally behind a syslog header
+        Self::csv_start(line).is_some()
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let start = Self::csv_start(line).ok_or_else(|| anyhow!("Failed to parse PAN-OS log"))?;
+        let fields = split_csv(&line[start..], ',');
+        let field = |i: usize| fields.get(i).map(|f| f.as_str()).unwrap_or("");
+
+        // Generated time (field 6) is when the event happened; receive time is field 1
+        let time_re = Regex::new(r"^(\d{4})/(\d{2})/(\d{2}) (\d{2}):(\d{2}):(\d{2})$").unwrap();
+        let caps = time_re.captures(field(6))
+            .or_else(|| time_re.captures(field(1)))
+            .ok_or_else(|| anyhow!("Invalid PAN-OS timestamp"))?;
+
+        // The syslog header carries the device name; exports only have the serial number
+        let header: Vec<&str> = line[..start].split_whitespace().collect();
+        let host = if header.len() >= 4 { header[3] } else { field(2) }.to_string();
+
+        let log_type = field(3).to_lowercase();
+        let mut attrs = BTreeMap::new();
+        let mut severity = None;
+        let log_entry = if log_type == "traffic" || log_type == "threat" {
+            for (key, idx) in [
+                ("src", 7), ("dst", 8), ("rule", 11), ("src_user", 12), ("app", 14),
+                ("src_zone", 16), ("dst_zone", 17), ("src_port", 24), ("dst_port", 25),
+                ("proto", 29), ("action", 30),
+            ] {
+                if !field(idx).is_empty() {
+                    attrs.insert(key.to_string(), field(idx).to_string());
+                }
+            }
+
+            let mut message = format!("{} {} {}:{} -> {}:{} rule={} app={}",
+                field(30), field(29), field(7), field(24), field(8), field(25), field(11), field(14));
+            if log_type == "threat" {
+                // THREAT: misc (url/file), threat name(id), category, severity
+                attrs.insert("threat".to_string(), field(32).to_string());
+                attrs.insert("category".to_string(), field(33).to_string());
+                severity = severity_from_name(field(34));
+                message.push_str(&format!(" threat={} severity={}", field(32), field(34)));
+            } else if !field(31).is_empty() {
+                attrs.insert("bytes".to_string(), field(31).to_string());
+            }
+            message
+        } else {
+            fields[4..].iter().filter(|f| !f.is_empty()).cloned().collect::<Vec<_>>().join(" ")
+        };
+
+        Ok(LogEntry {
+            year: caps[1].parse()?,
+            month: caps[2].parse()?,
+            day: caps[3].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host,
+            daemon: format!("{}/{}", log_type, field(4)),
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PAN-OS"
+    }
+}
+
+pub struct FortiGateParser;
+
+impl LogParser for FortiGateParser {
+    fn is_type(&self, line: &str) -> bool {
+        // 'date=2023-11-14 time=10:00:00 devname="FG100E" logid="0000000013" type="traffic" ...'
+        let re = Regex::new(r#"(?:^|\s)date="?\d{4}-\d{2}-\d{2}"? time="?\d{2}:\d{2}:\d{2}"? .*\blogid="?\d+"?"#).unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let kv = parse_key_values(line);
+        let date_re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").unwrap();
+        let time_re = Regex::new(r"^(\d{2}):(\d{2}):(\d{2})$").unwrap();
+
+        let date = kv.get("date").and_then(|d| date_re.captures(d)).ok_or_else(|| anyhow!("Invalid FortiGate date"))?;
+        let time = kv.get("time").and_then(|t| time_re.captures(t)).ok_or_else(|| anyhow!("Invalid FortiGate time"))?;
+        let get = |key: &str| kv.get(key).map(|v| v.as_str()).unwrap_or("");
+
+        let mut attrs = BTreeMap::new();
+        for (key, source) in [
+            ("src", "srcip"), ("dst", "dstip"), ("src_port", "srcport"), ("dst_port", "dstport"),
+            ("src_intf", "srcintf"), ("dst_intf", "dstintf"), ("action", "action"),
+            ("service", "service"), ("logid", "logid"), ("user", "user"),
+        ] {
+            if !get(source).is_empty() {
+                attrs.insert(key.to_string(), get(source).to_string());
+            }
+        }
+        if !get("proto").is_empty() {
+            attrs.insert("proto".to_string(), protocol_name(get("proto")));
+        }
+        // Policies are referenced by name when one is set, otherwise by ID
+        let rule = if get("policyname").is_empty() { get("policyid") } else { get("policyname") };
+        if !rule.is_empty() {
+            attrs.insert("rule".to_string(), rule.to_string());
+        }
+
+        let log_entry = if attrs.contains_key("src") && attrs.contains_key("dst") {
+            let mut message = format!("{} {} {}:{} -> {}:{} rule={}",
+                get("action"), attrs.get("proto").map(|p| p.as_str()).unwrap_or(""),
+                get("srcip"), get("srcport"), get("dstip"), get("dstport"), rule);
+            if !get("msg").is_empty() {
+                message.push_str(&format!(" msg={}", get("msg")));
+            }
+            message
+        } else if !get("msg").is_empty() {
+            get("msg").to_string()
+        } else {
+            get("logdesc").to_string()
+        };
+
+        let host = if get("devname").is_empty() { "localhost" } else { get("devname") }.to_string();
+
+        Ok(LogEntry {
+            year: date[1].parse()?,
+            month: date[2].parse()?,
+            day: date[3].parse()?,
+            hour: time[1].parse()?,
+            minute: time[2].parse()?,
+            second: time[3].parse()?,
+            host,
+            daemon: format!("{}/{}", get("type"), get("subtype")),
+            log_entry,
+            severity: severity_from_name(get("level")),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "FortiGate"
+    }
+}
+
+/// Check Point Log Exporter syslog: 'key:"value";' pairs in brackets
+/// '<134>1 2023-11-14T10:00:00Z gw01 CheckPoint 12345 - [action:"Drop"; ifdir:"inbound"; src:"1.2.3.4"; ...]'
+pub struct CheckPointParser;
+
+impl LogParser for CheckPointParser {
+    fn is_type(&self, line: &str) -> bool {
+        let re = Regex::new(r#"\[\w+:"[^"]*";"#).unwrap();
+        re.is_match(line) && (line.contains("action:\"") || line.contains("product:\""))
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let field_re = Regex::new(r#"(\w+):"((?:[^"\\]|\\.)*)""#).unwrap();
+        let header_re = Regex::new(r"^(?:<\d+>1 (\S+) (\S+) |(\w{3}\s+\d{1,2} \d{2}:\d{2}:\d{2}) (\S+) )").unwrap();
+        let fields: BTreeMap<String, String> = field_re.captures_iter(line)
+            .map(|caps| (caps[1].to_string(), caps[2].replace("\\\"", "\"")))
+            .collect();
+        let get = |key: &str| fields.get(key).map(|v| v.as_str()).unwrap_or("");
+        let header = header_re.captures(line);
+
+        // The event time is epoch seconds; the syslog header is the fallback
+        let timestamp = fields.get("time")
+            .and_then(|t| t.parse::<f64>().ok())
+            .and_then(epoch_to_local)
+            .or_else(|| {
+                let header = header.as_ref()?;
+                match header.get(1) {
+                    Some(ts) => DateTime::parse_from_rfc3339(ts.as_str()).ok()
+                        .map(|dt| dt.with_timezone(&Local).naive_local()),
+                    None => {
+                        let ts = format!("{} {}", Local::now().year(), header.get(3)?.as_str());
+                        NaiveDateTime::parse_from_str(&ts, "%Y %b %d %H:%M:%S").ok()
+                    }
+                }
+            })
+            .ok_or_else(|| anyhow!("Check Point log without time"))?;
+
+        let mut attrs = BTreeMap::new();
+        for (key, source) in [
+            ("src", "src"), ("dst", "dst"), ("src_port", "s_port"), ("dst_port", "service"),
+            ("interface", "ifname"), ("direction", "ifdir"), ("user", "src_user_name"),
+            ("blade", "product"), ("reason", "reason"),
+        ] {
+            if !get(source).is_empty() {
+                attrs.insert(key.to_string(), get(source).to_string());
+            }
+        }
+        if !get("action").is_empty() {
+            attrs.insert("action".to_string(), get("action").to_lowercase());
+        }
+        if !get("proto").is_empty() {
+            attrs.insert("proto".to_string(), protocol_name(get("proto")));
+        }
+        // Rules are logged by name when they have one, otherwise by number
+        let rule = if get("rule_name").is_empty() { get("rule") } else { get("rule_name") };
+        if !rule.is_empty() {
+            attrs.insert("rule".to_string(), rule.to_string());
+        }
+
+        let attr = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("-");
+        let log_entry = if attrs.contains_key("src") && attrs.contains_key("dst") {
+            let mut message = format!("{} {} {}:{} -> {}:{} rule={}",
+                attr("action"), attr("proto"), attr("src"), attr("src_port"), attr("dst"), attr("dst_port"), attr("rule"));
+            if let Some(reason) = attrs.get("reason") {
+                message.push_str(&format!(" reason={}", reason));
+            }
+            message
+        } else if !get("msg").is_empty() {
+            get("msg").to_string()
+        } else {
+            format!("{} {}", attr("action"), get("description")).trim().to_string()
+        };
+
+        let host = header.as_ref()
+            .and_then(|h| h.get(2).or_else(|| h.get(4)))
+            .map(|h| h.as_str())
+            .filter(|h| *h != "-")
+            .or_else(|| fields.get("origin").map(|o| o.as_str()))
+            .unwrap_or("localhost")
+            .to_string();
+        let daemon = if get("product").is_empty() { "checkpoint" } else { get("product") }.to_string();
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry,
+            severity: severity_from_name(get("severity")),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "CheckPoint"
+    }
+}
+
+/// Juniper SRX structured syslog (RFC 5424 with junos@ structured data):
+/// '<14>1 2023-11-14T10:00:00.123+01:00 srx01 RT_FLOW - RT_FLOW_SESSION_DENY [junos@2636.1.1.1.2.26 source-address="10.0.0.5" ...] session denied ...'
+pub struct JuniperSrxParser;
+
+impl JuniperSrxParser {
+    fn header_re() -> Regex {
+        // Also when a relay put its own syslog header in front
+        Regex::new(r"(?:<(\d+)>)?1 (\d{4}-\d{2}-\d{2}T\S+) (\S+) (\S+) (\S+) ([A-Z][A-Z0-9_]+) \[junos@[\d.]+ ").unwrap()
+    }
+}
+
+impl LogParser for JuniperSrxParser {
+    fn is_type(&self, line: &str) -> bool {
+        line.contains("[junos@") && Self::header_re().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::header_re().captures(line).ok_or_else(|| anyhow!("Failed to parse Juniper structured syslog"))?;
+        let timestamp = DateTime::parse_from_rfc3339(&caps[2])
+            .map(|dt| dt.with_timezone(&Local).naive_local())?;
+        let event = caps[6].to_string();
+
+        let rest = &line[caps.get(0).unwrap().end()..];
+        let (data, message) = rest.split_once("] ").unwrap_or((rest.trim_end_matches(']'), ""));
+        let field_re = Regex::new(r#"([\w-]+)="((?:[^"\\]|\\.)*)""#).unwrap();
+        let fields: BTreeMap<String, String> = field_re.captures_iter(data)
+            .map(|c| (c[1].to_string(), c[2].to_string()))
+            .collect();
+        let get = |key: &str| fields.get(key).map(|v| v.as_str()).filter(|v| !v.is_empty() && *v != "N/A");
+
+        let mut attrs = BTreeMap::new();
+        for (key, source) in [
+            ("src", "source-address"), ("dst", "destination-address"), ("src_port", "source-port"),
+            ("dst_port", "destination-port"), ("rule", "policy-name"), ("src_zone", "source-zone-name"),
+            ("dst_zone", "destination-zone-name"), ("interface", "packet-incoming-interface"),
+            ("service", "service-name"), ("application", "application"), ("nat_src", "nat-source-address"),
+            ("reason", "reason"), ("user", "username"), ("session_id", "session-id-32"),
+        ] {
+            if let Some(value) = get(source) {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+        if let Some(proto) = get("protocol-id") {
+            attrs.insert("proto".to_string(), protocol_name(proto));
+        }
+        if let (Some(client), Some(server)) = (get("bytes-from-client"), get("bytes-from-server")) {
+            if let (Ok(client), Ok(server)) = (client.parse::<u64>(), server.parse::<u64>()) {
+                attrs.insert("bytes".to_string(), (client + server).to_string());
+            }
+        }
+        // RT_FLOW_SESSION_CREATE/CLOSE are permitted sessions, DENY is a policy drop
+        let action = match event.as_str() {
+            "RT_FLOW_SESSION_CREATE" => Some("permit"),
+            "RT_FLOW_SESSION_CLOSE" => Some("close"),
+            "RT_FLOW_SESSION_DENY" => Some("deny"),
+            _ => None,
+        };
+        if let Some(action) = action {
+            attrs.insert("action".to_string(), action.to_string());
+        }
+
+        let attr = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("-");
+        let log_entry = if attrs.contains_key("action") && attrs.contains_key("src") {
+            let mut entry = format!("{} {} {}:{} -> {}:{} rule={}",
+                attr("action"), attr("proto"), attr("src"), attr("src_port"), attr("dst"), attr("dst_port"), attr("rule"));
+            if let Some(reason) = attrs.get("reason") {
+                entry.push_str(&format!(" reason={}", reason));
+            }
+            entry
+        } else {
+            message.trim().to_string()
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: caps[3].to_string(),
+            daemon: event,
+            log_entry,
+            severity: caps.get(1).and_then(|p| p.as_str().parse::<u8>().ok()).map(|p| p & 7),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "JuniperSRX"
+    }
+}
+
+pub struct FilterlogParser;
+
+impl LogParser for FilterlogParser {
+    fn is_type(&self, line: &str) -> bool {
+        // pfSense/OPNsense: "Mar  5 10:00:00 pfsense filterlog[1234]: 5,,,1000000103,igb1,match,block,in,4,..."
+        SyslogParser.is_type(line) &&
+        line.split_whitespace().nth(4).map(|d| d.starts_with("filterlog")).unwrap_or(false)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut entry = SyslogParser.parse(line)?;
+        let fields = split_csv(&entry.log_entry, ',');
+        let field = |i: usize| fields.get(i).map(|f| f.as_str()).unwrap_or("");
+
+        // Layout after the IP version differs between IPv4 and IPv6
+        let (proto, src, dst, src_port, dst_port) = match field(8) {
+            "4" => (field(16), field(18), field(19), 20, 21),
+            "6" => (field(12), field(15), field(16), 17, 18),
+            // Other record types (e.g. CARP) are kept as logged
+            _ => return Ok(entry),
+        };
+        let has_ports = matches!(proto, "tcp" | "udp");
+
+        let mut attrs = BTreeMap::new();
+        for (key, value) in [
+            ("rule", field(0)), ("tracker", field(3)), ("interface", field(4)),
+            ("reason", field(5)), ("action", field(6)), ("direction", field(7)),
+            ("proto", proto), ("src", src), ("dst", dst),
+        ] {
+            if !value.is_empty() {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        entry.log_entry = if has_ports {
+            attrs.insert("src_port".to_string(), field(src_port).to_string());
+            attrs.insert("dst_port".to_string(), field(dst_port).to_string());
+            format!("{} {} {} {} {}:{} -> {}:{} rule={}",
+                field(6), field(7), field(4), proto, src, field(src_port), dst, field(dst_port), field(0))
+        } else {
+            format!("{} {} {} {} {} -> {} rule={}", field(6), field(7), field(4), proto, src, dst, field(0))
+        };
+        entry.attrs = attrs;
+
+        Ok(entry)
+    }
+
+    fn name(&self) -> &'static str {
+        "filterlog"
+    }
+}
+
+pub struct LeefParser;
+
+impl LeefParser {
+    /// LEEF 2.0 delimiters are given as a character or as hex ("x09", "0x09")
+    fn delimiter(spec: &str) -> char {
+        let hex = spec.strip_prefix("0x").or_else(|| spec.strip_prefix('x'));
+        match hex.and_then(|h| u32::from_str_radix(h, 16).ok()).and_then(char::from_u32) {
+            Some(c) => c,
+            None => spec.chars().next().unwrap_or('\t'),
+        }
+    }
+
+    /// devTime is epoch milliseconds, the LEEF default "MMM dd yyyy HH:mm:ss", or described by devTimeFormat
+    fn parse_dev_time(value: &str, format: Option<&str>) -> Option<NaiveDateTime> {
+        if let Ok(epoch) = value.parse::<i64>() {
+            let seconds = if value.len() > 10 { epoch / 1000 } else { epoch };
+            return epoch_to_local(seconds as f64);
+        }
+
+        let mut value = value.to_string();
+        let mut formats = Vec::new();
+        if let Some(java) = format {
+            let mut java = java.to_string();
+            // chrono cannot parse zone names ("z", e.g. "CET"), so they are
+            // dropped from format and value and the time is read as local
+            if java.contains('z') {
+                let zone_name = Regex::new(r"\b[A-Z]{2,5}\b").unwrap();
+                java = java.replace('z', " ").split_whitespace().collect::<Vec<_>>().join(" ");
+                value = zone_name.replace_all(&value, " ").split_whitespace().collect::<Vec<_>>().join(" ");
+            }
+            // Translate the common SimpleDateFormat tokens
+            let chrono_format = java
+                .replace("yyyy", "%Y").replace("MMM", "%b").replace("MM", "%m").replace("dd", "%d")
+                .replace("HH", "%H").replace("mm", "%M").replace("ss", "%S").replace(".SSS", "%.3f")
+                .replace('Z', "%z").replace("'T'", "T");
+            formats.push(chrono_format);
+        }
+        formats.push("%b %d %Y %H:%M:%S".to_string());
+        formats.push("%Y-%m-%dT%H:%M:%S".to_string());
+        formats.push("%Y-%m-%d %H:%M:%S".to_string());
+
+        formats.iter().find_map(|f| {
+            DateTime::parse_from_str(&value, f).map(|dt| dt.with_timezone(&Local).naive_local())
+                .or_else(|_| NaiveDateTime::parse_from_str(&value, f))
+                .ok()
+        })
+    }
+
+    /// LEEF severity is 1-10; map it onto syslog levels
+    fn severity(sev: &str) -> Option<u8> {
+        match sev.parse::<u8>().ok()? {
+            8..=10 => Some(2),
+            6..=7 => Some(3),
+            4..=5 => Some(4),
+            2..=3 => Some(5),
+            _ => Some(6),
+        }
+    }
+}
+
+impl LogParser for LeefParser {
+    fn is_type(&self, line: &str) -> bool {
+        // "LEEF:1.0|Vendor|Product|Version|EventID|key=value\tkey=value", optionally behind a syslog header
+        let re = Regex::new(r"(?:^|\s)LEEF:[12]\.0\|").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let start = line.find("LEEF:").ok_or_else(|| anyhow!("Failed to parse LEEF log"))?;
+        let version = line[start + 5..].split('|').next().unwrap_or("");
+        let header_fields = if version == "2.0" { 7 } else { 6 };
+
+        let parts: Vec<&str> = line[start..].splitn(header_fields, '|').collect();
+        if parts.len() < header_fields {
+            return Err(anyhow!("Truncated LEEF header"));
+        }
+        let (vendor, product, event_id) = (parts[1], parts[2], parts[4]);
+        let delimiter = if version == "2.0" { Self::delimiter(parts[5]) } else { '\t' };
+
+        let mut pairs: Vec<(&str, &str)> = Vec::new();
+        for pair in parts[header_fields - 1].split(delimiter) {
+            if let Some((key, value)) = pair.split_once('=') {
+                pairs.push((key.trim(), value.trim()));
+            }
+        }
+        let get = |key: &str| pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+
+        // Syslog header in front of the LEEF payload, if the file was captured from syslog
+        let header_re = Regex::new(r"^(\S+) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2}) (\S+)").unwrap();
+        let header = header_re.captures(&line[..start]);
+
+        let timestamp = match get("devTime").and_then(|t| Self::parse_dev_time(t, get("devTimeFormat"))) {
+            Some(ts) => ts,
+            None => {
+                let caps = header.as_ref().ok_or_else(|| anyhow!("LEEF entry without devTime"))?;
+                let month = parse_month(&caps[1]).ok_or_else(|| anyhow!("Invalid month"))?;
+                NaiveDate::from_ymd_opt(Local::now().year(), month, caps[2].parse()?)
+                    .and_then(|d| d.and_hms_opt(caps[3].parse().ok()?, caps[4].parse().ok()?, caps[5].parse().ok()?))
+                    .ok_or_else(|| anyhow!("Invalid syslog timestamp"))?
+            }
+        };
+
+        let host = get("src")
+            .or_else(|| header.as_ref().and_then(|c| c.get(6)).map(|h| h.as_str()))
+            .unwrap_or("localhost")
+            .to_string();
+
+        let mut attrs: BTreeMap<String, String> = pairs.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        attrs.insert("vendor".to_string(), vendor.to_string());
+        attrs.insert("event_id".to_string(), event_id.to_string());
+        if let Some(user) = get("usrName") {
+            attrs.insert("user".to_string(), user.to_string());
+        }
+
+        let details: Vec<String> = pairs.iter()
+            .filter(|(k, _)| !matches!(*k, "devTime" | "devTimeFormat"))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: product.to_string(),
+            log_entry: format!("{} {}", event_id, details.join(" ")).trim_end().to_string(),
+            severity: get("sev").and_then(Self::severity),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "LEEF"
+    }
+}
+
+pub struct GelfParser;
+
+impl GelfParser {
+    /// Fields that commonly name the emitting application, most specific first
+    const DAEMON_FIELDS: &'static [&'static str] = &[
+        "_container_name", "_application_name", "_app", "_tag", "_logger", "facility",
+    ];
+}
+
+impl LogParser for GelfParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"version":"1.1","host":"web01","short_message":"...","timestamp":1699956000.123,"level":6,"_app":"api"}
+        let line = line.trim_start();
+        line.starts_with('{') && line.contains("\"short_message\"") && line.contains("\"version\"")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        let object = json.as_object().ok_or_else(|| anyhow!("GELF message is not an object"))?;
+        let text = |value: &serde_json::Value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        let timestamp = object.get("timestamp")
+            .and_then(|t| t.as_f64().or_else(|| t.as_str().and_then(|s| s.parse().ok())))
+            .and_then(epoch_to_local)
+            .ok_or_else(|| anyhow!("GELF message without timestamp"))?;
+
+        let host = object.get("host").map(text).unwrap_or_else(|| "localhost".to_string());
+        let daemon = Self::DAEMON_FIELDS.iter()
+            .find_map(|f| object.get(*f).map(text))
+            .map(|d| d.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| "gelf".to_string());
+
+        // Additional fields are prefixed with "_"; keep them without it
+        let mut attrs: BTreeMap<String, String> = object.iter()
+            .filter_map(|(k, v)| k.strip_prefix('_').map(|k| (k.to_string(), text(v))))
+            .collect();
+        if let Some(full) = object.get("full_message") {
+            attrs.insert("full_message".to_string(), text(full));
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry: object.get("short_message").map(text).unwrap_or_default(),
+            severity: object.get("level").and_then(|l| l.as_u64()).filter(|l| *l <= 7).map(|l| l as u8),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "GELF"
+    }
+}
+
+/// Zeek TSV logs describe their columns in "#fields" header lines, so the
+/// parser remembers the most recent header while lines are parsed in order
+pub struct ZeekParser {
+    header: Mutex<ZeekHeader>,
+}
+
+#[derive(Default)]
+struct ZeekHeader {
+    separator: Option<char>,
+    path: Option<String>,
+    fields: Vec<String>,
+}
+
+impl Default for ZeekParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZeekParser {
+    pub fn new() -> Self {
+        Self {
+            header: Mutex::new(ZeekHeader::default()),
+        }
+    }
+}
+
+impl LogParser for ZeekParser {
+    fn is_type(&self, line: &str) -> bool {
+        let header_re = Regex::new(r"^#(separator|set_separator|empty_field|unset_field|path|open|close|fields|types)\b").unwrap();
+        let data_re = Regex::new(r"^\d{9,10}\.\d{6}\t\S").unwrap();
+        header_re.is_match(line) || data_re.is_match(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        let rest = match line.strip_prefix('#') {
+            Some(rest) => rest,
+            None => return false,
+        };
+        let mut header = self.header.lock().unwrap();
+
+        // "#separator \x09" uses a space; all other header lines use the separator itself
+        if let Some(spec) = rest.strip_prefix("separator ") {
+            header.separator = spec.trim().strip_prefix("\\x")
+                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                .and_then(char::from_u32);
+            return true;
+        }
+        let separator = header.separator.unwrap_or('\t');
+        let mut parts = rest.split(separator);
+        match parts.next() {
+            Some("fields") => header.fields = parts.map(|f| f.to_string()).collect(),
+            Some("path") => header.path = parts.next().map(|p| p.to_string()),
+            _ => {}
+        }
+        true
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let header = self.header.lock().unwrap();
+
+        if header.fields.is_empty() {
+            return Err(anyhow!("Zeek data line before #fields header"));
+        }
+
+        let values: Vec<&str> = line.split(header.separator.unwrap_or('\t')).collect();
+        let mut attrs = BTreeMap::new();
+        for (name, value) in header.fields.iter().zip(values.iter()) {
+            // "-" is the unset marker and "(empty)" an empty set
+            if *value != "-" && *value != "(empty)" {
+                attrs.insert(name.clone(), value.to_string());
+            }
+        }
+
+        let timestamp = attrs.get("ts")
+            .and_then(|ts| ts.parse::<f64>().ok())
+            .and_then(epoch_to_local)
+            .ok_or_else(|| anyhow!("Zeek line without ts"))?;
+
+        let get = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("-");
+        let mut message = Vec::new();
+        if attrs.contains_key("id.orig_h") {
+            message.push(format!("{}:{} -> {}:{}", get("id.orig_h"), get("id.orig_p"), get("id.resp_h"), get("id.resp_p")));
+        }
+        for name in &header.fields {
+            if name != "ts" && name != "uid" && !name.starts_with("id.") {
+                if let Some(value) = attrs.get(name) {
+                    message.push(format!("{}={}", name, value));
+                }
+            }
+        }
+
+        let host = attrs.get("id.orig_h").cloned().unwrap_or_else(|| "localhost".to_string());
+        let daemon = match &header.path {
+            Some(path) => format!("zeek/{}", path),
+            None => "zeek".to_string(),
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry: message.join(" "),
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Zeek"
+    }
+}
+
+pub struct SuricataEveParser;
+
+impl LogParser for SuricataEveParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"timestamp":"2024-03-05T10:00:00.123456+0000","flow_id":...,"event_type":"alert",...}
+        let line = line.trim_start();
+        line.starts_with('{') && line.contains("\"event_type\"") && line.contains("\"timestamp\"")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        let text = |value: &serde_json::Value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let get = |path: &str| json.pointer(path).map(text);
+
+        let timestamp = json["timestamp"].as_str()
+            .and_then(|ts| DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f%z").ok())
+            .map(|dt| dt.with_timezone(&Local).naive_local())
+            .ok_or_else(|| anyhow!("eve.json event without valid timestamp"))?;
+        let event_type = get("/event_type").unwrap_or_else(|| "unknown".to_string());
+
+        let mut attrs = BTreeMap::new();
+        let mut set = |key: &str, path: &str| {
+            if let Some(value) = get(path) {
+                attrs.insert(key.to_string(), value);
+            }
+        };
+        set("src", "/src_ip");
+        set("dst", "/dest_ip");
+        set("src_port", "/src_port");
+        set("dst_port", "/dest_port");
+        set("proto", "/proto");
+        set("interface", "/in_iface");
+        set("app_proto", "/app_proto");
+
+        let endpoint = |ip: &str, port: &str| match (get(ip), get(port)) {
+            (Some(ip), Some(port)) => format!("{}:{}", ip, port),
+            (Some(ip), None) => ip,
+            _ => "-".to_string(),
+        };
+        let flow = format!("{{{}}} {} -> {}",
+            get("/proto").unwrap_or_default(), endpoint("/src_ip", "/src_port"), endpoint("/dest_ip", "/dest_port"));
+
+        let mut severity = None;
+        let log_entry = match event_type.as_str() {
+            "alert" => {
+                set("signature", "/alert/signature");
+                set("signature_id", "/alert/signature_id");
+                set("category", "/alert/category");
+                set("alert_severity", "/alert/severity");
+                set("action", "/alert/action");
+                // Suricata priority 1 is the most severe
+                severity = json.pointer("/alert/severity").and_then(|s| s.as_u64()).map(|s| match s {
+                    1 => 2,
+                    2 => 3,
+                    3 => 4,
+                    _ => 5,
+                });
+                // Same layout as fast.log
+                format!("[{}:{}:{}] {} [Classification: {}] [Priority: {}] {}",
+                    get("/alert/gid").unwrap_or_default(), get("/alert/signature_id").unwrap_or_default(),
+                    get("/alert/rev").unwrap_or_default(), get("/alert/signature").unwrap_or_default(),
+                    get("/alert/category").unwrap_or_default(), get("/alert/severity").unwrap_or_default(), flow)
+            }
+            "flow" => {
+                set("state", "/flow/state");
+                set("bytes_toserver", "/flow/bytes_toserver");
+                set("bytes_toclient", "/flow/bytes_toclient");
+                format!("{} state={} reason={} bytes={}/{}", flow,
+                    get("/flow/state").unwrap_or_default(), get("/flow/reason").unwrap_or_default(),
+                    get("/flow/bytes_toserver").unwrap_or_default(), get("/flow/bytes_toclient").unwrap_or_default())
+            }
+            "dns" => {
+                // eve v2 answers carry rrname/rrtype at the top; v3 queries use a "queries" array
+                let rrname = get("/dns/rrname").or_else(|| get("/dns/queries/0/rrname")).unwrap_or_default();
+                let rrtype = get("/dns/rrtype").or_else(|| get("/dns/queries/0/rrtype")).unwrap_or_default();
+                attrs.insert("rrname".to_string(), rrname.clone());
+                attrs.insert("rrtype".to_string(), rrtype.clone());
+                let rcode = get("/dns/rcode");
+                if let Some(rcode) = &rcode {
+                    attrs.insert("rcode".to_string(), rcode.clone());
+                }
+                format!("{} {} {} {}{}", get("/dns/type").unwrap_or_else(|| "query".to_string()), rrtype, rrname, flow,
+                    rcode.map(|r| format!(" rcode={}", r)).unwrap_or_default())
+            }
+            other if attrs.contains_key("src") => format!("{} {}", other, flow),
+            other => other.to_string(),
+        };
+        attrs.insert("event_type".to_string(), event_type.clone());
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: get("/src_ip").unwrap_or_else(|| "localhost".to_string()),
+            daemon: format!("suricata/{}", event_type),
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Suricata"
+    }
+}
+
+pub struct SnortFastParser;
+
+impl LogParser for SnortFastParser {
+    fn is_type(&self, line: &str) -> bool {
+        // "03/05-10:00:00.123456  [**] [1:2001219:20] ET SCAN Potential SSH Scan [**] [Classification: ...] [Priority: 2] {TCP} a:p -> b:p"
+        let re = Regex::new(r"^\d{2}/\d{2}(?:/\d{2})?-\d{2}:\d{2}:\d{2}\.\d+\s+\[\*\*\] \[\d+:\d+:\d+\] ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^(\d{2})/(\d{2})(?:/(\d{2}))?-(\d{2}):(\d{2}):(\d{2})\.\d+\s+\[\*\*\] \[(\d+:\d+:\d+)\] (.*?) \[\*\*\](?: \[Classification: ([^\]]*)\])?(?: \[Priority: (\d+)\])? \{(\S+)\} (\S+) -> (\S+)").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Snort alert"))?;
+
+        // snort -y adds a two-digit year
+        let year = match caps.get(3) {
+            Some(y) => 2000 + y.as_str().parse::<i32>()?,
+            None => Local::now().year(),
+        };
+        let proto = caps[11].to_string();
+        let split_endpoint = |endpoint: &str| -> (String, Option<String>) {
+            match endpoint.rsplit_once(':') {
+                Some((ip, port)) if proto == "TCP" || proto == "UDP" => (ip.to_string(), Some(port.to_string())),
+                _ => (endpoint.to_string(), None),
+            }
+        };
+        let (src, src_port) = split_endpoint(&caps[12]);
+        let (dst, dst_port) = split_endpoint(&caps[13]);
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("rule_id".to_string(), caps[7].to_string());
+        attrs.insert(format!("rule.{}", &caps[7]), caps[8].to_string());
+        attrs.insert("proto".to_string(), proto.clone());
+        attrs.insert("src".to_string(), src.clone());
+        attrs.insert("dst".to_string(), dst);
+        if let Some(port) = src_port {
+            attrs.insert("src_port".to_string(), port);
+        }
+        if let Some(port) = dst_port {
+            attrs.insert("dst_port".to_string(), port);
+        }
+        if let Some(classification) = caps.get(9) {
+            attrs.insert("category".to_string(), classification.as_str().to_string());
+        }
+        attrs.insert("sample".to_string(), format!("{{{}}} {} -> {}", proto, &caps[12], &caps[13]));
+
+        // Priority 1 is the most severe
+        let severity = caps.get(10).and_then(|p| p.as_str().parse::<u8>().ok()).map(|p| match p {
+            1 => 2,
+            2 => 3,
+            3 => 4,
+            _ => 5,
+        });
+
+        Ok(LogEntry {
+            year,
+            month: caps[1].parse()?,
+            day: caps[2].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host: src,
+            daemon: "snort".to_string(),
+            log_entry: line[caps.get(7).unwrap().start() - 1..].to_string(),
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Snort"
+    }
+}
+
+/// ModSecurity serial audit log: one transaction per "--id-A--" ... "--id-Z--" block
+pub struct ModSecurityAuditParser;
+
+impl LogParser for ModSecurityAuditParser {
+    fn is_type(&self, line: &str) -> bool {
+        let re = Regex::new(r"^--[0-9A-Za-z]+-A--$").unwrap();
+        re.is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Cheap check: detection calls this while walking back through whole records
+        !(line.starts_with("--") && line.ends_with("-A--"))
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let boundary_re = Regex::new(r"^--[0-9A-Za-z]+-([A-Z])--$").unwrap();
+        let mut sections: BTreeMap<char, Vec<&str>> = BTreeMap::new();
+        let mut current = None;
+        for line in record.lines() {
+            if let Some(caps) = boundary_re.captures(line) {
+                current = caps[1].chars().next();
+            } else if let Some(section) = current {
+                if !line.is_empty() {
+                    sections.entry(section).or_default().push(line);
+                }
+            }
+        }
+        let section = |name: char| sections.get(&name).map(|s| s.as_slice()).unwrap_or(&[]);
+
+        // A: "[05/Mar/2024:10:00:00 +0000] uniqueid 192.0.2.44 40000 10.0.0.8 80"
+        let header_re = Regex::new(r"^\[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2})(?:\.\d+)? [+-]\d{4}\] (\S+) (\S+) (\d+) (\S+) (\d+)").unwrap();
+        let header = section('A').first()
+            .and_then(|l| header_re.captures(l))
+            .ok_or_else(|| anyhow!("ModSecurity record without A section"))?;
+
+        let request = section('B').first().copied().unwrap_or("-");
+        let status = section('F').first()
+            .and_then(|l| l.split_whitespace().nth(1))
+            .unwrap_or("-");
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("unique_id".to_string(), header[7].to_string());
+        attrs.insert("src".to_string(), header[8].to_string());
+        attrs.insert("src_port".to_string(), header[9].to_string());
+        attrs.insert("dst".to_string(), header[10].to_string());
+        attrs.insert("dst_port".to_string(), header[11].to_string());
+        attrs.insert("request".to_string(), request.to_string());
+        attrs.insert("sample".to_string(), request.to_string());
+        attrs.insert("status".to_string(), status.to_string());
+
+        // H: 'Message: Warning. ... [id "942100"] [msg "SQL Injection Attack"] [severity "CRITICAL"]'
+        let tag_re = Regex::new(r#"\[(id|msg|severity) "([^"]*)"\]"#).unwrap();
+        let mut rule_ids = Vec::new();
+        let mut severity: Option<u8> = None;
+        for message in section('H').iter().filter_map(|l| l.strip_prefix("Message: ")) {
+            let mut id = None;
+            let mut msg = "";
+            for tag in tag_re.captures_iter(message) {
+                match &tag[1] {
+                    "id" => id = Some(tag.get(2).unwrap().as_str()),
+                    "msg" => msg = tag.get(2).unwrap().as_str(),
+                    _ => {
+                        if let Some(level) = severity_from_name(&tag[2]) {
+                            severity = Some(severity.map_or(level, |s| s.min(level)));
+                        }
+                    }
+                }
+            }
+            if let Some(id) = id {
+                attrs.insert(format!("rule.{}", id), msg.to_string());
+                rule_ids.push(id.to_string());
+            }
+        }
+        if let Some(action) = section('H').iter().find_map(|l| l.strip_prefix("Action: ")) {
+            attrs.insert("action".to_string(), action.to_string());
+        }
+        if !rule_ids.is_empty() {
+            attrs.insert("rule_id".to_string(), rule_ids.join(","));
+        }
+
+        let log_entry = if rule_ids.is_empty() {
+            format!("{} {}", request, status)
+        } else {
+            format!("{} {} rules={}", request, status, rule_ids.join(","))
+        };
+
+        Ok(LogEntry {
+            year: header[3].parse()?,
+            month: parse_month(&header[2]).ok_or_else(|| anyhow!("Invalid month"))?,
+            day: header[1].parse()?,
+            hour: header[4].parse()?,
+            minute: header[5].parse()?,
+            second: header[6].parse()?,
+            host: header[8].to_string(),
+            daemon: "modsecurity".to_string(),
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "ModSecurity"
+    }
+}
+
+/// Pieces of a klog header: "I0114 10:12:13.123456       1 file.go:123] msg"
+struct KlogLine<'a> {
+    severity: u8,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    thread: &'a str,
+    file: &'a str,
+    source: &'a str,
+    message: &'a str,
+}
+
+impl<'a> KlogLine<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let re = Regex::new(r"^([IWEF])(\d{2})(\d{2}) (\d{2}):(\d{2}):(\d{2})\.\d+\s+(\d+) (([^:\s\]]+):\d+)\] ?(.*)$").unwrap();
+        let caps = re.captures(line)?;
+        let text = |i: usize| caps.get(i).unwrap().as_str();
+
+        Some(Self {
+            severity: match text(1) {
+                "I" => 6,
+                "W" => 4,
+                "E" => 3,
+                _ => 2,
+            },
+            month: text(2).parse().ok()?,
+            day: text(3).parse().ok()?,
+            hour: text(4).parse().ok()?,
+            minute: text(5).parse().ok()?,
+            second: text(6).parse().ok()?,
+            thread: text(7),
+            source: text(8),
+            file: text(9),
+            message: text(10),
+        })
+    }
+
+    fn attrs(&self) -> BTreeMap<String, String> {
+        let mut attrs = BTreeMap::new();
+        attrs.insert("thread".to_string(), self.thread.to_string());
+        attrs.insert("source".to_string(), self.source.to_string());
+        attrs
+    }
+}
+
+pub struct KlogParser;
+
+impl LogParser for KlogParser {
+    fn is_type(&self, line: &str) -> bool {
+        KlogLine::parse(line).is_some()
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let klog = KlogLine::parse(line).ok_or_else(|| anyhow!("Failed to parse klog line"))?;
+
+        // klog headers carry no year
+        Ok(LogEntry {
+            year: Local::now().year(),
+            month: klog.month,
+            day: klog.day,
+            hour: klog.hour,
+            minute: klog.minute,
+            second: klog.second,
+            host: "localhost".to_string(),
+            daemon: klog.file.to_string(),
+            log_entry: klog.message.to_string(),
+            severity: Some(klog.severity),
+            attrs: klog.attrs(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "klog"
+    }
+}
+
+pub struct KubectlParser;
+
+impl KubectlParser {
+    fn split(line: &str) -> Option<(NaiveDateTime, &str)> {
+        let (timestamp, message) = line.split_once(' ')?;
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).ok()?;
+        Some((timestamp.with_timezone(&Local).naive_local(), message))
+    }
+}
+
+impl LogParser for KubectlParser {
+    fn is_type(&self, line: &str) -> bool {
+        // "kubectl logs --timestamps": "2024-03-05T10:00:00.123456789Z message"
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z ").unwrap();
+        if !re.is_match(line) {
+            return false;
+        }
+
+        // rsyslog lines look the same up to the timestamp, but continue with "host daemon:"
+        let daemon_re = Regex::new(r"^[a-zA-Z0-9_\-\.\/]+(\[[0-9]+\])?:$").unwrap();
+        !line.split_whitespace().nth(2).map(|d| daemon_re.is_match(d)).unwrap_or(false)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let (timestamp, message) = Self::split(line).ok_or_else(|| anyhow!("Failed to parse kubectl log"))?;
+
+        // Control-plane components log in klog format behind the prefix
+        let (daemon, log_entry, severity, attrs) = match KlogLine::parse(message) {
+            Some(klog) => (klog.file.to_string(), klog.message.to_string(), Some(klog.severity), klog.attrs()),
+            None => ("kubectl".to_string(), message.to_string(), None, BTreeMap::new()),
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "kubectl"
+    }
+}
+
+/// containerd/CRI-O container logs: "2023-11-14T10:00:00.000000000Z stdout F message".
+/// "P" marks a partial line that the following line(s) complete.
+pub struct CriParser;
+
+impl CriParser {
+    fn split(line: &str) -> Option<(&str, &str, &str, &str)> {
+        let mut parts = line.splitn(4, ' ');
+        let timestamp = parts.next()?;
+        let stream = parts.next()?;
+        let tag = parts.next()?;
+        let message = parts.next().unwrap_or("");
+        if !matches!(stream, "stdout" | "stderr") || !tag.starts_with(['F', 'P']) {
+            return None;
+        }
+        Some((timestamp, stream, tag, message))
+    }
+}
+
+impl LogParser for CriParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::split(line)
+            .map(|(timestamp, _, _, _)| DateTime::parse_from_rfc3339(timestamp).is_ok())
+            .unwrap_or(false)
+    }
+
+    fn is_continuation(&self, previous: &str, _line: &str) -> bool {
+        Self::split(previous).map(|(_, _, tag, _)| tag.starts_with('P')).unwrap_or(false)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        // Reassemble partial lines; the first one carries the timestamp
+        let mut message = String::new();
+        let mut first = None;
+        for line in record.lines() {
+            let (timestamp, stream, _, part) = Self::split(line).ok_or_else(|| anyhow!("Failed to parse CRI log"))?;
+            first.get_or_insert((timestamp, stream));
+            message.push_str(part);
+        }
+        let (timestamp, stream) = first.ok_or_else(|| anyhow!("Empty CRI record"))?;
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Local).naive_local();
+
+        // Static control-plane pods log in klog format
+        let (daemon, log_entry, severity, mut attrs) = match KlogLine::parse(&message) {
+            Some(klog) => (klog.file.to_string(), klog.message.to_string(), Some(klog.severity), klog.attrs()),
+            None => (stream.to_string(), message.clone(), None, BTreeMap::new()),
+        };
+        attrs.insert("stream".to_string(), stream.to_string());
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "CRI"
+    }
+}
+
+pub struct HerokuParser;
+
+impl HerokuParser {
+    /// Returns (timestamp, host, source, dyno, message) for CLI output or logplex drain frames
+    fn split(line: &str) -> Option<(String, String, String, String, String)> {
+        // heroku logs: "2024-03-05T10:00:00.123456+00:00 app[web.1]: message"
+        let cli_re = Regex::new(r"^(\S+) (app|heroku)\[([\w.\-]+)\]: ?(.*)$").unwrap();
+        // Drain: "83 <190>1 2024-03-05T10:00:00.123456+00:00 host app web.1 - message"
+        let drain_re = Regex::new(r"^(?:\d+ )?<\d+>1 (\S+) (\S+) (app|heroku) ([\w.\-]+) - ?(.*)$").unwrap();
+
+        if let Some(caps) = cli_re.captures(line) {
+            Some((caps[1].to_string(), "localhost".to_string(), caps[2].to_string(), caps[3].to_string(), caps[4].to_string()))
+        } else {
+            drain_re.captures(line).map(|caps| {
+                (caps[1].to_string(), caps[2].to_string(), caps[3].to_string(), caps[4].to_string(), caps[5].to_string())
+            })
+        }
+    }
+}
+
+impl LogParser for HerokuParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::split(line)
+            .map(|(timestamp, _, _, _, _)| DateTime::parse_from_rfc3339(&timestamp).is_ok())
+            .unwrap_or(false)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let (timestamp, host, source, dyno, message) = Self::split(line).ok_or_else(|| anyhow!("Failed to parse Heroku log"))?;
+        let timestamp = DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Local).naive_local();
+
+        let mut attrs = BTreeMap::new();
+        // The router logs requests as key=value pairs
+        if dyno == "router" {
+            attrs = parse_key_values(&message);
+            if let Some(service) = attrs.get("service").and_then(|s| s.strip_suffix("ms")) {
+                attrs.insert("duration_ms".to_string(), service.to_string());
+            }
+            if let Some(fwd) = attrs.get("fwd").cloned() {
+                attrs.insert("client".to_string(), fwd);
+            }
+        }
+        attrs.insert("source".to_string(), source);
+        // Router lines name the dyno that served the request
+        attrs.entry("dyno".to_string()).or_insert_with(|| dyno.clone());
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: dyno,
+            log_entry: message,
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Heroku"
+    }
+}
+
+/// Google Cloud Logging `LogEntry` records, one JSON object per line as a
+/// sink writes them (`gcloud logging read --format=json` output is an array
+/// and needs `jq -c '.[]'` first). The log name is the daemon; payload, HTTP
+/// request and resource labels are kept as structured fields
+pub struct GcpLogParser;
+
+impl GcpLogParser {
+    /// Resource labels that name the machine or workload, most specific first
+    const HOST_LABELS: &'static [&'static str] = &[
+        "instance_id", "pod_name", "service_name", "function_name", "job_name", "cluster_name", "project_id",
+    ];
+}
+
+impl LogParser for GcpLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"insertId":"...","logName":"projects/p/logs/syslog","resource":{"type":"gce_instance",...},"timestamp":"...","textPayload":"..."}
+        let line = line.trim_start();
+        line.starts_with('{') && line.contains("\"logName\"") && line.contains("\"resource\"") && line.contains("\"timestamp\"")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        let text = |value: &serde_json::Value| value.as_str().map(|s| s.to_string()).filter(|s| !s.is_empty());
+
+        let timestamp = json["timestamp"].as_str()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|dt| dt.with_timezone(&Local).naive_local())
+            .ok_or_else(|| anyhow!("Cloud Logging entry without timestamp"))?;
+
+        let resource_type = text(&json["resource"]["type"]);
+        let host = Self::HOST_LABELS.iter()
+            .find_map(|label| text(&json["resource"]["labels"][label]))
+            .unwrap_or_else(|| "localhost".to_string());
+        // "projects/my-project/logs/cloudaudit.googleapis.com%2Factivity"
+        let daemon = json["logName"].as_str()
+            .and_then(|name| name.rsplit_once("/logs/"))
+            .map(|(_, id)| id.replace("%2F", "/").replace("%2f", "/"))
+            .or_else(|| resource_type.clone())
+            .unwrap_or_else(|| "gcp".to_string());
+
+        let mut attrs = BTreeMap::new();
+        if let Some(resource_type) = &resource_type {
+            attrs.insert("resource_type".to_string(), resource_type.clone());
+        }
+        flatten_json("resource", &json["resource"]["labels"], &mut attrs);
+        flatten_json("labels", &json["labels"], &mut attrs);
+        flatten_json("payload", &json["jsonPayload"], &mut attrs);
+        flatten_json("http", &json["httpRequest"], &mut attrs);
+        for key in ["insertId", "trace", "spanId"] {
+            if let Some(value) = text(&json[key]) {
+                attrs.insert(key.to_string(), value);
+            }
+        }
+
+        // Audit logs carry a protoPayload with the call, the caller and the outcome
+        let audit = &json["protoPayload"];
+        for (key, pointer) in [
+            ("service", "/serviceName"), ("method", "/methodName"), ("resource_name", "/resourceName"),
+            ("user", "/authenticationInfo/principalEmail"), ("src", "/requestMetadata/callerIp"),
+            ("status_message", "/status/message"),
+        ] {
+            if let Some(value) = audit.pointer(pointer).and_then(text) {
+                attrs.insert(key.to_string(), value);
+            }
+        }
+        if let Some(ip) = text(&json["httpRequest"]["remoteIp"]) {
+            attrs.entry("src".to_string()).or_insert(ip);
+        }
+
+        let http = &json["httpRequest"];
+        let log_entry = if let Some(payload) = text(&json["textPayload"]) {
+            payload
+        } else if let Some(message) = text(&json["jsonPayload"]["message"]).or_else(|| text(&json["jsonPayload"]["msg"])) {
+            message
+        } else if let Some(method) = audit.pointer("/methodName").and_then(text) {
+            let mut message = method;
+            if let Some(resource) = audit.pointer("/resourceName").and_then(text) {
+                message.push_str(&format!(" on {}", resource));
+            }
+            if let Some(user) = audit.pointer("/authenticationInfo/principalEmail").and_then(text) {
+                message.push_str(&format!(" by {}", user));
+            }
+            if let Some(status) = audit.pointer("/status/message").and_then(text) {
+                message.push_str(&format!(": {}", status));
+            }
+            message
+        } else if http.is_object() {
+            format!("{} {} {}", text(&http["requestMethod"]).unwrap_or_else(|| "-".to_string()),
+                text(&http["requestUrl"]).unwrap_or_else(|| "-".to_string()), http["status"])
+        } else if json["jsonPayload"].is_object() {
+            json["jsonPayload"].to_string()
+        } else {
+            String::new()
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry,
+            // DEFAULT has no severity
+            severity: json["severity"].as_str().and_then(severity_from_name),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "GCP"
+    }
+}
+
+/// Azure Activity Log and resource (diagnostic) log records, one JSON object
+/// per line as Azure writes them to storage or Event Hubs; the portal and
+/// REST export layout (eventTimestamp, operationName.value, status.value) is
+/// read too. The resource name is the host and the category the daemon
+pub struct AzureLogParser;
+
+impl LogParser for AzureLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"time":"2024-03-05T10:00:00.1234567Z","resourceId":"/SUBSCRIPTIONS/.../VIRTUALMACHINES/VM1","operationName":"...","category":"Administrative",...}
+        let line = line.trim_start();
+        line.starts_with('{') && line.contains("\"resourceId\"") && line.contains("\"operationName\"")
+            && (line.contains("\"time\"") || line.contains("\"eventTimestamp\""))
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        // Fields are plain strings in storage exports and {"value", "localizedValue"} in the REST layout
+        let text = |value: &serde_json::Value| value.as_str().or_else(|| value["value"].as_str())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
+
+        // Some resource logs write UTC times without an offset
+        let timestamp = text(&json["time"]).or_else(|| text(&json["eventTimestamp"]))
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok()
+                .or_else(|| t.get(..19).and_then(|t| DateTime::parse_from_rfc3339(&format!("{}Z", t)).ok())))
+            .map(|dt| dt.with_timezone(&Local).naive_local())
+            .ok_or_else(|| anyhow!("Azure log record without time"))?;
+
+        let resource_id = text(&json["resourceId"]).unwrap_or_default();
+        let host = resource_id.rsplit('/').find(|s| !s.is_empty()).unwrap_or("azure").to_string();
+        let daemon = text(&json["category"]).unwrap_or_else(|| "azure".to_string());
+        let operation = text(&json["operationName"]).unwrap_or_else(|| "-".to_string());
+        let result = text(&json["resultType"]).or_else(|| text(&json["status"]));
+        let caller = text(&json["caller"])
+            .or_else(|| text(&json["identity"]["claims"]["http://schemas.xmlsoap.org/ws/2005/05/identity/claims/upn"]))
+            .or_else(|| text(&json["identity"]["claims"]["name"]));
+
+        let mut attrs = BTreeMap::new();
+        flatten_json("properties", &json["properties"], &mut attrs);
+        for (key, value) in [
+            ("resource_id", Some(resource_id.clone())), ("operation", Some(operation.clone())), ("result", result.clone()),
+            ("result_signature", text(&json["resultSignature"])), ("correlation_id", text(&json["correlationId"])),
+            ("location", text(&json["location"])), ("duration_ms", json["durationMs"].as_str().map(|s| s.to_string()).or_else(|| json["durationMs"].as_u64().map(|d| d.to_string()))),
+            ("user", caller.clone()), ("src", text(&json["callerIpAddress"])),
+        ] {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                attrs.insert(key.to_string(), value);
+            }
+        }
+        // "/subscriptions/<id>/resourceGroups/<group>/providers/..."
+        let segments: Vec<&str> = resource_id.split('/').collect();
+        for (key, name) in [("subscriptions", "subscription"), ("resourcegroups", "resource_group")] {
+            if let Some(i) = segments.iter().position(|s| s.eq_ignore_ascii_case(key)) {
+                if let Some(value) = segments.get(i + 1) {
+                    attrs.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+
+        let mut log_entry = operation;
+        if let Some(result) = &result {
+            log_entry.push_str(&format!(" {}", result));
+        }
+        if let Some(caller) = &caller {
+            log_entry.push_str(&format!(" by {}", caller));
+        }
+
+        let severity = match text(&json["level"]).map(|l| l.to_lowercase()).as_deref() {
+            Some("verbose") => Some(7),
+            Some(level) => severity_from_name(level),
+            None => None,
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Azure"
+    }
+}
+
+pub struct CloudflareParser;
+
+impl CloudflareParser {
+    /// Logpush timestamps are RFC3339 or Unix seconds/milliseconds/nanoseconds, depending on the job
+    fn timestamp(value: &serde_json::Value) -> Option<NaiveDateTime> {
+        if let Some(text) = value.as_str() {
+            return DateTime::parse_from_rfc3339(text).ok().map(|dt| dt.with_timezone(&Local).naive_local());
+        }
+        let number = value.as_f64()?;
+        let seconds = if number > 1e17 {
+            number / 1e9
+        } else if number > 1e11 {
+            number / 1e3
+        } else {
+            number
+        };
+        epoch_to_local(seconds)
+    }
+}
+
+impl LogParser for CloudflareParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"ClientIP":"192.0.2.1","ClientRequestURI":"/","EdgeResponseStatus":200,"EdgeStartTimestamp":"2024-03-05T10:00:00Z",...}
+        let line = line.trim_start();
+        line.starts_with('{') && line.contains("\"EdgeStartTimestamp\"") && line.contains("\"ClientIP\"")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        let text = |key: &str| match &json[key] {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Null => "-".to_string(),
+            other => other.to_string(),
+        };
+
+        let timestamp = Self::timestamp(&json["EdgeStartTimestamp"])
+            .ok_or_else(|| anyhow!("Logpush record without EdgeStartTimestamp"))?;
+
+        let method = text("ClientRequestMethod");
+        let protocol = text("ClientRequestProtocol");
+        let request = format!("{} {} {}", method, text("ClientRequestURI"), protocol);
+
+        let mut attrs = BTreeMap::new();
+        for (key, field) in [
+            ("method", "ClientRequestMethod"), ("path", "ClientRequestURI"), ("status", "EdgeResponseStatus"),
+            ("bytes", "EdgeResponseBytes"), ("request_host", "ClientRequestHost"), ("ray_id", "RayID"),
+            ("country", "ClientCountry"), ("cache_status", "CacheCacheStatus"), ("origin_status", "OriginResponseStatus"),
+        ] {
+            if !json[field].is_null() {
+                attrs.insert(key.to_string(), text(field));
+            }
+        }
+        // OriginResponseTime is in nanoseconds
+        if let Some(origin_ns) = json["OriginResponseTime"].as_f64() {
+            attrs.insert("duration_ms".to_string(), format!("{}", (origin_ns / 1e6).round()));
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: text("ClientIP"),
+            daemon: if method == "-" { "HTTP".to_string() } else { method },
+            log_entry: format!("{} {} {} \"{}\" \"{}\"", request, text("EdgeResponseStatus"), text("EdgeResponseBytes"),
+                text("ClientRequestReferer"), text("ClientRequestUserAgent")),
+            severity: None,
+            attrs: with_src(attrs, &text("ClientIP")),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Cloudflare"
+    }
+}
+
+pub struct EnvoyParser;
+
+impl LogParser for EnvoyParser {
+    fn is_type(&self, line: &str) -> bool {
+        // [2024-03-05T10:00:00.310Z] "POST /api HTTP/2" 204 - 154 0 226 100 "10.0.35.28" "curl" "req-id" "api.example.com" "10.0.2.1:80"
+        let re = Regex::new(r#"^\[\d{4}-\d{2}-\d{2}T[^\]]+\] "[^"]*" (\d{3}|-) \S+ "#).unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        // Newer Envoy versions add response code details and termination details after the flags
+        let re = Regex::new(r#"^\[([^\]]+)\] "([^"]*)" (\d{3}|-) (\S+)(?: (\S+) (\S+) "[^"]*")? (\d+|-) (\d+|-) (\d+|-) (\d+|-) "([^"]*)" "([^"]*)" "([^"]*)" "([^"]*)" "([^"]*)""#).unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Envoy access log"))?;
+        let text = |i: usize| caps.get(i).map(|m| m.as_str()).unwrap_or("-");
+
+        let timestamp = DateTime::parse_from_rfc3339(text(1))?.with_timezone(&Local).naive_local();
+        let request = text(2);
+        let method = request.split_whitespace().next().unwrap_or("HTTP");
+
+        let mut attrs = BTreeMap::new();
+        for (key, value) in [
+            ("method", method), ("path", request.split_whitespace().nth(1).unwrap_or("-")),
+            ("status", text(3)), ("response_flags", text(4)), ("response_code_details", text(5)),
+            ("bytes_received", text(7)), ("bytes", text(8)), ("duration_ms", text(9)),
+            ("upstream_service_time", text(10)), ("user_agent", text(12)), ("request_id", text(13)),
+            ("authority", text(14)), ("upstream", text(15)),
+        ] {
+            if value != "-" && !value.is_empty() {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        // The default format has no downstream address; X-Forwarded-For is the closest thing
+        let host = text(11).split(',').next().map(|h| h.trim()).filter(|h| !h.is_empty() && *h != "-").unwrap_or("localhost");
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: host.to_string(),
+            daemon: method.to_string(),
+            log_entry: format!("{} {} {} {} upstream={} \"{}\"", request, text(3), text(4), text(8), text(15), text(12)),
+            severity: None,
+            attrs: with_src(attrs, host),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Envoy"
+    }
+}
+
+pub struct TraefikParser;
+
+impl LogParser for TraefikParser {
+    fn is_type(&self, line: &str) -> bool {
+        // CLF plus: request count, "router", "service URL" and duration, e.g. ... "curl/8.0" 42 "web@docker" "http://10.0.0.5:80" 12ms
+        let re = Regex::new(r#"^\S+ \S+ \S+ \[[^\]]+\] "[^"]*" \d{3} \S+ "[^"]*" "[^"]*" \d+ "[^"]*" "[^"]*" \d+ms$"#).unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r#"^(\S+) \S+ (\S+) \[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2}) [+-]\d{4}\] "([^"]*)" (\d{3}) (\S+) "([^"]*)" "([^"]*)" (\d+) "([^"]*)" "([^"]*)" (\d+)ms$"#).unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Traefik access log"))?;
+        let text = |i: usize| caps.get(i).unwrap().as_str();
+
+        let request = text(9);
+        let method = request.split_whitespace().next().unwrap_or("HTTP");
+
+        let mut attrs = BTreeMap::new();
+        for (key, value) in [
+            ("method", method), ("path", request.split_whitespace().nth(1).unwrap_or("-")),
+            ("status", text(10)), ("bytes", text(11)), ("user", text(2)), ("router", text(15)),
+            ("upstream", text(16)), ("duration_ms", text(17)),
+        ] {
+            if value != "-" && !value.is_empty() {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(LogEntry {
+            year: text(5).parse()?,
+            month: parse_month(text(4)).ok_or_else(|| anyhow!("Invalid month"))?,
+            day: text(3).parse()?,
+            hour: text(6).parse()?,
+            minute: text(7).parse()?,
+            second: text(8).parse()?,
+            host: text(1).to_string(),
+            daemon: method.to_string(),
+            log_entry: format!("{} {} {} \"{}\" \"{}\" router={} {}ms", request, text(10), text(11), text(12), text(13), text(15), text(17)),
+            severity: None,
+            attrs: with_src(attrs, text(1)),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Traefik"
+    }
+}
+
+/// Tomcat (JULI), Log4j, Logback and Spring Boot default layouts. Stack traces
+/// following an entry are folded into it.
+pub struct JavaLogParser;
+
+struct JavaHeader<'a> {
+    date: Option<(i32, u32, u32)>,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    level: &'a str,
+    thread: Option<&'a str>,
+    logger: &'a str,
+    message: &'a str,
+}
+
+impl JavaLogParser {
+    fn header(line: &str) -> Option<JavaHeader<'_>> {
+        // Tomcat: "14-Nov-2023 10:00:00.123 SEVERE [main] org.apache.catalina.startup.Catalina.start message"
+        let tomcat_re = Regex::new(r"^(\d{2})-(\w{3})-(\d{4}) (\d{2}):(\d{2}):(\d{2})(?:\.\d+)? (SEVERE|WARNING|INFO|CONFIG|FINE|FINER|FINEST) \[([^\]]+)\] (\S+) ?(.*)$").unwrap();
+        // Log4j/Spring Boot: "2023-11-14 10:00:00,123 ERROR [main] com.example.App - message",
+        // "2023-11-14 10:00:00.123  INFO 1234 --- [           main] c.e.App    : message"
+        let dated_re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})[ T](\d{2}):(\d{2}):(\d{2})(?:[,.]\d+)?(?:Z|[+-]\d{2}:?\d{2})?\s+(?:\[([^\]]+)\]\s+)?(TRACE|DEBUG|INFO|WARN|WARNING|ERROR|FATAL)\s+(?:\d+ --- (?:\[[^\]]*\] )?)?(?:\[\s*([^\]]+)\]\s+)?(\S+)\s+[-:] ?(.*)$").unwrap();
+        // Logback default (time only): "10:00:00.123 [main] INFO  com.example.App - message"
+        let logback_re = Regex::new(r"^(\d{2}):(\d{2}):(\d{2})\.\d{3} \[([^\]]+)\] (TRACE|DEBUG|INFO|WARN|ERROR)\s+(\S+) - ?(.*)$").unwrap();
+
+        fn text<'t>(caps: &regex::Captures<'t>, i: usize) -> Option<&'t str> {
+            caps.get(i).map(|m| m.as_str())
+        }
+
+        if let Some(caps) = tomcat_re.captures(line) {
+            Some(JavaHeader {
+                date: Some((caps[3].parse().ok()?, parse_month(&caps[2])?, caps[1].parse().ok()?)),
+                hour: caps[4].parse().ok()?,
+                minute: caps[5].parse().ok()?,
+                second: caps[6].parse().ok()?,
+                level: caps.get(7)?.as_str(),
+                thread: text(&caps, 8),
+                logger: caps.get(9)?.as_str(),
+                message: caps.get(10)?.as_str(),
+            })
+        } else if let Some(caps) = dated_re.captures(line) {
+            Some(JavaHeader {
+                date: Some((caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?)),
+                hour: caps[4].parse().ok()?,
+                minute: caps[5].parse().ok()?,
+                second: caps[6].parse().ok()?,
+                level: caps.get(8)?.as_str(),
+                thread: text(&caps, 7).or_else(|| text(&caps, 9)).map(|t| t.trim()),
+                logger: caps.get(10)?.as_str(),
+                message: caps.get(11)?.as_str(),
+            })
+        } else {
+            logback_re.captures(line).and_then(|caps| {
+                Some(JavaHeader {
+                    date: None,
+                    hour: caps[1].parse().ok()?,
+                    minute: caps[2].parse().ok()?,
+                    second: caps[3].parse().ok()?,
+                    level: caps.get(5)?.as_str(),
+                    thread: text(&caps, 4),
+                    logger: caps.get(6)?.as_str(),
+                    message: caps.get(7)?.as_str(),
+                })
+            })
+        }
+    }
+
+    fn severity(level: &str) -> Option<u8> {
+        match level {
+            "SEVERE" => Some(3),
+            "CONFIG" | "FINE" | "FINER" | "FINEST" => Some(7),
+            other => severity_from_name(other),
+        }
+    }
+
+    /// "\tat com.example...", "Caused by: ...", "... 12 more" and the exception line itself
+    fn is_stack_trace(line: &str) -> bool {
+        if line.starts_with([' ', '\t']) || line.starts_with("Caused by:") || line.starts_with("Suppressed:") {
+            return true;
+        }
+        let first = line.split([':', ' ']).next().unwrap_or("");
+        first.contains('.') && (first.ends_with("Exception") || first.ends_with("Error") || first.ends_with("Throwable"))
+    }
+
+    /// Keep the entry on one line; the exception (root cause if chained) is the useful summary
+    fn fold_stack_trace(message: &str, trace: &str, attrs: &mut BTreeMap<String, String>) -> String {
+        let message = message.trim();
+        if trace.is_empty() {
+            return message.to_string();
+        }
+        attrs.insert("stack_trace".to_string(), trace.to_string());
+        let exception = trace.lines()
+            .rev()
+            .find(|l| l.starts_with("Caused by: "))
+            .map(|l| l.trim_start_matches("Caused by: "))
+            .or_else(|| trace.lines().find(|l| !l.starts_with([' ', '\t'])));
+        match exception {
+            Some(exception) => {
+                attrs.insert("exception".to_string(), exception.to_string());
+                format!("{} | {}", message, exception)
+            }
+            None => message.to_string(),
+        }
+    }
+}
+
+impl LogParser for JavaLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::header(line).is_some()
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        Self::is_stack_trace(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let header = Self::header(first).ok_or_else(|| anyhow!("Failed to parse Java log"))?;
+
+        // Logback's default pattern has no date
+        let (year, month, day) = header.date.unwrap_or_else(|| {
+            let today = Local::now();
+            (today.year(), today.month(), today.day())
+        });
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("level".to_string(), header.level.to_string());
+        attrs.insert("logger".to_string(), header.logger.to_string());
+        if let Some(thread) = header.thread {
+            attrs.insert("thread".to_string(), thread.to_string());
+        }
+
+        let log_entry = Self::fold_stack_trace(header.message, trace, &mut attrs);
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour: header.hour,
+            minute: header.minute,
+            second: header.second,
+            host: "localhost".to_string(),
+            daemon: header.logger.to_string(),
+            log_entry,
+            severity: Self::severity(header.level),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Java"
+    }
+}
+
+/// Kafka server.log, controller.log and state-change.log:
+/// "[2023-11-14 10:00:00,123] INFO [KafkaServer id=1] started (kafka.server.KafkaServer)"
+pub struct KafkaParser;
+
+impl KafkaParser {
+    fn line_re() -> Regex {
+        Regex::new(r"^\[(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})(?:,\d+)?\] (TRACE|DEBUG|INFO|WARN|ERROR|FATAL) (.*?)(?: \(([\w.$]+)\))?$").unwrap()
+    }
+}
+
+impl LogParser for KafkaParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        JavaLogParser::is_stack_trace(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::line_re().captures(first).ok_or_else(|| anyhow!("Failed to parse Kafka log"))?;
+
+        let mut attrs = BTreeMap::new();
+        let logger = caps.get(9).map(|m| m.as_str()).unwrap_or("kafka");
+        attrs.insert("logger".to_string(), logger.to_string());
+        let log_entry = JavaLogParser::fold_stack_trace(&caps[8], trace, &mut attrs);
+
+        Ok(LogEntry {
+            year: caps[1].parse()?,
+            month: caps[2].parse()?,
+            day: caps[3].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host: "localhost".to_string(),
+            daemon: logger.to_string(),
+            log_entry,
+            severity: severity_from_name(&caps[7]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Kafka"
+    }
+}
+
+/// ZooKeeper: "2023-11-14 10:00:00,123 [myid:1] - INFO  [main:QuorumPeerMain@123] - message"
+pub struct ZooKeeperParser;
+
+impl ZooKeeperParser {
+    fn line_re() -> Regex {
+        Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})(?:,\d+)? \[myid:(\d*)\] - (TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+\[(.*):([\w.$]+)@(\d+)\] - (.*)$").unwrap()
+    }
+}
+
+impl LogParser for ZooKeeperParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        JavaLogParser::is_stack_trace(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::line_re().captures(first).ok_or_else(|| anyhow!("Failed to parse ZooKeeper log"))?;
+
+        // 3.5+ abbreviates the package (o.a.z.s.q.QuorumPeer); the class name is enough
+        let class = caps[10].rsplit('.').next().unwrap_or(&caps[10]);
+        let mut attrs = BTreeMap::new();
+        attrs.insert("thread".to_string(), caps[9].to_string());
+        attrs.insert("source".to_string(), format!("{}@{}", &caps[10], &caps[11]));
+        let host = if caps[7].is_empty() {
+            "localhost".to_string()
+        } else {
+            attrs.insert("myid".to_string(), caps[7].to_string());
+            format!("myid:{}", &caps[7])
+        };
+        let log_entry = JavaLogParser::fold_stack_trace(&caps[12], trace, &mut attrs);
+
+        Ok(LogEntry {
+            year: caps[1].parse()?,
+            month: caps[2].parse()?,
+            day: caps[3].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host,
+            daemon: class.to_string(),
+            log_entry,
+            severity: severity_from_name(&caps[8]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "ZooKeeper"
+    }
+}
+
+/// Cassandra system.log/debug.log:
+/// "INFO  [main] 2023-11-14 10:00:00,123 CassandraDaemon.java:123 - Startup complete"
+pub struct CassandraParser;
+
+impl CassandraParser {
+    fn line_re() -> Regex {
+        Regex::new(r"^(TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+\[(.*?)\] (\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})(?:,\d+)? ([\w$]+)\.java:(\d+) - (.*)$").unwrap()
+    }
+}
+
+impl LogParser for CassandraParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        JavaLogParser::is_stack_trace(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::line_re().captures(first).ok_or_else(|| anyhow!("Failed to parse Cassandra log"))?;
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("thread".to_string(), caps[2].to_string());
+        attrs.insert("source".to_string(), format!("{}.java:{}", &caps[9], &caps[10]));
+
+        // GCInspector: "G1 Young Generation GC in 245ms.  G1 Eden Space: ..."
+        let gc_re = Regex::new(r"^(.+?) GC in (\d+)ms").unwrap();
+        if &caps[9] == "GCInspector" {
+            if let Some(gc) = gc_re.captures(&caps[11]) {
+                attrs.insert("gc".to_string(), gc[1].to_string());
+                attrs.insert("duration_ms".to_string(), gc[2].to_string());
+            }
+        }
+        let log_entry = JavaLogParser::fold_stack_trace(&caps[11], trace, &mut attrs);
+
+        Ok(LogEntry {
+            year: caps[3].parse()?,
+            month: caps[4].parse()?,
+            day: caps[5].parse()?,
+            hour: caps[6].parse()?,
+            minute: caps[7].parse()?,
+            second: caps[8].parse()?,
+            host: "localhost".to_string(),
+            daemon: caps[9].to_string(),
+            log_entry,
+            severity: severity_from_name(&caps[1]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Cassandra"
+    }
+}
+
+pub struct ApacheCommonParser;
+
+impl LogParser for ApacheCommonParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Apache Common Log Format: IP - user [timestamp] "request" status bytes
+        let re = Regex::new(r#"^\S+ \S+ \S+ \[\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}\] "\S+ \S+ \S+" \d+ (?:\d+|-)$"#).unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        // Format: IP ident authuser [timestamp] "request" status bytes
+        let re = Regex::new(r#"^(\S+) (\S+) (\S+) \[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2}) ([+-]\d{4})\] "([^"]+)" (\d+) (\S+)"#).unwrap();
+
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Apache Common Log"))?;
+
+        let ip = caps.get(1).unwrap().as_str();
+        let day: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let month_str = caps.get(5).unwrap().as_str();
+        let year: i32 = caps.get(6).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(7).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(8).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(9).unwrap().as_str().parse()?;
+        let request = caps.get(11).unwrap().as_str();
+        let status = caps.get(12).unwrap().as_str();
+        let bytes = caps.get(13).unwrap().as_str();
+
+        let month = match month_str {
+            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
+            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
+            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
+            _ => return Err(anyhow!("Invalid month")),
+        };
+
+        // Extract HTTP method from request
+        let daemon = request.split_whitespace().next().unwrap_or("HTTP").to_string();
+        let log_entry = format!("{} {} {}", request, status, bytes);
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: ip.to_string(),
+            daemon,
+            log_entry,
+            severity: None,
+            attrs: with_user(with_src(BTreeMap::new(), ip), caps.get(3).unwrap().as_str()),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "ApacheCommon"
+    }
+}
+
+pub struct ApacheCombinedParser;
+
+impl LogParser for ApacheCombinedParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Apache Combined Log Format: IP - user [timestamp] "request" status bytes "referer" "user-agent"
+        let re = Regex::new(r#"^\S+ \S+ \S+ \[\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}\] "\S+ \S+ \S+" \d+ (?:\d+|-) "[^"]*" "[^"]*"$"#).unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        // Format: IP ident authuser [timestamp] "request" status bytes "referer" "user-agent"
+        let re = Regex::new(r#"^(\S+) (\S+) (\S+) \[(\d{2})/(\w{3})/(\d{4}):(\d{2}):(\d{2}):(\d{2}) ([+-]\d{4})\] "([^"]+)" (\d+) (\S+) "([^"]*)" "([^"]*)"#).unwrap();
+
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse Apache Combined Log"))?;
+
+        let ip = caps.get(1).unwrap().as_str();
+        let day: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let month_str = caps.get(5).unwrap().as_str();
+        let year: i32 = caps.get(6).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(7).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(8).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(9).unwrap().as_str().parse()?;
+        let request = caps.get(11).unwrap().as_str();
+        let status = caps.get(12).unwrap().as_str();
+        let bytes = caps.get(13).unwrap().as_str();
+        let referer = caps.get(14).unwrap().as_str();
+        let user_agent = caps.get(15).unwrap().as_str();
+
+        let month = match month_str {
+            "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4,
+            "May" => 5, "Jun" => 6, "Jul" => 7, "Aug" => 8,
+            "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
+            _ => return Err(anyhow!("Invalid month")),
+        };
+
+        // Extract HTTP method from request
+        let daemon = request.split_whitespace().next().unwrap_or("HTTP").to_string();
+        let log_entry = format!("{} {} {} \"{}\" \"{}\"", request, status, bytes, referer, user_agent);
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: ip.to_string(),
+            daemon,
+            log_entry,
+            severity: None,
+            attrs: with_user(with_src(BTreeMap::new(), ip), caps.get(3).unwrap().as_str()),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "ApacheCombined"
+    }
+}
+
+pub struct AwsElbParser;
+
+impl LogParser for AwsElbParser {
+    fn is_type(&self, line: &str) -> bool {
+        // AWS ELB format: timestamp elb client:port backend:port request_time backend_time response_time elb_status backend_status ...
+        // Backend field can be "-" when no backend connection, the status codes
+        // are "-" for TCP and SSL listeners
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z \S+ \d+\.\d+\.\d+\.\d+:\d+ (\d+\.\d+\.\d+\.\d+:\d+|-) [\d\.-]+ [\d\.-]+ [\d\.-]+ (?:\d+|-) ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 13 {
+            return Err(anyhow!("Invalid AWS ELB log format"));
+        }
+
+        // Parse timestamp: 2015-05-24T19:21:39.218145Z
+        let timestamp_str = parts[0];
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})").unwrap();
+        let caps = re.captures(timestamp_str).ok_or_else(|| anyhow!("Failed to parse timestamp"))?;
+
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+
+        let _elb_name = parts[1];
+        let client = parts[2].split(':').next().unwrap_or("unknown");
+        let backend = parts[3];
+        let elb_status = parts[7];
+        let backend_status = parts[8];
+
+        // Extract HTTP method from request (parts 11 onwards contain the quoted request)
+        let request_start = line.find('"').unwrap_or(0);
+        let request_end = line.rfind('"').unwrap_or(line.len());
+        let request = if request_start < request_end {
+            &line[request_start+1..request_end]
+        } else {
+            "-"
+        };
+
+        // TCP and SSL listeners log "- - - " for the request and no status codes
+        let (daemon, log_entry) = if line.contains(" \"- - - \" ") {
+            let ssl_cipher = parts.get(parts.len().saturating_sub(2)).copied().unwrap_or("-");
+            let daemon = if ssl_cipher == "-" { "TCP" } else { "SSL" };
+            (daemon.to_string(), format!("{} connection backend={} received_bytes={} sent_bytes={}", daemon, backend, parts[9], parts[10]))
+        } else {
+            (request.split_whitespace().next().unwrap_or("HTTP").to_string(),
+                format!("{} elb_status={} backend_status={}", request, elb_status, backend_status))
+        };
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: client.to_string(),
+            daemon,
+            log_entry,
+            severity: None,
+            attrs: with_src(BTreeMap::new(), client),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "AWS-ELB"
+    }
+}
+
+/// ALB access log fields in documented order; all but the time, the
+/// client and the request are kept as structured fields under these names
+const ALB_FIELDS: [&str; 30] = [
+    "type", "time", "elb", "client", "target",
+    "request_processing_time", "target_processing_time", "response_processing_time",
+    "elb_status_code", "target_status_code", "received_bytes", "sent_bytes",
+    "request", "user_agent", "ssl_cipher", "ssl_protocol", "target_group_arn",
+    "trace_id", "domain_name", "chosen_cert_arn", "matched_rule_priority",
+    "request_creation_time", "actions_executed", "redirect_url", "error_reason",
+    "target_port_list", "target_status_code_list", "classification",
+    "classification_reason", "conn_trace_id",
+];
+
+pub struct AwsAlbParser;
+
+impl LogParser for AwsAlbParser {
+    fn is_type(&self, line: &str) -> bool {
+        // AWS ALB format starts with: http/https/h2/grpc/ws/wss timestamp
+        let re = Regex::new(r"^(http|https|h2|grpc|ws|wss) \d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        // Request, user agent, trace ID and the other quoted fields may hold spaces
+        let parts = split_csv(line, ' ');
+        if parts.len() < 13 {
+            return Err(anyhow!("Invalid AWS ALB log format"));
+        }
+
+        // Parse timestamp: 2018-07-02T22:23:00.186641Z
+        let timestamp_str = &parts[1];
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})").unwrap();
+        let caps = re.captures(timestamp_str).ok_or_else(|| anyhow!("Failed to parse timestamp"))?;
+
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+
+        let protocol = &parts[0];
+        // IPv6 clients are logged as "2001:db8::1:2817"
+        let client = parts[3].rsplit_once(':').map(|(ip, _)| ip).unwrap_or(&parts[3]);
+        let elb_status = &parts[8];
+        let target_status = &parts[9];
+        let request = if parts[12].is_empty() { "-" } else { &parts[12] };
+
+        let mut attrs = BTreeMap::new();
+        for (i, value) in parts.iter().enumerate() {
+            // Fields added to the format after these are numbered
+            let name = ALB_FIELDS.get(i).map(|n| n.to_string()).unwrap_or_else(|| format!("field{}", i));
+            if !matches!(i, 1 | 3 | 12) && !value.is_empty() && value != "-" {
+                attrs.insert(name, value.clone());
+            }
+        }
+
+        let daemon = request.split_whitespace().next().unwrap_or(protocol).to_string();
+        let log_entry = format!("{} elb_status={} target_status={} protocol={}", request, elb_status, target_status, protocol);
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: client.to_string(),
+            daemon,
+            log_entry,
+            severity: None,
+            attrs: with_src(attrs, client),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "AWS-ALB"
+    }
+}
+
+/// NLB access log fields in documented order, kept as structured fields
+/// like those of the ALB
+const NLB_FIELDS: [&str; 22] = [
+    "type", "version", "time", "elb", "listener", "client", "destination",
+    "connection_time", "tls_handshake_time", "received_bytes", "sent_bytes",
+    "incoming_tls_alert", "chosen_cert_arn", "chosen_cert_serial", "tls_cipher",
+    "tls_protocol_version", "tls_named_group", "domain_name", "alpn_fe_protocol",
+    "alpn_be_protocol", "alpn_client_preference_list", "tls_connection_creation_time",
+];
+
+/// Network Load Balancer access logs, written for TLS listeners: one line per
+/// connection, with the client as host and "TLS" as daemon
+pub struct AwsNlbParser;
+
+impl LogParser for AwsNlbParser {
+    fn is_type(&self, line: &str) -> bool {
+        // tls 2.0 2018-12-20T02:59:40 net/my-network-loadbalancer/c6e77e28c25b2234 listener client:port destination:port ...
+        let re = Regex::new(r"^tls \d+\.\d+ \d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2} net/\S+ \S+ \S+:\d+ \S+:\d+ ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 16 {
+            return Err(anyhow!("Invalid AWS NLB log format"));
+        }
+
+        let timestamp = NaiveDateTime::parse_from_str(parts[2], "%Y-%m-%dT%H:%M:%S")
+            .map_err(|_| anyhow!("Failed to parse timestamp"))?;
+        let client = parts[5].rsplit_once(':').map(|(ip, _)| ip).unwrap_or(parts[5]);
+        let destination = parts[6];
+
+        let mut attrs = BTreeMap::new();
+        for (i, value) in parts.iter().enumerate() {
+            let name = NLB_FIELDS.get(i).map(|n| n.to_string()).unwrap_or_else(|| format!("field{}", i));
+            if !matches!(i, 2 | 5) && *value != "-" {
+                attrs.insert(name, value.to_string());
+            }
+        }
+
+        // A TLS alert from the client means a failed handshake
+        let alert = match parts[11] {
+            "-" => String::new(),
+            alert => format!(" tls_alert={}", alert),
+        };
+        let log_entry = format!("connection to {} {} {}{} received_bytes={} sent_bytes={}",
+            destination, parts[15], parts[14], alert, parts[9], parts[10]);
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: client.to_string(),
+            daemon: "TLS".to_string(),
+            log_entry,
+            severity: if alert.is_empty() { None } else { Some(4) },
+            attrs: with_src(attrs, client),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "AWS-NLB"
+    }
+}
+
+/// Fields of the default (version 2) VPC Flow Logs format, in order
+const VPC_FLOW_FIELDS: [&str; 14] = [
+    "version", "account-id", "interface-id", "srcaddr", "dstaddr", "srcport", "dstport",
+    "protocol", "packets", "bytes", "start", "end", "action", "log-status",
+];
+
+/// AWS VPC Flow Logs: one flow per line, space separated, with epoch start and
+/// end times. The default version 2 layout is assumed unless the file starts
+/// with the header line S3 delivery writes, which also covers custom formats.
+/// Addresses, ports, protocol and action are kept under the names the firewall
+/// parsers use, so `--firewall-report` and `--flow-report` work on them
+pub struct VpcFlowParser {
+    fields: Mutex<Vec<String>>,
+}
+
+impl Default for VpcFlowParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VpcFlowParser {
+    pub fn new() -> Self {
+        Self {
+            fields: Mutex::new(VPC_FLOW_FIELDS.iter().map(|f| f.to_string()).collect()),
+        }
+    }
+}
+
+impl LogParser for VpcFlowParser {
+    fn is_type(&self, line: &str) -> bool {
+        // 2 123456789010 eni-1235b8ca123456789 172.31.16.139 172.31.16.21 20641 22 6 20 4249 1418530010 1418530070 ACCEPT OK
+        let header_re = Regex::new(r"^(?:[a-z-]+ )*(?:srcaddr|interface-id)(?: [a-z-]+)*$").unwrap();
+        let data_re = Regex::new(r"^\S+(?: \S+){5,}$").unwrap();
+        header_re.is_match(line) || (data_re.is_match(line)
+            && line.split(' ').any(|f| f.starts_with("eni-"))
+            && line.split(' ').any(|f| matches!(f, "ACCEPT" | "REJECT" | "NODATA" | "SKIPDATA")))
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        if !line.split(' ').any(|f| f == "srcaddr" || f == "interface-id") {
+            return false;
+        }
+        *self.fields.lock().unwrap() = line.split_whitespace().map(|f| f.to_string()).collect();
+        true
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let fields = self.fields.lock().unwrap();
+        let values: HashMap<&str, &str> = fields.iter().map(|f| f.as_str()).zip(line.split_whitespace()).collect();
+        let get = |name: &str| values.get(name).copied().filter(|v| *v != "-").unwrap_or("");
+
+        let timestamp = get("start").parse::<f64>().ok()
+            .and_then(epoch_to_local)
+            .ok_or_else(|| anyhow!("VPC flow record without a start time"))?;
+
+        let mut attrs = BTreeMap::new();
+        for (name, value) in &values {
+            if *value == "-" {
+                continue;
+            }
+            let (name, value) = match *name {
+                "srcaddr" => ("src".to_string(), value.to_string()),
+                "dstaddr" => ("dst".to_string(), value.to_string()),
+                "srcport" => ("src_port".to_string(), value.to_string()),
+                "dstport" => ("dst_port".to_string(), value.to_string()),
+                "protocol" => ("proto".to_string(), protocol_name(value)),
+                "interface-id" => ("interface".to_string(), value.to_string()),
+                "action" => ("action".to_string(), value.to_lowercase()),
+                name => (name.replace('-', "_"), value.to_string()),
+            };
+            attrs.insert(name, value);
+        }
+
+        let interface = get("interface-id");
+        let status = get("log-status");
+        // NODATA and SKIPDATA records only say that nothing was captured in the window
+        let log_entry = if get("srcaddr").is_empty() {
+            format!("{} no flows captured", if status.is_empty() { "-" } else { status })
+        } else {
+            let proto = attrs.get("proto").map(|p| p.as_str()).unwrap_or("-");
+            let action = attrs.get("action").map(|a| a.as_str()).unwrap_or("-");
+            let endpoint = |addr: &str, port: &str| if port.is_empty() || port == "0" { addr.to_string() } else { format!("{}:{}", addr, port) };
+            let mut message = format!("{} {} {} -> {}", action, proto, endpoint(get("srcaddr"), get("srcport")), endpoint(get("dstaddr"), get("dstport")));
+            for count in ["packets", "bytes"] {
+                if !get(count).is_empty() {
+                    message.push_str(&format!(" {}={}", count, get(count)));
+                }
+            }
+            message
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: if interface.is_empty() { "localhost".to_string() } else { interface.to_string() },
+            daemon: "vpc-flow".to_string(),
+            log_entry,
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "VPC-Flow"
+    }
+}
+
+pub struct MysqlGeneralParser;
+
+impl LogParser for MysqlGeneralParser {
+    fn is_type(&self, line: &str) -> bool {
+        // MySQL general log: YYYY-MM-DDTHH:MM:SS.microsZ    thread_id command_type    query
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z\s+\d+\s+(Query|Connect|Quit|Init|Execute)").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        // Format: 2023-11-14T10:30:45.123456Z    42 Query     SELECT * FROM users
+        // Query text is optional (e.g., Quit command has no query)
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})\.\d+Z\s+(\d+)\s+(\w+)\s*(.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse MySQL general log"))?;
+
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+        let thread_id = caps.get(7).unwrap().as_str();
+        let command_type = caps.get(8).unwrap().as_str();
+        let query = caps.get(9).unwrap().as_str();
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: format!("thread_{}", thread_id),
+            daemon: command_type.to_string(),
+            log_entry: query.to_string(),
+            severity: None,
+            attrs: BTreeMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "MySQL-General"
+    }
+}
+
+pub struct MysqlSlowParser;
+
+impl LogParser for MysqlSlowParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Each query block starts with "# Time:", or with "# User@Host:" when the
+        // server skipped the repeated time (5.6 and earlier)
+        line.starts_with("# Time: ") || line.starts_with("# User@Host: ") || self.consume_header(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        // Written at startup and after every FLUSH LOGS:
+        // /usr/sbin/mysqld, Version: 8.0.35 (MySQL Community Server - GPL). started with:
+        // Tcp port: 3306  Unix socket: /var/run/mysqld/mysqld.sock
+        // Time                 Id Command    Argument
+        (line.contains(", Version: ") && line.ends_with("started with:"))
+            || line.starts_with("Tcp port: ")
+            || (line.starts_with("Time ") && line.ends_with(" Argument"))
+    }
+
+    fn is_continuation(&self, previous: &str, line: &str) -> bool {
+        if line.starts_with("# Time: ") {
+            return false;
+        }
+        !line.starts_with("# User@Host: ") || previous.starts_with("# Time: ")
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        // # Time: 2023-11-14T10:00:00.123456Z
+        // # User@Host: app[app] @ web1 [10.0.0.5]  Id:    42
+        // # Query_time: 2.345678  Lock_time: 0.000123 Rows_sent: 10  Rows_examined: 500000
+        // use shop;
+        // SET timestamp=1699956000;
+        // SELECT * FROM orders WHERE customer_id = 42;
+        let user_re = Regex::new(r"^# User@Host: (\S*?)\[[^\]]*\] @ (\S*) \[([^\]]*)\]").unwrap();
+        let metric_re = Regex::new(r"(\w+): (\S+)").unwrap();
+        let iso_re = Regex::new(r"^# Time: (\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2}))").unwrap();
+        let legacy_re = Regex::new(r"^# Time: (\d{2})(\d{2})(\d{2})\s+(\d{1,2}):(\d{2}):(\d{2})").unwrap();
+
+        let mut timestamp: Option<NaiveDateTime> = None;
+        let mut user = "-".to_string();
+        let mut client = "-".to_string();
+        let mut attrs = BTreeMap::new();
+        let mut query: Vec<&str> = Vec::new();
+
+        for line in record.lines() {
+            if let Some(caps) = iso_re.captures(line) {
+                timestamp = DateTime::parse_from_rfc3339(&caps[1].replace('Z', "+00:00")).ok()
+                    .map(|dt| dt.with_timezone(&Local).naive_local());
+            } else if let Some(caps) = legacy_re.captures(line) {
+                let parts: Vec<u32> = (1..=6).filter_map(|i| caps[i].parse().ok()).collect();
+                timestamp = NaiveDate::from_ymd_opt(2000 + parts[0] as i32, parts[1], parts[2])
+                    .and_then(|d| d.and_hms_opt(parts[3], parts[4], parts[5]));
+            } else if let Some(caps) = user_re.captures(line) {
+                user = caps[1].to_string();
+                // The client name is empty when skip-name-resolve is on
+                client = if caps[3].is_empty() { caps[2].to_string() } else { caps[3].to_string() };
+            } else if let Some(metrics) = line.strip_prefix("# ") {
+                // Query_time, Lock_time, Rows_sent, Rows_examined and any extended
+                // (Percona/MariaDB) metrics
+                for caps in metric_re.captures_iter(metrics) {
+                    attrs.insert(caps[1].to_lowercase(), caps[2].to_string());
+                }
+            } else if let Some(epoch) = line.strip_prefix("SET timestamp=") {
+                // The statement start; more precise than "# Time:", which is the end
+                if let Some(ts) = epoch.trim_end_matches(';').parse::<f64>().ok().and_then(epoch_to_local) {
+                    timestamp = Some(ts);
+                }
+            } else if let Some(database) = line.strip_prefix("use ") {
+                attrs.insert("database".to_string(), database.trim_end_matches(';').to_string());
+            } else {
+                query.push(line.trim());
+            }
+        }
+
+        let timestamp = timestamp.ok_or_else(|| anyhow!("Failed to parse MySQL slow log timestamp"))?;
+        let query = query.join(" ");
+        attrs.insert("user".to_string(), user.clone());
+        attrs.insert("fingerprint".to_string(), fingerprint_query(&query));
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: format!("{}@{}", user, client),
+            daemon: "slow_query".to_string(),
+            log_entry: query,
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "MySQL-Slow"
+    }
+}
+
+/// MySQL/MariaDB error log: "2023-11-14T10:00:01.123456Z 0 [ERROR] [MY-010119] [Server] Aborting"
+/// (8.0), or without the error code and subsystem (5.7)
+pub struct MysqlErrorParser;
+
+impl LogParser for MysqlErrorParser {
+    fn is_type(&self, line: &str) -> bool {
+        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2})? +\d+ \[(?:System|ERROR|Error|Warning|Note)\] ").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})[T ](\d{2}):(\d{2}):(\d{2})(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2})? +(\d+) \[(\w+)\](?: \[(MY-\d+)\])?(?: \[(\w+)\])? ?(.*)$").unwrap();
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse MySQL error log"))?;
+
+        let severity = match &caps[8] {
+            "ERROR" | "Error" => 3,
+            "Warning" => 4,
+            "System" => 5,
+            _ => 6,
+        };
+        let mut attrs = BTreeMap::new();
+        attrs.insert("thread".to_string(), caps[7].to_string());
+        if let Some(code) = caps.get(9) {
+            attrs.insert("error_code".to_string(), code.as_str().to_string());
+        }
+        if let Some(subsystem) = caps.get(10) {
+            attrs.insert("subsystem".to_string(), subsystem.as_str().to_string());
+        }
+
+        Ok(LogEntry {
+            year: caps[1].parse()?,
+            month: caps[2].parse()?,
+            day: caps[3].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host: "localhost".to_string(),
+            daemon: "mysqld".to_string(),
+            log_entry: caps[11].to_string(),
+            severity: Some(severity),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "MySQL-Error"
+    }
+}
+
+/// PostgreSQL stderr logs with any common `log_line_prefix`, and csvlog
+pub struct PostgresqlParser;
+
+const PG_LEVELS: &str = "LOG|ERROR|WARNING|FATAL|PANIC|DEBUG[1-5]?|INFO|NOTICE|STATEMENT|DETAIL|HINT|CONTEXT|QUERY|LOCATION";
+
+impl PostgresqlParser {
+    fn stderr_re() -> Regex {
+        // The prefix starts with %m or %t; what follows is auto-detected from the usual
+        // escapes: [%p], [%l-1], %c, %u@%d and user=%u,db=%d,app=%a,client=%h
+        Regex::new(&format!(
+            r"^(\d{{4}})-(\d{{2}})-(\d{{2}}) (\d{{2}}):(\d{{2}}):(\d{{2}})(?:\.\d+)?(?: (?:[A-Za-z][\w+:/-]*|[+-]\d{{2}}(?::?\d{{2}})?))?:?\s+((?:(?:\S*@\S*|\[[^\]]*\]:?|\w+=\S*|[0-9a-f]+\.[0-9a-f]+|\d+)\s+)*)({}):\s+(.*)$",
+            PG_LEVELS
+        )).unwrap()
+    }
+
+    fn csvlog_re() -> Regex {
+        // log_time,user_name,database_name,process_id,connection_from,session_id,session_line_num,...
+        Regex::new(r#"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?(?: \S+)?,(?:"[^"]*"|[^,"]*),(?:"[^"]*"|[^,"]*),\d+,(?:"[^"]*"|[^,"]*),[0-9a-f]+\.[0-9a-f]+,\d+,"#).unwrap()
+    }
+
+    fn severity(level: &str) -> Option<u8> {
+        match level {
+            "PANIC" => Some(0),
+            "FATAL" => Some(2),
+            "ERROR" => Some(3),
+            "WARNING" => Some(4),
+            "NOTICE" => Some(5),
+            l if l.starts_with("DEBUG") => Some(7),
+            _ => Some(6),
+        }
+    }
+
+    fn starts_with_timestamp(line: &str) -> bool {
+        let b = line.as_bytes();
+        b.len() >= 19 && b[4] == b'-' && b[7] == b'-' && b[10] == b' ' && b[13] == b':'
+            && b[..4].iter().all(u8::is_ascii_digit)
+    }
+
+    fn parse_timestamp(ts: &str) -> Result<(i32, u32, u32, u32, u32, u32)> {
+        // "2023-11-14 10:00:00.123 UTC"; the zone is ignored like in the stderr format
+        let ts = ts.get(..19).ok_or_else(|| anyhow!("Invalid PostgreSQL timestamp"))?;
+        let dt = NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S")?;
+        Ok((dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second()))
+    }
+
+    fn host(attrs: &BTreeMap<String, String>) -> String {
+        match (attrs.get("user"), attrs.get("database"), attrs.get("client")) {
+            (Some(user), Some(database), _) => format!("{}@{}", user, database),
+            (_, _, Some(client)) => client.clone(),
+            _ => "localhost".to_string(),
+        }
+    }
+
+    fn parse_csvlog(&self, record: &str) -> Result<LogEntry> {
+        let fields = split_csv(record, ',');
+        if fields.len() < 14 {
+            return Err(anyhow!("Failed to parse PostgreSQL csvlog"));
+        }
+        let (year, month, day, hour, minute, second) = Self::parse_timestamp(&fields[0])?;
+        let level = fields[11].as_str();
+
+        let mut attrs = BTreeMap::new();
+        let named = [
+            (1, "user"), (2, "database"), (3, "pid"), (4, "client"), (12, "sql_state"),
+            (14, "detail"), (15, "hint"), (18, "context"), (19, "query"), (22, "application"),
+            (23, "backend_type"),
+        ];
+        for (idx, name) in named {
+            if let Some(value) = fields.get(idx).filter(|v| !v.is_empty()) {
+                attrs.insert(name.to_string(), value.clone());
+            }
+        }
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: Self::host(&attrs),
+            daemon: level.to_string(),
+            log_entry: fields[13].split_whitespace().collect::<Vec<_>>().join(" "),
+            severity: Self::severity(level),
+            attrs,
+        })
+    }
+}
+
+impl LogParser for PostgresqlParser {
+    fn is_type(&self, line: &str) -> bool {
+        // stderr: YYYY-MM-DD HH:MM:SS.mmm TZ [pid] user@database LEVEL:  message
+        // csvlog: YYYY-MM-DD HH:MM:SS.mmm TZ,"user","database",pid,"host:port",session,line,...
+        Self::starts_with_timestamp(line) && (Self::stderr_re().is_match(line) || Self::csvlog_re().is_match(line))
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Multi-line statements (tab indented) and quoted newlines in csvlog fields
+        !Self::starts_with_timestamp(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        if Self::csvlog_re().is_match(record) {
+            return self.parse_csvlog(record);
+        }
+
+        // Format: 2023-11-14 10:30:45.123 UTC [12345] postgres@testdb LOG: message
+        let (first, rest) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::stderr_re().captures(first).ok_or_else(|| anyhow!("Failed to parse PostgreSQL log"))?;
+
+        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
+        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
+        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
+        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
+        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
+        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+        let prefix = caps.get(7).unwrap().as_str();
+        let level = caps.get(8).unwrap().as_str();
+        let mut message = caps.get(9).unwrap().as_str().to_string();
+        for line in rest.lines() {
+            message.push(' ');
+            message.push_str(line.trim());
+        }
+
+        let mut attrs = BTreeMap::new();
+        for token in prefix.split_whitespace() {
+            if token.contains('=') {
+                for (key, value) in token.split(',').filter_map(|kv| kv.split_once('=')) {
+                    let key = match key {
+                        "db" => "database",
+                        "app" => "application",
+                        "host" | "remote" => "client",
+                        other => other,
+                    };
+                    if !value.is_empty() {
+                        attrs.insert(key.to_string(), value.to_string());
+                    }
+                }
+            } else if let Some((user, database)) = token.split_once('@') {
+                attrs.insert("user".to_string(), user.to_string());
+                attrs.insert("database".to_string(), database.to_string());
+            } else if token.starts_with('[') && !attrs.contains_key("pid") {
+                // [%p] comes before [%l-1]
+                let pid = token.trim_start_matches('[').trim_end_matches(':').trim_end_matches(']');
+                if pid.chars().all(|c| c.is_ascii_digit()) {
+                    attrs.insert("pid".to_string(), pid.to_string());
+                }
+            }
+        }
+
+        Ok(LogEntry {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            host: Self::host(&attrs),
+            daemon: level.to_string(),
+            log_entry: message,
+            severity: Self::severity(level),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PostgreSQL"
+    }
+}
+
+/// MongoDB structured JSON logs (4.4+) and the older plain-text format
+pub struct MongoDbParser;
+
+impl MongoDbParser {
+    fn legacy_re() -> Regex {
+        // 2023-11-14T10:00:00.000+0000 I NETWORK  [conn1] end connection 10.0.0.5:54321 (1 connection now open)
+        Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2}))\s+([FEWID]\d?)\s+(\S+)\s+\[([^\]]+)\]\s?(.*)$").unwrap()
+    }
+
+    fn severity(code: &str) -> Option<u8> {
+        match code.chars().next() {
+            Some('F') => Some(2),
+            Some('E') => Some(3),
+            Some('W') => Some(4),
+            Some('I') => Some(6),
+            Some('D') => Some(7),
+            _ => None,
+        }
+    }
+
+    fn timestamp(ts: &str) -> Option<NaiveDateTime> {
+        DateTime::parse_from_rfc3339(ts).ok()
+            .or_else(|| DateTime::parse_from_str(&ts.replace('Z', "+0000"), "%Y-%m-%dT%H:%M:%S%.f%z").ok())
+            .map(|dt| dt.with_timezone(&Local).naive_local())
+    }
+
+    /// Client address without the port, from "remote":"10.0.0.5:54321"
+    fn remote_host(remote: &str) -> String {
+        match remote.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => {
+                host.trim_start_matches('[').trim_end_matches(']').to_string()
+            }
+            _ => remote.to_string(),
+        }
+    }
+
+    fn parse_json(&self, line: &str) -> Result<LogEntry> {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        let text = |value: &serde_json::Value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        let timestamp = json.pointer("/t/$date")
+            .and_then(|t| t.as_str())
+            .and_then(Self::timestamp)
+            .ok_or_else(|| anyhow!("MongoDB log entry without timestamp"))?;
+
+        let mut attrs = BTreeMap::new();
+        for key in ["id", "ctx"] {
+            if let Some(value) = json.get(key) {
+                attrs.insert(key.to_string(), text(value));
+            }
+        }
+        if let Some(fields) = json.get("attr").and_then(|a| a.as_object()) {
+            for (key, value) in fields {
+                attrs.insert(key.clone(), text(value));
+            }
+        }
+        if let Some(duration) = attrs.get("durationMillis").cloned() {
+            attrs.insert("duration_ms".to_string(), duration);
+        }
+
+        let host = attrs.get("remote").map(|r| Self::remote_host(r)).unwrap_or_else(|| "localhost".to_string());
+        let severity = json.get("s").and_then(|s| s.as_str()).and_then(Self::severity);
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: json.get("c").map(text).unwrap_or_else(|| "-".to_string()),
+            log_entry: json.get("msg").map(text).unwrap_or_default(),
+            severity,
+            attrs,
+        })
+    }
+}
+
+impl LogParser for MongoDbParser {
+    fn is_type(&self, line: &str) -> bool {
+        // {"t":{"$date":"2023-11-14T10:00:00.123+00:00"},"s":"I","c":"NETWORK","id":22943,"ctx":"listener","msg":"..."}
+        if line.starts_with("{\"t\":{\"$date\"") {
+            return true;
+        }
+        Self::legacy_re().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        if line.starts_with('{') {
+            return self.parse_json(line);
+        }
+
+        let caps = Self::legacy_re().captures(line).ok_or_else(|| anyhow!("Failed to parse MongoDB log"))?;
+        let timestamp = Self::timestamp(&caps[1]).ok_or_else(|| anyhow!("Invalid MongoDB timestamp"))?;
+        let message = caps[5].to_string();
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("ctx".to_string(), caps[4].to_string());
+        let remote_re = Regex::new(r"(?:connection accepted from|end connection) ([0-9a-fA-F.:\[\]]+:\d+)").unwrap();
+        let host = remote_re.captures(&message)
+            .map(|c| Self::remote_host(&c[1]))
+            .unwrap_or_else(|| "localhost".to_string());
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: caps[3].to_string(),
+            log_entry: message,
+            severity: Self::severity(&caps[2]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "MongoDB"
+    }
+}
+
+/// Ceph cluster log (ceph.log) and daemon logs (ceph-osd.N.log, ceph-mon.X.log, ...)
+pub struct CephParser;
+
+impl CephParser {
+    fn cluster_re() -> Regex {
+        // 2023-11-14T10:00:00.000000+0000 mon.a (mon.0) 1234 : cluster [WRN] Health check failed: ...
+        // 2023-11-14 10:00:00.000000 mon.a mon.0 10.0.0.1:6789/0 1234 : cluster [WRN] ... (before Octopus)
+        Regex::new(r"^(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{4})?) (\S+) (?:\(\S+\)|\S+ \S+) \d+ : (\w+) \[(DBG|INF|WRN|ERR|SEC)\] (.*)$").unwrap()
+    }
+
+    fn daemon_re() -> Regex {
+        // 2023-11-14T10:00:00.123+0000 7f8a1b2c3700 -1 osd.3 1234 heartbeat_check: no reply from ...
+        Regex::new(r"^(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{4})?) ([0-9a-f]{6,16}) +(-?\d+) (.*)$").unwrap()
+    }
+
+    fn timestamp(ts: &str) -> Option<NaiveDateTime> {
+        let ts = ts.replacen(' ', "T", 1).replace('Z', "+0000");
+        DateTime::parse_from_str(&ts, "%Y-%m-%dT%H:%M:%S%.f%z").ok()
+            .map(|dt| dt.with_timezone(&Local).naive_local())
+            .or_else(|| NaiveDateTime::parse_from_str(&ts, "%Y-%m-%dT%H:%M:%S%.f").ok())
+    }
+
+    fn health_severity(level: &str) -> Option<u8> {
+        match level {
+            "ERR" => Some(3),
+            "WRN" | "SEC" => Some(4),
+            "INF" => Some(6),
+            "DBG" => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Debug levels: -1 is an error, 0 is always logged, higher levels are debug output
+    fn debug_severity(level: i32) -> u8 {
+        match level {
+            i32::MIN..=-1 => 3,
+            0 => 5,
+            1..=4 => 6,
+            _ => 7,
+        }
+    }
+
+    /// Slow op counters from health checks and OSD warnings
+    fn slow_ops(message: &str, attrs: &mut BTreeMap<String, String>) {
+        // "5 slow ops, oldest one blocked for 64 sec, daemons [osd.1,osd.3] have slow ops. (SLOW_OPS)"
+        // "3 slow requests, 1 included below; oldest blocked for > 32.123456 secs"
+        let count_re = Regex::new(r"(\d+) slow (?:ops|requests)").unwrap();
+        let blocked_re = Regex::new(r"oldest (?:one )?blocked for >? ?([\d.]+) secs?").unwrap();
+        let daemons_re = Regex::new(r"(?:daemons \[([^\]]+)\] have|(\S+) has) slow ops").unwrap();
+
+        if let Some(caps) = count_re.captures(message) {
+            attrs.insert("slow_ops".to_string(), caps[1].to_string());
+            if let Some(blocked) = blocked_re.captures(message) {
+                attrs.insert("blocked_sec".to_string(), blocked[1].to_string());
+            }
+            if let Some(daemons) = daemons_re.captures(message) {
+                let daemons = daemons.get(1).or_else(|| daemons.get(2)).unwrap().as_str();
+                attrs.insert("slow_daemons".to_string(), daemons.to_string());
+            }
+        }
+    }
+}
+
+impl LogParser for CephParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::cluster_re().is_match(line) || Self::daemon_re().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut attrs = BTreeMap::new();
+        let health_check_re = Regex::new(r"\(([A-Z_]+)\)$").unwrap();
+
+        let (timestamp, host, daemon, message, severity) = if let Some(caps) = Self::cluster_re().captures(line) {
+            // Cluster log: the reporting daemon, the channel and the health level
+            attrs.insert("level".to_string(), caps[4].to_string());
+            (caps[1].to_string(), caps[2].to_string(), caps[3].to_string(), caps[5].to_string(), Self::health_severity(&caps[4]))
+        } else {
+            let caps = Self::daemon_re().captures(line).ok_or_else(|| anyhow!("Failed to parse Ceph log"))?;
+            let level: i32 = caps[3].parse()?;
+            let message = caps[4].to_string();
+            attrs.insert("thread".to_string(), caps[2].to_string());
+
+            let entity_re = Regex::new(r"^((?:mon|osd|mgr|mds|client)\.[\w-]+)").unwrap();
+            let channel_re = Regex::new(r"^log_channel\((\w+)\) log \[(\w+)\] : (.*)$").unwrap();
+            let subsystem_re = Regex::new(r"^([a-z][a-z_]*)[.(:]").unwrap();
+
+            if let Some(channel) = channel_re.captures(&message) {
+                // Cluster log messages as the daemon sent them
+                attrs.insert("level".to_string(), channel[2].to_string());
+                (caps[1].to_string(), "localhost".to_string(), channel[1].to_string(), channel[3].to_string(), Self::health_severity(&channel[2]))
+            } else {
+                let (host, daemon) = match entity_re.captures(&message) {
+                    Some(entity) => {
+                        let entity = entity[1].to_string();
+                        let kind = entity.split('.').next().unwrap_or("ceph").to_string();
+                        (entity, kind)
+                    }
+                    None => {
+                        let subsystem = subsystem_re.captures(&message)
+                            .map(|s| s[1].to_string())
+                            .unwrap_or_else(|| "ceph".to_string());
+                        ("localhost".to_string(), subsystem)
+                    }
+                };
+                (caps[1].to_string(), host, daemon, message, Some(Self::debug_severity(level)))
+            }
+        };
+
+        let timestamp = Self::timestamp(&timestamp).ok_or_else(|| anyhow!("Invalid Ceph timestamp"))?;
+        if let Some(check) = health_check_re.captures(&message) {
+            attrs.insert("health_check".to_string(), check[1].to_string());
+        }
+        Self::slow_ops(&message, &mut attrs);
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Ceph"
+    }
+}
+
+/// macOS unified log as printed by `log show --style syslog` or `--style default`
+pub struct MacUnifiedLogParser;
+
+impl MacUnifiedLogParser {
+    fn syslog_re() -> Regex {
+        // 2023-11-14 10:00:00.123456+0100  localhost sshd[1234]: (libpam.2.dylib) [com.apple.opendirectoryd:auth] message
+        Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?[+-]\d{4})\s+(\S+) (.+?)\[(\d+)\]: (.*)$").unwrap()
+    }
+
+    fn default_re() -> Regex {
+        // Timestamp                       Thread     Type        Activity             PID    TTL
+        // 2023-11-14 10:00:00.123456+0100 0x1a2b     Error       0x0                  1234   0    sshd: (libpam.2.dylib) [com.apple.xxx:auth] message
+        Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?[+-]\d{4})\s+(0x[0-9a-f]+)\s+(Default|Info|Debug|Error|Fault|Activity|Signpost|State|Timesync)\s+(0x[0-9a-f]+)\s+(\d+)\s+(\d+)\s+(.+?): (.*)$").unwrap()
+    }
+
+    fn severity(kind: &str) -> Option<u8> {
+        match kind {
+            "Fault" => Some(2),
+            "Error" => Some(3),
+            "Default" => Some(5),
+            "Info" => Some(6),
+            "Debug" => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Split "(sender) [subsystem:category] message" into attrs and the message
+    fn message(text: &str, attrs: &mut BTreeMap<String, String>) -> String {
+        let re = Regex::new(r"^(?:\(([^)]*)\) )?(?:\[([^\]:\s]+):([^\]]+)\] )?(.*)$").unwrap();
+        match re.captures(text) {
+            Some(caps) => {
+                if let Some(sender) = caps.get(1) {
+                    attrs.insert("sender".to_string(), sender.as_str().to_string());
+                }
+                if let (Some(subsystem), Some(category)) = (caps.get(2), caps.get(3)) {
+                    attrs.insert("subsystem".to_string(), subsystem.as_str().to_string());
+                    attrs.insert("category".to_string(), category.as_str().to_string());
+                }
+                caps[4].to_string()
+            }
+            None => text.to_string(),
+        }
+    }
+}
+
+impl LogParser for MacUnifiedLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::default_re().is_match(line) || Self::syslog_re().is_match(line) || self.consume_header(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        // Column headers, the filter description and the closing statistics
+        line.starts_with("Timestamp ")
+            || line.starts_with("Filtering the log data")
+            || line.starts_with("Skipping info and debug messages")
+            || (line.starts_with("---") && line.chars().all(|c| c == '-'))
+            || line.starts_with("Log      - ")
+            || line.starts_with("Activity - ")
+            || line.starts_with("Boundary - ")
+            || line.starts_with("Signpost - ")
+            || line.starts_with("Ttl      - ")
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Messages may span lines; every record starts with the date
+        let b = line.as_bytes();
+        !(b.len() >= 19 && b[4] == b'-' && b[7] == b'-' && b[..4].iter().all(u8::is_ascii_digit))
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, rest) = record.split_once('\n').unwrap_or((record, ""));
+        let mut attrs = BTreeMap::new();
+
+        let (timestamp, host, process, pid, text, severity) = if let Some(caps) = Self::default_re().captures(first) {
+            attrs.insert("thread".to_string(), caps[2].to_string());
+            attrs.insert("type".to_string(), caps[3].to_string());
+            (caps[1].to_string(), "localhost".to_string(), caps[7].to_string(), caps[5].to_string(), caps[8].to_string(), Self::severity(&caps[3]))
+        } else {
+            let caps = Self::syslog_re().captures(first).ok_or_else(|| anyhow!("Failed to parse macOS unified log"))?;
+            (caps[1].to_string(), caps[2].to_string(), caps[3].to_string(), caps[4].to_string(), caps[5].to_string(), None)
+        };
+
+        let timestamp = DateTime::parse_from_str(&timestamp, "%Y-%m-%d %H:%M:%S%.f%z")
+            .map(|dt| dt.with_timezone(&Local).naive_local())?;
+        attrs.insert("pid".to_string(), pid.clone());
+        let mut log_entry = Self::message(&text, &mut attrs);
+        for line in rest.lines() {
+            log_entry.push(' ');
+            log_entry.push_str(line.trim());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: format!("{}[{}]", process, pid),
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "macOS"
+    }
+}
+
+/// Android `logcat -v threadtime` (optionally with `-v year`)
+pub struct LogcatParser;
+
+impl LogcatParser {
+    fn line_re() -> Regex {
+        // 11-14 10:00:00.123  1234  5678 E ActivityManager: ANR in com.example.app
+        Regex::new(r"^(?:(\d{4})-)?(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})\.\d+\s+(\d+)\s+(\d+) ([VDIWEFA]) (.*?)\s*: (.*)$").unwrap()
+    }
+
+    fn severity(priority: &str) -> Option<u8> {
+        match priority {
+            "F" | "A" => Some(2),
+            "E" => Some(3),
+            "W" => Some(4),
+            "I" => Some(6),
+            "D" | "V" => Some(7),
+            _ => None,
+        }
+    }
+}
+
+impl LogParser for LogcatParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line) || self.consume_header(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        // "--------- beginning of main", "--------- switch to crash"
+        line.starts_with("--------- beginning of ") || line.starts_with("--------- switch to ")
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::line_re().captures(line).ok_or_else(|| anyhow!("Failed to parse logcat line"))?;
+
+        // threadtime has no year
+        let year = match caps.get(1) {
+            Some(year) => year.as_str().parse()?,
+            None => Local::now().year(),
+        };
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("pid".to_string(), caps[7].to_string());
+        attrs.insert("tid".to_string(), caps[8].to_string());
+        attrs.insert("priority".to_string(), caps[9].to_string());
+
+        Ok(LogEntry {
+            year,
+            month: caps[2].parse()?,
+            day: caps[3].parse()?,
+            hour: caps[4].parse()?,
+            minute: caps[5].parse()?,
+            second: caps[6].parse()?,
+            host: "localhost".to_string(),
+            daemon: caps[10].to_string(),
+            log_entry: caps[11].to_string(),
+            severity: Self::severity(&caps[9]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "logcat"
+    }
+}
+
+/// Column names of a W3C extended log, taken from its "#Fields:" header
+struct W3cFields {
+    fields: Mutex<Vec<String>>,
+    defaults: &'static [&'static str],
+}
+
+impl W3cFields {
+    fn new(defaults: &'static [&'static str]) -> Self {
+        Self {
+            fields: Mutex::new(Vec::new()),
+            defaults,
+        }
+    }
+
+    /// "#Version:", "#Software:", "#Date:" and "#Fields:" lines, and the blank
+    /// line pfirewall.log has after them
+    fn consume(&self, line: &str) -> bool {
+        let rest = match line.strip_prefix('#') {
+            Some(rest) => rest,
+            None => return line.trim().is_empty(),
+        };
+        if let Some(fields) = rest.strip_prefix("Fields:") {
+            *self.fields.lock().unwrap() = fields.split_whitespace().map(|f| f.to_string()).collect();
+        }
+        true
+    }
+
+    /// Values by column name; "-" marks an empty value
+    fn record(&self, line: &str) -> BTreeMap<String, String> {
+        let fields = self.fields.lock().unwrap();
+        let names: Vec<&str> = if fields.is_empty() {
+            self.defaults.to_vec()
+        } else {
+            fields.iter().map(|f| f.as_str()).collect()
+        };
+        names.into_iter()
+            .zip(line.split_whitespace())
+            .filter(|(_, value)| *value != "-")
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+/// Windows Defender Firewall log (pfirewall.log)
+pub struct WindowsFirewallParser {
+    w3c: W3cFields,
+}
+
+impl Default for WindowsFirewallParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowsFirewallParser {
+    pub fn new() -> Self {
+        Self {
+            w3c: W3cFields::new(&[
+                "date", "time", "action", "protocol", "src-ip", "dst-ip", "src-port", "dst-port", "size",
+                "tcpflags", "tcpsyn", "tcpack", "tcpwin", "icmptype", "icmpcode", "info", "path", "pid",
+            ]),
+        }
+    }
+}
+
+impl LogParser for WindowsFirewallParser {
+    fn is_type(&self, line: &str) -> bool {
+        // 2023-11-14 10:00:00 DROP TCP 10.0.0.5 10.0.0.1 54321 3389 52 S 123456 0 8192 - - - RECEIVE 4
+        let data_re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} (?:ALLOW|DROP|OPEN|CLOSE|OPEN-INBOUND|INFO-EVENTS-LOST) ").unwrap();
+        line.starts_with("#Software: Microsoft Windows Firewall")
+            || line.starts_with("#Fields: date time action protocol")
+            || line.starts_with("#Time Format: ")
+            || line == "#Version: 1.5"
+            || data_re.is_match(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        self.w3c.consume(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut attrs = self.w3c.record(line);
+
+        // "#Time Format: Local"
+        let date = attrs.get("date").ok_or_else(|| anyhow!("Windows Firewall line without date"))?;
+        let time = attrs.get("time").ok_or_else(|| anyhow!("Windows Firewall line without time"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S")?;
+
+        // Same keys as the other firewall parsers
+        for (from, to) in [("src-ip", "src"), ("dst-ip", "dst"), ("src-port", "src_port"), ("dst-port", "dst_port")] {
+            if let Some(value) = attrs.remove(from) {
+                attrs.insert(to.to_string(), value);
+            }
+        }
+        if let Some(action) = attrs.get("action").map(|a| a.to_lowercase()) {
+            attrs.insert("action".to_string(), action);
+        }
+        if let Some(proto) = attrs.remove("protocol") {
+            attrs.insert("proto".to_string(), proto.to_lowercase());
+        }
+
+        let get = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("-");
+        let direction = match get("path") {
+            "RECEIVE" => "in",
+            "SEND" => "out",
+            other => other,
+        };
+        // ICMP has no ports
+        let endpoint = |ip: &str, port: &str| match attrs.get(port) {
+            Some(port) => format!("{}:{}", get(ip), port),
+            None => get(ip).to_string(),
+        };
+        let log_entry = format!("{} {} {} {} -> {}",
+            get("action"), direction, get("proto"), endpoint("src", "src_port"), endpoint("dst", "dst_port"));
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: get("src").to_string(),
+            daemon: "pfirewall".to_string(),
+            log_entry,
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "WindowsFirewall"
+    }
+}
+
+/// IIS FTP service logs (W3C extended format)
+pub struct IisFtpParser {
+    w3c: W3cFields,
+}
+
+impl Default for IisFtpParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IisFtpParser {
+    pub fn new() -> Self {
+        Self {
+            w3c: W3cFields::new(&[
+                "date", "time", "c-ip", "c-port", "cs-username", "s-ip", "s-port", "cs-method", "cs-uri-stem",
+                "sc-status", "sc-win32-status", "sc-substatus", "x-session", "x-fullpath",
+            ]),
+        }
+    }
+}
+
+impl LogParser for IisFtpParser {
+    fn is_type(&self, line: &str) -> bool {
+        // 2023-11-14 10:00:01 10.0.0.5 54321 bob 10.0.0.1 21 PASS *** 530 1326 41 7d1f2c3a-... -
+        let data_re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} [0-9a-fA-F.:]+ \d+ \S+ [0-9a-fA-F.:]+ \d+ [A-Za-z-]+ \S+ (?:\d{3}|-) (?:\d+|-) (?:\d+|-) [0-9a-f-]+ \S+$").unwrap();
+        (line.starts_with("#Fields: ") && line.contains(" x-session"))
+            || line.starts_with("#Software: Microsoft Internet Information Services")
+            || line.starts_with("#Version: 1.0")
+            || line.starts_with("#Date: ")
+            || data_re.is_match(line)
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        self.w3c.consume(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut attrs = self.w3c.record(line);
+
+        // IIS logs in UTC
+        let date = attrs.get("date").ok_or_else(|| anyhow!("IIS FTP line without date"))?;
+        let time = attrs.get("time").ok_or_else(|| anyhow!("IIS FTP line without time"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S")?
+            .and_utc()
+            .with_timezone(&Local)
+            .naive_local();
+
+        for (from, to) in [
+            ("c-ip", "src"), ("s-ip", "dst"), ("s-port", "dst_port"), ("cs-username", "user"),
+            ("cs-method", "method"), ("cs-uri-stem", "path"), ("sc-status", "status"), ("x-session", "session"),
+        ] {
+            if let Some(value) = attrs.remove(from) {
+                attrs.insert(to.to_string(), value);
+            }
+        }
+
+        let get = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("-");
+        let log_entry = format!("{} {} {} {}", get("method"), get("path"), get("status"), get("sc-win32-status"));
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: get("src").to_string(),
+            daemon: get("method").to_string(),
+            log_entry,
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "IIS-FTP"
+    }
+}
+
+/// Timestamp prefix shared by ESXi and vCenter logs, plus the ESXi 7+ syslog-style
+/// "In(166) Hostd[2099876]:" tag whose number is the syslog priority
+const VMWARE_TS: &str = r"(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2})?)";
+
+fn vmware_timestamp(ts: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(&ts.replace('Z', "+00:00")).ok()
+        .map(|dt| dt.with_timezone(&Local).naive_local())
+        .or_else(|| NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f").ok())
+}
+
+/// ESXi vmkernel.log, vmkwarning.log and vobd.log:
+/// "2023-11-14T10:00:00.123Z cpu1:12345)WARNING: NMP: nmp_DeviceRequestFastDeviceProbe:237: ..."
+pub struct VmkernelParser;
+
+impl VmkernelParser {
+    fn line_re() -> Regex {
+        Regex::new(&format!(r"^{} (?:\w{{2}}\((\d+)\) \S+?(?:\[\d+\])?: )?cpu(\d+):(\d+)(?: opID=(\S+))?\)(.*)$", VMWARE_TS)).unwrap()
+    }
+}
+
+impl LogParser for VmkernelParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::line_re().captures(line).ok_or_else(|| anyhow!("Failed to parse vmkernel log"))?;
+        let timestamp = vmware_timestamp(&caps[1]).ok_or_else(|| anyhow!("Invalid vmkernel timestamp"))?;
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("cpu".to_string(), caps[3].to_string());
+        attrs.insert("world".to_string(), caps[4].to_string());
+        if let Some(op_id) = caps.get(5) {
+            attrs.insert("opID".to_string(), op_id.as_str().to_string());
+        }
+
+        // "WARNING: NMP: ..." / "ALERT: ..."; otherwise the priority of the 7.x tag
+        let mut message = caps[6].trim();
+        let mut severity = caps.get(2).and_then(|p| p.as_str().parse::<u8>().ok()).map(|p| p & 7);
+        for (prefix, level) in [("WARNING: ", 4), ("ALERT: ", 1)] {
+            if let Some(rest) = message.strip_prefix(prefix) {
+                message = rest;
+                severity = Some(level);
+            }
+        }
+
+        // The vmkernel module ("NMP", "ScsiDeviceIO", "HBX") leads the message
+        let module_re = Regex::new(r"^([A-Za-z][\w.-]*): (.*)$").unwrap();
+        let (daemon, message) = match module_re.captures(message) {
+            Some(m) => (m[1].to_string(), m.get(2).unwrap().as_str()),
+            None => ("vmkernel".to_string(), message),
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: message.to_string(),
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "vmkernel"
+    }
+}
+
+/// ESXi hostd/vpxa and vCenter vpxd logs:
+/// "2023-11-14T10:00:00.123Z info vpxd[12345] [Originator@6876 sub=vpxLro opID=lro-123] [VpxLRO] -- BEGIN task-123"
+pub struct VmwareServiceParser;
+
+impl VmwareServiceParser {
+    fn line_re() -> Regex {
+        Regex::new(&format!(
+            r"^{} (?:(verbose|trivia|info|warning|error|panic)|\w{{2}}\((\d+)\)) ([\w.-]+)\[(\d+)\]:? (?:\[Originator@\d+ ([^\]]*)\] ?)?(.*)$",
+            VMWARE_TS
+        )).unwrap()
+    }
+
+    fn severity(level: &str) -> Option<u8> {
+        match level {
+            "panic" => Some(0),
+            "error" => Some(3),
+            "warning" => Some(4),
+            "info" => Some(6),
+            "verbose" | "trivia" => Some(7),
+            _ => None,
+        }
+    }
+}
+
+impl LogParser for VmwareServiceParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::line_re().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Multi-line messages continue with "--> "
+        line.starts_with("-->") || line.starts_with([' ', '\t'])
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, rest) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::line_re().captures(first).ok_or_else(|| anyhow!("Failed to parse VMware log"))?;
+        let timestamp = vmware_timestamp(&caps[1]).ok_or_else(|| anyhow!("Invalid VMware timestamp"))?;
+
+        let severity = match (caps.get(2), caps.get(3)) {
+            (Some(level), _) => Self::severity(level.as_str()),
+            (None, Some(priority)) => priority.as_str().parse::<u8>().ok().map(|p| p & 7),
+            _ => None,
+        };
+
+        // "sub=Vimsvc.TaskManager opID=abc-12 user=vpxuser"
+        let mut attrs = caps.get(6).map(|o| parse_key_values(o.as_str())).unwrap_or_default();
+        attrs.insert("pid".to_string(), caps[5].to_string());
+
+        let mut log_entry = caps[7].trim().to_string();
+        for line in rest.lines() {
+            log_entry.push(' ');
+            log_entry.push_str(line.trim_start_matches("-->").trim());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: caps[4].to_lowercase(),
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "VMware"
+    }
+}
+
+/// Proxmox VE task index (/var/log/pve/tasks/index):
+/// "UPID:pve1:00001A2B:0012D687:6553A0E0:qmstart:100:root@pam: 6553A0E5 OK"
+pub struct PveTaskIndexParser;
+
+impl LogParser for PveTaskIndexParser {
+    fn is_type(&self, line: &str) -> bool {
+        let re = Regex::new(r"^UPID:[^:\s]+:[0-9A-Fa-f]{8}:[0-9A-Fa-f]{8,9}:[0-9A-Fa-f]{8}:[^:\s]+:[^:\s]*:[^:\s]+:\s+[0-9A-Fa-f]{8}\s").unwrap();
+        re.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let (upid, end, status) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(upid), Some(end), Some(status)) => (upid, end, status.trim()),
+            _ => return Err(anyhow!("Failed to parse Proxmox task index line")),
+        };
+        let task = Upid::parse(upid).ok_or_else(|| anyhow!("Invalid UPID"))?;
+        let end = i64::from_str_radix(end, 16)?;
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("upid".to_string(), upid.to_string());
+        attrs.insert("user".to_string(), task.user.clone());
+        attrs.insert("end".to_string(), end.to_string());
+        attrs.insert("status".to_string(), status.to_string());
+        if let Some(start) = task.start.and_local_timezone(Local).single() {
+            attrs.insert("duration".to_string(), (end - start.timestamp()).to_string());
+        }
+
+        Ok(LogEntry {
+            year: task.start.year(),
+            month: task.start.month(),
+            day: task.start.day(),
+            hour: task.start.hour(),
+            minute: task.start.minute(),
+            second: task.start.second(),
+            host: task.node.clone(),
+            daemon: task.task_type.clone(),
+            log_entry: format!("{} {} by {}: {}", task.task_type, task.id, task.user, status),
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PVE-Tasks"
+    }
+}
+
+/// wu-ftpd xferlog format, also written by vsftpd and ProFTPD:
+/// 'Tue Nov 14 10:00:00 2023 2 192.0.2.10 1048576 /pub/file.iso b _ o r alice ftp 0 * c'
+pub struct XferlogParser;
+
+impl XferlogParser {
+    fn regex() -> Regex {
+        // Filenames may contain spaces, so everything between the size and the
+        // fixed trailing fields is the filename
+        Regex::new(r"^\w{3} (\w{3}\s+\d{1,2} \d{2}:\d{2}:\d{2} \d{4}) (\d+) (\S+) (\d+) (.+) ([ab]) (\S+) ([oid]) ([agr]) (\S+) (\S+) ([01]) (\S+) ([ci])$").unwrap()
+    }
+}
+
+impl LogParser for XferlogParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse xferlog line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1].split_whitespace().collect::<Vec<_>>().join(" "), "%b %d %H:%M:%S %Y")?;
+
+        let direction = match &caps[8] {
+            "o" => "download",
+            "i" => "upload",
+            _ => "delete",
+        };
+        let mode = match &caps[9] {
+            "a" => "anonymous",
+            "g" => "guest",
+            _ => "real",
+        };
+        let status = if &caps[14] == "c" { "complete" } else { "incomplete" };
+        let client = caps[3].trim_start_matches("::ffff:").to_string();
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("client".to_string(), client.clone());
+        attrs.insert("user".to_string(), caps[10].to_string());
+        attrs.insert("file".to_string(), caps[5].to_string());
+        attrs.insert("bytes".to_string(), caps[4].to_string());
+        attrs.insert("duration".to_string(), caps[2].to_string());
+        attrs.insert("direction".to_string(), direction.to_string());
+        attrs.insert("mode".to_string(), mode.to_string());
+        attrs.insert("type".to_string(), if &caps[6] == "a" { "ascii" } else { "binary" }.to_string());
+        attrs.insert("status".to_string(), status.to_string());
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: client,
+            daemon: direction.to_string(),
+            log_entry: format!("{} {} {} bytes in {}s user={} {}", direction, &caps[5], &caps[4], &caps[2], &caps[10], status),
+            severity: None,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "xferlog"
+    }
+}
+
+/// Samba vfs_full_audit via syslog, with the default "%u|%I" prefix or a longer
+/// one such as "%u|%I|%S": 'Nov 14 10:00:00 fs01 smbd_audit: alice|192.0.2.10|projects|openat|ok|r|report.docx'
+pub struct SambaAuditParser;
+
+impl SambaAuditParser {
+    fn regex() -> Regex {
+        Regex::new(r"^(\w{3})\s+(\d{1,2}) (\d{2}):(\d{2}):(\d{2}) (?:(\S+) )?smbd_audit(?:\[\d+\])?: (.*)$").unwrap()
+    }
+}
+
+impl LogParser for SambaAuditParser {
+    fn is_type(&self, line: &str) -> bool {
+        line.contains("smbd_audit") && Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse smbd_audit line"))?;
+        let month = parse_month(&caps[1]).ok_or_else(|| anyhow!("Invalid month"))?;
+        let message = caps[7].to_string();
+
+        // The prefix length is configurable, so the operation is found by the
+        // "ok"/"fail (reason)" field that always follows it
+        let fields: Vec<&str> = message.split('|').collect();
+        let mut attrs = BTreeMap::new();
+        let mut severity = None;
+        if let Some(idx) = (2..fields.len()).find(|&i| fields[i] == "ok" || fields[i].starts_with("fail")) {
+            for (key, value) in ["user", "ip", "share"].iter().zip(&fields[..idx - 1]) {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+            attrs.insert("operation".to_string(), fields[idx - 1].to_string());
+            let (status, reason) = match fields[idx].split_once(' ') {
+                Some((status, reason)) => (status, Some(reason.trim_matches(|c| c == '(' || c == ')'))),
+                None => (fields[idx], None),
+            };
+            attrs.insert("status".to_string(), status.to_string());
+            if let Some(reason) = reason {
+                attrs.insert("reason".to_string(), reason.to_string());
+            }
+            if status != "ok" {
+                severity = Some(4);
+            }
+            // openat logs "r|path", renameat "from|to": the path is the last argument
+            if let Some(path) = fields[idx + 1..].last() {
+                attrs.insert("path".to_string(), path.to_string());
+            }
+            if fields.len() > idx + 2 {
+                attrs.insert("args".to_string(), fields[idx + 1..].join("|"));
+            }
+        }
+
+        Ok(LogEntry {
+            year: Local::now().year(),
+            month,
+            day: caps[2].parse()?,
+            hour: caps[3].parse()?,
+            minute: caps[4].parse()?,
+            second: caps[5].parse()?,
+            host: caps.get(6).map(|h| h.as_str()).unwrap_or("localhost").to_string(),
+            daemon: "smbd_audit".to_string(),
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "SambaAudit"
+    }
+}
+
+/// CUPS access_log: Common Log Format followed by the IPP operation and status
+/// 'localhost - alice [14/Nov/2023:10:00:00 +0100] "POST /printers/Office HTTP/1.1" 200 1234 Print-Job successful-ok'
+pub struct CupsAccessParser;
+
+impl CupsAccessParser {
+    fn regex() -> Regex {
+        Regex::new(r#"^(\S+) \S+ (\S+) \[(\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2}) [+-]\d{4}\] "(\S+) (\S+) [^"]*" (\d{3}) (\d+|-) ([A-Za-z][A-Za-z-]*|-) ([a-z][a-z-]*|-)$"#).unwrap()
+    }
+}
+
+/// "/printers/Office" or "/classes/Floor2" -> the queue name
+fn cups_queue(path: &str) -> Option<&str> {
+    let path = path.split('?').next().unwrap_or(path);
+    path.strip_prefix("/printers/")
+        .or_else(|| path.strip_prefix("/classes/"))
+        .filter(|queue| !queue.is_empty())
+}
+
+impl LogParser for CupsAccessParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse CUPS access_log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[3], "%d/%b/%Y:%H:%M:%S")?;
+        let path = &caps[5];
+
+        let mut attrs = BTreeMap::new();
+        for (key, idx) in [("user", 2), ("method", 4), ("path", 5), ("status", 6), ("bytes", 7), ("operation", 8), ("ipp_status", 9)] {
+            if &caps[idx] != "-" {
+                attrs.insert(key.to_string(), caps[idx].to_string());
+            }
+        }
+        let queue = cups_queue(path);
+        if let Some(queue) = queue {
+            attrs.insert("printer".to_string(), queue.to_string());
+        }
+
+        let status: u16 = caps[6].parse()?;
+        let ipp_error = caps[9].starts_with("client-error") || caps[9].starts_with("server-error");
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: caps[1].to_string(),
+            daemon: queue.map(|q| format!("cups/{}", q)).unwrap_or_else(|| "cupsd".to_string()),
+            log_entry: format!("{} {} {} {} {}", &caps[4], path, &caps[6], &caps[8], &caps[9]),
+            severity: if status >= 500 { Some(3) } else if status >= 400 || ipp_error { Some(4) } else { None },
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "CUPS-Access"
+    }
+}
+
+/// CUPS error_log: 'I [14/Nov/2023:10:00:00 +0100] [Job 42] Queued on "Office" by "alice".'
+/// Only the "Queued on" line names the printer, so the parser remembers each
+/// job's printer to group the job's later state changes under it
+pub struct CupsErrorParser {
+    jobs: Mutex<HashMap<String, String>>,
+}
+
+impl Default for CupsErrorParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CupsErrorParser {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn regex() -> Regex {
+        Regex::new(r"^([EWNIDdACX]) \[(\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2})(?:\.\d+)? [+-]\d{4}\] (.*)$").unwrap()
+    }
+}
+
+impl LogParser for CupsErrorParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse CUPS error_log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[2], "%d/%b/%Y:%H:%M:%S")?;
+        let message = caps[3].to_string();
+        let severity = match &caps[1] {
+            "X" => 0,
+            "A" => 1,
+            "C" => 2,
+            "E" => 3,
+            "W" => 4,
+            "N" => 5,
+            "I" => 6,
+            _ => 7,
+        };
+
+        let job_re = Regex::new(r"^\[Job (\d+)\] (.*)$").unwrap();
+        let queued_re = Regex::new(r#"Queued on "([^"]+)" by "([^"]*)""#).unwrap();
+        let state_re = Regex::new(r"(?i)\b(queued|completed|stopped|canceled|cancelled|held|aborted|released|restarted)\b").unwrap();
+
+        let mut attrs = BTreeMap::new();
+        let mut daemon = "cupsd".to_string();
+        if let Some(job) = job_re.captures(&message) {
+            let id = job[1].to_string();
+            let mut jobs = self.jobs.lock().unwrap();
+            if let Some(queued) = queued_re.captures(&job[2]) {
+                jobs.insert(id.clone(), queued[1].to_string());
+                attrs.insert("user".to_string(), queued[2].to_string());
+            }
+            if let Some(state) = state_re.captures(&job[2]) {
+                let state = state[1].to_lowercase().replace("cancelled", "canceled");
+                attrs.insert("state".to_string(), state);
+            }
+            daemon = "cups".to_string();
+            if let Some(printer) = jobs.get(&id) {
+                attrs.insert("printer".to_string(), printer.clone());
+                daemon = format!("cups/{}", printer);
+            }
+            attrs.insert("job".to_string(), id);
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: message,
+            severity: Some(severity),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "CUPS-Error"
+    }
+}
+
+/// Asterisk full log: '[Nov 14 10:00:00] VERBOSE[1234][C-0000abcd] pbx.c: Executing [100@from-internal:1] ...'
+/// (newer versions log '[2023-11-14 10:00:00.123]'); the call ID bracket is only
+/// present on lines that belong to a call
+pub struct AsteriskParser;
+
+impl AsteriskParser {
+    fn regex() -> Regex {
+        Regex::new(r"^\[(\w{3}\s+\d{1,2} \d{2}:\d{2}:\d{2}|\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})(?:\.\d+)?\] ([A-Z]+)\[(\d+)\](?:\[(C-[0-9a-fA-F]+)\])? ([\w.-]+?)(?::\d+ \w+)?: ?(.*)$").unwrap()
+    }
+}
+
+impl LogParser for AsteriskParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse Asterisk log line"))?;
+        let timestamp = match NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%d %H:%M:%S") {
+            Ok(ts) => ts,
+            Err(_) => {
+                let ts = format!("{} {}", Local::now().year(), caps[1].split_whitespace().collect::<Vec<_>>().join(" "));
+                NaiveDateTime::parse_from_str(&ts, "%Y %b %d %H:%M:%S")?
+            }
+        };
+
+        let level = &caps[2];
+        let mut attrs = BTreeMap::new();
+        attrs.insert("level".to_string(), level.to_string());
+        attrs.insert("lwp".to_string(), caps[3].to_string());
+        if let Some(call_id) = caps.get(4) {
+            attrs.insert("call_id".to_string(), call_id.as_str().to_string());
+        }
+
+        // "chan_sip.c" -> "chan_sip"
+        let daemon = caps[5].trim_end_matches(".c").to_string();
+        // VERBOSE messages are indented with "--" and "==" markers
+        let message = caps[6].trim_start_matches([' ', '-', '=']).trim_end().to_string();
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: message,
+            severity: severity_from_name(level).or(match level {
+                "SECURITY" => Some(5),
+                "VERBOSE" | "DTMF" => Some(6),
+                _ => None,
+            }),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Asterisk"
+    }
+}
+
+/// CI console logs with a timestamp on every line: Jenkins with the Timestamper
+/// plugin ('[2023-11-14T10:00:00.123Z] [Pipeline] stage') and GitHub Actions
+/// raw logs ('2023-11-14T10:00:00.1234567Z ##[group]Run make'). Lines are put
+/// under the pipeline stage or step group they were logged in
+pub struct BuildLogParser {
+    state: Mutex<BuildState>,
+}
+
+#[derive(Default)]
+struct BuildState {
+    // Jenkins nests unnamed blocks ("[Pipeline] {") between stages, kept as ""
+    stages: Vec<String>,
+    node: Option<String>,
+}
+
+impl Default for BuildLogParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildLogParser {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(BuildState::default()),
+        }
+    }
+
+    fn regex() -> Regex {
+        Regex::new(r"^(?:\[(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2}))\] ?|(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{7}Z) )(.*)$").unwrap()
+    }
+}
+
+impl LogParser for BuildLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Envoy access logs share the bracketed ISO timestamp
+        Self::regex().is_match(line) && !EnvoyParser.is_type(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse build log line"))?;
+        let ts = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+        let timestamp = DateTime::parse_from_rfc3339(ts)
+            .or_else(|_| DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f%z"))
+            .map(|dt| dt.with_timezone(&Local).naive_local())?;
+        let message = caps[3].trim_end().to_string();
+
+        let stage_re = Regex::new(r"^(?:\[Pipeline\] \{ \((.+)\)|##\[group\](.+))$").unwrap();
+        let node_re = Regex::new(r"^Running on (\S+) in ").unwrap();
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(stage) = stage_re.captures(&message) {
+            state.stages.push(stage.get(1).or_else(|| stage.get(2)).unwrap().as_str().to_string());
+        } else if message == "[Pipeline] {" {
+            state.stages.push(String::new());
+        }
+        if let Some(node) = node_re.captures(&message) {
+            state.node = Some(node[1].to_string());
+        }
+        let stage = state.stages.iter().rev().find(|s| !s.is_empty()).cloned();
+        let host = state.node.clone().unwrap_or_else(|| "localhost".to_string());
+        // The closing line still belongs to the stage it closes
+        if message == "[Pipeline] }" || message == "##[endgroup]" {
+            state.stages.pop();
+        }
+        drop(state);
+
+        let mut attrs = BTreeMap::new();
+        if let Some(stage) = &stage {
+            attrs.insert("stage".to_string(), stage.clone());
+        }
+        let severity = if message.starts_with("##[error]") || message.starts_with("ERROR") || message.starts_with("FATAL")
+            || message.starts_with("BUILD FAILED") || message.starts_with("Finished: FAILURE") {
+            Some(3)
+        } else if message.starts_with("##[warning]") || message.starts_with("WARNING") || message.starts_with("Finished: UNSTABLE") {
+            Some(4)
+        } else {
+            None
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon: stage.unwrap_or_else(|| "build".to_string()),
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "BuildLog"
+    }
+}
+
+/// Job event of a scheduler log line: (event, job id, user, partition, nodes, exit code)
+type JobEvent<'a> = (&'static str, &'a str, Option<&'a str>, Option<&'a str>, Option<&'a str>, Option<&'a str>);
+
+/// Store a scheduler job event as the job/event/user/partition/nodes/exit fields
+fn insert_job_event(attrs: &mut BTreeMap<String, String>, event: JobEvent) {
+    let (event, job, user, partition, nodes, exit) = event;
+    attrs.insert("event".to_string(), event.to_string());
+    attrs.insert("job".to_string(), job.to_string());
+    for (key, value) in [("user", user), ("partition", partition), ("nodes", nodes), ("exit", exit)] {
+        if let Some(value) = value {
+            attrs.insert(key.to_string(), value.to_string());
+        }
+    }
+}
+
+/// slurmctld/slurmd log: '[2023-11-14T10:00:00.123] sched: Allocate JobId=1234 NodeList=node01 #CPUs=4 Partition=batch'
+pub struct SlurmParser;
+
+impl SlurmParser {
+    fn regex() -> Regex {
+        Regex::new(r"^\[(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2})(?:\.\d+)?([+-]\d{2}:?\d{2})?\] (.*)$").unwrap()
+    }
+
+    fn job_event(message: &str) -> Option<JobEvent<'_>> {
+        let submit_re = Regex::new(r"_slurm_rpc_(?:submit_batch_job|allocate_resources):? JobId=(\d+)").unwrap();
+        let alloc_re = Regex::new(r"sched(?:/backfill)?: Allocate JobId=(\d+) NodeList=(\S+).*? Partition=(\S+)").unwrap();
+        let backfill_re = Regex::new(r"backfill: Started JobId=(\d+) in (\S+) on (\S+)").unwrap();
+        let complete_re = Regex::new(r"_job_complete: JobId=(\d+) (WEXITSTATUS|WTERMSIG) (\d+)").unwrap();
+        let timeout_re = Regex::new(r"Time limit exhausted for JobId=(\d+)").unwrap();
+        let cancel_re = Regex::new(r"REQUEST_KILL_JOB JobId=(\d+) uid (\d+)").unwrap();
+        let node_fail_re = Regex::new(r"Killing JobId=(\d+) on failed node (\S+)").unwrap();
+
+        if let Some(caps) = submit_re.captures(message) {
+            let user = Regex::new(r"\buid[= ](\d+)").unwrap().captures(message).map(|u| u.get(1).unwrap().as_str());
+            Some(("submit", caps.get(1)?.as_str(), user, None, None, None))
+        } else if let Some(caps) = alloc_re.captures(message) {
+            Some(("start", caps.get(1)?.as_str(), None, Some(caps.get(3)?.as_str()), Some(caps.get(2)?.as_str()), None))
+        } else if let Some(caps) = backfill_re.captures(message) {
+            Some(("start", caps.get(1)?.as_str(), None, Some(caps.get(2)?.as_str()), Some(caps.get(3)?.as_str()), None))
+        } else if let Some(caps) = complete_re.captures(message) {
+            let failed = &caps[2] == "WTERMSIG" || &caps[3] != "0";
+            Some((if failed { "fail" } else { "end" }, caps.get(1)?.as_str(), None, None, None, Some(caps.get(3)?.as_str())))
+        } else if let Some(caps) = timeout_re.captures(message) {
+            Some(("timeout", caps.get(1)?.as_str(), None, None, None, None))
+        } else if let Some(caps) = cancel_re.captures(message) {
+            Some(("cancel", caps.get(1)?.as_str(), Some(caps.get(2)?.as_str()), None, None, None))
+        } else if let Some(caps) = node_fail_re.captures(message) {
+            Some(("fail", caps.get(1)?.as_str(), None, None, Some(caps.get(2)?.as_str()), None))
+        } else {
+            None
+        }
+    }
+}
+
+impl LogParser for SlurmParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse SLURM log line"))?;
+        // LogTimeFormat=iso8601 is local time; the rfc5424 formats add an offset
+        let timestamp = match caps.get(2) {
+            Some(offset) => DateTime::parse_from_str(&format!("{}{}", &caps[1], offset.as_str()), "%Y-%m-%dT%H:%M:%S%z")
+                .map(|dt| dt.with_timezone(&Local).naive_local())?,
+            None => NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%dT%H:%M:%S")?,
+        };
+        let message = caps[3].to_string();
+
+        let mut attrs = BTreeMap::new();
+        // slurmstepd: "[1234.batch] done with job"; slurmctld: "sched: ..."
+        let step_re = Regex::new(r"^\[(\d+)(?:\.(\w+))?\] ").unwrap();
+        let prefix_re = Regex::new(r"^(\w[\w/]*): ").unwrap();
+        let daemon = if let Some(step) = step_re.captures(&message) {
+            attrs.insert("job".to_string(), step[1].to_string());
+            "slurmstepd".to_string()
+        } else if let Some(prefix) = prefix_re.captures(&message) {
+            prefix[1].to_string()
+        } else {
+            "slurm".to_string()
+        };
+        if let Some(event) = Self::job_event(&message) {
+            insert_job_event(&mut attrs, event);
+        }
+
+        let severity = if message.starts_with("error:") || message.contains(" error:") {
+            Some(3)
+        } else if message.starts_with("fatal:") {
+            Some(2)
+        } else if message.starts_with("Warning:") || message.starts_with("warning:") {
+            Some(4)
+        } else {
+            None
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "SLURM"
+    }
+}
+
+/// PBS Pro/OpenPBS/Torque server and MoM logs:
+/// '11/14/2023 10:00:00;0008;Server@pbs01;Job;1234.pbs01;Job Queued at request of alice@login01, owner = alice@login01, ...'
+/// and accounting logs: '11/14/2023 11:00:00;E;1234.pbs01;user=alice queue=workq ... Exit_status=0'
+pub struct PbsParser;
+
+impl PbsParser {
+    fn server_regex() -> Regex {
+        Regex::new(r"^(\d{2}/\d{2}/\d{4} \d{2}:\d{2}:\d{2})(?:\.\d+)?;([0-9a-fA-F]{4});([^;]*);([^;]*);([^;]*);(.*)$").unwrap()
+    }
+
+    fn accounting_regex() -> Regex {
+        Regex::new(r"^(\d{2}/\d{2}/\d{4} \d{2}:\d{2}:\d{2});([QSEDAR]);([^;]+);(.*)$").unwrap()
+    }
+
+    fn server_event<'a>(job: &'a str, message: &'a str) -> Option<JobEvent<'a>> {
+        let queued_re = Regex::new(r"Job Queued at request of ([^@,\s]+)\S*,.*queue = (\w+)").unwrap();
+        let enqueue_re = Regex::new(r"enqueuing into (\w+),").unwrap();
+        let run_re = Regex::new(r"Job Run at request of \S+(?: on exec_vnode \(?([^:)\s]+))?").unwrap();
+        let exit_re = Regex::new(r"Exit_status=(-?\d+)").unwrap();
+        let delete_re = Regex::new(r"Job deleted at request of ([^@\s]+)").unwrap();
+        let walltime_re = Regex::new(r"walltime \S+ exceeded limit").unwrap();
+
+        if let Some(caps) = queued_re.captures(message) {
+            Some(("submit", job, Some(caps.get(1)?.as_str()), Some(caps.get(2)?.as_str()), None, None))
+        } else if let Some(caps) = enqueue_re.captures(message) {
+            Some(("submit", job, None, Some(caps.get(1)?.as_str()), None, None))
+        } else if let Some(caps) = run_re.captures(message) {
+            Some(("start", job, None, None, caps.get(1).map(|n| n.as_str()), None))
+        } else if walltime_re.is_match(message) {
+            Some(("timeout", job, None, None, None, None))
+        } else if let Some(caps) = exit_re.captures(message) {
+            let exit = caps.get(1)?.as_str();
+            Some((if exit == "0" { "end" } else { "fail" }, job, None, None, None, Some(exit)))
+        } else if let Some(caps) = delete_re.captures(message) {
+            Some(("cancel", job, Some(caps.get(1)?.as_str()), None, None, None))
+        } else {
+            None
+        }
+    }
+}
+
+impl LogParser for PbsParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::server_regex().is_match(line) || Self::accounting_regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let mut attrs = BTreeMap::new();
+        let (ts, host, daemon, log_entry, severity) = if let Some(caps) = Self::server_regex().captures(line) {
+            // "Server@pbs01" or "pbs_mom"
+            let (source, host) = caps[3].split_once('@').unwrap_or((&caps[3], "localhost"));
+            let daemon = if source == "Server" || source == "PBS_Server" { "pbs_server" } else { source };
+            if &caps[4] == "Job" {
+                attrs.insert("job".to_string(), caps[5].to_string());
+                if let Some(event) = Self::server_event(&caps[5], &caps[6]) {
+                    insert_job_event(&mut attrs, event);
+                }
+            }
+            // Bit 0x0001 of the event class marks errors
+            let class = u16::from_str_radix(&caps[2], 16).unwrap_or(0);
+            let severity = if class & 0x0001 != 0 { Some(3) } else { None };
+            (caps[1].to_string(), host.to_string(), daemon.to_string(), format!("{} {}", &caps[4], &caps[6]).trim().to_string(), severity)
+        } else {
+            let caps = Self::accounting_regex().captures(line).ok_or_else(|| anyhow!("Failed to parse PBS log line"))?;
+            let fields = parse_key_values(&caps[4]);
+            let get = |key: &str| fields.get(key).map(|v| v.as_str());
+            let event = match &caps[2] {
+                "Q" => "submit",
+                "S" | "R" => "start",
+                "E" if get("Exit_status") == Some("0") => "end",
+                "E" => "fail",
+                "D" => "cancel",
+                _ => "fail",
+            };
+            let user = get("user").or_else(|| get("requestor").map(|r| r.split('@').next().unwrap_or(r)));
+            let nodes = get("exec_host").map(|h| h.split('/').next().unwrap_or(h));
+            insert_job_event(&mut attrs, (event, &caps[3], user, get("queue"), nodes, get("Exit_status")));
+            let log_entry = format!("{} {} {}", &caps[2], &caps[3], &caps[4]);
+            (caps[1].to_string(), "localhost".to_string(), "pbs_accounting".to_string(), log_entry, None)
+        };
+        let timestamp = NaiveDateTime::parse_from_str(&ts, "%m/%d/%Y %H:%M:%S")?;
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PBS"
+    }
+}
+
+/// Rails request logs, with or without Ruby Logger's
+/// 'I, [2023-11-14T10:00:00.123456 #1234]  INFO -- : ' prefix. Each request,
+/// from 'Started GET "/" ...' to 'Completed 200 OK in 45ms', becomes one entry
+pub struct RailsParser;
+
+impl RailsParser {
+    fn prefix_re() -> Regex {
+        Regex::new(r"^[DIWEFA], \[(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2})\.\d+ #(\d+)\]\s+([A-Z]+) -- [^:]*: (?:\[([^\]]+)\] )?").unwrap()
+    }
+
+    /// Cheap test for the Logger prefix ("I, ["); detection calls is_continuation
+    /// while walking back through whole records
+    fn has_prefix(line: &str) -> bool {
+        let b = line.as_bytes();
+        b.len() > 4 && b"DIWEFA".contains(&b[0]) && &b[1..4] == b", ["
+    }
+
+    /// The line without the Logger prefix and request tag
+    fn message(line: &str) -> &str {
+        if !Self::has_prefix(line) {
+            return line;
+        }
+        match Self::prefix_re().find(line) {
+            Some(prefix) => &line[prefix.end()..],
+            None => line,
+        }
+    }
+}
+
+impl LogParser for RailsParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::prefix_re().is_match(line) || Self::message(line).starts_with("Started ") && line.contains("\" for ")
+    }
+
+    fn is_continuation(&self, previous: &str, line: &str) -> bool {
+        if Self::message(line).starts_with("Started ") {
+            return false;
+        }
+        // Errors are logged after "Completed", under the same request tag
+        let tag = |l: &str| {
+            if !Self::has_prefix(l) {
+                return None;
+            }
+            Self::prefix_re().captures(l).and_then(|c| c.get(4)).map(|t| t.as_str().to_string())
+        };
+        if let Some(current) = tag(line) {
+            return tag(previous) == Some(current);
+        }
+        // Lines between requests (boot messages, jobs) are not part of the last request
+        !Self::message(previous).starts_with("Completed ") || line.trim().is_empty()
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let started_re = Regex::new(r#"^Started (\w+) "([^"]*)" for (\S+) at (\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} [+-]\d{4})"#).unwrap();
+        let processing_re = Regex::new(r"^Processing by (\S+) as (\S+)").unwrap();
+        let completed_re = Regex::new(r"^Completed (\d{3})[^(]*? in (\d+(?:\.\d+)?)ms(?: \((.*)\))?").unwrap();
+        let exception_re = Regex::new(r"^([A-Z]\w*(?:::[A-Z]\w*)+) \((.*)\)").unwrap();
+        let component_re = Regex::new(r"(\w+): (\d+(?:\.\d+)?)ms").unwrap();
+
+        let first = record.lines().next().unwrap_or("");
+        let prefix = Self::prefix_re().captures(first);
+
+        let mut attrs = BTreeMap::new();
+        let mut timestamp = prefix.as_ref()
+            .and_then(|p| NaiveDateTime::parse_from_str(&p[1], "%Y-%m-%dT%H:%M:%S").ok());
+        let mut severity = prefix.as_ref().and_then(|p| severity_from_name(&p[3]));
+        if let Some(prefix) = &prefix {
+            attrs.insert("pid".to_string(), prefix[2].to_string());
+            if let Some(tag) = prefix.get(4) {
+                attrs.insert("request_id".to_string(), tag.as_str().to_string());
+            }
+        }
+
+        let mut host = "localhost".to_string();
+        let mut daemon = "rails".to_string();
+        let mut request = None;
+        let mut exception = None;
+        for line in record.lines() {
+            let message = Self::message(line).trim();
+            if let Some(caps) = started_re.captures(message) {
+                attrs.insert("method".to_string(), caps[1].to_string());
+                attrs.insert("path".to_string(), caps[2].to_string());
+                host = caps[3].to_string();
+                // Without the Logger prefix this is the only timestamp
+                if timestamp.is_none() {
+                    timestamp = DateTime::parse_from_str(&caps[4], "%Y-%m-%d %H:%M:%S %z").ok()
+                        .map(|dt| dt.with_timezone(&Local).naive_local());
+                }
+                request = Some(format!("{} {}", &caps[1], &caps[2]));
+            } else if let Some(caps) = processing_re.captures(message) {
+                daemon = caps[1].to_string();
+                attrs.insert("action".to_string(), caps[1].to_string());
+                attrs.insert("format".to_string(), caps[2].to_string());
+            } else if let Some(caps) = completed_re.captures(message) {
+                attrs.insert("status".to_string(), caps[1].to_string());
+                attrs.insert("duration_ms".to_string(), caps[2].to_string());
+                // "(Views: 30.0ms | ActiveRecord: 5.0ms | Allocations: 1234)"
+                if let Some(components) = caps.get(3) {
+                    for component in component_re.captures_iter(components.as_str()) {
+                        attrs.insert(format!("{}_ms", component[1].to_lowercase()), component[2].to_string());
+                    }
+                }
+            } else if exception.is_none() {
+                if let Some(caps) = exception_re.captures(message) {
+                    exception = Some(format!("{} ({})", &caps[1], &caps[2]));
+                }
+            }
+        }
+        let timestamp = timestamp.ok_or_else(|| anyhow!("Rails log record without timestamp"))?;
+
+        if let Some(status) = attrs.get("status").and_then(|s| s.parse::<u16>().ok()) {
+            let status_severity = if status >= 500 { Some(3) } else if status >= 400 { Some(4) } else { None };
+            severity = match (severity, status_severity) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+        let mut log_entry = match &request {
+            Some(request) => {
+                let status = attrs.get("status").map(|s| s.as_str()).unwrap_or("-");
+                match attrs.get("duration_ms") {
+                    Some(duration) => format!("{} {} in {}ms", request, status, duration),
+                    None => format!("{} {}", request, status),
+                }
+            }
+            None => record.lines()
+                .map(|l| Self::message(l).trim())
+                .find(|m| !m.is_empty())
+                .unwrap_or("")
+                .to_string(),
+        };
+        if let Some(exception) = exception {
+            log_entry = format!("{} | {}", log_entry, exception);
+            attrs.insert("exception".to_string(), exception);
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host,
+            daemon,
+            log_entry,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Rails"
+    }
+}
+
+/// PHP-FPM master log: '[14-Nov-2023 10:00:00] WARNING: [pool www] server reached pm.max_children setting (5), consider raising it'
+pub struct PhpFpmParser;
+
+impl PhpFpmParser {
+    fn regex() -> Regex {
+        Regex::new(r"^\[(\d{2}-\w{3}-\d{4} \d{2}:\d{2}:\d{2})(?:\.\d+)?\] (ALERT|ERROR|WARNING|NOTICE|DEBUG): (?:\[pool ([^\]]+)\] )?(.*)$").unwrap()
+    }
+}
+
+impl LogParser for PhpFpmParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse PHP-FPM log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1], "%d-%b-%Y %H:%M:%S")?;
+        let message = caps[4].to_string();
+
+        let mut attrs = BTreeMap::new();
+        let daemon = match caps.get(3) {
+            Some(pool) => {
+                attrs.insert("pool".to_string(), pool.as_str().to_string());
+                format!("php-fpm/{}", pool.as_str())
+            }
+            None => "php-fpm".to_string(),
+        };
+        // "child 1234 exited on signal 11 (SIGSEGV)", "child 1234 said into stderr: ..."
+        if let Some(child) = Regex::new(r"\bchild (\d+)").unwrap().captures(&message) {
+            attrs.insert("child".to_string(), child[1].to_string());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: message,
+            severity: severity_from_name(&caps[2]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PHP-FPM"
+    }
+}
+
+/// PHP error_log: '[14-Nov-2023 10:00:00 UTC] PHP Fatal error:  Uncaught Exception: boom in /var/www/index.php:12',
+/// followed by untimestamped "Stack trace:" lines that are folded into the entry
+pub struct PhpErrorParser;
+
+impl PhpErrorParser {
+    fn regex() -> Regex {
+        Regex::new(r"^\[(\d{2}-\w{3}-\d{4} \d{2}:\d{2}:\d{2}) ([A-Za-z_]+(?:/[A-Za-z_+-]+)*)\] (.*)$").unwrap()
+    }
+}
+
+impl LogParser for PhpErrorParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Cheap check: detection calls this while walking back through whole records
+        !line.starts_with('[')
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::regex().captures(first).ok_or_else(|| anyhow!("Failed to parse PHP error_log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1], "%d-%b-%Y %H:%M:%S")?;
+        // Only UTC can be converted without a timezone database; other zones are
+        // usually the server's own and are taken as local time
+        let timestamp = if &caps[2] == "UTC" {
+            timestamp.and_utc().with_timezone(&Local).naive_local()
+        } else {
+            timestamp
+        };
+        let message = caps[3].split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let level_re = Regex::new(r"^PHP ([A-Za-z ]+?):").unwrap();
+        let location_re = Regex::new(r" in (/\S+?)(?::(\d+)| on line (\d+))").unwrap();
+
+        let mut attrs = BTreeMap::new();
+        let level = level_re.captures(&message).map(|c| c[1].to_lowercase());
+        let severity = match level.as_deref() {
+            Some("fatal error") | Some("parse error") | Some("core error") | Some("compile error") => Some(2),
+            Some("recoverable fatal error") | Some("error") => Some(3),
+            Some("warning") | Some("core warning") | Some("compile warning") => Some(4),
+            Some("notice") | Some("deprecated") | Some("strict standards") => Some(5),
+            _ => None,
+        };
+        if let Some(level) = level {
+            attrs.insert("level".to_string(), level);
+        }
+        if let Some(location) = location_re.captures(&message) {
+            attrs.insert("file".to_string(), location[1].to_string());
+            if let Some(line) = location.get(2).or_else(|| location.get(3)) {
+                attrs.insert("line".to_string(), line.as_str().to_string());
+            }
+        }
+        if !trace.trim().is_empty() {
+            attrs.insert("stack_trace".to_string(), trace.to_string());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: "php".to_string(),
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "PHP"
+    }
+}
+
+/// Python logging with the common formats
+/// '%(asctime)s - %(name)s - %(levelname)s - %(message)s' and
+/// '%(asctime)s [%(levelname)s] %(name)s: %(message)s'; tracebacks are folded into the entry
+pub struct PythonLogParser;
+
+impl PythonLogParser {
+    fn regex() -> Regex {
+        Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})(?:[,.]\d+)? (?:- (?:([\w.-]+) - )?(DEBUG|INFO|WARNING|ERROR|CRITICAL) - |\[(DEBUG|INFO|WARNING|ERROR|CRITICAL)\] (?:([\w.-]+): )?)(.*)$").unwrap()
+    }
+}
+
+impl LogParser for PythonLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        !starts_with_date(line, b'-')
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::regex().captures(first).ok_or_else(|| anyhow!("Failed to parse Python log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%d %H:%M:%S")?;
+        let level = caps.get(3).or_else(|| caps.get(4)).unwrap().as_str();
+        let daemon = caps.get(2).or_else(|| caps.get(5)).map(|n| n.as_str()).unwrap_or("python").to_string();
+
+        let mut attrs = BTreeMap::new();
+        let mut log_entry = caps[6].trim().to_string();
+        if !trace.trim().is_empty() {
+            attrs.insert("stack_trace".to_string(), trace.to_string());
+            // The last unindented traceback line is the exception that was raised
+            // ("ValueError: bad input"), also when exceptions were chained
+            if let Some(exception) = trace.lines().rev().find(|l| !l.trim().is_empty() && !l.starts_with([' ', '\t'])) {
+                if exception != "Traceback (most recent call last):" {
+                    attrs.insert("exception".to_string(), exception.to_string());
+                    log_entry = format!("{} | {}", log_entry, exception);
+                }
+            }
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry,
+            severity: severity_from_name(level),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Python"
+    }
+}
+
+/// Go's standard log package: '2023/11/14 10:00:00 message', optionally with
+/// microseconds and 'main.go:12: ' (Lshortfile/Llongfile); panic stack traces are folded
+pub struct GoLogParser;
+
+impl GoLogParser {
+    fn regex() -> Regex {
+        Regex::new(r"^(\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2})(?:\.\d+)? (?:(\S+\.go:\d+): )?(.*)$").unwrap()
+    }
+}
+
+impl LogParser for GoLogParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        !starts_with_date(line, b'/')
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::regex().captures(first).ok_or_else(|| anyhow!("Failed to parse Go log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1], "%Y/%m/%d %H:%M:%S")?;
+
+        let mut attrs = BTreeMap::new();
+        if let Some(caller) = caps.get(2) {
+            attrs.insert("caller".to_string(), caller.as_str().to_string());
+        }
+        if !trace.trim().is_empty() {
+            attrs.insert("stack_trace".to_string(), trace.to_string());
+        }
+        let message = caps[3].trim().to_string();
+        // log.Panic and runtime panics; log.Fatal has no marker of its own
+        let severity = if message.starts_with("panic:") || trace.starts_with("panic:") { Some(2) } else { None };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: "go".to_string(),
+            log_entry: message,
+            severity,
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "GoLog"
+    }
+}
+
+/// zap's console encoder: '2023-11-14T10:00:00.000Z\tINFO\thttp\tserver/main.go:42\tlistening\t{"addr":":8080"}',
+/// tab separated, with logger name and caller only when configured. The JSON
+/// context suffix becomes attrs
+pub struct ZapConsoleParser;
+
+impl ZapConsoleParser {
+    fn regex() -> Regex {
+        Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2}))\s+(DEBUG|INFO|WARN|ERROR|DPANIC|PANIC|FATAL)\s+(.*)$").unwrap()
+    }
+}
+
+impl LogParser for ZapConsoleParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        // Stack traces of Error level and above
+        !starts_with_date(line, b'-')
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::regex().captures(first).ok_or_else(|| anyhow!("Failed to parse zap console line"))?;
+        let ts = caps[1].replace('Z', "+0000");
+        let timestamp = DateTime::parse_from_str(&ts, "%Y-%m-%dT%H:%M:%S%.f%z")
+            .map(|dt| dt.with_timezone(&Local).naive_local())?;
+        let level = &caps[2];
+        let mut rest = caps[3].trim_end().to_string();
+
+        let mut attrs = BTreeMap::new();
+        // The context is the trailing JSON object, if any
+        if rest.ends_with('}') {
+            let split = rest.rfind("\t{").or_else(|| rest.find(" {"));
+            if let Some(idx) = split {
+                if let Ok(serde_json::Value::Object(fields)) = serde_json::from_str::<serde_json::Value>(rest[idx..].trim()) {
+                    for (key, value) in fields {
+                        let value = match value {
+                            serde_json::Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        attrs.insert(key, value);
+                    }
+                    rest.truncate(idx);
+                }
+            }
+        }
+
+        let caller_re = Regex::new(r"^\S+\.go:\d+$").unwrap();
+        let mut fields: Vec<&str> = rest.split('\t').map(|f| f.trim()).collect();
+        let mut daemon = "zap".to_string();
+        // Only tabs tell the logger name apart from the message
+        if fields.len() > 1 {
+            if let Some(idx) = fields.iter().position(|f| caller_re.is_match(f)) {
+                attrs.insert("caller".to_string(), fields.remove(idx).to_string());
+            }
+            if fields.len() > 1 {
+                daemon = fields.remove(0).to_string();
+            }
+        } else if let Some((caller, message)) = rest.split_once(' ').filter(|(c, _)| caller_re.is_match(c)) {
+            attrs.insert("caller".to_string(), caller.to_string());
+            fields = vec![message];
+        }
+        if !trace.trim().is_empty() {
+            attrs.insert("stack_trace".to_string(), trace.to_string());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon,
+            log_entry: fields.join(" ").trim().to_string(),
+            severity: severity_from_name(level).or(Some(2)),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Zap"
+    }
+}
+
+/// JVM unified logging (-Xlog:gc*), with any decorators:
+/// '[2023-11-14T10:00:00.123+0000][info][gc] GC(123) Pause Young (Normal) (G1 Evacuation Pause) 24M->4M(256M) 12.345ms'
+pub struct JvmGcParser;
+
+impl JvmGcParser {
+    /// Decorators: time, uptime, level and tags (padded with spaces), plus pid/tid
+    fn decorators(line: &str) -> Option<(Vec<&str>, &str)> {
+        let mut decorators = Vec::new();
+        let mut rest = line;
+        while let Some(inner) = rest.strip_prefix('[') {
+            let end = inner.find(']')?;
+            decorators.push(inner[..end].trim());
+            rest = &inner[end + 1..];
+        }
+        Some((decorators, rest.trim_start()))
+    }
+
+    fn is_level(decorator: &str) -> bool {
+        matches!(decorator, "trace" | "debug" | "info" | "warning" | "error")
+    }
+}
+
+impl LogParser for JvmGcParser {
+    fn is_type(&self, line: &str) -> bool {
+        let tags_re = Regex::new(r"^(?:gc|safepoint)(?:,[a-z0-9]+)*$").unwrap();
+        match Self::decorators(line) {
+            Some((decorators, _)) => decorators.iter().any(|d| Self::is_level(d)) && decorators.iter().any(|d| tags_re.is_match(d)),
+            None => false,
+        }
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let (decorators, message) = Self::decorators(line).ok_or_else(|| anyhow!("Failed to parse JVM log line"))?;
+        let uptime_re = Regex::new(r"^(\d+(?:\.\d+)?)(s|ms)$").unwrap();
+        let tags_re = Regex::new(r"^[a-z]+(?:,[a-z0-9]+)*$").unwrap();
+
+        let mut attrs = BTreeMap::new();
+        let mut timestamp = None;
+        let mut uptime = None;
+        let mut level = None;
+        let mut tags = "jvm".to_string();
+        for decorator in decorators {
+            if let Ok(dt) = DateTime::parse_from_str(decorator, "%Y-%m-%dT%H:%M:%S%.f%z") {
+                timestamp = Some(dt.with_timezone(&Local).naive_local());
+            } else if let Some(caps) = uptime_re.captures(decorator) {
+                let value: f64 = caps[1].parse()?;
+                uptime = Some(if &caps[2] == "ms" { value / 1000.0 } else { value });
+                attrs.insert("uptime".to_string(), format!("{:.3}", uptime.unwrap_or(0.0)));
+            } else if Self::is_level(decorator) {
+                level = Some(decorator);
+            } else if tags_re.is_match(decorator) {
+                tags = decorator.to_string();
+            }
+        }
+        // With only the uptime decorator there is no wall clock: entries are placed
+        // at the epoch plus the uptime, which keeps graphs over the JVM's lifetime
+        let timestamp = match (timestamp, uptime) {
+            (Some(ts), _) => ts,
+            (None, Some(uptime)) => DateTime::from_timestamp(uptime.trunc() as i64, 0)
+                .map(|dt| dt.naive_utc())
+                .ok_or_else(|| anyhow!("Invalid uptime"))?,
+            (None, None) => return Err(anyhow!("JVM log line without time or uptime decorator")),
+        };
+
+        // "GC(123) Pause Young (Normal) (G1 Evacuation Pause) 24M->4M(256M) 12.345ms"
+        let pause_re = Regex::new(r"^GC\((\d+)\) (Pause .*?) (?:(\d+)([KMG])->(\d+)([KMG])\((\d+)([KMG])\) )?(\d+(?:\.\d+)?)ms$").unwrap();
+        let gc_id_re = Regex::new(r"^GC\((\d+)\)").unwrap();
+        if let Some(caps) = pause_re.captures(message) {
+            attrs.insert("pause".to_string(), caps[2].trim().to_string());
+            attrs.insert("pause_ms".to_string(), caps[9].to_string());
+            if caps.get(3).is_some() {
+                attrs.insert("heap_before".to_string(), format!("{}{}", &caps[3], &caps[4]));
+                attrs.insert("heap_after".to_string(), format!("{}{}", &caps[5], &caps[6]));
+                attrs.insert("heap_total".to_string(), format!("{}{}", &caps[7], &caps[8]));
+            }
+        }
+        if let Some(caps) = gc_id_re.captures(message) {
+            attrs.insert("gc_id".to_string(), caps[1].to_string());
+        }
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: tags,
+            log_entry: message.to_string(),
+            severity: level.and_then(severity_from_name),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "JVM-GC"
+    }
+}
+
+/// Kernel ring buffer as printed by dmesg, with human readable times
+/// (`dmesg -T`), ISO times (`dmesg --time-format iso`) or seconds since boot,
+/// optionally with the facility and level (`dmesg -x`):
+/// "[Fri Nov 14 10:00:00 2025] e1000e: eth0 NIC Link is Down",
+/// "kern  :err   : [  123.456789] Out of memory: Killed process 4242 (java)"
+pub struct DmesgParser;
+
+impl DmesgParser {
+    fn line_re() -> Regex {
+        Regex::new(r"^(?:(\w+)\s*:(\w+)\s*: )?(?:\[(\w{3} \w{3} [ \d]\d \d{2}:\d{2}:\d{2} \d{4})\]|\[\s*(\d+\.\d{6})\]|(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2},\d{6}[+-]\d{2}:\d{2})) ?(.*)$").unwrap()
+    }
+}
+
+impl LogParser for DmesgParser {
+    fn is_type(&self, line: &str) -> bool {
+        // Apache 2.2 error logs share the ctime in brackets, followed by the
+        // level: "[Fri Nov 14 10:00:00 2025] [error] [client 192.0.2.1] ..."
+        let apache_level = Regex::new(r"^\[(?:\w*:)?(?:emerg|alert|crit|error|warn|notice|info|debug|trace\d)\]").unwrap();
+        match Self::line_re().captures(line) {
+            Some(caps) => caps.get(3).is_none() || !apache_level.is_match(&caps[6]),
+            None => false,
+        }
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::line_re().captures(line).ok_or_else(|| anyhow!("Failed to parse dmesg line"))?;
+
+        let mut attrs = BTreeMap::new();
+        if let Some(facility) = caps.get(1) {
+            attrs.insert("facility".to_string(), facility.as_str().to_string());
+        }
+        // Without a wall clock, entries are placed at the epoch plus the time
+        // since boot, as for JVM logs with only the uptime decorator
+        let timestamp = if let Some(ctime) = caps.get(3) {
+            NaiveDateTime::parse_from_str(ctime.as_str(), "%a %b %e %H:%M:%S %Y")?
+        } else if let Some(uptime) = caps.get(4) {
+            attrs.insert("uptime".to_string(), uptime.as_str().to_string());
+            let uptime: f64 = uptime.as_str().parse()?;
+            DateTime::from_timestamp(uptime.trunc() as i64, 0)
+                .map(|dt| dt.naive_utc())
+                .ok_or_else(|| anyhow!("Invalid uptime"))?
+        } else {
+            let iso = caps.get(5).map(|m| m.as_str()).unwrap_or_default();
+            DateTime::parse_from_str(iso, "%Y-%m-%dT%H:%M:%S,%6f%:z")?.with_timezone(&Local).naive_local()
+        };
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: "kernel".to_string(),
+            log_entry: caps[6].to_string(),
+            severity: caps.get(2).and_then(|level| severity_from_name(level.as_str())),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "dmesg"
+    }
+}
+
+/// varnishncsa with the cache verdict appended to the combined format, e.g.
+/// -F '%h %l %u %t "%r" %s %b "%{Referer}i" "%{User-agent}i" %{Varnish:hitmiss}x %{Varnish:handling}x %D %{VCL_Log:backend}x'
+pub struct VarnishNcsaParser;
+
+impl VarnishNcsaParser {
+    fn regex() -> Regex {
+        Regex::new(r#"^(\S+) \S+ (\S+) \[(\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2}) [+-]\d{4}\] "([^"]*)" (\d{3}) (\d+|-) "([^"]*)" "([^"]*)" (hit|miss|pass|pipe|synth)\b ?(.*)$"#).unwrap()
+    }
+}
+
+impl LogParser for VarnishNcsaParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = Self::regex().captures(line).ok_or_else(|| anyhow!("Failed to parse varnishncsa line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[3], "%d/%b/%Y:%H:%M:%S")?;
+        let request = &caps[4];
+        let mut parts = request.split_whitespace();
+        let method = parts.next().unwrap_or("-");
+        let path = parts.next().unwrap_or("-");
+
+        let mut attrs = BTreeMap::new();
+        for (key, value) in [("method", method), ("path", path), ("status", &caps[5]), ("bytes", &caps[6]),
+            ("user", &caps[2]), ("referer", &caps[7]), ("user_agent", &caps[8]), ("cache", &caps[9])] {
+            if value != "-" && !value.is_empty() {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+        // The extensions after the verdict vary: handling, %D (microseconds),
+        // time to first byte (seconds) and a backend name
+        for token in caps[10].split_whitespace().filter(|t| *t != "-") {
+            if matches!(token, "hit" | "miss" | "pass" | "pipe" | "synth" | "error" | "abandon" | "fail") {
+                attrs.insert("handling".to_string(), token.to_string());
+            } else if let Ok(micros) = token.parse::<u64>() {
+                attrs.insert("duration_ms".to_string(), format!("{:.3}", micros as f64 / 1000.0));
+            } else if token.contains('.') && token.parse::<f64>().is_ok() {
+                attrs.insert("ttfb".to_string(), token.to_string());
+            } else {
+                attrs.insert("backend".to_string(), token.to_string());
+            }
+        }
+
+        let status: u16 = caps[5].parse()?;
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: caps[1].to_string(),
+            daemon: format!("varnish/{}", &caps[9]),
+            log_entry: format!("{} {} {}", request, &caps[5], &caps[6]),
+            severity: if status >= 500 { Some(3) } else { None },
+            attrs: with_src(attrs, &caps[1]),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Varnish"
+    }
+}
+
+/// Keycloak server log (Quarkus/WildFly format), including the login events of
+/// org.keycloak.events: '2023-11-14 10:00:00,123 WARN  [org.keycloak.events] (executor-thread-1) type="LOGIN_ERROR", realmId="...", ...'
+pub struct KeycloakParser;
+
+impl KeycloakParser {
+    fn regex() -> Regex {
+        Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})(?:,\d+)? (TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+\[([^\]]+)\] \(([^)]*)\) (.*)$").unwrap()
+    }
+}
+
+impl LogParser for KeycloakParser {
+    fn is_type(&self, line: &str) -> bool {
+        Self::regex().is_match(line)
+    }
+
+    fn is_continuation(&self, _previous: &str, line: &str) -> bool {
+        JavaLogParser::is_stack_trace(line)
+    }
+
+    fn parse(&self, record: &str) -> Result<LogEntry> {
+        let (first, trace) = record.split_once('\n').unwrap_or((record, ""));
+        let caps = Self::regex().captures(first).ok_or_else(|| anyhow!("Failed to parse Keycloak log line"))?;
+        let timestamp = NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%d %H:%M:%S")?;
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("thread".to_string(), caps[4].to_string());
+        // Event fields: 'type=LOGIN_ERROR, realmId=master, ...', quoted since Keycloak 23
+        if caps[3].starts_with("org.keycloak.events") {
+            let field_re = Regex::new(r#"(\w+)=(?:"([^"]*)"|([^,\s]*))"#).unwrap();
+            for field in field_re.captures_iter(&caps[5]) {
+                let value = field.get(2).or_else(|| field.get(3)).map(|v| v.as_str()).unwrap_or("");
+                if !value.is_empty() && value != "null" {
+                    attrs.insert(field[1].to_string(), value.to_string());
+                }
+            }
+        }
+        let log_entry = JavaLogParser::fold_stack_trace(&caps[5], trace, &mut attrs);
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: "localhost".to_string(),
+            daemon: caps[3].to_string(),
+            log_entry,
+            severity: severity_from_name(&caps[2]),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Keycloak"
+    }
+}
+
+/// Column layout for `--csv-map`: which columns (counted from 0) hold the time,
+/// host, daemon, level and message. Columns joined with '+' are concatenated
+/// with a space, for exports with separate date and time columns
+#[derive(Debug, Clone)]
+pub struct CsvMap {
+    pub time: Vec<usize>,
+    pub host: Vec<usize>,
+    pub daemon: Vec<usize>,
+    pub level: Vec<usize>,
+    pub msg: Vec<usize>,
+    pub delimiter: char,
+    pub header: bool,
+}
+
+impl CsvMap {
+    /// Parse a mapping such as "time=0,host=2,msg=5" or "time=1+2,level=0,msg=6"
+    pub fn parse(spec: &str, delimiter: char, header: bool) -> Result<Self> {
+        let mut map = Self {
+            time: Vec::new(),
+            host: Vec::new(),
+            daemon: Vec::new(),
+            level: Vec::new(),
+            msg: Vec::new(),
+            delimiter,
+            header,
+        };
+
+        for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let (key, columns) = part.split_once('=')
+                .ok_or_else(|| anyhow!("Invalid CSV mapping '{}', expected field=column", part))?;
+            let columns = columns.split('+')
+                .map(|c| c.trim().parse::<usize>().map_err(|_| anyhow!("Invalid CSV column '{}' for {}", c, key)))
+                .collect::<Result<Vec<_>>>()?;
+            let field = match key.trim() {
+                "time" => &mut map.time,
+                "host" => &mut map.host,
+                "daemon" => &mut map.daemon,
+                "level" | "severity" => &mut map.level,
+                "msg" | "message" => &mut map.msg,
+                other => return Err(anyhow!("Unknown CSV field '{}', expected time, host, daemon, level or msg", other)),
+            };
+            *field = columns;
+        }
+
+        if map.time.is_empty() {
+            return Err(anyhow!("CSV mapping needs a time column, e.g. time=0"));
+        }
+        Ok(map)
+    }
+
+    fn mapped(&self, column: usize) -> bool {
+        [&self.time, &self.host, &self.daemon, &self.level, &self.msg].iter().any(|c| c.contains(&column))
+    }
+}
+
+/// Generic delimited input laid out by a `CsvMap`. Never auto-detected; the
+/// columns not mapped are kept as structured fields, named after the header
+/// row when there is one
+pub struct CsvParser {
+    map: CsvMap,
+    header: Mutex<Option<Vec<String>>>,
+}
+
+impl CsvParser {
+    pub fn new(map: CsvMap) -> Self {
+        Self {
+            map,
+            header: Mutex::new(None),
+        }
+    }
+
+    fn join(fields: &[String], columns: &[usize]) -> String {
+        columns.iter()
+            .filter_map(|&c| fields.get(c))
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl LogParser for CsvParser {
+    fn is_type(&self, line: &str) -> bool {
+        let columns = split_csv(line, self.map.delimiter).len();
+        self.map.time.iter().all(|&c| c < columns)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let fields = split_csv(line, self.map.delimiter);
+        let time = Self::join(&fields, &self.map.time);
+        let timestamp = parse_loose_timestamp(&time)
+            .ok_or_else(|| anyhow!("Unrecognized CSV timestamp '{}'", time))?;
+
+        let header = self.header.lock().unwrap();
+        let mut attrs = BTreeMap::new();
+        let mut rest = Vec::new();
+        for (i, value) in fields.iter().enumerate() {
+            let value = value.trim();
+            if self.map.mapped(i) || value.is_empty() {
+                continue;
+            }
+            rest.push(value);
+            let name = header.as_ref()
+                .and_then(|names| names.get(i))
+                .filter(|n| !n.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("col{}", i));
+            attrs.insert(name, value.to_string());
+        }
+
+        // Without a message column, whatever is left over makes up the message
+        let log_entry = if self.map.msg.is_empty() {
+            rest.join(" ")
+        } else {
+            Self::join(&fields, &self.map.msg)
+        };
+        let level = Self::join(&fields, &self.map.level);
+        let host = Self::join(&fields, &self.map.host);
+        let daemon = Self::join(&fields, &self.map.daemon);
+
+        Ok(LogEntry {
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: if host.is_empty() { "localhost".to_string() } else { host },
+            daemon: if daemon.is_empty() { "csv".to_string() } else { daemon },
+            log_entry,
+            severity: severity_from_name(&level),
+            attrs,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return true;
+        }
+        let mut header = self.header.lock().unwrap();
+        if self.map.header && header.is_none() {
+            *header = Some(split_csv(line, self.map.delimiter).into_iter().map(|f| f.trim().to_string()).collect());
+            return true;
+        }
+        false
+    }
+}
+
+/// What a field of a `LogFormat` holds
+#[derive(Debug, Clone, PartialEq)]
+enum FormatField {
+    /// Client address (%h, %a, $remote_addr)
+    Client,
+    /// Authenticated user (%u, $remote_user)
+    User,
+    /// "10/Oct/2000:13:55:36 -0700" (%t, $time_local)
+    ClfTime,
+    /// "2000-10-10T13:55:36-07:00" ($time_iso8601)
+    IsoTime,
+    /// Unix epoch in seconds or milliseconds, with or without a fraction
+    /// (%{sec}t, %{msec}t, $msec)
+    EpochTime,
+    /// strftime layout (%{...}t)
+    CustomTime(String),
+    /// Request line (%r, $request)
+    Request,
+    Method,
+    Path,
+    Query,
+    Protocol,
+    Status,
+    Bytes,
+    /// Request header, lowercase (%{Referer}i, $http_referer)
+    Header(String),
+    /// Kept as a structured field of this name
+    Other(String),
+}
+
+/// Line layout of a customized web server access log, from an Apache
+/// `LogFormat` string ('%h %l %u %t "%r" %>s %b %D') or an nginx `log_format`
+/// ('$remote_addr - $remote_user [$time_local] "$request" $status ...'),
+/// compiled to a regex with one group per field
+#[derive(Debug, Clone)]
+pub struct LogFormat {
+    regex: Regex,
+    fields: Vec<FormatField>,
+}
+
+impl LogFormat {
+    /// Apache when the format has `%` directives, nginx when it has `$`
+    /// variables. An nginx directive copied from the config, with
+    /// "log_format name" and the quoted parts, is accepted as well
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let nginx = spec.starts_with("log_format") || (spec.contains('$') && !Regex::new(r"%[<>]?(?:\{[^}]*\})?[a-zA-Z%]").unwrap().is_match(spec));
+        let (literals, fields) = if nginx {
+            Self::split_nginx(&Self::strip_nginx_directive(spec))?
+        } else {
+            Self::split_apache(spec)?
+        };
+
+        if !fields.iter().any(|f| matches!(f, FormatField::ClfTime | FormatField::IsoTime | FormatField::EpochTime | FormatField::CustomTime(_))) {
+            return Err(anyhow!("Log format has no time field (%t or $time_local)"));
+        }
+
+        let mut pattern = String::from("^");
+        for (i, field) in fields.iter().enumerate() {
+            let before = &literals[i];
+            pattern.push_str(&Self::literal_pattern(before));
+            let after = literals[i + 1].chars().next();
+            let group = match field {
+                FormatField::ClfTime if !before.ends_with('[') => r"\[([^\]]*)\]".to_string(),
+                _ if before.ends_with('[') => r"([^\]]*)".to_string(),
+                _ if before.ends_with('"') && after == Some('"') => r#"((?:[^"\\]|\\.)*)"#.to_string(),
+                FormatField::CustomTime(layout) => format!(r"(\S+{})", r"\s+\S+".repeat(layout.split_whitespace().count().saturating_sub(1))),
+                _ => r"(\S*)".to_string(),
+            };
+            pattern.push_str(&group);
+        }
+        pattern.push_str(&Self::literal_pattern(literals.last().map(|s| s.as_str()).unwrap_or("")));
+
+        let regex = Regex::new(&pattern).map_err(|e| anyhow!("Cannot build a parser for the log format: {}", e))?;
+        Ok(Self { regex, fields })
+    }
+
+    /// Text between fields must match as written, with any run of spaces
+    /// matching one or more
+    fn literal_pattern(text: &str) -> String {
+        let mut pattern = String::new();
+        let mut spaces = false;
+        for c in text.chars() {
+            if c == ' ' {
+                if !spaces {
+                    pattern.push_str(" +");
+                }
+            } else {
+                pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])));
+            }
+            spaces = c == ' ';
+        }
+        pattern
+    }
+
+    /// `log_format main '$remote_addr ...' '"$http_user_agent"';` to the
+    /// format itself
+    fn strip_nginx_directive(spec: &str) -> String {
+        let spec = spec.trim_end_matches(';').trim();
+        if !spec.starts_with("log_format") {
+            return spec.to_string();
+        }
+        let quoted = Regex::new(r#"'([^']*)'|"((?:[^"\\]|\\.)*)""#).unwrap();
+        let parts: String = quoted.captures_iter(spec)
+            .map(|c| c.get(1).or_else(|| c.get(2)).map(|m| m.as_str()).unwrap_or("").to_string())
+            .collect();
+        if parts.is_empty() {
+            // Unquoted: "log_format name [escape=json] $var ..."
+            spec.split_whitespace().skip(2).filter(|w| !w.starts_with("escape=")).collect::<Vec<_>>().join(" ")
+        } else {
+            parts
+        }
+    }
+
+    /// Literal text around each field: one more literal than fields
+    fn split_apache(spec: &str) -> Result<(Vec<String>, Vec<FormatField>)> {
+        let mut literals = vec![String::new()];
+        let mut fields = Vec::new();
+        let mut chars = spec.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literals.last_mut().unwrap().push(c);
+                continue;
+            }
+            // Status conditions and the original/final request modifiers
+            while let Some(&m) = chars.peek() {
+                if m == '<' || m == '>' || m == '!' || m == ',' || m.is_ascii_digit() {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let mut arg = None;
+            if chars.peek() == Some(&'{') {
+                chars.next();
+                let mut text = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    text.push(c);
+                }
+                arg = Some(text);
+            }
+            let directive = chars.next().ok_or_else(|| anyhow!("Log format ends in the middle of a directive"))?;
+            let field = match (directive, arg.as_deref()) {
+                ('%', _) => {
+                    literals.last_mut().unwrap().push('%');
+                    continue;
+                }
+                ('h' | 'a', _) => FormatField::Client,
+                ('u', _) => FormatField::User,
+                ('t', None) => FormatField::ClfTime,
+                ('t', Some(layout)) => {
+                    let layout = layout.trim_start_matches("begin:").trim_start_matches("end:");
+                    match layout {
+                        "sec" | "msec" | "msec_frac" | "usec" => FormatField::EpochTime,
+                        _ => FormatField::CustomTime(layout.to_string()),
+                    }
+                }
+                ('r', _) => FormatField::Request,
+                ('m', _) => FormatField::Method,
+                ('U', _) => FormatField::Path,
+                ('q', _) => FormatField::Query,
+                ('H', _) => FormatField::Protocol,
+                ('s', _) => FormatField::Status,
+                ('b' | 'B', _) => FormatField::Bytes,
+                ('i', Some(header)) => FormatField::Header(header.to_lowercase()),
+                ('o', Some(header)) => FormatField::Other(format!("response_{}", header.to_lowercase())),
+                ('e' | 'n' | 'C', Some(name)) => FormatField::Other(name.to_lowercase()),
+                (d, _) => FormatField::Other(match d {
+                    'A' => "local_addr",
+                    'D' => "duration_us",
+                    'T' => "duration",
+                    'v' | 'V' => "server_name",
+                    'p' => "port",
+                    'P' => "pid",
+                    'l' => "ident",
+                    'I' => "bytes_in",
+                    'O' => "bytes_out",
+                    'S' => "bytes_transferred",
+                    'X' => "connection_status",
+                    'k' => "keepalive",
+                    'L' => "log_id",
+                    'R' => "handler",
+                    'f' => "filename",
+                    'e' | 'n' | 'C' | 'i' | 'o' => return Err(anyhow!("Log format directive %{} needs a name, e.g. %{{NAME}}{}", d, d)),
+                    _ => return Err(anyhow!("Unknown log format directive %{}", d)),
+                }.to_string()),
+            };
+            fields.push(field);
+            literals.push(String::new());
+        }
+        Ok((literals, fields))
+    }
 
-        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
-        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
-        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
-        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
-        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
-        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
+    fn split_nginx(spec: &str) -> Result<(Vec<String>, Vec<FormatField>)> {
+        let variable = Regex::new(r"\$(?:\{([A-Za-z0-9_]+)\}|([A-Za-z0-9_]+))").unwrap();
+        let mut literals = Vec::new();
+        let mut fields = Vec::new();
+        let mut last = 0;
+        for caps in variable.captures_iter(spec) {
+            let whole = caps.get(0).unwrap();
+            literals.push(spec[last..whole.start()].to_string());
+            last = whole.end();
+            let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            fields.push(match name {
+                "remote_addr" | "realip_remote_addr" | "binary_remote_addr" => FormatField::Client,
+                "remote_user" => FormatField::User,
+                "time_local" => FormatField::ClfTime,
+                "time_iso8601" => FormatField::IsoTime,
+                "msec" => FormatField::EpochTime,
+                "request" => FormatField::Request,
+                "request_method" => FormatField::Method,
+                "uri" | "document_uri" => FormatField::Path,
+                "args" | "query_string" => FormatField::Query,
+                "server_protocol" => FormatField::Protocol,
+                "status" => FormatField::Status,
+                "body_bytes_sent" | "bytes_sent" => FormatField::Bytes,
+                header if header.starts_with("http_") => FormatField::Header(header["http_".len()..].replace('_', "-")),
+                other => FormatField::Other(other.to_string()),
+            });
+        }
+        literals.push(spec[last..].to_string());
+        if fields.is_empty() {
+            return Err(anyhow!("Log format has no fields"));
+        }
+        Ok((literals, fields))
+    }
+
+    fn parse_time(field: &FormatField, text: &str) -> Option<NaiveDateTime> {
+        match field {
+            // Wall-clock time as written, like the stock Apache formats
+            FormatField::ClfTime => NaiveDateTime::parse_from_str(text.split_whitespace().next()?, "%d/%b/%Y:%H:%M:%S").ok(),
+            FormatField::IsoTime => NaiveDateTime::parse_from_str(text.get(..19)?, "%Y-%m-%dT%H:%M:%S").ok(),
+            FormatField::EpochTime => {
+                let value: f64 = text.parse().ok()?;
+                match text.split('.').next()?.len() {
+                    13 => epoch_to_local(value / 1000.0),
+                    16 => epoch_to_local(value / 1_000_000.0),
+                    _ => epoch_to_local(value),
+                }
+            }
+            FormatField::CustomTime(layout) => NaiveDateTime::parse_from_str(text, layout).ok()
+                .or_else(|| NaiveDate::parse_from_str(text, layout).ok().and_then(|d| d.and_hms_opt(0, 0, 0))),
+            _ => None,
+        }
+    }
+}
 
-        let protocol = parts[0];
-        let client = parts[3].split(':').next().unwrap_or("unknown");
-        let elb_status = parts[8];
-        let target_status = parts[9];
+/// Access logs laid out by a `LogFormat`. Never auto-detected; entries look
+/// like those of the stock Apache formats: the client as host, the method
+/// as daemon and "request status bytes" as the message, with the fields of
+/// the format kept as structured fields
+pub struct LogFormatParser {
+    format: LogFormat,
+}
 
-        // Extract HTTP request (in quotes)
-        let request_start = line.find('"').unwrap_or(0);
-        let request_end = line[request_start+1..].find('"').map(|i| i + request_start + 1).unwrap_or(line.len());
-        let request = if request_start < request_end {
-            &line[request_start+1..request_end]
-        } else {
-            "-"
-        };
+impl LogFormatParser {
+    pub fn new(format: LogFormat) -> Self {
+        Self { format }
+    }
+}
 
-        let daemon = request.split_whitespace().next().unwrap_or(protocol).to_string();
-        let log_entry = format!("{} elb_status={} target_status={} protocol={}", request, elb_status, target_status, protocol);
+impl LogParser for LogFormatParser {
+    fn is_type(&self, line: &str) -> bool {
+        self.format.regex.is_match(line)
+    }
+
+    fn parse(&self, line: &str) -> Result<LogEntry> {
+        let caps = self.format.regex.captures(line)
+            .ok_or_else(|| anyhow!("Line does not match the log format"))?;
+
+        let mut timestamp = None;
+        let mut client = "";
+        let mut user = "";
+        let mut request = String::new();
+        let (mut method, mut path, mut query, mut protocol) = ("", "", "", "");
+        let (mut status, mut bytes, mut referer, mut user_agent) = ("", "", "", "");
+        let mut attrs = BTreeMap::new();
+        for (i, field) in self.format.fields.iter().enumerate() {
+            let value = caps.get(i + 1).map(|m| m.as_str()).unwrap_or("");
+            match field {
+                FormatField::ClfTime | FormatField::IsoTime | FormatField::EpochTime | FormatField::CustomTime(_) => {
+                    if timestamp.is_none() {
+                        timestamp = Some(LogFormat::parse_time(field, value)
+                            .ok_or_else(|| anyhow!("Unrecognized timestamp '{}'", value))?);
+                    }
+                }
+                FormatField::Client => client = value,
+                FormatField::User => user = value,
+                FormatField::Request => request = value.to_string(),
+                FormatField::Method => method = value,
+                FormatField::Path => path = value,
+                FormatField::Query => query = value,
+                FormatField::Protocol => protocol = value,
+                FormatField::Status => status = value,
+                FormatField::Bytes => bytes = value,
+                FormatField::Header(name) if name == "referer" => referer = value,
+                FormatField::Header(name) if name == "user-agent" => user_agent = value,
+                FormatField::Header(name) | FormatField::Other(name) => {
+                    if !value.is_empty() && value != "-" {
+                        attrs.insert(name.clone(), value.to_string());
+                    }
+                }
+            }
+        }
+        let timestamp = timestamp.ok_or_else(|| anyhow!("Line has no timestamp"))?;
+
+        // Without %r the request line is put together from its parts
+        if request.is_empty() {
+            let query = match query {
+                "" | "-" => String::new(),
+                q if q.starts_with('?') => q.to_string(),
+                q => format!("?{}", q),
+            };
+            let target = format!("{}{}", path, query);
+            request = [method, target.as_str(), protocol].iter().filter(|p| !p.is_empty()).copied().collect::<Vec<_>>().join(" ");
+        }
+        let daemon = match method {
+            "" => request.split_whitespace().next().unwrap_or("HTTP"),
+            method => method,
+        }.to_string();
+        let mut log_entry = [request.as_str(), status, bytes].iter().filter(|p| !p.is_empty()).copied().collect::<Vec<_>>().join(" ");
+        // Referer and user agent go with the request, as in the Combined format
+        if !referer.is_empty() || !user_agent.is_empty() {
+            log_entry.push_str(&format!(" \"{}\" \"{}\"", referer, user_agent));
+        }
 
         Ok(LogEntry {
-            year,
-            month,
-            day,
-            hour,
-            minute,
-            second,
-            host: client.to_string(),
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: if client.is_empty() { "localhost".to_string() } else { client.to_string() },
             daemon,
             log_entry,
+            severity: None,
+            attrs: with_user(with_src(attrs, client), user),
         })
     }
 
     fn name(&self) -> &'static str {
-        "AWS-ALB"
+        "LogFormat"
     }
 }
 
-pub struct MysqlGeneralParser;
+/// Log exports from NAS web consoles: Synology Log Center ("Level","Log","Time",
+/// "User","Event", and "Time","IP address","User","Event","File/Folder",
+/// "File size","File name" for file transfers) and QNAP ("Type","Date","Time",
+/// "Users","Source IP","Computer name","Content", with "Connection type",
+/// "Accessed resources","Action" in the connection log)
+pub struct NasCsvParser {
+    columns: Mutex<Vec<String>>,
+}
 
-impl LogParser for MysqlGeneralParser {
+impl Default for NasCsvParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NasCsvParser {
+    pub fn new() -> Self {
+        Self {
+            columns: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn row_regex() -> Regex {
+        Regex::new(r#"^"?(?:(?:Info|Information|Notice|Warn|Warning|Error|Err|Crit|Critical)"?,(?:"?[A-Za-z ]+"?,)?"?\d{4}/\d{2}/\d{2}|\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}"?,)"#).unwrap()
+    }
+
+    fn is_header(fields: &[String]) -> bool {
+        matches!(fields.first().map(|f| f.as_str()), Some("Level" | "Type" | "Time"))
+            && fields.iter().any(|f| f == "Time")
+            && fields.iter().any(|f| matches!(f.as_str(), "Event" | "Content" | "Action"))
+    }
+
+    /// Column names for exports saved without their header row, told apart by shape
+    fn default_columns(fields: &[String]) -> Vec<&'static str> {
+        let second = fields.get(1).map(|f| f.as_str()).unwrap_or("");
+        if NaiveDateTime::parse_from_str(fields[0].trim(), "%Y/%m/%d %H:%M:%S").is_ok() {
+            vec!["Time", "IP address", "User", "Event", "File/Folder", "File size", "File name"]
+        } else if NaiveDate::parse_from_str(second, "%Y/%m/%d").is_ok() {
+            vec!["Type", "Date", "Time", "Users", "Source IP", "Computer name", "Content"]
+        } else if fields.len() >= 5 {
+            vec!["Level", "Log", "Time", "User", "Event"]
+        } else {
+            vec!["Level", "Time", "User", "Event"]
+        }
+    }
+}
+
+impl LogParser for NasCsvParser {
     fn is_type(&self, line: &str) -> bool {
-        // MySQL general log: YYYY-MM-DDTHH:MM:SS.microsZ    thread_id command_type    query
-        let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+Z\s+\d+\s+(Query|Connect|Quit|Init|Execute)").unwrap();
-        re.is_match(line)
+        Self::row_regex().is_match(line) || Self::is_header(&split_csv(line, ','))
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        // Format: 2023-11-14T10:30:45.123456Z    42 Query     SELECT * FROM users
-        // Query text is optional (e.g., Quit command has no query)
-        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})\.\d+Z\s+(\d+)\s+(\w+)\s*(.*)$").unwrap();
-        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse MySQL general log"))?;
+        let fields = split_csv(line, ',');
+        let stored = self.columns.lock().unwrap();
+        let names: Vec<&str> = if stored.is_empty() {
+            Self::default_columns(&fields)
+        } else {
+            stored.iter().map(|c| c.as_str()).collect()
+        };
+        let record: HashMap<&str, &str> = names.into_iter()
+            .zip(fields.iter().map(|f| f.trim()))
+            .filter(|(_, value)| !value.is_empty() && *value != "---")
+            .collect();
+        let get = |keys: &[&str]| keys.iter().find_map(|k| record.get(k).copied());
+
+        let time = match record.get("Date") {
+            Some(date) => format!("{} {}", date, record.get("Time").unwrap_or(&"00:00:00")),
+            None => get(&["Time", "Date and time"]).unwrap_or("").to_string(),
+        };
+        let timestamp = parse_loose_timestamp(&time)
+            .ok_or_else(|| anyhow!("Unrecognized NAS log timestamp '{}'", time))?;
+
+        let qnap = record.contains_key("Date") || record.contains_key("Content") || record.contains_key("Users");
+        let level = get(&["Level", "Type"]);
+        let user = get(&["User", "Users", "User name"]);
+        let ip = get(&["Source IP", "IP address", "IP"]);
+        let resource = get(&["Accessed resources", "File name", "File/Folder"]);
+
+        let mut message = get(&["Event", "Content", "Action"]).unwrap_or("").to_string();
+        if let Some(resource) = resource {
+            if !message.contains(resource) {
+                message = format!("{} {}", message, resource).trim().to_string();
+            }
+        }
 
-        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
-        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
-        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
-        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
-        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
-        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
-        let thread_id = caps.get(7).unwrap().as_str();
-        let command_type = caps.get(8).unwrap().as_str();
-        let query = caps.get(9).unwrap().as_str();
+        let mut attrs = BTreeMap::new();
+        for (key, value) in [("level", level), ("user", user), ("ip", ip), ("computer", get(&["Computer name"])),
+            ("resource", resource), ("size", get(&["File size"])), ("action", get(&["Action"]))] {
+            if let Some(value) = value {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        let daemon = match get(&["Connection type", "Log", "Category"]) {
+            Some(category) => category.to_lowercase(),
+            None if qnap => "qnap".to_string(),
+            None => "synology".to_string(),
+        };
 
         Ok(LogEntry {
-            year,
-            month,
-            day,
-            hour,
-            minute,
-            second,
-            host: format!("thread_{}", thread_id),
-            daemon: command_type.to_string(),
-            log_entry: query.to_string(),
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: ip.unwrap_or("localhost").to_string(),
+            daemon,
+            log_entry: message,
+            severity: level.and_then(severity_from_name),
+            attrs,
         })
     }
 
     fn name(&self) -> &'static str {
-        "MySQL-General"
+        "NAS-CSV"
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return true;
+        }
+        let fields = split_csv(line, ',');
+        if Self::is_header(&fields) {
+            *self.columns.lock().unwrap() = fields.into_iter().map(|f| f.trim().to_string()).collect();
+            return true;
+        }
+        false
     }
 }
 
-pub struct PostgresqlParser;
+/// Microsoft 365 Unified Audit Log: CSV exports from the Purview audit
+/// search or `Search-UnifiedAuditLog` ("CreationDate,UserIds,Operations,AuditData",
+/// newer exports with RecordId, RecordType, Operation and UserId columns)
+/// and the AuditData records themselves, one JSON object per line as the
+/// Management Activity API returns them. AuditData is flattened into the
+/// structured fields; Name/Value lists such as `Parameters` of
+/// `New-InboxRule` or `ExtendedProperties` of sign-ins become one field per
+/// name ("Parameters.ForwardTo")
+pub struct UalParser {
+    columns: Mutex<Vec<String>>,
+}
 
-impl LogParser for PostgresqlParser {
+impl Default for UalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UalParser {
+    pub fn new() -> Self {
+        Self {
+            columns: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn is_header(fields: &[String]) -> bool {
+        fields.iter().any(|f| f == "CreationDate") && fields.iter().any(|f| f == "AuditData")
+    }
+
+    /// UAL times are UTC: "2024-03-05T10:00:00", with fractions or a "Z" in
+    /// newer exports and "3/5/2024 10:00:00 AM" in older ones
+    fn timestamp(text: &str) -> Option<NaiveDateTime> {
+        let text = text.trim();
+        let utc = DateTime::parse_from_rfc3339(text).ok().map(|dt| dt.naive_utc())
+            .or_else(|| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok())
+            .or_else(|| NaiveDateTime::parse_from_str(text, "%m/%d/%Y %I:%M:%S %p").ok())
+            .or_else(|| NaiveDateTime::parse_from_str(text, "%m/%d/%Y %H:%M:%S").ok())?;
+        Some(utc.and_utc().with_timezone(&Local).naive_local())
+    }
+
+    /// The address of "203.0.113.5", "203.0.113.5:51234" or "[2001:db8::1]:443"
+    fn client_ip(text: &str) -> &str {
+        if let Some(rest) = text.strip_prefix('[') {
+            return rest.split(']').next().unwrap_or(rest);
+        }
+        match text.rsplit_once(':') {
+            Some((ip, port)) if !ip.contains(':') && port.chars().all(|c| c.is_ascii_digit()) => ip,
+            _ => text,
+        }
+    }
+
+    fn flatten(audit: &serde_json::Value, attrs: &mut BTreeMap<String, String>) {
+        let object = match audit.as_object() {
+            Some(object) => object,
+            None => return,
+        };
+        for (key, value) in object {
+            let pairs = value.as_array().filter(|items| !items.is_empty() && items.iter().all(|i| i.get("Name").is_some()));
+            match pairs {
+                Some(items) => {
+                    for item in items {
+                        let name = item["Name"].as_str().unwrap_or("");
+                        match &item["Value"] {
+                            serde_json::Value::String(s) => {
+                                attrs.insert(format!("{}.{}", key, name), s.clone());
+                            }
+                            serde_json::Value::Null => {}
+                            other => {
+                                attrs.insert(format!("{}.{}", key, name), other.to_string());
+                            }
+                        }
+                    }
+                }
+                None => flatten_json(key, value, attrs),
+            }
+        }
+    }
+}
+
+impl LogParser for UalParser {
     fn is_type(&self, line: &str) -> bool {
-        // PostgreSQL log: YYYY-MM-DD HH:MM:SS.mmm TZ [pid] user@database LEVEL: message
-        let re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d+ \w+ \[\d+\] \S+@\S+ (LOG|ERROR|WARNING|FATAL|PANIC|DEBUG|INFO|NOTICE|STATEMENT):").unwrap();
-        re.is_match(line)
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('{') {
+            // {"CreationTime":"2024-03-05T10:00:00","Id":"...","Operation":"MailItemsAccessed","Workload":"Exchange",...}
+            return trimmed.contains("\"CreationTime\"") && trimmed.contains("\"Operation\"") && trimmed.contains("\"Workload\"");
+        }
+        Self::is_header(&split_csv(line, ',')) || (line.contains("{\"\"CreationTime\"\"") && line.contains("\"\"Operation\"\""))
+    }
+
+    fn consume_header(&self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return true;
+        }
+        let fields = split_csv(line, ',');
+        if Self::is_header(&fields) {
+            *self.columns.lock().unwrap() = fields.into_iter().map(|f| f.trim().to_string()).collect();
+            return true;
+        }
+        false
     }
 
     fn parse(&self, line: &str) -> Result<LogEntry> {
-        // Format: 2023-11-14 10:30:45.123 UTC [12345] postgres@testdb LOG: message
-        let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2}):(\d{2})\.\d+ \w+ \[(\d+)\] (\S+)@(\S+) (\w+):\s*(.*)$").unwrap();
-        let caps = re.captures(line).ok_or_else(|| anyhow!("Failed to parse PostgreSQL log"))?;
+        let mut record: HashMap<String, String> = HashMap::new();
+        let audit: serde_json::Value = if line.trim_start().starts_with('{') {
+            serde_json::from_str(line)?
+        } else {
+            let fields = split_csv(line, ',');
+            let stored = self.columns.lock().unwrap();
+            let names: Vec<&str> = if stored.is_empty() {
+                vec!["CreationDate", "UserIds", "Operations", "AuditData"]
+            } else {
+                stored.iter().map(|c| c.as_str()).collect()
+            };
+            for (name, value) in names.into_iter().zip(fields) {
+                record.insert(name.to_string(), value);
+            }
+            record.get("AuditData").and_then(|data| serde_json::from_str(data).ok()).unwrap_or(serde_json::Value::Null)
+        };
+        let field = |audit_key: &str, columns: &[&str]| audit[audit_key].as_str().map(|s| s.to_string())
+            .or_else(|| columns.iter().find_map(|c| record.get(*c).cloned()))
+            .filter(|s| !s.is_empty());
+
+        let time = field("CreationTime", &["CreationDate"]).unwrap_or_default();
+        let timestamp = Self::timestamp(&time)
+            .ok_or_else(|| anyhow!("Unrecognized audit log timestamp '{}'", time))?;
+        let operation = field("Operation", &["Operation", "Operations"]).unwrap_or_else(|| "-".to_string());
+        let user = field("UserId", &["UserId", "UserIds"]);
+        let client = field("ClientIP", &[]).or_else(|| field("ActorIpAddress", &[])).or_else(|| field("ClientIPAddress", &[]));
+        let client = client.as_deref().map(Self::client_ip);
+        let workload = field("Workload", &[]);
+
+        let mut attrs = BTreeMap::new();
+        Self::flatten(&audit, &mut attrs);
+        for column in ["RecordId", "RecordType"] {
+            if let Some(value) = record.get(column).filter(|v| !v.is_empty()) {
+                attrs.entry(column.to_string()).or_insert_with(|| value.clone());
+            }
+        }
+        attrs.insert("Operation".to_string(), operation.clone());
 
-        let year: i32 = caps.get(1).unwrap().as_str().parse()?;
-        let month: u32 = caps.get(2).unwrap().as_str().parse()?;
-        let day: u32 = caps.get(3).unwrap().as_str().parse()?;
-        let hour: u32 = caps.get(4).unwrap().as_str().parse()?;
-        let minute: u32 = caps.get(5).unwrap().as_str().parse()?;
-        let second: u32 = caps.get(6).unwrap().as_str().parse()?;
-        let _pid = caps.get(7).unwrap().as_str();
-        let user = caps.get(8).unwrap().as_str();
-        let database = caps.get(9).unwrap().as_str();
-        let level = caps.get(10).unwrap().as_str();
-        let message = caps.get(11).unwrap().as_str();
+        let mut message = operation.clone();
+        if let Some(user) = &user {
+            message.push_str(&format!(" by {}", user));
+        }
+        if let Some(client) = client {
+            message.push_str(&format!(" from {}", client));
+        }
+        if let Some(status) = field("ResultStatus", &[]) {
+            message.push_str(&format!(" {}", status));
+        }
+        if let Some(object) = field("ObjectId", &[]) {
+            message.push_str(&format!(" on {}", object));
+        }
+
+        let mut attrs = with_user(attrs, user.as_deref().unwrap_or(""));
+        if let Some(client) = client {
+            attrs = with_src(attrs, client);
+        }
 
         Ok(LogEntry {
-            year,
-            month,
-            day,
-            hour,
-            minute,
-            second,
-            host: format!("{}@{}", user, database),
-            daemon: level.to_string(),
-            log_entry: message.to_string(),
+            year: timestamp.year(),
+            month: timestamp.month(),
+            day: timestamp.day(),
+            hour: timestamp.hour(),
+            minute: timestamp.minute(),
+            second: timestamp.second(),
+            host: workload.unwrap_or_else(|| "m365".to_string()),
+            daemon: operation,
+            log_entry: message,
+            severity: None,
+            attrs,
         })
     }
 
     fn name(&self) -> &'static str {
-        "PostgreSQL"
+        "O365-UAL"
     }
 }
 
@@ -703,16 +7272,68 @@ impl LogParser for RawParser {
     }
 }
 
+/// Joins continuation lines into records and parses each record once the
+/// next one starts, so input can be parsed as it arrives
+pub struct RecordAssembler<'a> {
+    parser: &'a dyn LogParser,
+    record: Option<String>,
+    previous: String,
+}
+
+impl<'a> RecordAssembler<'a> {
+    pub fn new(parser: &'a dyn LogParser) -> Self {
+        Self {
+            parser,
+            record: None,
+            previous: String::new(),
+        }
+    }
+
+    /// Add a line; returns the previous record's entry when this line starts a new one
+    pub fn push(&mut self, line: String) -> Option<LogEntry> {
+        if self.parser.consume_header(&line) {
+            return None;
+        }
+
+        let mut finished = None;
+        match self.record.as_mut() {
+            Some(record) if self.parser.is_continuation(&self.previous, &line) => {
+                record.push('\n');
+                record.push_str(&line);
+            }
+            _ => finished = self.record.replace(line.clone()),
+        }
+        self.previous = line;
+
+        finished.map(|record| self.parse(&record))
+    }
+
+    /// The entry of the record still being assembled, if any
+    pub fn finish(&mut self) -> Option<LogEntry> {
+        self.record.take().map(|record| self.parse(&record))
+    }
+
+    fn parse(&self, record: &str) -> LogEntry {
+        self.parser.parse(record).unwrap_or_else(|_| {
+            // Try to parse as abnormal entry
+            let mut entry = LogEntry::new();
+            entry.set_abnormal(record);
+            entry
+        })
+    }
+}
+
 pub struct CrunchLog {
     pub entries: Vec<LogEntry>,
     pub parser_type: String,
 }
 
 impl CrunchLog {
+    /// Standard input is read as a stream, so a pipe is parsed as it arrives
     pub fn from_stdin() -> Result<Self> {
         let stdin = std::io::stdin();
         let reader = BufReader::new(stdin.lock());
-        Self::from_reader(reader)
+        Self::from_stream(reader)
     }
 
     pub fn from_file(filename: &str) -> Result<Self> {
@@ -728,10 +7349,56 @@ impl CrunchLog {
             });
         }
 
-        // Otherwise, use text-based parsing
+        // Otherwise, use text-based parsing; named pipes are streamed like stdin
         let file = File::open(filename)?;
+        let is_fifo = Self::is_fifo(&file);
         let reader = BufReader::new(file);
-        Self::from_reader(reader)
+        if is_fifo {
+            Self::from_stream(reader)
+        } else {
+            Self::from_reader(reader)
+        }
+    }
+
+    #[cfg(unix)]
+    fn is_fifo(file: &File) -> bool {
+        use std::os::unix::fs::FileTypeExt;
+        file.metadata().map(|m| m.file_type().is_fifo()).unwrap_or(false)
+    }
+
+    #[cfg(not(unix))]
+    fn is_fifo(_file: &File) -> bool {
+        false
+    }
+
+    /// Detect the format from the first `DETECT_LINES` lines, then parse the
+    /// rest line by line without holding the whole input
+    fn from_stream<R: BufRead>(reader: R) -> Result<Self> {
+        let mut lines = reader.lines();
+        let head: Vec<String> = lines.by_ref().take(DETECT_LINES).collect::<std::io::Result<Vec<_>>>()?;
+        if head.is_empty() {
+            return Err(anyhow!("No data found"));
+        }
+
+        let parser = Self::detect_format(&head);
+        let mut assembler = RecordAssembler::new(parser.as_ref());
+        let mut entries = Vec::new();
+        for line in head.into_iter().map(Ok).chain(lines) {
+            entries.extend(assembler.push(line?));
+        }
+        entries.extend(assembler.finish());
+
+        Ok(CrunchLog {
+            entries,
+            parser_type: parser.name().to_string(),
+        })
+    }
+
+    /// The parser that fits a sample of the lines best, falling back to raw lines
+    pub fn detect_format(lines: &[String]) -> Box<dyn LogParser> {
+        let mut parsers = Self::parsers();
+        let idx = Self::detect_parser(lines, &parsers).unwrap_or(parsers.len() - 1);
+        parsers.swap_remove(idx)
     }
 
     fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
@@ -742,42 +7409,133 @@ impl CrunchLog {
         }
 
         // Try to detect the log format
+        let parsers = Self::parsers();
+        let parser_idx = Self::detect_parser(&lines, &parsers)?;
+        Ok(Self::parse_lines(lines, parsers[parser_idx].as_ref()))
+    }
+
+    /// Every text format, in detection order; the raw fallback comes last
+    pub fn parsers() -> Vec<Box<dyn LogParser>> {
         // Order matters: more specific parsers should come first
-        let parsers: Vec<Box<dyn LogParser>> = vec![
+        vec![
             Box::new(AwsElbParser),
             Box::new(AwsAlbParser),
+            Box::new(AwsNlbParser),
+            Box::new(VpcFlowParser::new()),
             Box::new(MysqlGeneralParser),
+            Box::new(MysqlSlowParser),
+            Box::new(MysqlErrorParser),
             Box::new(PostgresqlParser),
+            Box::new(MongoDbParser),
+            Box::new(CephParser),
+            Box::new(MacUnifiedLogParser),
+            Box::new(LogcatParser),
+            Box::new(WindowsFirewallParser::new()),
+            Box::new(UalParser::new()),
+            Box::new(NasCsvParser::new()),
+            Box::new(IisFtpParser::new()),
+            Box::new(XferlogParser),
+            Box::new(SambaAuditParser),
+            Box::new(CupsAccessParser),
+            Box::new(CupsErrorParser::new()),
+            Box::new(AsteriskParser),
+            Box::new(SlurmParser),
+            Box::new(PbsParser),
+            Box::new(RailsParser),
+            Box::new(PhpFpmParser),
+            Box::new(PhpErrorParser),
+            Box::new(JvmGcParser),
+            Box::new(DmesgParser),
+            Box::new(VarnishNcsaParser),
+            Box::new(VmkernelParser),
+            Box::new(VmwareServiceParser),
+            Box::new(PveTaskIndexParser),
+            Box::new(GelfParser),
+            Box::new(SuricataEveParser),
+            Box::new(SnortFastParser),
+            Box::new(ModSecurityAuditParser),
+            Box::new(ZeekParser::new()),
+            Box::new(LeefParser),
+            Box::new(CiscoAsaParser),
+            Box::new(PanOsParser),
+            Box::new(FortiGateParser),
+            Box::new(CheckPointParser),
+            Box::new(JuniperSrxParser),
+            Box::new(FilterlogParser),
+            Box::new(Fail2banParser),
+            Box::new(BindQueryParser),
+            Box::new(WindowsDnsParser),
+            Box::new(KeaParser),
+            Box::new(OpenVpnParser),
+            Box::new(FreeRadiusParser),
+            Box::new(CloudflareParser),
+            Box::new(GcpLogParser),
+            Box::new(AzureLogParser),
+            Box::new(HerokuParser),
+            Box::new(CriParser),
+            Box::new(KafkaParser),
+            Box::new(ZooKeeperParser),
+            Box::new(CassandraParser),
+            Box::new(PythonLogParser),
+            Box::new(KeycloakParser),
+            Box::new(JavaLogParser),
+            Box::new(GoLogParser),
+            Box::new(ZapConsoleParser),
+            Box::new(BuildLogParser::new()),
+            Box::new(KubectlParser),
+            Box::new(KlogParser),
             Box::new(RSyslogParser),
+            Box::new(EximParser),
+            Box::new(MailLogParser),
+            Box::new(DovecotParser),
+            Box::new(DnsmasqParser),
+            Box::new(DhcpdParser),
+            Box::new(WireGuardParser),
             Box::new(JournalctlParser),
+            Box::new(EnvoyParser),
+            Box::new(TraefikParser),
             Box::new(ApacheCombinedParser),
             Box::new(ApacheCommonParser),
             Box::new(SyslogParser),
             Box::new(SecureLogParser),
             Box::new(RawParser),
-        ];
+        ]
+    }
 
-        let parser_idx = Self::detect_parser(&lines, &parsers)?;
-        let detected_parser = &parsers[parser_idx];
+    /// Read standard input with the given parser instead of detecting one
+    pub fn from_stdin_with_parser(parser: &dyn LogParser) -> Result<Self> {
+        let stdin = std::io::stdin();
+        let lines: Vec<String> = stdin.lock().lines().collect::<std::io::Result<Vec<_>>>()?;
+        if lines.is_empty() {
+            return Err(anyhow!("No data found"));
+        }
+        Ok(Self::parse_lines(lines, parser))
+    }
+
+    /// Read a text log with the given parser instead of detecting one
+    pub fn from_file_with_parser(filename: &str, parser: &dyn LogParser) -> Result<Self> {
+        let reader = BufReader::new(File::open(filename)?);
+        let lines: Vec<String> = reader.lines().collect::<std::io::Result<Vec<_>>>()?;
+        if lines.is_empty() {
+            return Err(anyhow!("No data found"));
+        }
+        Ok(Self::parse_lines(lines, parser))
+    }
+
+    fn parse_lines(lines: Vec<String>, detected_parser: &dyn LogParser) -> Self {
         let parser_type = detected_parser.name().to_string();
 
+        let mut assembler = RecordAssembler::new(detected_parser);
         let mut entries = Vec::new();
         for line in lines {
-            match detected_parser.parse(&line) {
-                Ok(entry) => entries.push(entry),
-                Err(_) => {
-                    // Try to parse as abnormal entry
-                    let mut entry = LogEntry::new();
-                    entry.set_abnormal(&line);
-                    entries.push(entry);
-                }
-            }
+            entries.extend(assembler.push(line));
         }
+        entries.extend(assembler.finish());
 
-        Ok(CrunchLog {
+        CrunchLog {
             entries,
             parser_type,
-        })
+        }
     }
 
     fn detect_parser(lines: &[String], parsers: &[Box<dyn LogParser>]) -> Result<usize> {
@@ -786,10 +7544,9 @@ impl CrunchLog {
 
         for _ in 0..sample_size {
             let idx = rand::random::<usize>() % lines.len();
-            let line = &lines[idx];
 
             for (i, parser) in parsers.iter().enumerate() {
-                if parser.is_type(line) {
+                if parser.is_type(Self::record_start(lines, idx, parser.as_ref())) {
                     scores[i] += 1;
                 }
             }
@@ -809,34 +7566,84 @@ impl CrunchLog {
         Ok(parsers.len() - 1)
     }
 
+    /// First line of the record the given line belongs to, as the parser sees it
+    fn record_start<'a>(lines: &'a [String], idx: usize, parser: &dyn LogParser) -> &'a str {
+        let mut start = idx;
+        while start > 0 && idx - start < MAX_RECORD_LINES && parser.is_continuation(&lines[start - 1], &lines[start]) {
+            start -= 1;
+        }
+        &lines[start]
+    }
+
     fn entry_to_datetime(entry: &LogEntry) -> DateTime<Local> {
-        let naive_date = NaiveDate::from_ymd_opt(entry.year, entry.month, entry.day)
-            .unwrap_or_else(|| NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
-        let naive_time = NaiveTime::from_hms_opt(entry.hour, entry.minute, entry.second)
-            .unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-        let naive_datetime = NaiveDateTime::new(naive_date, naive_time);
-        DateTime::from_naive_utc_and_offset(naive_datetime, *Local::now().offset())
+        DateTime::from_naive_utc_and_offset(entry.naive_datetime(), *Local::now().offset())
+    }
+
+    /// Shift all parsed timestamps by the given number of seconds
+    pub fn shift_time(&mut self, seconds: i64) {
+        for entry in &mut self.entries {
+            if !entry.is_undated() {
+                entry.shift_seconds(seconds);
+            }
+        }
+    }
+
+    /// Record the input file on every entry, so merged logs can be split by source
+    pub fn set_input(&mut self, name: &str) {
+        for entry in &mut self.entries {
+            entry.attrs.insert(INPUT_ATTR.to_string(), name.to_string());
+        }
+    }
+
+    /// Collapse runs of identical consecutive entries, returning how many were removed
+    pub fn dedupe(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.dedup();
+        before - self.entries.len()
+    }
+
+    /// See `LogEntry::normalize_daemon`
+    pub fn normalize_daemons(&mut self) {
+        for entry in &mut self.entries {
+            entry.normalize_daemon();
+        }
+    }
+
+    /// Merge entries of another log into this one
+    pub fn append(&mut self, other: CrunchLog) {
+        if !self.parser_type.split(", ").any(|t| t == other.parser_type) {
+            self.parser_type = format!("{}, {}", self.parser_type, other.parser_type);
+        }
+        self.entries.extend(other.entries);
+    }
+
+    /// Entries without a timestamp, see `LogEntry::is_undated`
+    pub fn undated_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_undated()).count()
     }
 
     pub fn filter_by_time(&mut self, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>) {
-        self.entries.retain(|entry| {
-            let entry_dt = Self::entry_to_datetime(entry);
+        self.entries.retain(|entry| Self::in_time_range(entry, from, to));
+    }
 
-            // Check 'from' filter
-            if let Some(from_dt) = from {
-                if entry_dt < from_dt {
-                    return false;
-                }
+    /// Whether an entry passes the `from`/`to` filters
+    pub fn in_time_range(entry: &LogEntry, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>) -> bool {
+        let entry_dt = Self::entry_to_datetime(entry);
+
+        // Check 'from' filter
+        if let Some(from_dt) = from {
+            if entry_dt < from_dt {
+                return false;
             }
+        }
 
-            // Check 'to' filter
-            if let Some(to_dt) = to {
-                if entry_dt > to_dt {
-                    return false;
-                }
+        // Check 'to' filter
+        if let Some(to_dt) = to {
+            if entry_dt > to_dt {
+                return false;
             }
+        }
 
-            true
-        });
+        true
     }
 }
@@ -0,0 +1,131 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use anyhow::{Result, anyhow};
+use chrono::{Local, TimeZone};
+use log::info;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Label names and values of a stream
+type Labels = BTreeMap<String, String>;
+
+/// Entries sent per push unless set otherwise
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Pushes entries to Grafana Loki, one stream per host, daemon and severity.
+/// The line is the message; the labels carry the rest of the normalized line
+pub struct LokiExporter {
+    url: String,
+    tenant: Option<String>,
+    labels: Vec<(String, String)>,
+    batch_size: usize,
+}
+
+impl LokiExporter {
+    /// A Loki address such as "http://loki:3100"; the push path is added
+    /// unless the URL already ends with it
+    pub fn new(endpoint: &str) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with("/loki/api/v1/push") {
+            endpoint.to_string()
+        } else {
+            format!("{}/loki/api/v1/push", endpoint)
+        };
+        Self { url, tenant: None, labels: vec![("job".to_string(), "glancelog".to_string())], batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    /// The tenant for multi-tenant Loki (X-Scope-OrgID)
+    pub fn set_tenant(&mut self, tenant: Option<String>) {
+        self.tenant = tenant;
+    }
+
+    /// An extra label for every stream as "name=value", replacing the default
+    /// job="glancelog" when it names job
+    pub fn add_label(&mut self, spec: &str) -> Result<()> {
+        let (name, value) = spec.split_once('=')
+            .ok_or_else(|| anyhow!("Invalid label '{}', expected name=value", spec))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(anyhow!("Invalid label name '{}'", name));
+        }
+        self.labels.retain(|(n, _)| n != name);
+        self.labels.push((name.to_string(), value.trim().to_string()));
+        Ok(())
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Push all entries of the log, returning how many Loki accepted; entries
+    /// without a timestamp are left out
+    pub fn export(&self, log: &CrunchLog) -> Result<usize> {
+        let mut sent = 0;
+        for batch in log.entries.chunks(self.batch_size) {
+            let payload = self.payload(batch);
+            self.post(&payload)?;
+            sent += payload["streams"].as_array().iter().flat_map(|streams| streams.iter())
+                .map(|stream| stream["values"].as_array().map(|v| v.len()).unwrap_or(0))
+                .sum::<usize>();
+            info!("Pushed {} of {} entries to {}", sent, log.entries.len(), self.url);
+        }
+        Ok(sent)
+    }
+
+    /// A push request for the entries, streams in label order and each
+    /// stream's values in time order
+    pub fn payload(&self, entries: &[LogEntry]) -> Value {
+        let mut streams: BTreeMap<Labels, Vec<(i64, String)>> = BTreeMap::new();
+        for entry in entries {
+            if entry.is_undated() {
+                continue;
+            }
+            let time = match Local.from_local_datetime(&entry.naive_datetime()).earliest().and_then(|t| t.timestamp_nanos_opt()) {
+                Some(time) => time,
+                None => continue,
+            };
+            let message = entry.log_entry.strip_prefix(": ").unwrap_or(&entry.log_entry);
+            streams.entry(self.stream_labels(entry)).or_default().push((time, message.to_string()));
+        }
+
+        let streams: Vec<Value> = streams.into_iter()
+            .map(|(labels, mut values)| {
+                values.sort_by_key(|(time, _)| *time);
+                json!({
+                    "stream": labels,
+                    "values": values.into_iter().map(|(time, line)| json!([time.to_string(), line])).collect::<Vec<Value>>(),
+                })
+            })
+            .collect();
+        json!({"streams": streams})
+    }
+
+    /// host, daemon without the PID (which would make a stream per process),
+    /// severity when known, and the extra labels
+    fn stream_labels(&self, entry: &LogEntry) -> Labels {
+        let mut labels: Labels = self.labels.iter().cloned().collect();
+        labels.insert("host".to_string(), entry.host.clone());
+        labels.insert("daemon".to_string(), entry.program().to_string());
+        if let Some(severity) = entry.severity_name() {
+            labels.insert("severity".to_string(), severity.to_string());
+        }
+        labels
+    }
+
+    fn post(&self, payload: &Value) -> Result<()> {
+        let mut request = ureq::post(&self.url).timeout(Duration::from_secs(60));
+        if let Some(tenant) = &self.tenant {
+            request = request.set("X-Scope-OrgID", tenant);
+        }
+        request.send_json(payload).map_err(|e| match e {
+            ureq::Error::Status(code, response) => anyhow!("Push to {} failed with HTTP {}: {}",
+                self.url, code, response.into_string().unwrap_or_default().trim()),
+            e => anyhow!("Push to {} failed: {}", self.url, e),
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// One delivery attempt of a queued message to a single recipient
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub queue_id: String,
+    pub from: String,
+    pub to: String,
+    pub relay: String,
+    pub status: String,
+}
+
+pub struct MailReport {
+    attempts: Vec<DeliveryAttempt>,
+    messages: usize,
+    /// postfix "NOQUEUE: reject:" lines by SMTP code and reason; these never
+    /// got a queue ID, so they are not counted as messages
+    rejects: HashMap<String, usize>,
+}
+
+impl MailReport {
+    /// Correlate postfix/sendmail lines by queue ID into delivery attempts
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let queue_re = Regex::new(r"^([0-9A-Za-z]{6,}): (.*)$").unwrap();
+        let field_re = Regex::new(r"(\w+)=(<[^>]*>|[^,]*)").unwrap();
+        // "NOQUEUE: reject: RCPT from unknown[192.0.2.1]: 554 5.7.1 <bob@example.com>: Relay access denied; from=<...> to=<...>"
+        let reject_re = Regex::new(r"^NOQUEUE: reject: \S+ from [^:]*: (\d{3}) [\d.]+ (?:<[^>]*>: )?([^;]*)").unwrap();
+
+        let mut senders: HashMap<String, String> = HashMap::new();
+        let mut attempts = Vec::new();
+        let mut rejects: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            if entry.log_entry.starts_with("NOQUEUE:") {
+                if let Some(caps) = reject_re.captures(&entry.log_entry) {
+                    *rejects.entry(format!("{} {}", &caps[1], caps[2].trim())).or_insert(0) += 1;
+                }
+                continue;
+            }
+            let caps = match queue_re.captures(&entry.log_entry) {
+                Some(caps) => caps,
+                None => continue,
+            };
+            let queue_id = caps[1].to_string();
+
+            let mut fields: HashMap<&str, &str> = HashMap::new();
+            for field in field_re.captures_iter(caps.get(2).unwrap().as_str()) {
+                let key = field.get(1).unwrap().as_str();
+                let value = field.get(2).unwrap().as_str().trim();
+                fields.entry(key).or_insert(value);
+            }
+
+            if let Some(from) = fields.get("from") {
+                senders.insert(queue_id.clone(), Self::address(from));
+            }
+
+            if let Some(to) = fields.get("to") {
+                // postfix: status=sent (250 ok); sendmail: stat=Sent (...)
+                let status = fields.get("status")
+                    .or_else(|| fields.get("stat"))
+                    .and_then(|s| s.split_whitespace().next())
+                    .map(|s| s.trim_end_matches(':').to_lowercase())
+                    .filter(|s| matches!(s.as_str(), "sent" | "deferred" | "bounced" | "expired"))
+                    .unwrap_or_else(|| Self::status_from_dsn(fields.get("dsn").copied()));
+
+                attempts.push(DeliveryAttempt {
+                    from: senders.get(&queue_id).cloned().unwrap_or_else(|| "-".to_string()),
+                    queue_id,
+                    to: Self::address(to),
+                    relay: fields.get("relay").map(|r| r.to_string()).unwrap_or_else(|| "-".to_string()),
+                    status,
+                });
+            }
+        }
+
+        Self {
+            attempts,
+            messages: senders.len(),
+            rejects,
+        }
+    }
+
+    fn address(value: &str) -> String {
+        let address = value.trim_start_matches('<').trim_end_matches('>');
+        if address.is_empty() {
+            "<>".to_string()
+        } else {
+            address.to_string()
+        }
+    }
+
+    fn status_from_dsn(dsn: Option<&str>) -> String {
+        match dsn.and_then(|d| d.chars().next()) {
+            Some('2') => "sent",
+            Some('4') => "deferred",
+            Some('5') => "bounced",
+            _ => "unknown",
+        }
+        .to_string()
+    }
+
+    pub fn attempts(&self) -> &[DeliveryAttempt] {
+        &self.attempts
+    }
+
+    /// Share of delivery attempts that bounced, in percent
+    pub fn bounce_rate(&self) -> f64 {
+        if self.attempts.is_empty() {
+            return 0.0;
+        }
+        let bounced = self.attempts.iter().filter(|a| a.status == "bounced").count();
+        bounced as f64 * 100.0 / self.attempts.len() as f64
+    }
+
+    /// Number of postfix NOQUEUE rejects
+    pub fn rejected(&self) -> usize {
+        self.rejects.values().sum()
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.attempts.is_empty() && self.rejects.is_empty() {
+            println!("No mail delivery attempts found");
+            return;
+        }
+
+        println!("Messages: {}\tDelivery attempts: {}\tBounce rate: {:.1}%\tRejected: {}",
+            self.messages, self.attempts.len(), self.bounce_rate(), self.rejected());
+        println!();
+
+        print_top("Reject reasons", self.rejects.clone(), limit);
+
+        let mut statuses: HashMap<String, usize> = HashMap::new();
+        let mut domains: HashMap<String, usize> = HashMap::new();
+        let mut relays: HashMap<String, usize> = HashMap::new();
+        let mut senders: HashMap<String, usize> = HashMap::new();
+
+        for attempt in &self.attempts {
+            *statuses.entry(attempt.status.clone()).or_insert(0) += 1;
+            let domain = attempt.to.rsplit('@').next().unwrap_or(&attempt.to).to_lowercase();
+            *domains.entry(domain).or_insert(0) += 1;
+            *relays.entry(attempt.relay.clone()).or_insert(0) += 1;
+            *senders.entry(attempt.from.clone()).or_insert(0) += 1;
+        }
+
+        print_top("Status", statuses, limit);
+        print_top("Top destinations", domains, limit);
+        print_top("Top relays", relays, limit);
+        print_top("Top senders", senders, limit);
+
+        let bounced: Vec<_> = self.attempts.iter().filter(|a| a.status == "bounced").collect();
+        if !bounced.is_empty() {
+            println!("Bounced deliveries:");
+            for attempt in bounced.iter().take(limit) {
+                println!("{}\t{} -> {}\t{}", attempt.queue_id, attempt.from, attempt.to, attempt.relay);
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::filter::Filter;
+use crate::log_entry::CrunchLog;
+use crate::split::SplitKey;
+use std::collections::HashMap;
+
+/// Entry counts for each pair of keys, e.g. hosts against daemons. Keys are
+/// scrubbed with the same filters as the host and daemon reports
+pub struct Matrix {
+    rows: SplitKey,
+    columns: SplitKey,
+    counts: HashMap<(String, String), usize>,
+}
+
+impl Matrix {
+    pub fn from_log(log: &CrunchLog, rows: (SplitKey, &Filter), columns: (SplitKey, &Filter)) -> Self {
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for entry in &log.entries {
+            let row = rows.1.scrub(rows.0.value(entry));
+            let column = columns.1.scrub(columns.0.value(entry));
+            *counts.entry((row, column)).or_insert(0) += 1;
+        }
+
+        Self {
+            rows: rows.0,
+            columns: columns.0,
+            counts,
+        }
+    }
+
+    pub fn count(&self, row: &str, column: &str) -> usize {
+        self.counts.get(&(row.to_string(), column.to_string())).copied().unwrap_or(0)
+    }
+
+    fn axis_name(key: SplitKey) -> &'static str {
+        match key {
+            SplitKey::Daemon => "daemon",
+            SplitKey::Host => "host",
+        }
+    }
+
+    /// Keys of one axis with their totals, largest first
+    fn totals(&self, row_axis: bool) -> Vec<(String, usize)> {
+        let mut totals: HashMap<&str, usize> = HashMap::new();
+        for ((row, column), count) in &self.counts {
+            let key = if row_axis { row } else { column };
+            *totals.entry(key.as_str()).or_insert(0) += count;
+        }
+        let mut totals: Vec<(String, usize)> = totals.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        totals
+    }
+
+    /// Print the top `limit` rows against the top `limit` columns; the rest of
+    /// each row is summed up under "other"
+    pub fn display(&self, limit: usize) {
+        if self.counts.is_empty() {
+            println!("No entries found");
+            return;
+        }
+
+        let rows = self.totals(true);
+        let columns = self.totals(false);
+        let shown: Vec<&str> = columns.iter().take(limit).map(|(c, _)| c.as_str()).collect();
+        let hidden = columns.len() > shown.len();
+
+        println!("Entries by {} and {} ({} x {}, top {} of each):",
+            Self::axis_name(self.rows), Self::axis_name(self.columns), rows.len(), columns.len(), limit);
+        let mut header = format!("{}\\{}", Self::axis_name(self.rows), Self::axis_name(self.columns));
+        for column in &shown {
+            header.push('\t');
+            header.push_str(column);
+        }
+        if hidden {
+            header.push_str("\tother");
+        }
+        header.push_str("\ttotal");
+        println!("{}", header);
+
+        for (row, total) in rows.iter().take(limit) {
+            let mut line = row.clone();
+            let mut listed = 0;
+            for column in &shown {
+                let count = self.count(row, column);
+                listed += count;
+                line.push('\t');
+                line.push_str(&if count == 0 { "-".to_string() } else { count.to_string() });
+            }
+            if hidden {
+                line.push_str(&format!("\t{}", total - listed));
+            }
+            line.push_str(&format!("\t{}", total));
+            println!("{}", line);
+        }
+        if rows.len() > limit {
+            println!("({} more {}s)", rows.len() - limit, Self::axis_name(self.rows));
+        }
+        println!();
+    }
+}
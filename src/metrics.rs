@@ -0,0 +1,123 @@
+use crate::log_entry::LogEntry;
+use anyhow::{Result, anyhow};
+use log::debug;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::net::{TcpStream, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Keeps statsd datagrams under a typical MTU
+const MAX_DATAGRAM: usize = 1400;
+
+/// Where counters are sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsTarget {
+    /// statsd over UDP, "statsd://host:8125"
+    Statsd(String),
+    /// Graphite plaintext protocol over TCP, "graphite://host:2003"
+    Graphite(String),
+}
+
+impl MetricsTarget {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (scheme, address) = spec.split_once("://")
+            .ok_or_else(|| anyhow!("Invalid metrics target '{}', expected statsd://host:port or graphite://host:port", spec))?;
+        let address = address.trim_end_matches('/');
+        let with_port = |port: u16| if address.contains(':') { address.to_string() } else { format!("{}:{}", address, port) };
+        match scheme {
+            "statsd" => Ok(Self::Statsd(with_port(8125))),
+            "graphite" => Ok(Self::Graphite(with_port(2003))),
+            _ => Err(anyhow!("Unknown metrics target '{}', expected statsd:// or graphite://", scheme)),
+        }
+    }
+}
+
+/// Counts entries per daemon and severity and sends the counts of each
+/// interval: `<prefix>.entries`, `<prefix>.daemon.<name>` and
+/// `<prefix>.severity.<name>`
+pub struct MetricsEmitter {
+    target: MetricsTarget,
+    prefix: String,
+    socket: Option<UdpSocket>,
+    counts: BTreeMap<String, u64>,
+}
+
+impl MetricsEmitter {
+    pub fn new(target: MetricsTarget, prefix: &str) -> Result<Self> {
+        let socket = match &target {
+            MetricsTarget::Statsd(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(address).map_err(|e| anyhow!("Cannot reach statsd at {}: {}", address, e))?;
+                Some(socket)
+            }
+            MetricsTarget::Graphite(_) => None,
+        };
+        let prefix = prefix.split('.').map(metric_name).filter(|p| !p.is_empty()).collect::<Vec<_>>().join(".");
+        Ok(Self { target, prefix, socket, counts: BTreeMap::new() })
+    }
+
+    pub fn count(&mut self, entry: &LogEntry) {
+        *self.counts.entry("entries".to_string()).or_insert(0) += 1;
+        let daemon = Some(metric_name(entry.program())).filter(|d| !d.is_empty()).unwrap_or_else(|| "unknown".to_string());
+        *self.counts.entry(format!("daemon.{}", daemon)).or_insert(0) += 1;
+        if let Some(severity) = entry.severity_name() {
+            *self.counts.entry(format!("severity.{}", severity)).or_insert(0) += 1;
+        }
+    }
+
+    fn path(&self, name: &str) -> String {
+        if self.prefix.is_empty() { name.to_string() } else { format!("{}.{}", self.prefix, name) }
+    }
+
+    /// Send the counts since the last flush and start counting from zero
+    pub fn flush(&mut self) -> Result<()> {
+        if self.counts.is_empty() {
+            return Ok(());
+        }
+        let counts = std::mem::take(&mut self.counts);
+
+        match &self.target {
+            MetricsTarget::Statsd(_) => {
+                let socket = self.socket.as_ref().ok_or_else(|| anyhow!("statsd socket is not open"))?;
+                let mut packet = String::new();
+                for (name, count) in &counts {
+                    let line = format!("{}:{}|c", self.path(name), count);
+                    if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_DATAGRAM {
+                        socket.send(packet.as_bytes())?;
+                        packet.clear();
+                    }
+                    if !packet.is_empty() {
+                        packet.push('\n');
+                    }
+                    packet.push_str(&line);
+                }
+                socket.send(packet.as_bytes())?;
+            }
+            MetricsTarget::Graphite(address) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+                let lines: String = counts.iter()
+                    .map(|(name, count)| format!("{} {} {}\n", self.path(name), count, now))
+                    .collect();
+                // A new connection for each flush, so a restarted Graphite is picked up again
+                let addr = address.parse().ok();
+                let mut stream = match addr {
+                    Some(addr) => TcpStream::connect_timeout(&addr, Duration::from_secs(5)),
+                    None => TcpStream::connect(address.as_str()),
+                }.map_err(|e| anyhow!("Cannot connect to Graphite at {}: {}", address, e))?;
+                stream.write_all(lines.as_bytes())?;
+            }
+        }
+        debug!("Sent {} counters to {:?}", counts.len(), self.target);
+        Ok(())
+    }
+}
+
+/// A name safe for a metric path segment: dots and anything but letters,
+/// digits, '-' and '_' become '_'
+fn metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect::<String>()
+        .trim_matches('_')
+        .to_string()
+}
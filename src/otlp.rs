@@ -0,0 +1,134 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use anyhow::{Result, anyhow};
+use chrono::{Local, TimeZone};
+use log::info;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Entries sent per request unless set otherwise
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Sends entries to an OpenTelemetry collector or backend as OTLP/HTTP (JSON
+/// encoding) log records: one resource per host and daemon, with the
+/// timestamp, severity, message body and structured fields of each entry
+pub struct OtlpExporter {
+    url: String,
+    headers: Vec<(String, String)>,
+    batch_size: usize,
+}
+
+impl OtlpExporter {
+    /// An endpoint such as "http://localhost:4318"; "/v1/logs" is added
+    /// unless the URL already names the logs path
+    pub fn new(endpoint: &str) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with("/v1/logs") {
+            endpoint.to_string()
+        } else {
+            format!("{}/v1/logs", endpoint)
+        };
+        Self { url, headers: Vec::new(), batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    /// A request header as "Name=value", e.g. an API key the backend expects
+    pub fn add_header(&mut self, spec: &str) -> Result<()> {
+        let (name, value) = spec.split_once('=')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(|| anyhow!("Invalid header '{}', expected Name=value", spec))?;
+        self.headers.push((name.trim().to_string(), value.trim().to_string()));
+        Ok(())
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send all entries of the log, returning how many were accepted
+    pub fn export(&self, log: &CrunchLog) -> Result<usize> {
+        let mut sent = 0;
+        for batch in log.entries.chunks(self.batch_size) {
+            let mut request = ureq::post(&self.url);
+            for (name, value) in &self.headers {
+                request = request.set(name, value);
+            }
+            request.send_json(Self::payload(batch)).map_err(|e| match e {
+                ureq::Error::Status(code, response) => anyhow!("{} rejected the logs with HTTP {}: {}",
+                    self.url, code, response.into_string().unwrap_or_default().trim()),
+                e => anyhow!("Cannot send logs to {}: {}", self.url, e),
+            })?;
+            sent += batch.len();
+            info!("Sent {} of {} entries to {}", sent, log.entries.len(), self.url);
+        }
+        Ok(sent)
+    }
+
+    /// An ExportLogsServiceRequest for the entries
+    pub fn payload(entries: &[LogEntry]) -> Value {
+        let mut resources: BTreeMap<(&str, &str), Vec<Value>> = BTreeMap::new();
+        for entry in entries {
+            resources.entry((entry.host.as_str(), entry.program())).or_default().push(Self::log_record(entry));
+        }
+
+        let resource_logs: Vec<Value> = resources.into_iter()
+            .map(|((host, service), records)| json!({
+                "resource": {
+                    "attributes": [string_attr("host.name", host), string_attr("service.name", service)],
+                },
+                "scopeLogs": [{
+                    "scope": {"name": "glancelog", "version": env!("CARGO_PKG_VERSION")},
+                    "logRecords": records,
+                }],
+            }))
+            .collect();
+        json!({"resourceLogs": resource_logs})
+    }
+
+    fn log_record(entry: &LogEntry) -> Value {
+        // Entry timestamps are local time; abnormal entries are sent without one
+        let time = Local.from_local_datetime(&entry.naive_datetime()).earliest()
+            .filter(|_| !entry.is_undated())
+            .and_then(|t| t.timestamp_nanos_opt())
+            .map(|nanos| nanos.to_string())
+            .unwrap_or_else(|| "0".to_string());
+
+        let mut attributes: Vec<Value> = entry.attrs.iter().map(|(key, value)| string_attr(key, value)).collect();
+        let pid = entry.pid().and_then(|p| p.parse::<i64>().ok());
+        if let Some(pid) = pid {
+            attributes.push(json!({"key": "process.pid", "value": {"intValue": pid.to_string()}}));
+        }
+
+        let mut record = json!({
+            "timeUnixNano": time,
+            "observedTimeUnixNano": time,
+            "body": {"stringValue": entry.log_entry},
+            "attributes": attributes,
+        });
+        if let (Some(severity), Some(name)) = (entry.severity, entry.severity_name()) {
+            record["severityNumber"] = json!(severity_number(severity));
+            record["severityText"] = json!(name.to_uppercase());
+        }
+        record
+    }
+}
+
+/// OpenTelemetry severity number of a syslog severity
+fn severity_number(severity: u8) -> u8 {
+    match severity {
+        0 => 24,  // FATAL4
+        1 => 23,  // FATAL3
+        2 => 21,  // FATAL
+        3 => 17,  // ERROR
+        4 => 13,  // WARN
+        5 => 10,  // INFO2
+        6 => 9,   // INFO
+        _ => 5,   // DEBUG
+    }
+}
+
+fn string_attr(key: &str, value: &str) -> Value {
+    json!({"key": key, "value": {"stringValue": value}})
+}
@@ -0,0 +1,171 @@
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::fs::{create_dir_all, read_dir};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Curated packs and their checksums, shipped with the binary so a download
+// can't change what it is verified against
+const EMBEDDED_INDEX: &str = include_str!("../filters/packs/index");
+
+/// Where the curated packs are downloaded from, unless GLANCELOG_PACKS_URL is set
+pub const DEFAULT_PACKS_URL: &str = "https://raw.githubusercontent.com/kost/glancelog/master/filters/packs/";
+
+/// Filter files a pack can extend
+pub const PACK_FILTERS: [&str; 4] = ["hash", "daemon", "host", "words"];
+
+/// A stopword pack: extra patterns for one of the filter files, installed as
+/// `<filter>.d/<name>.stopwords` in the filter directory
+#[derive(Debug, Clone)]
+pub struct FilterPack {
+    pub name: String,
+    /// Filter file the patterns are added to: hash, daemon, host or words
+    pub filter: String,
+    /// Expected SHA-256 of the file, None for a URL given without one
+    pub sha256: Option<String>,
+    pub url: String,
+    pub description: String,
+}
+
+impl FilterPack {
+    /// The curated packs: "name<TAB>filter<TAB>sha256<TAB>description" lines
+    pub fn available() -> Vec<Self> {
+        let base = std::env::var("GLANCELOG_PACKS_URL").unwrap_or_else(|_| DEFAULT_PACKS_URL.to_string());
+        let base = if base.ends_with('/') { base } else { format!("{}/", base) };
+
+        EMBEDDED_INDEX.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.splitn(4, '\t').collect();
+                match fields.as_slice() {
+                    [name, filter, sha256, description] => Some(Self {
+                        name: name.to_string(),
+                        filter: filter.to_string(),
+                        sha256: Some(sha256.to_lowercase()),
+                        url: format!("{}{}.stopwords", base, name),
+                        description: description.to_string(),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// A curated pack by name, or a pack at a URL named after its file
+    pub fn resolve(spec: &str, filter: Option<&str>, sha256: Option<&str>) -> Result<Self> {
+        if let Some(filter) = filter {
+            if !PACK_FILTERS.contains(&filter) {
+                return Err(anyhow!("Unknown filter '{}', expected one of {}", filter, PACK_FILTERS.join(", ")));
+            }
+        }
+
+        if !spec.contains("://") {
+            let mut pack = Self::available().into_iter()
+                .find(|p| p.name == spec)
+                .ok_or_else(|| anyhow!("No filter pack named '{}', see 'glancelog filters list'", spec))?;
+            if let Some(filter) = filter {
+                pack.filter = filter.to_string();
+            }
+            return Ok(pack);
+        }
+
+        let file = spec.trim_end_matches('/').rsplit('/').next().unwrap_or(spec);
+        let name = file.trim_end_matches(".stopwords");
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+            return Err(anyhow!("Cannot name a pack after '{}'", file));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            filter: filter.unwrap_or("hash").to_string(),
+            sha256: sha256.map(|s| s.to_lowercase()),
+            url: spec.to_string(),
+            description: String::new(),
+        })
+    }
+
+    /// Where the pack is installed in a filter directory
+    pub fn path_in(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.d", self.filter)).join(format!("{}.stopwords", self.name))
+    }
+
+    /// Download the pack, check its checksum and patterns, and write it to the
+    /// filter directory
+    pub fn install(&self, dir: &Path) -> Result<PathBuf> {
+        let content = fetch(&self.url)?;
+        let sum = sha256_hex(&content);
+        match &self.sha256 {
+            Some(expected) if *expected != sum => {
+                return Err(anyhow!("Checksum mismatch for {}: expected {}, got {}", self.url, expected, sum));
+            }
+            Some(_) => info!("Checksum verified: {}", sum),
+            None => warn!("No checksum given for {}, not verified (SHA-256 is {})", self.url, sum),
+        }
+
+        let text = String::from_utf8(content).map_err(|_| anyhow!("{} is not a text file", self.url))?;
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            Regex::new(line).map_err(|e| anyhow!("{} has an invalid pattern '{}': {}", self.url, line, e))?;
+        }
+
+        let path = self.path_in(dir);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        std::fs::write(&path, text)?;
+        Ok(path)
+    }
+
+    /// Packs installed in a filter directory, as (filter, name) pairs
+    pub fn installed(dir: &Path) -> Vec<(String, String)> {
+        let mut packs = Vec::new();
+        for filter in PACK_FILTERS {
+            for path in pack_files(&dir.join(format!("{}.d", filter))) {
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    packs.push((filter.to_string(), name.to_string()));
+                }
+            }
+        }
+        packs
+    }
+}
+
+/// The `*.stopwords` files of a pack directory, in name order
+pub fn pack_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().map(|e| e == "stopwords").unwrap_or(false))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+/// The filter directory packs are installed to: the given one or ~/.glancelog/filters
+pub fn pack_dir(custom_dir: Option<&str>) -> Result<PathBuf> {
+    match custom_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::home_dir()
+            .map(|home| home.join(".glancelog").join("filters"))
+            .ok_or_else(|| anyhow!("Could not determine home directory")),
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download with curl, which also handles proxies and file:// URLs
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    info!("Fetching {}", url);
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", "--max-time", "60", url])
+        .output()
+        .map_err(|e| anyhow!("Cannot run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!("Download of {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
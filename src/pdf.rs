@@ -0,0 +1,141 @@
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Local, Timelike};
+use pdf_writer::{Content, Date, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+const FONT_SIZE: f32 = 8.0;
+const LEADING: f32 = 10.0;
+/// Courier glyphs are 600/1000 of the font size wide
+const COLUMNS: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (FONT_SIZE * 0.6)) as usize;
+/// Body lines between the title and the page number
+const ROWS: usize = ((PAGE_HEIGHT - 2.0 * MARGIN - 3.0 * LEADING) / LEADING) as usize;
+
+/// Renders a text report as a PDF for archiving: A4 pages of monospaced
+/// text under a title, with page numbers. Long lines wrap; a line starting
+/// with "===" (a section heading) is set in bold
+pub struct PdfReport {
+    title: String,
+    text: String,
+}
+
+impl PdfReport {
+    pub fn new(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.to_string() }
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.render()).map_err(|e| anyhow!("Cannot write {}: {}", path, e))
+    }
+
+    pub fn render(&self) -> Vec<u8> {
+        let pages = self.pages();
+
+        let catalog_id = Ref::new(1);
+        let tree_id = Ref::new(2);
+        let font_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let info_id = Ref::new(5);
+        // Each page and its content stream
+        let page_ids: Vec<Ref> = (0..pages.len()).map(|i| Ref::new(6 + 2 * i as i32)).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(tree_id);
+        pdf.pages(tree_id).kids(page_ids.iter().copied()).count(pages.len() as i32);
+        pdf.type1_font(font_id).base_font(Name(b"Courier")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold_id).base_font(Name(b"Courier-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        let now = Local::now();
+        let date = Date::new(now.year() as u16)
+            .month(now.month() as u8)
+            .day(now.day() as u8)
+            .hour(now.hour() as u8)
+            .minute(now.minute() as u8)
+            .second(now.second() as u8);
+        let mut info = pdf.document_info(info_id);
+        info.title(TextStr(&self.title));
+        info.producer(TextStr(concat!("glancelog ", env!("CARGO_PKG_VERSION"))));
+        info.creation_date(date);
+        info.finish();
+
+        for (i, lines) in pages.iter().enumerate() {
+            let page_id = page_ids[i];
+            let content_id = Ref::new(page_id.get() + 1);
+
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(tree_id);
+            page.contents(content_id);
+            let mut resources = page.resources();
+            resources.fonts().pair(Name(b"F1"), font_id).pair(Name(b"F2"), bold_id);
+            resources.finish();
+            page.finish();
+
+            let mut content = Content::new();
+            let top = PAGE_HEIGHT - MARGIN;
+            show_line(&mut content, b"F2", FONT_SIZE + 2.0, MARGIN, top, &self.title);
+            for (row, line) in lines.iter().enumerate() {
+                let font: &[u8] = if line.starts_with("===") { b"F2" } else { b"F1" };
+                let y = top - 3.0 * LEADING - row as f32 * LEADING;
+                show_line(&mut content, font, FONT_SIZE, MARGIN, y, line);
+            }
+            let footer = format!("Page {} of {}", i + 1, pages.len());
+            let x = PAGE_WIDTH - MARGIN - footer.len() as f32 * FONT_SIZE * 0.6;
+            show_line(&mut content, b"F1", FONT_SIZE, x, MARGIN / 2.0, &footer);
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+
+    /// The text wrapped to the page width and cut into pages; a section
+    /// heading near the bottom of a page moves to the next one
+    fn pages(&self) -> Vec<Vec<String>> {
+        let mut pages: Vec<Vec<String>> = vec![Vec::new()];
+        for line in self.text.lines() {
+            let line = line.replace('\t', "    ");
+            let chars: Vec<char> = line.chars().collect();
+            let wrapped: Vec<String> = if chars.is_empty() {
+                vec![String::new()]
+            } else {
+                chars.chunks(COLUMNS).map(|c| c.iter().collect()).collect()
+            };
+
+            let page_len = pages.last().map(|p| p.len()).unwrap_or(0);
+            if line.starts_with("===") && page_len > 0 && page_len + 3 > ROWS {
+                pages.push(Vec::new());
+            }
+            for row in wrapped {
+                let page = pages.last_mut().expect("at least one page");
+                if page.len() == ROWS {
+                    pages.push(vec![row]);
+                } else if !(page.is_empty() && row.is_empty()) {
+                    // Blank lines are dropped at the top of a page
+                    page.push(row);
+                }
+            }
+        }
+        pages
+    }
+}
+
+fn show_line(content: &mut Content, font: &[u8], size: f32, x: f32, y: f32, text: &str) {
+    content.begin_text();
+    content.set_font(Name(font), size);
+    content.next_line(x, y);
+    content.show(Str(&win_ansi(text)));
+    content.end_text();
+}
+
+/// The text in the fonts' WinAnsiEncoding; characters outside Latin-1
+/// become '?'
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7e | 0xa0..=0xff => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use anyhow::{Result, anyhow};
+use chrono::{Duration, NaiveDateTime};
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A host and PID, as PIDs are only unique on one host
+type ProcessKey = (String, String);
+
+/// One process of a trace with the span of its entries
+#[derive(Debug, Clone)]
+pub struct TracedProcess {
+    pub host: String,
+    pub name: String,
+    pub pid: String,
+    /// PID of the traced process that forked this one
+    pub parent: Option<String>,
+    pub entries: usize,
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+}
+
+/// All entries of the processes picked by PID or by a regex on "name[pid]",
+/// optionally with the children they forked, as one timeline
+pub struct PidTrace {
+    processes: Vec<TracedProcess>,
+    entries: Vec<LogEntry>,
+}
+
+impl PidTrace {
+    /// `spec` is a PID, or a regex matched against "name[pid]" of each
+    /// process (e.g. "^sshd"). With `children`, processes a traced one
+    /// reports forking or spawning ("Forked child 1234", "spawned worker
+    /// (pid: 1234)") are traced too, and their children in turn
+    pub fn from_log(log: &CrunchLog, spec: &str, children: bool) -> Result<Self> {
+        let pattern = if spec.chars().all(|c| c.is_ascii_digit()) {
+            None
+        } else {
+            Some(Regex::new(spec).map_err(|e| anyhow!("Invalid PID pattern '{}': {}", spec, e))?)
+        };
+        // The PID after "pid" if the line names one, otherwise the first number
+        let fork_re = Regex::new(r"(?i)\b(?:fork(?:ed|ing)?|spawn(?:ed|ing)?)\b(?:.*?\bpid\W{0,3}(\d+)|\D*?(\d+))").unwrap();
+
+        let mut traced: HashSet<ProcessKey> = HashSet::new();
+        let mut forks: HashMap<ProcessKey, Vec<String>> = HashMap::new();
+        for entry in &log.entries {
+            let pid = match entry.pid() {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let key = (entry.host.clone(), pid.to_string());
+            let selected = match &pattern {
+                None => pid == spec,
+                Some(re) => re.is_match(&format!("{}[{}]", entry.program(), pid)),
+            };
+            if selected {
+                traced.insert(key.clone());
+            }
+            if children {
+                if let Some(caps) = fork_re.captures(&entry.log_entry) {
+                    let child = caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str()).unwrap_or_default();
+                    if child != pid {
+                        forks.entry(key).or_default().push(child.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut parents: HashMap<ProcessKey, String> = HashMap::new();
+        let mut queue: VecDeque<ProcessKey> = traced.iter().cloned().collect();
+        while let Some(key) = queue.pop_front() {
+            for child in forks.get(&key).into_iter().flatten() {
+                let child_key = (key.0.clone(), child.clone());
+                if traced.insert(child_key.clone()) {
+                    parents.insert(child_key.clone(), key.1.clone());
+                    queue.push_back(child_key);
+                }
+            }
+        }
+
+        let mut entries: Vec<LogEntry> = log.entries.iter()
+            .filter(|e| e.pid().map(|pid| traced.contains(&(e.host.clone(), pid.to_string()))).unwrap_or(false))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.naive_datetime());
+
+        let mut processes: Vec<TracedProcess> = Vec::new();
+        let mut index: HashMap<ProcessKey, usize> = HashMap::new();
+        for entry in &entries {
+            let pid = entry.pid().unwrap_or_default().to_string();
+            let key = (entry.host.clone(), pid.clone());
+            let time = entry.naive_datetime();
+            let idx = *index.entry(key.clone()).or_insert_with(|| {
+                processes.push(TracedProcess {
+                    host: entry.host.clone(),
+                    name: entry.program().to_string(),
+                    pid,
+                    parent: parents.get(&key).cloned(),
+                    entries: 0,
+                    first: time,
+                    last: time,
+                });
+                processes.len() - 1
+            });
+            let process = &mut processes[idx];
+            process.entries += 1;
+            process.last = time;
+        }
+
+        Ok(Self { processes, entries })
+    }
+
+    /// Traced processes, in the order they first appear
+    pub fn processes(&self) -> &[TracedProcess] {
+        &self.processes
+    }
+
+    /// Entries of the traced processes in time order
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn display(&self) {
+        let (first, last) = match (self.entries.first(), self.entries.last()) {
+            (Some(first), Some(last)) => (first.naive_datetime(), last.naive_datetime()),
+            _ => {
+                println!("No entries of a matching process found");
+                return;
+            }
+        };
+
+        println!("Processes: {}\tEntries: {}\t{} .. {}", self.processes.len(), self.entries.len(),
+            first.format("%Y-%m-%d %H:%M:%S"), last.format("%Y-%m-%d %H:%M:%S"));
+        for process in &self.processes {
+            let parent = process.parent.as_ref().map(|p| format!("\tchild of {}", p)).unwrap_or_default();
+            println!("{}\t{}[{}]\t{} entries, {} .. {}{}", process.host, process.name, process.pid, process.entries,
+                process.first.format("%H:%M:%S"), process.last.format("%H:%M:%S"), parent);
+        }
+        println!();
+
+        for entry in &self.entries {
+            let message = entry.log_entry.strip_prefix(": ").or_else(|| entry.log_entry.strip_prefix(" ")).unwrap_or(&entry.log_entry);
+            println!("{}\t{}\t{} {}[{}]: {}", entry.naive_datetime().format("%Y-%m-%d %H:%M:%S"),
+                elapsed(entry.naive_datetime() - first), entry.host, entry.program(), entry.pid().unwrap_or_default(), message);
+        }
+    }
+}
+
+/// Time since the first traced entry, as "+H:MM:SS"
+fn elapsed(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    format!("+{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
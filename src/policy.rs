@@ -0,0 +1,133 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+
+/// What a `--fail-if-count` condition counts
+#[derive(Debug, Clone)]
+pub enum CountKey {
+    /// All entries
+    Total,
+    /// Entries of severity err or worse
+    Errors,
+    /// Entries of a daemon, by name with or without the PID ("sshd" or "sshd[123]")
+    Daemon(String),
+    Host(String),
+    /// Entries whose normalized line matches
+    Pattern(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+/// A count condition such as "errors>0", "daemon:sshd>=100" or "pattern:oom-killer>2"
+#[derive(Debug, Clone)]
+pub struct CountCondition {
+    pub spec: String,
+    pub key: CountKey,
+    pub comparison: Comparison,
+    pub threshold: usize,
+}
+
+impl CountCondition {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let re = Regex::new(r"^(.+?)(>=|<=|==|>|<|=)(\d+)$").unwrap();
+        let caps = re.captures(spec.trim())
+            .ok_or_else(|| anyhow!("Invalid condition '{}', expected KEY>N (or >=, <, <=, =)", spec))?;
+
+        let key = match caps[1].split_once(':') {
+            None if &caps[1] == "total" => CountKey::Total,
+            None if &caps[1] == "errors" => CountKey::Errors,
+            Some(("daemon", name)) => CountKey::Daemon(name.to_string()),
+            Some(("host", name)) => CountKey::Host(name.to_string()),
+            Some(("pattern", pattern)) => CountKey::Pattern(Regex::new(pattern)?),
+            _ => return Err(anyhow!("Unknown key '{}', expected total, errors, daemon:NAME, host:NAME or pattern:REGEX", &caps[1])),
+        };
+        let comparison = match &caps[2] {
+            ">" => Comparison::Greater,
+            ">=" => Comparison::GreaterOrEqual,
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessOrEqual,
+            _ => Comparison::Equal,
+        };
+
+        Ok(Self {
+            spec: spec.trim().to_string(),
+            key,
+            comparison,
+            threshold: caps[3].parse()?,
+        })
+    }
+
+    fn counts(&self, entry: &LogEntry) -> bool {
+        match &self.key {
+            CountKey::Total => true,
+            CountKey::Errors => entry.severity.map(|s| s <= 3).unwrap_or(false),
+            CountKey::Daemon(name) => entry.daemon.trim_end_matches(':') == name || entry.program() == name,
+            CountKey::Host(name) => &entry.host == name,
+            CountKey::Pattern(re) => re.is_match(&entry.to_normalized()),
+        }
+    }
+
+    pub fn count(&self, log: &CrunchLog) -> usize {
+        log.entries.iter().filter(|e| self.counts(e)).count()
+    }
+
+    pub fn is_met(&self, count: usize) -> bool {
+        match self.comparison {
+            Comparison::Greater => count > self.threshold,
+            Comparison::GreaterOrEqual => count >= self.threshold,
+            Comparison::Less => count < self.threshold,
+            Comparison::LessOrEqual => count <= self.threshold,
+            Comparison::Equal => count == self.threshold,
+        }
+    }
+}
+
+/// Conditions on the log contents that make glancelog exit non-zero, for
+/// gating CI jobs and health checks
+#[derive(Debug, Clone, Default)]
+pub struct FailPolicy {
+    patterns: Vec<Regex>,
+    conditions: Vec<CountCondition>,
+}
+
+impl FailPolicy {
+    pub fn new(patterns: &[String], conditions: &[String]) -> Result<Self> {
+        Ok(Self {
+            patterns: patterns.iter()
+                .map(|p| Regex::new(p).map_err(|e| anyhow!("Invalid pattern '{}': {}", p, e)))
+                .collect::<Result<Vec<_>>>()?,
+            conditions: conditions.iter().map(|c| CountCondition::parse(c)).collect::<Result<Vec<_>>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.conditions.is_empty()
+    }
+
+    /// A description of each condition met; empty when the log passes
+    pub fn check(&self, log: &CrunchLog) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        for pattern in &self.patterns {
+            let matches = log.entries.iter().filter(|e| pattern.is_match(&e.to_normalized())).count();
+            if matches > 0 {
+                failures.push(format!("'{}' matched {} entries", pattern.as_str(), matches));
+            }
+        }
+        for condition in &self.conditions {
+            let count = condition.count(log);
+            if condition.is_met(count) {
+                failures.push(format!("{} (count is {})", condition.spec, count));
+            }
+        }
+
+        failures
+    }
+}
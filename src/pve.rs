@@ -0,0 +1,186 @@
+use crate::log_entry::{epoch_to_local, CrunchLog};
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Proxmox VE unique task ID: "UPID:node:pid:pstart:starttime:type:id:user:",
+/// with pid, pstart and starttime in hex
+#[derive(Debug, Clone)]
+pub struct Upid {
+    pub node: String,
+    pub pid: u32,
+    pub start: NaiveDateTime,
+    pub task_type: String,
+    pub id: String,
+    pub user: String,
+}
+
+impl Upid {
+    pub fn parse(upid: &str) -> Option<Self> {
+        let re = Regex::new(r"^UPID:([^:]+):([0-9A-Fa-f]{8}):([0-9A-Fa-f]{8,9}):([0-9A-Fa-f]{8}):([^:]+):([^:]*):([^:]+):?$").unwrap();
+        let caps = re.captures(upid)?;
+        Some(Self {
+            node: caps[1].to_string(),
+            pid: u32::from_str_radix(&caps[2], 16).ok()?,
+            start: epoch_to_local(i64::from_str_radix(&caps[4], 16).ok()? as f64)?,
+            task_type: caps[5].to_string(),
+            id: caps[6].to_string(),
+            user: caps[7].to_string(),
+        })
+    }
+}
+
+/// A task from start to end (or to the end of the log)
+#[derive(Debug, Clone)]
+pub struct PveTask {
+    pub upid: Upid,
+    pub start: NaiveDateTime,
+    pub end: Option<NaiveDateTime>,
+    pub status: Option<String>,
+}
+
+impl PveTask {
+    /// Task length in seconds, if the end was seen
+    pub fn duration(&self) -> Option<i64> {
+        self.end.map(|end| (end - self.start).num_seconds())
+    }
+
+    /// "OK" and "WARNINGS: n" count as success
+    pub fn failed(&self) -> bool {
+        match &self.status {
+            Some(status) => status != "OK" && !status.starts_with("WARNINGS"),
+            None => false,
+        }
+    }
+}
+
+pub struct PveTaskReport {
+    tasks: Vec<PveTask>,
+}
+
+impl PveTaskReport {
+    /// Pair pvedaemon/pveproxy "starting task"/"end task" lines and task index
+    /// entries by UPID
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // pvedaemon[1234]: <root@pam> starting task UPID:pve1:00001A2B:0012D687:6553A0E0:qmstart:100:root@pam:
+        // pvedaemon[1234]: <root@pam> end task UPID:pve1:00001A2B:0012D687:6553A0E0:qmstart:100:root@pam: OK
+        let start_re = Regex::new(r"starting task (UPID:\S+)").unwrap();
+        let end_re = Regex::new(r"end task (UPID:\S+) (.*)$").unwrap();
+
+        let mut tasks: Vec<PveTask> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            // Task index lines carry the whole task. Syslog timestamps have no year, so a
+            // logged start line's own time is preferred over the start time in the UPID
+            let (upid, start, end) = if let Some(upid) = entry.attrs.get("upid") {
+                let end = entry.attrs.get("status").map(|status| {
+                    let end = entry.attrs.get("end")
+                        .and_then(|e| e.parse::<f64>().ok())
+                        .and_then(epoch_to_local);
+                    (end, status.clone())
+                });
+                (upid.clone(), None, end)
+            } else if let Some(caps) = start_re.captures(&entry.log_entry) {
+                (caps[1].to_string(), Some(entry.naive_datetime()), None)
+            } else if let Some(caps) = end_re.captures(&entry.log_entry) {
+                (caps[1].to_string(), None, Some((Some(entry.naive_datetime()), caps[2].to_string())))
+            } else {
+                continue;
+            };
+
+            let idx = match seen.get(&upid) {
+                Some(&idx) => idx,
+                None => {
+                    let parsed = match Upid::parse(&upid) {
+                        Some(parsed) => parsed,
+                        None => continue,
+                    };
+                    seen.insert(upid, tasks.len());
+                    tasks.push(PveTask {
+                        start: start.unwrap_or(parsed.start),
+                        upid: parsed,
+                        end: None,
+                        status: None,
+                    });
+                    tasks.len() - 1
+                }
+            };
+            if let Some((end, status)) = end {
+                tasks[idx].end = end;
+                tasks[idx].status = Some(status);
+            }
+        }
+
+        tasks.sort_by_key(|t| t.start);
+        Self { tasks }
+    }
+
+    pub fn tasks(&self) -> &[PveTask] {
+        &self.tasks
+    }
+
+    fn format_duration(seconds: i64) -> String {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    }
+
+    fn describe(task: &PveTask) -> String {
+        let duration = task.duration()
+            .map(Self::format_duration)
+            .unwrap_or_else(|| "running".to_string());
+        format!("{}\t{}\t{}\t{} {}\t{}\t{}",
+            task.start.format("%Y-%m-%dT%H:%M:%S"), duration, task.upid.node,
+            task.upid.task_type, task.upid.id, task.upid.user,
+            task.status.as_deref().unwrap_or("-"))
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.tasks.is_empty() {
+            println!("No Proxmox tasks found");
+            return;
+        }
+
+        let running = self.tasks.iter().filter(|t| t.status.is_none()).count();
+        let failed = self.tasks.iter().filter(|t| t.failed()).count();
+        println!("Tasks: {}\tFinished: {}\tFailed: {}\tRunning or unfinished: {}",
+            self.tasks.len(), self.tasks.len() - running, failed, running);
+        println!();
+
+        let mut types: HashMap<String, usize> = HashMap::new();
+        let mut users: HashMap<String, usize> = HashMap::new();
+        let mut nodes: HashMap<String, usize> = HashMap::new();
+        let mut failed_types: HashMap<String, usize> = HashMap::new();
+        for task in &self.tasks {
+            *types.entry(task.upid.task_type.clone()).or_insert(0) += 1;
+            *users.entry(task.upid.user.clone()).or_insert(0) += 1;
+            *nodes.entry(task.upid.node.clone()).or_insert(0) += 1;
+            if task.failed() {
+                *failed_types.entry(task.upid.task_type.clone()).or_insert(0) += 1;
+            }
+        }
+        print_top("Task types", types, limit);
+        print_top("Failed task types", failed_types, limit);
+        print_top("Tasks per user", users, limit);
+        print_top("Tasks per node", nodes, limit);
+
+        let mut longest: Vec<&PveTask> = self.tasks.iter().filter(|t| t.duration().is_some()).collect();
+        longest.sort_by_key(|t| std::cmp::Reverse(t.duration()));
+        if !longest.is_empty() {
+            println!("Longest tasks:");
+            for task in longest.iter().take(limit) {
+                println!("{}", Self::describe(task));
+            }
+            println!();
+        }
+
+        let failures: Vec<&PveTask> = self.tasks.iter().filter(|t| t.failed()).collect();
+        if !failures.is_empty() {
+            println!("Failed tasks:");
+            for task in failures.iter().take(limit) {
+                println!("{}", Self::describe(task));
+            }
+            println!();
+        }
+    }
+}
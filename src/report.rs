@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Print a titled "count:\tkey" section with the most frequent keys first
+pub fn print_top(title: &str, counts: HashMap<String, usize>, limit: usize) {
+    let _ = write_top(&mut std::io::stdout().lock(), title, counts, limit);
+}
+
+/// `print_top` into any writer, e.g. a digest being assembled
+pub fn write_top<W: Write>(out: &mut W, title: &str, counts: HashMap<String, usize>, limit: usize) -> std::io::Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut items: Vec<_> = counts.into_iter().collect();
+    items.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    writeln!(out, "{}:", title)?;
+    for (key, count) in items.into_iter().take(limit) {
+        writeln!(out, "{}:\t{}", count, key)?;
+    }
+    writeln!(out)
+}
@@ -0,0 +1,54 @@
+use crate::log_entry::LogEntry;
+use anyhow::{Result, anyhow};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Network prefix lengths addresses are rolled up to, e.g. "/24" (IPv6 then
+/// defaults to /64) or "/24,/48"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rollup {
+    pub v4: u8,
+    pub v6: u8,
+}
+
+impl Rollup {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let prefix = |part: &str, max: u8| -> Result<u8> {
+            let bits: u8 = part.trim().trim_start_matches('/').parse()
+                .map_err(|_| anyhow!("Invalid prefix '{}' in rollup '{}', expected /N", part, spec))?;
+            if bits > max {
+                return Err(anyhow!("Prefix /{} in rollup '{}' is longer than /{}", bits, spec, max));
+            }
+            Ok(bits)
+        };
+
+        match spec.split_once(',') {
+            Some((v4, v6)) => Ok(Self { v4: prefix(v4, 32)?, v6: prefix(v6, 128)? }),
+            None => Ok(Self { v4: prefix(spec, 32)?, v6: 64 }),
+        }
+    }
+
+    /// The network an address belongs to, as "203.0.113.0/24"
+    pub fn network(&self, ip: IpAddr) -> String {
+        match ip {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX.checked_shl(32 - self.v4 as u32).unwrap_or(0);
+                format!("{}/{}", Ipv4Addr::from(u32::from(ip) & mask), self.v4)
+            }
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX.checked_shl(128 - self.v6 as u32).unwrap_or(0);
+                format!("{}/{}", Ipv6Addr::from(u128::from(ip) & mask), self.v6)
+            }
+        }
+    }
+}
+
+/// The client or source address of an entry, as recorded by the web, load
+/// balancer, firewall and IDS parsers in the "src" field
+pub fn source_ip(entry: &LogEntry) -> Option<IpAddr> {
+    let src = entry.attrs.get("src")?;
+    // IPv4-mapped IPv6 ("::ffff:192.0.2.1") counts as the IPv4 address
+    match src.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok()? {
+        IpAddr::V6(ip) => Some(ip.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(IpAddr::V6(ip))),
+        ip => Some(ip),
+    }
+}
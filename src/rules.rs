@@ -0,0 +1,202 @@
+use crate::log_entry::{severity_from_name, CrunchLog, LogEntry};
+use crate::report::print_top;
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+/// Entry attribute holding the tag assigned by the rules
+pub const TAG_ATTR: &str = "tag";
+
+/// One classification rule: every condition given must match for the entry
+/// to get the tag
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub tag: String,
+    /// Matched against the daemon name without the PID
+    pub daemon: Option<Regex>,
+    pub host: Option<Regex>,
+    /// Matched against the message
+    pub pattern: Option<Regex>,
+    /// This severity or a more severe one (lower number)
+    pub severity: Option<u8>,
+}
+
+impl Rule {
+    fn from_yaml(idx: usize, value: &Value) -> Result<Self> {
+        let map = value.as_mapping().ok_or_else(|| anyhow!("rule {}: expected a mapping", idx + 1))?;
+        let mut rule = Self {
+            name: format!("rule {}", idx + 1),
+            tag: String::new(),
+            daemon: None,
+            host: None,
+            pattern: None,
+            severity: None,
+        };
+
+        for (key, value) in map {
+            let key = key.as_str().ok_or_else(|| anyhow!("rule {}: keys must be strings", idx + 1))?;
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                _ => return Err(anyhow!("rule {}: {} must be a string", idx + 1, key)),
+            };
+            let regex = |text: &str| Regex::new(text).map_err(|e| anyhow!("rule {}: invalid {} '{}': {}", idx + 1, key, text, e));
+
+            match key {
+                "name" => rule.name = text,
+                "tag" => rule.tag = text,
+                "daemon" => rule.daemon = Some(regex(&text)?),
+                "host" => rule.host = Some(regex(&text)?),
+                "pattern" => rule.pattern = Some(regex(&text)?),
+                "severity" => rule.severity = Some(text.parse::<u8>().ok().filter(|s| *s <= 7)
+                    .or_else(|| severity_from_name(&text))
+                    .ok_or_else(|| anyhow!("rule {}: unknown severity '{}'", idx + 1, text))?),
+                _ => return Err(anyhow!("rule {}: unknown key '{}', expected name, tag, daemon, host, pattern or severity", idx + 1, key)),
+            }
+        }
+
+        if rule.tag.is_empty() {
+            return Err(anyhow!("rule {}: no tag", idx + 1));
+        }
+        if rule.daemon.is_none() && rule.host.is_none() && rule.pattern.is_none() && rule.severity.is_none() {
+            return Err(anyhow!("rule {}: no daemon, host, pattern or severity to match", idx + 1));
+        }
+        Ok(rule)
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.daemon.as_ref().map(|re| re.is_match(entry.program())).unwrap_or(true)
+            && self.host.as_ref().map(|re| re.is_match(&entry.host)).unwrap_or(true)
+            && self.pattern.as_ref().map(|re| re.is_match(&entry.log_entry)).unwrap_or(true)
+            && self.severity.map(|max| entry.severity.map(|s| s <= max).unwrap_or(false)).unwrap_or(true)
+    }
+}
+
+/// Tagging rules from a YAML file, checked in order; the first rule that
+/// matches an entry sets its tag:
+///
+/// ```text
+/// rules:
+///   - tag: critical
+///     severity: crit
+///   - name: cron chatter
+///     tag: noise
+///     daemon: ^(cron|CRON)$
+///   - tag: notable
+///     pattern: Failed password
+///     host: ^web
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read {}: {}", path, e))?;
+        Self::parse(&content)
+    }
+
+    /// A `rules:` list, or just the list
+    pub fn parse(content: &str) -> Result<Self> {
+        let yaml: Value = serde_yaml::from_str(content)?;
+        let list = match &yaml {
+            Value::Sequence(list) => list,
+            Value::Mapping(map) => map.get("rules")
+                .and_then(|r| r.as_sequence())
+                .ok_or_else(|| anyhow!("expected a 'rules' list"))?,
+            _ => return Err(anyhow!("expected a 'rules' list")),
+        };
+
+        Ok(Self {
+            rules: list.iter().enumerate().map(|(idx, value)| Rule::from_yaml(idx, value)).collect::<Result<Vec<_>>>()?,
+        })
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// The first matching rule
+    pub fn rule_for(&self, entry: &LogEntry) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.matches(entry))
+    }
+
+    /// Set the tag attribute of every entry a rule matches
+    pub fn apply(&self, log: &mut CrunchLog) {
+        for entry in &mut log.entries {
+            if let Some(rule) = self.rule_for(entry) {
+                entry.attrs.insert(TAG_ATTR.to_string(), rule.tag.clone());
+            }
+        }
+    }
+}
+
+/// Entries per tag and which rules did the tagging
+pub struct TagReport {
+    total: usize,
+    tags: HashMap<String, usize>,
+    daemons: HashMap<String, HashMap<String, usize>>,
+    rule_hits: Vec<(String, String, usize)>,
+}
+
+impl TagReport {
+    pub fn from_log(log: &CrunchLog, rules: &RuleSet) -> Self {
+        let mut tags: HashMap<String, usize> = HashMap::new();
+        let mut daemons: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let mut hits = vec![0; rules.rules().len()];
+
+        for entry in &log.entries {
+            let tag = match rules.rules().iter().position(|rule| rule.matches(entry)) {
+                Some(idx) => {
+                    hits[idx] += 1;
+                    rules.rules()[idx].tag.clone()
+                }
+                None => "untagged".to_string(),
+            };
+            *daemons.entry(tag.clone()).or_default().entry(entry.program().to_string()).or_insert(0) += 1;
+            *tags.entry(tag).or_insert(0) += 1;
+        }
+
+        Self {
+            total: log.entries.len(),
+            tags,
+            daemons,
+            rule_hits: rules.rules().iter().zip(hits).map(|(rule, hits)| (rule.name.clone(), rule.tag.clone(), hits)).collect(),
+        }
+    }
+
+    pub fn tags(&self) -> &HashMap<String, usize> {
+        &self.tags
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.total == 0 {
+            println!("No entries to tag");
+            return;
+        }
+
+        let untagged = self.tags.get("untagged").copied().unwrap_or(0);
+        println!("Entries: {}\tTagged: {}\tUntagged: {}", self.total, self.total - untagged, untagged);
+        println!();
+
+        print_top("Tags", self.tags.clone(), limit);
+
+        let mut tags: Vec<(&String, &usize)> = self.tags.iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (tag, _) in tags {
+            if let Some(daemons) = self.daemons.get(tag) {
+                print_top(&format!("Daemons tagged {}", tag), daemons.clone(), limit);
+            }
+        }
+
+        // Rules that never match are worth pruning or fixing
+        println!("Rule hits:");
+        for (name, tag, hits) in &self.rule_hits {
+            println!("{}:\t{} -> {}", hits, name, tag);
+        }
+        println!();
+    }
+}
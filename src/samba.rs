@@ -0,0 +1,102 @@
+use crate::log_entry::{CrunchLog, LogEntry};
+use crate::report::print_top;
+use std::collections::HashMap;
+
+/// One file operation from the vfs_full_audit module
+#[derive(Debug, Clone)]
+pub struct FileAccess {
+    pub user: String,
+    pub ip: String,
+    pub share: String,
+    pub operation: String,
+    pub path: String,
+    pub success: bool,
+    pub entry: LogEntry,
+}
+
+impl FileAccess {
+    /// Operations that remove or move files
+    pub fn is_destructive(&self) -> bool {
+        matches!(self.operation.as_str(), "unlink" | "unlinkat" | "rmdir" | "rename" | "renameat")
+    }
+}
+
+pub struct SambaReport {
+    accesses: Vec<FileAccess>,
+}
+
+impl SambaReport {
+    /// Collect smbd_audit operations; the share is "-" unless the audit prefix includes %S
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let accesses = log.entries.iter()
+            .filter(|entry| entry.daemon == "smbd_audit")
+            .filter_map(|entry| {
+                let attr = |key: &str| entry.attrs.get(key).cloned().unwrap_or_else(|| "-".to_string());
+                Some(FileAccess {
+                    user: attr("user"),
+                    ip: attr("ip"),
+                    share: attr("share"),
+                    operation: entry.attrs.get("operation")?.clone(),
+                    path: attr("path"),
+                    success: entry.attrs.get("status").map(|s| s == "ok").unwrap_or(true),
+                    entry: entry.clone(),
+                })
+            })
+            .collect();
+
+        Self { accesses }
+    }
+
+    pub fn accesses(&self) -> &[FileAccess] {
+        &self.accesses
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.accesses.is_empty() {
+            println!("No Samba audit entries found");
+            return;
+        }
+
+        let failures = self.accesses.iter().filter(|a| !a.success).count();
+        println!("File operations: {}\tSucceeded: {}\tFailed: {}",
+            self.accesses.len(), self.accesses.len() - failures, failures);
+        println!();
+
+        let mut operations: HashMap<String, usize> = HashMap::new();
+        let mut users: HashMap<String, usize> = HashMap::new();
+        let mut shares: HashMap<String, usize> = HashMap::new();
+        let mut user_shares: HashMap<String, usize> = HashMap::new();
+        let mut user_ips: HashMap<String, usize> = HashMap::new();
+        let mut paths: HashMap<String, usize> = HashMap::new();
+        let mut failed: HashMap<String, usize> = HashMap::new();
+        for access in &self.accesses {
+            let result = if access.success { "ok" } else { "failed" };
+            *operations.entry(format!("{} {}", access.operation, result)).or_insert(0) += 1;
+            *users.entry(access.user.clone()).or_insert(0) += 1;
+            *shares.entry(access.share.clone()).or_insert(0) += 1;
+            *user_shares.entry(format!("{} on {}", access.user, access.share)).or_insert(0) += 1;
+            *user_ips.entry(format!("{} from {}", access.user, access.ip)).or_insert(0) += 1;
+            *paths.entry(access.path.clone()).or_insert(0) += 1;
+            if !access.success {
+                *failed.entry(format!("{} {} {}", access.user, access.operation, access.path)).or_insert(0) += 1;
+            }
+        }
+        print_top("Operations", operations, limit);
+        print_top("Operations per user", users, limit);
+        print_top("Operations per share", shares, limit);
+        print_top("Users by share", user_shares, limit);
+        print_top("Users by client", user_ips, limit);
+        print_top("Most accessed paths", paths, limit);
+        print_top("Failed operations", failed, limit);
+
+        // Deletes and renames are usually what an investigation is after
+        let destructive: Vec<&FileAccess> = self.accesses.iter().filter(|a| a.is_destructive()).collect();
+        if !destructive.is_empty() {
+            println!("Deletes and renames:");
+            for access in destructive.iter().take(limit) {
+                println!("{}", access.entry.to_normalized());
+            }
+            println!();
+        }
+    }
+}
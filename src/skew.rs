@@ -0,0 +1,175 @@
+use crate::log_entry::CrunchLog;
+use log::warn;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+// How many preceding lines are considered "neighbours" when comparing hosts
+const NEIGHBOUR_WINDOW: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct HostOffset {
+    /// Estimated offset in seconds relative to the reference host (positive = host clock ahead)
+    pub offset: i64,
+    /// Number of interleaving samples the estimate is based on
+    pub samples: usize,
+    /// Last clock offset reported by NTP/chrony lines from this host, if any
+    pub ntp_offset: Option<f64>,
+}
+
+pub struct ClockSkew {
+    reference: String,
+    offsets: BTreeMap<String, HostOffset>,
+    threshold: i64,
+}
+
+impl ClockSkew {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut skew = Self {
+            reference: String::new(),
+            offsets: BTreeMap::new(),
+            threshold: 5,
+        };
+
+        // The busiest host is the reference clock everything else is compared to
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for entry in &log.entries {
+            if entry.host != "#" {
+                *counts.entry(entry.host.as_str()).or_insert(0) += 1;
+            }
+        }
+        let reference = match counts.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0))) {
+            Some((host, _)) => host.to_string(),
+            None => return skew,
+        };
+
+        // Merged logs are written in arrival order, so neighbouring lines from
+        // different hosts should carry (almost) the same timestamps
+        let mut samples: HashMap<String, Vec<i64>> = HashMap::new();
+        let mut last_seen: HashMap<&str, (usize, i64)> = HashMap::new();
+
+        for (idx, entry) in log.entries.iter().enumerate() {
+            if entry.host == "#" {
+                continue;
+            }
+            let ts = entry.naive_datetime().and_utc().timestamp();
+
+            if entry.host == reference {
+                for (host, (seen_idx, seen_ts)) in &last_seen {
+                    if *host != reference && idx - seen_idx <= NEIGHBOUR_WINDOW {
+                        samples.entry(host.to_string()).or_default().push(seen_ts - ts);
+                    }
+                }
+            } else if let Some((seen_idx, seen_ts)) = last_seen.get(reference.as_str()) {
+                if idx - seen_idx <= NEIGHBOUR_WINDOW {
+                    samples.entry(entry.host.clone()).or_default().push(ts - seen_ts);
+                }
+            }
+
+            last_seen.insert(entry.host.as_str(), (idx, ts));
+        }
+
+        let ntp_offsets = Self::ntp_offsets(log);
+
+        for host in counts.keys() {
+            if *host == reference {
+                continue;
+            }
+            let mut host_samples = samples.remove(*host).unwrap_or_default();
+            let ntp_offset = ntp_offsets.get(*host).copied();
+
+            // Fall back to NTP-reported offset when hosts never interleave
+            let offset = if host_samples.is_empty() {
+                ntp_offset.map(|o| o.round() as i64).unwrap_or(0)
+            } else {
+                host_samples.sort();
+                host_samples[host_samples.len() / 2]
+            };
+
+            skew.offsets.insert(host.to_string(), HostOffset {
+                offset,
+                samples: host_samples.len(),
+                ntp_offset,
+            });
+        }
+
+        skew.reference = reference;
+        skew
+    }
+
+    /// Clock offsets reported by ntpd/chronyd, converted to "host minus true time"
+    fn ntp_offsets(log: &CrunchLog) -> HashMap<String, f64> {
+        let patterns = [
+            Regex::new(r"adjusting local clock by (-?[0-9.]+)s").unwrap(),
+            Regex::new(r"time reset ([+-]?[0-9.]+) s").unwrap(),
+            Regex::new(r"step time server \S+ offset ([+-]?[0-9.]+) sec").unwrap(),
+            Regex::new(r"System clock wrong by ([+-]?[0-9.]+) seconds").unwrap(),
+        ];
+
+        let mut offsets = HashMap::new();
+        for entry in &log.entries {
+            for re in &patterns {
+                if let Some(caps) = re.captures(&entry.log_entry) {
+                    if let Ok(value) = caps[1].parse::<f64>() {
+                        offsets.insert(entry.host.clone(), -value);
+                    }
+                }
+            }
+        }
+        offsets
+    }
+
+    pub fn set_threshold(&mut self, threshold: i64) {
+        self.threshold = threshold;
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    pub fn offsets(&self) -> &BTreeMap<String, HostOffset> {
+        &self.offsets
+    }
+
+    /// Hosts whose clocks disagree with the reference by more than the threshold
+    pub fn skewed_hosts(&self) -> Vec<&str> {
+        self.offsets
+            .iter()
+            .filter(|(_, o)| o.offset.abs() > self.threshold)
+            .map(|(host, _)| host.as_str())
+            .collect()
+    }
+
+    /// Shift entries of skewed hosts so they line up with the reference host
+    pub fn apply(&self, log: &mut CrunchLog) {
+        for entry in &mut log.entries {
+            if let Some(offset) = self.offsets.get(&entry.host) {
+                if offset.offset.abs() > self.threshold {
+                    entry.shift_seconds(-offset.offset);
+                }
+            }
+        }
+    }
+
+    pub fn display(&self) {
+        if self.reference.is_empty() {
+            println!("No hosts found");
+            return;
+        }
+
+        println!("Reference host: {}", self.reference);
+        println!();
+
+        for (host, offset) in &self.offsets {
+            let ntp = offset.ntp_offset
+                .map(|o| format!("\tntp: {:+.3}s", o))
+                .unwrap_or_default();
+            let warning = if offset.offset.abs() > self.threshold { "\tWARNING: skewed" } else { "" };
+            println!("{:+}s\t{}\t({} samples){}{}", offset.offset, host, offset.samples, ntp, warning);
+        }
+
+        let skewed = self.skewed_hosts();
+        if !skewed.is_empty() {
+            warn!("{} host(s) differ from {} by more than {}s", skewed.len(), self.reference, self.threshold);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::log_entry::CrunchLog;
+use chrono::NaiveDateTime;
+
+/// The attr naming the input file of an entry when several files are merged
+pub const INPUT_ATTR: &str = "input";
+
+/// What one input file contributed to a merged analysis
+#[derive(Debug, Clone)]
+pub struct SourceSummary {
+    pub name: String,
+    pub format: String,
+    pub entries: usize,
+    /// Lines no parser could make sense of, kept as abnormal entries
+    pub unparsed: usize,
+    pub first: Option<NaiveDateTime>,
+    pub last: Option<NaiveDateTime>,
+}
+
+impl SourceSummary {
+    pub fn from_log(name: &str, log: &CrunchLog) -> Self {
+        let dated: Vec<NaiveDateTime> = log.entries.iter()
+            .filter(|e| !e.is_undated())
+            .map(|e| e.naive_datetime())
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            format: log.parser_type.clone(),
+            entries: log.entries.len(),
+            unparsed: log.entries.len() - dated.len(),
+            first: dated.iter().min().copied(),
+            last: dated.iter().max().copied(),
+        }
+    }
+}
+
+pub struct SourceReport {
+    sources: Vec<SourceSummary>,
+}
+
+impl SourceReport {
+    pub fn new(sources: Vec<SourceSummary>) -> Self {
+        Self { sources }
+    }
+
+    pub fn sources(&self) -> &[SourceSummary] {
+        &self.sources
+    }
+
+    pub fn display(&self) {
+        if self.sources.is_empty() {
+            println!("No inputs");
+            return;
+        }
+
+        let total: usize = self.sources.iter().map(|s| s.entries).sum();
+        println!("Entries\tShare\tUnparsed\tFirst\t\t\tLast\t\t\tFormat\tInput");
+        for source in &self.sources {
+            let time = |t: Option<NaiveDateTime>| t
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "-\t\t".to_string());
+            let share = if total > 0 { source.entries as f64 / total as f64 * 100.0 } else { 0.0 };
+            println!("{}\t{:.1}%\t{}\t\t{}\t{}\t{}\t{}",
+                source.entries, share, source.unparsed, time(source.first), time(source.last),
+                source.format, source.name);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,79 @@
+use crate::filter::Filter;
+use crate::log_entry::{CrunchLog, LogEntry};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs::{File, create_dir_all};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SplitKey {
+    Daemon,
+    Host,
+}
+
+impl SplitKey {
+    /// The raw (unscrubbed) key of an entry
+    pub fn value<'a>(&self, entry: &'a LogEntry) -> &'a str {
+        match self {
+            SplitKey::Daemon => &entry.daemon,
+            SplitKey::Host => &entry.host,
+        }
+    }
+}
+
+pub struct LogSplitter {
+    key: SplitKey,
+    filter: Filter,
+}
+
+impl LogSplitter {
+    pub fn new(key: SplitKey, filter: Filter) -> Self {
+        Self { key, filter }
+    }
+
+    /// Group entries by the scrubbed key, the same way the daemon/host reports do
+    pub fn group<'a>(&self, log: &'a CrunchLog) -> BTreeMap<String, Vec<&'a LogEntry>> {
+        let mut groups: BTreeMap<String, Vec<&LogEntry>> = BTreeMap::new();
+
+        for entry in &log.entries {
+            let name = Self::file_name(&self.filter.scrub(self.key.value(entry)));
+            groups.entry(name).or_default().push(entry);
+        }
+
+        groups
+    }
+
+    /// Write each group to its own file in normalized format
+    pub fn write_to_dir(&self, log: &CrunchLog, out_dir: &Path) -> Result<Vec<(PathBuf, usize)>> {
+        create_dir_all(out_dir)?;
+
+        let mut written = Vec::new();
+        for (name, entries) in self.group(log) {
+            let file_path = out_dir.join(format!("{}.log", name));
+            let mut writer = BufWriter::new(File::create(&file_path)?);
+            for entry in &entries {
+                writeln!(writer, "{}", entry.to_normalized())?;
+            }
+            writer.flush()?;
+            written.push((file_path, entries.len()));
+        }
+
+        Ok(written)
+    }
+
+    /// Turn a daemon/host key into a safe file name (e.g. "sshd[#]:" -> "sshd")
+    fn file_name(key: &str) -> String {
+        let sanitized: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let sanitized = sanitized.trim_matches(|c| c == '_' || c == '.');
+
+        if sanitized.is_empty() {
+            "unknown".to_string()
+        } else {
+            sanitized.to_string()
+        }
+    }
+}
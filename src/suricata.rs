@@ -0,0 +1,104 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use std::collections::HashMap;
+
+/// One IDS alert from Suricata's eve.json
+#[derive(Debug, Clone)]
+pub struct SuricataAlert {
+    pub signature: String,
+    pub signature_id: String,
+    pub category: String,
+    pub severity: String,
+    pub action: String,
+    pub src: String,
+    pub dst: String,
+    pub dst_port: String,
+}
+
+pub struct SuricataReport {
+    alerts: Vec<SuricataAlert>,
+    event_types: HashMap<String, usize>,
+}
+
+impl SuricataReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut alerts = Vec::new();
+        let mut event_types: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let event_type = match entry.attrs.get("event_type") {
+                Some(event_type) => event_type,
+                None => continue,
+            };
+            *event_types.entry(event_type.clone()).or_insert(0) += 1;
+            if event_type != "alert" {
+                continue;
+            }
+
+            let attr = |key: &str| entry.attrs.get(key).cloned().unwrap_or_else(|| "-".to_string());
+            alerts.push(SuricataAlert {
+                signature: attr("signature"),
+                signature_id: attr("signature_id"),
+                category: attr("category"),
+                severity: attr("alert_severity"),
+                action: attr("action"),
+                src: attr("src"),
+                dst: attr("dst"),
+                dst_port: attr("dst_port"),
+            });
+        }
+
+        Self { alerts, event_types }
+    }
+
+    pub fn alerts(&self) -> &[SuricataAlert] {
+        &self.alerts
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.event_types.is_empty() {
+            println!("No Suricata events found");
+            return;
+        }
+
+        let events: usize = self.event_types.values().sum();
+        let blocked = self.alerts.iter().filter(|a| a.action == "blocked").count();
+        println!("Events: {}\tAlerts: {}\tBlocked: {}", events, self.alerts.len(), blocked);
+        println!();
+
+        print_top("Event types", self.event_types.clone(), limit);
+
+        let mut signatures: HashMap<String, usize> = HashMap::new();
+        let mut severities: HashMap<String, usize> = HashMap::new();
+        let mut categories: HashMap<String, usize> = HashMap::new();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        let mut destinations: HashMap<String, usize> = HashMap::new();
+        let mut pairs: HashMap<String, usize> = HashMap::new();
+
+        for alert in &self.alerts {
+            *signatures.entry(format!("[{}] {}", alert.signature_id, alert.signature)).or_insert(0) += 1;
+            *severities.entry(format!("severity {}", alert.severity)).or_insert(0) += 1;
+            *categories.entry(alert.category.clone()).or_insert(0) += 1;
+            *sources.entry(alert.src.clone()).or_insert(0) += 1;
+            *destinations.entry(format!("{}:{}", alert.dst, alert.dst_port)).or_insert(0) += 1;
+            *pairs.entry(format!("{} -> {}", alert.src, alert.dst)).or_insert(0) += 1;
+        }
+
+        print_top("Alerts by severity", severities, limit);
+        print_top("Top signatures", signatures, limit);
+        print_top("Top categories", categories, limit);
+        print_top("Top alert sources", sources, limit);
+        print_top("Top alert destinations", destinations, limit);
+        print_top("Top source -> destination", pairs, limit);
+
+        // Severity 1 alerts are listed individually
+        let critical: Vec<_> = self.alerts.iter().filter(|a| a.severity == "1").collect();
+        if !critical.is_empty() {
+            println!("Severity 1 alerts:");
+            for alert in critical.iter().take(limit) {
+                println!("{}\t{} -> {}:{}\t{}", alert.signature, alert.src, alert.dst, alert.dst_port, alert.action);
+            }
+            println!();
+        }
+    }
+}
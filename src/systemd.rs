@@ -0,0 +1,242 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitEventKind {
+    Started,
+    Stopped,
+    Reloaded,
+    /// The unit entered the failed state
+    Failed,
+    /// systemd scheduled an automatic restart
+    Restart,
+    /// Restarts came too quickly and systemd gave up on the unit
+    RestartLimit,
+    /// The kernel OOM killer killed a process, or a process of the unit
+    OomKill,
+    Segfault,
+}
+
+impl UnitEventKind {
+    /// Whether the event is something going wrong rather than routine
+    pub fn is_problem(&self) -> bool {
+        !matches!(self, Self::Started | Self::Stopped | Self::Reloaded)
+    }
+}
+
+/// A unit lifecycle event, or a crash of a process. `unit` is the unit name
+/// ("nginx.service"), the description when systemd logs only that, or the
+/// process name for kernel OOM kills and segfaults
+#[derive(Debug, Clone)]
+pub struct UnitEvent {
+    pub timestamp: NaiveDateTime,
+    pub unit: String,
+    pub kind: UnitEventKind,
+    /// The failure result ("exit-code", "oom-kill"), or the restart counter
+    pub detail: Option<String>,
+}
+
+/// Automatic restarts of one unit
+#[derive(Debug, Clone)]
+pub struct RestartLoop {
+    pub unit: String,
+    pub restarts: usize,
+    /// Highest restart counter systemd reported
+    pub counter: usize,
+    pub gave_up: bool,
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+}
+
+/// Unit starts, stops and failures from systemd, restart loops, and the
+/// OOM kills and segfaults the kernel reports, as in journalctl output
+pub struct SystemdReport {
+    events: Vec<UnitEvent>,
+}
+
+impl SystemdReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // "Started nginx.service - A high performance web server." (systemd 246+) or "Started Daily apt upgrade."
+        let lifecycle = Regex::new(r"^(Started|Stopped|Reloaded) (.+?)\.?$").unwrap();
+        // "nginx.service: Failed with result 'exit-code'."
+        let unit_message = Regex::new(r"^(\S+\.(?:service|socket|timer|mount|automount|swap|target|path|slice|scope)): (.+)$").unwrap();
+        let failed_result = Regex::new(r"^Failed with result '([^']+)'").unwrap();
+        let restart = Regex::new(r"^Scheduled restart job, restart counter is at (\d+)").unwrap();
+        // Before systemd 236
+        let entered_failed = Regex::new(r"^Unit (\S+) entered failed state").unwrap();
+        // "Out of memory: Killed process 1234 (java)", "Memory cgroup out of memory: Killed process 1234 (java)"
+        let oom_kill = Regex::new(r"[Oo]ut of memory: Kill(?:ed)? process \d+ \(([^)]+)\)").unwrap();
+        // "java[1234]: segfault at 0 ip ...", "traps: java[1234] general protection fault ip:..."
+        let segfault = Regex::new(r"(?:^|\s)([^\s\[]+)\[\d+\]:? (?:segfault at|general protection)").unwrap();
+
+        let mut events = Vec::new();
+        for entry in &log.entries {
+            let message = entry.log_entry.strip_prefix(": ").or_else(|| entry.log_entry.strip_prefix(" ")).unwrap_or(&entry.log_entry).trim_end();
+            let from_systemd = entry.program() == "systemd";
+            let mut push = |unit: &str, kind: UnitEventKind, detail: Option<&str>| events.push(UnitEvent {
+                timestamp: entry.naive_datetime(),
+                unit: unit.to_string(),
+                kind,
+                detail: detail.map(|d| d.to_string()),
+            });
+
+            if from_systemd {
+                if let Some(caps) = lifecycle.captures(message) {
+                    let kind = match &caps[1] {
+                        "Started" => UnitEventKind::Started,
+                        "Stopped" => UnitEventKind::Stopped,
+                        _ => UnitEventKind::Reloaded,
+                    };
+                    // Prefer the unit name over the description when both are given
+                    let target = &caps[2];
+                    let unit = target.split_once(" - ").map(|(unit, _)| unit).unwrap_or(target);
+                    push(unit, kind, None);
+                } else if let Some(caps) = unit_message.captures(message) {
+                    let unit = &caps[1];
+                    let text = &caps[2];
+                    if let Some(result) = failed_result.captures(text) {
+                        push(unit, UnitEventKind::Failed, Some(&result[1]));
+                    } else if let Some(counter) = restart.captures(text) {
+                        push(unit, UnitEventKind::Restart, Some(&counter[1]));
+                    } else if text.starts_with("Start request repeated too quickly") {
+                        push(unit, UnitEventKind::RestartLimit, None);
+                    } else if text.contains("killed by the OOM killer") {
+                        push(unit, UnitEventKind::OomKill, None);
+                    }
+                } else if let Some(caps) = entered_failed.captures(message) {
+                    push(&caps[1], UnitEventKind::Failed, None);
+                }
+            } else if let Some(caps) = oom_kill.captures(message) {
+                push(&caps[1], UnitEventKind::OomKill, None);
+            } else if let Some(caps) = segfault.captures(message) {
+                push(&caps[1], UnitEventKind::Segfault, None);
+            }
+        }
+        events.sort_by_key(|e| e.timestamp);
+
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[UnitEvent] {
+        &self.events
+    }
+
+    /// Units systemd restarted automatically, most restarts first
+    pub fn restart_loops(&self) -> Vec<RestartLoop> {
+        let mut loops: HashMap<&str, RestartLoop> = HashMap::new();
+        for event in &self.events {
+            if !matches!(event.kind, UnitEventKind::Restart | UnitEventKind::RestartLimit) {
+                continue;
+            }
+            let restart = loops.entry(event.unit.as_str()).or_insert_with(|| RestartLoop {
+                unit: event.unit.clone(),
+                restarts: 0,
+                counter: 0,
+                gave_up: false,
+                first: event.timestamp,
+                last: event.timestamp,
+            });
+            if event.kind == UnitEventKind::RestartLimit {
+                restart.gave_up = true;
+            } else {
+                restart.restarts += 1;
+                let counter = event.detail.as_ref().and_then(|d| d.parse().ok()).unwrap_or(0);
+                restart.counter = restart.counter.max(counter);
+            }
+            restart.last = event.timestamp;
+        }
+
+        let mut loops: Vec<RestartLoop> = loops.into_values().collect();
+        loops.sort_by(|a, b| b.restarts.cmp(&a.restarts).then(a.unit.cmp(&b.unit)));
+        loops
+    }
+
+    fn count(&self, kind: UnitEventKind) -> usize {
+        self.events.iter().filter(|e| e.kind == kind).count()
+    }
+
+    /// Problems per hour, or per day for logs spanning more than two days
+    fn problems_over_time(&self) -> (BTreeMap<NaiveDateTime, [usize; 4]>, &'static str) {
+        let span = match (self.events.first(), self.events.last()) {
+            (Some(first), Some(last)) => last.timestamp - first.timestamp,
+            _ => Duration::zero(),
+        };
+        let daily = span > Duration::days(2);
+
+        let mut buckets: BTreeMap<NaiveDateTime, [usize; 4]> = BTreeMap::new();
+        for event in self.events.iter().filter(|e| e.kind.is_problem() && e.kind != UnitEventKind::RestartLimit) {
+            let hour = event.timestamp.date().and_hms_opt(event.timestamp.hour(), 0, 0).unwrap_or(event.timestamp);
+            let start = if daily { event.timestamp.date().and_hms_opt(0, 0, 0).unwrap_or(hour) } else { hour };
+            let column = match event.kind {
+                UnitEventKind::Failed => 0,
+                UnitEventKind::Restart => 1,
+                UnitEventKind::OomKill => 2,
+                _ => 3,
+            };
+            buckets.entry(start).or_insert([0; 4])[column] += 1;
+        }
+        (buckets, if daily { "%Y-%m-%d" } else { "%Y-%m-%d %H:00" })
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.events.is_empty() {
+            println!("No systemd unit events found");
+            return;
+        }
+
+        println!("Started: {}\tStopped: {}\tReloaded: {}\tFailed: {}\tRestarts: {}\tOOM kills: {}\tSegfaults: {}",
+            self.count(UnitEventKind::Started), self.count(UnitEventKind::Stopped), self.count(UnitEventKind::Reloaded),
+            self.count(UnitEventKind::Failed), self.count(UnitEventKind::Restart),
+            self.count(UnitEventKind::OomKill), self.count(UnitEventKind::Segfault));
+        println!();
+
+        let mut failed: HashMap<String, usize> = HashMap::new();
+        let mut results: HashMap<String, usize> = HashMap::new();
+        let mut oom: HashMap<String, usize> = HashMap::new();
+        let mut segfaults: HashMap<String, usize> = HashMap::new();
+        for event in &self.events {
+            match event.kind {
+                UnitEventKind::Failed => {
+                    *failed.entry(event.unit.clone()).or_insert(0) += 1;
+                    if let Some(result) = &event.detail {
+                        *results.entry(result.clone()).or_insert(0) += 1;
+                    }
+                }
+                UnitEventKind::OomKill => *oom.entry(event.unit.clone()).or_insert(0) += 1,
+                UnitEventKind::Segfault => *segfaults.entry(event.unit.clone()).or_insert(0) += 1,
+                _ => {}
+            }
+        }
+
+        print_top("Failed units", failed, limit);
+        print_top("Failure results", results, limit);
+
+        let loops = self.restart_loops();
+        if !loops.is_empty() {
+            println!("Restart loops:");
+            for restart in loops.iter().take(limit) {
+                let gave_up = if restart.gave_up { ", gave up (start request repeated too quickly)" } else { "" };
+                println!("{}:\t{}\tcounter up to {}, {} .. {}{}", restart.restarts, restart.unit, restart.counter,
+                    restart.first.format("%Y-%m-%d %H:%M:%S"), restart.last.format("%Y-%m-%d %H:%M:%S"), gave_up);
+            }
+            println!();
+        }
+
+        print_top("OOM kills", oom, limit);
+        print_top("Segfaults", segfaults, limit);
+
+        let (buckets, format) = self.problems_over_time();
+        if !buckets.is_empty() {
+            println!("Problems over time:");
+            println!("failed\trestarts\toom\tsegfaults\ttime");
+            // The most recent ones when there are more than the limit
+            for (start, [failed, restarts, oom, segfaults]) in buckets.iter().skip(buckets.len().saturating_sub(limit)) {
+                println!("{}\t{}\t{}\t{}\t{}", failed, restarts, oom, segfaults, start.format(format));
+            }
+            println!();
+        }
+    }
+}
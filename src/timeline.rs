@@ -0,0 +1,87 @@
+use crate::filter::Filter;
+use crate::hours::shade;
+use crate::log_entry::CrunchLog;
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashMap;
+
+/// Column widths the time span is divided into, in seconds
+const STEPS: [i64; 20] = [
+    1, 5, 10, 15, 30, 60, 300, 600, 900, 1800, 3600, 7200, 10800, 21600, 43200,
+    86400, 172800, 604800, 2592000, 31536000,
+];
+
+/// Entries per host over the time span of the log, one row of columns per host
+pub struct HostTimeline {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    step: i64,
+    hosts: HashMap<String, Vec<usize>>,
+}
+
+impl HostTimeline {
+    /// At most `width` columns, each a round number of seconds, minutes,
+    /// hours or days
+    pub fn from_log(log: &CrunchLog, width: usize, filter: &Filter) -> Self {
+        let times: Vec<(NaiveDateTime, &str)> = log.entries.iter()
+            .filter(|entry| !entry.is_undated())
+            .map(|entry| (entry.naive_datetime(), entry.host.as_str()))
+            .collect();
+
+        let first = times.iter().map(|(t, _)| *t).min().unwrap_or_default();
+        let last = times.iter().map(|(t, _)| *t).max().unwrap_or_default();
+        let span = (last - first).num_seconds() + 1;
+        let width = width.max(1) as i64;
+        let step = STEPS.iter().copied()
+            .find(|step| (span + step - 1) / step <= width)
+            .unwrap_or(STEPS[STEPS.len() - 1]);
+
+        // Columns start on a multiple of the step, so they read as 10:15, 10:30...;
+        // columns of days or longer start at midnight of the first day
+        let align = step.min(86400);
+        let start = first - Duration::seconds(first.and_utc().timestamp().rem_euclid(align));
+        let columns = ((last - start).num_seconds() / step + 1) as usize;
+
+        let mut hosts: HashMap<String, Vec<usize>> = HashMap::new();
+        for (time, host) in times {
+            let column = ((time - start).num_seconds() / step) as usize;
+            hosts.entry(filter.scrub(host)).or_insert_with(|| vec![0; columns])[column] += 1;
+        }
+
+        Self { start, end: start + Duration::seconds(step * columns as i64), step, hosts }
+    }
+
+    pub fn counts(&self, host: &str) -> Option<&[usize]> {
+        self.hosts.get(host).map(|c| c.as_slice())
+    }
+
+    fn step_name(&self) -> String {
+        match self.step {
+            s if s % 86400 == 0 => format!("{} day{}", s / 86400, if s == 86400 { "" } else { "s" }),
+            s if s % 3600 == 0 => format!("{} hour{}", s / 3600, if s == 3600 { "" } else { "s" }),
+            s if s % 60 == 0 => format!("{} minute{}", s / 60, if s == 60 { "" } else { "s" }),
+            s => format!("{} second{}", s, if s == 1 { "" } else { "s" }),
+        }
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.hosts.is_empty() {
+            println!("No timestamped entries found");
+            return;
+        }
+
+        let mut rows: Vec<(&String, &Vec<usize>, usize)> = self.hosts.iter()
+            .map(|(host, counts)| (host, counts, counts.iter().sum()))
+            .collect();
+        rows.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)));
+
+        let format = if self.step < 60 { "%Y-%m-%d %H:%M:%S" } else { "%Y-%m-%d %H:%M" };
+        println!("Entries per {} for the top {} hosts, {} .. {} (each row scaled to its busiest column):",
+            self.step_name(), limit.min(rows.len()), self.start.format(format), self.end.format(format));
+        println!("  total    max");
+        for (host, counts, total) in rows.into_iter().take(limit) {
+            let peak = counts.iter().copied().max().unwrap_or(0);
+            println!("{:>7} {:>6}  |{}|  {}", total, peak, shade(counts), host);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,158 @@
+use crate::graph::GraphType;
+use crate::log_entry::CrunchLog;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike};
+use std::collections::BTreeMap;
+
+/// Width of the stacked bar of the busiest bucket
+const BAR_WIDTH: usize = 40;
+
+/// Longest run of empty buckets shown row by row
+const MAX_QUIET_ROWS: usize = 3;
+
+/// Entries, errors (emerg to err) and warnings in one time bucket
+#[derive(Debug, Clone, Copy)]
+pub struct TrendBucket {
+    pub start: NaiveDateTime,
+    pub total: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl TrendBucket {
+    /// Share of the bucket's entries that are errors, 0 to 1
+    pub fn error_ratio(&self) -> f64 {
+        if self.total == 0 { 0.0 } else { self.errors as f64 / self.total as f64 }
+    }
+}
+
+/// Error and warning counts per time bucket next to the total, so a rising
+/// share of errors at a steady volume stands out
+pub struct SeverityTrend {
+    graph_type: GraphType,
+    buckets: Vec<TrendBucket>,
+    rated: usize,
+}
+
+impl SeverityTrend {
+    pub fn from_log(log: &CrunchLog, graph_type: GraphType) -> Self {
+        let mut counts: BTreeMap<NaiveDateTime, TrendBucket> = BTreeMap::new();
+        let mut rated = 0;
+        for entry in &log.entries {
+            if entry.is_undated() {
+                continue;
+            }
+            let start = bucket_start(entry.naive_datetime(), graph_type);
+            let bucket = counts.entry(start).or_insert(TrendBucket { start, total: 0, errors: 0, warnings: 0 });
+            bucket.total += 1;
+            match entry.severity {
+                Some(s) if s <= 3 => bucket.errors += 1,
+                Some(4) => bucket.warnings += 1,
+                _ => {}
+            }
+            if entry.severity.is_some() {
+                rated += 1;
+            }
+        }
+
+        Self { graph_type, buckets: counts.into_values().collect(), rated }
+    }
+
+    /// Buckets with entries, in time order
+    pub fn buckets(&self) -> &[TrendBucket] {
+        &self.buckets
+    }
+
+    pub fn display(&self) {
+        if self.rated == 0 {
+            println!("No entries with a severity found");
+            return;
+        }
+
+        let format = match self.graph_type {
+            GraphType::Seconds => "%Y-%m-%d %H:%M:%S",
+            GraphType::Minutes | GraphType::Hours => "%Y-%m-%d %H:%M",
+            GraphType::Days => "%Y-%m-%d",
+            GraphType::Months => "%Y-%m",
+            GraphType::Years => "%Y",
+        };
+        let label_width = self.buckets.first().map(|b| b.start.format(format).to_string().len()).unwrap_or(0);
+        let max = self.buckets.iter().map(|b| b.total).max().unwrap_or(0).max(1);
+
+        println!("Errors (#) and warnings (+) among all entries (.) per bucket:");
+        println!("{:<width$}  {:>7} {:>7} {:>8} {:>6}", "", "total", "errors", "warnings", "err%", width = label_width);
+        let mut previous: Option<NaiveDateTime> = None;
+        for bucket in &self.buckets {
+            // A few quiet buckets in between are shown as empty rows, longer
+            // gaps as one "..." row
+            if let Some(previous) = previous {
+                let mut quiet = Vec::new();
+                let mut start = next_bucket(previous, self.graph_type);
+                while start < bucket.start && quiet.len() <= MAX_QUIET_ROWS {
+                    quiet.push(start);
+                    start = next_bucket(start, self.graph_type);
+                }
+                if quiet.len() > MAX_QUIET_ROWS {
+                    println!("...");
+                } else {
+                    for start in quiet {
+                        println!("{:<width$}  {:>7} {:>7} {:>8} {:>5.1}%", start.format(format), 0, 0, 0, 0.0, width = label_width);
+                    }
+                }
+            }
+            previous = Some(bucket.start);
+
+            // Errors and warnings keep at least one cell so they never vanish in a busy bucket
+            let cells = |count: usize| match count {
+                0 => 0,
+                _ => (count * BAR_WIDTH / max).max(1),
+            };
+            let errors = cells(bucket.errors);
+            let warnings = cells(bucket.warnings);
+            let rest = cells(bucket.total).saturating_sub(errors + warnings);
+            println!("{:<width$}  {:>7} {:>7} {:>8} {:>5.1}%  {}{}{}",
+                bucket.start.format(format), bucket.total, bucket.errors, bucket.warnings, bucket.error_ratio() * 100.0,
+                "#".repeat(errors), "+".repeat(warnings), ".".repeat(rest), width = label_width);
+        }
+        println!();
+
+        // The error share of the earlier half of the busy buckets against the later half
+        let half = self.buckets.len() / 2;
+        if half > 0 {
+            let ratio = |buckets: &[TrendBucket]| {
+                let total: usize = buckets.iter().map(|b| b.total).sum();
+                let errors: usize = buckets.iter().map(|b| b.errors).sum();
+                if total == 0 { 0.0 } else { errors as f64 * 100.0 / total as f64 }
+            };
+            let (earlier, later) = self.buckets.split_at(half);
+            println!("Error share: {:.1}% in the first half, {:.1}% in the second half", ratio(earlier), ratio(later));
+        }
+        let unrated = self.buckets.iter().map(|b| b.total).sum::<usize>() - self.rated;
+        if unrated > 0 {
+            println!("{} entries without a severity are counted in the totals only", unrated);
+        }
+    }
+}
+
+fn bucket_start(time: NaiveDateTime, graph_type: GraphType) -> NaiveDateTime {
+    let date = time.date();
+    let at = |date: NaiveDate, hour: u32, minute: u32, second: u32| date.and_hms_opt(hour, minute, second).unwrap_or(time);
+    match graph_type {
+        GraphType::Seconds => time,
+        GraphType::Minutes => at(date, time.hour(), time.minute(), 0),
+        GraphType::Hours => at(date, time.hour(), 0, 0),
+        GraphType::Days => at(date, 0, 0, 0),
+        GraphType::Months => at(date.with_day(1).unwrap_or(date), 0, 0, 0),
+        GraphType::Years => at(NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date), 0, 0, 0),
+    }
+}
+
+fn next_bucket(start: NaiveDateTime, graph_type: GraphType) -> NaiveDateTime {
+    match graph_type {
+        GraphType::Seconds => start + Duration::seconds(1),
+        GraphType::Minutes => start + Duration::minutes(1),
+        GraphType::Hours => start + Duration::hours(1),
+        GraphType::Days => start + Duration::days(1),
+        GraphType::Months => start + Months::new(1),
+        GraphType::Years => start + Months::new(12),
+    }
+}
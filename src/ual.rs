@@ -0,0 +1,94 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use std::collections::{BTreeSet, HashMap};
+
+/// Audit records of one user: operations with their counts, the client
+/// addresses seen and the time span
+#[derive(Debug, Clone)]
+pub struct UserActivity {
+    pub user: String,
+    pub records: usize,
+    pub operations: HashMap<String, usize>,
+    pub clients: BTreeSet<String>,
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+}
+
+/// Operations per user from Microsoft 365 Unified Audit Log entries, for
+/// spotting what a compromised account did (inbox rules, mail access,
+/// sign-ins from new addresses)
+pub struct UalReport {
+    users: Vec<UserActivity>,
+    operations: HashMap<String, usize>,
+}
+
+impl UalReport {
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let mut users: HashMap<String, UserActivity> = HashMap::new();
+        let mut operations: HashMap<String, usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let operation = match entry.attrs.get("Operation") {
+                Some(operation) => operation,
+                None => continue,
+            };
+            *operations.entry(operation.clone()).or_insert(0) += 1;
+
+            let user = entry.attrs.get("user").cloned().unwrap_or_else(|| "-".to_string());
+            let time = entry.naive_datetime();
+            let activity = users.entry(user.clone()).or_insert_with(|| UserActivity {
+                user,
+                records: 0,
+                operations: HashMap::new(),
+                clients: BTreeSet::new(),
+                first: time,
+                last: time,
+            });
+            activity.records += 1;
+            *activity.operations.entry(operation.clone()).or_insert(0) += 1;
+            if let Some(src) = entry.attrs.get("src") {
+                activity.clients.insert(src.clone());
+            }
+            activity.first = activity.first.min(time);
+            activity.last = activity.last.max(time);
+        }
+
+        let mut users: Vec<UserActivity> = users.into_values().collect();
+        users.sort_by(|a, b| b.records.cmp(&a.records).then(a.user.cmp(&b.user)));
+        Self { users, operations }
+    }
+
+    /// Users, most records first
+    pub fn users(&self) -> &[UserActivity] {
+        &self.users
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.users.is_empty() {
+            println!("No audit log records found");
+            return;
+        }
+
+        let records: usize = self.users.iter().map(|u| u.records).sum();
+        println!("Audit records: {}\tUsers: {}\tOperations: {}", records, self.users.len(), self.operations.len());
+        println!();
+
+        print_top("Operations", self.operations.clone(), limit);
+
+        println!("Operations per user:");
+        for activity in self.users.iter().take(limit) {
+            println!("{}\t{} records, {} .. {}", activity.user, activity.records,
+                activity.first.format("%Y-%m-%d %H:%M:%S"), activity.last.format("%Y-%m-%d %H:%M:%S"));
+            if !activity.clients.is_empty() {
+                let clients: Vec<&str> = activity.clients.iter().map(|c| c.as_str()).collect();
+                println!("\tfrom {}", clients.join(", "));
+            }
+            let mut operations: Vec<(&String, &usize)> = activity.operations.iter().collect();
+            operations.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            for (operation, count) in operations.into_iter().take(limit) {
+                println!("\t{}:\t{}", count, operation);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::log_entry::LogEntry;
+use anyhow::{Result, anyhow};
+use regex::Regex;
+
+/// Entry attribute parsers store the user in (web auth, EVTX, LEEF, database
+/// and firewall logs)
+pub const USER_ATTR: &str = "user";
+
+// Users named in syslog messages: (daemon, pattern). An empty daemon
+// matches any daemon, for PAM modules logging under their service's name
+const MESSAGE_PATTERNS: &[(&str, &str)] = &[
+    ("sshd", r"(?:Accepted|Failed) \S+ for (?:invalid user )?(?P<user>\S+) from "),
+    ("sshd", r"[Ii]nvalid user (?P<user>\S+) from "),
+    ("sshd", r"Disconnected from (?:invalid |authenticating )?user (?P<user>\S+) "),
+    ("sudo", r"^\s*(?P<user>\S+) : (?:[^;]*; )?TTY="),
+    ("su", r"(?:Successful su|FAILED SU|FAILED su) for \S+ by (?P<user>\S+)"),
+    ("login", r"(?:LOGIN ON \S+ BY|FAILED LOGIN \d+ FROM \S+ FOR) (?P<user>[^\s,]+)"),
+    ("systemd-logind", r"New session \S+ of user (?P<user>[^\s.]+)"),
+    ("useradd", r"new user: name=(?P<user>[^,\s]+)"),
+    ("passwd", r"password changed for (?P<user>\S+)"),
+    ("", r"pam_\w+\([^)]*\): session (?:opened|closed) for user (?P<user>[^\s(]+)"),
+    ("", r"pam_\w+\([^)]*\): authentication failure;.*\buser=(?P<user>\S+)"),
+    ("", r"\buser=<(?P<user>[^>]+)>"),
+];
+
+/// Finds the user an entry is about: extra patterns first, then the user
+/// field set by the parser, then the built-in sshd, PAM, sudo, su, login and
+/// logind patterns
+#[derive(Debug, Clone)]
+pub struct UserExtractor {
+    patterns: Vec<Regex>,
+    builtin: Vec<(&'static str, Regex)>,
+}
+
+impl Default for UserExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UserExtractor {
+    pub fn new() -> Self {
+        Self {
+            patterns: Vec::new(),
+            builtin: MESSAGE_PATTERNS.iter()
+                .map(|(daemon, pattern)| (*daemon, Regex::new(pattern).unwrap()))
+                .collect(),
+        }
+    }
+
+    /// Add a pattern matched against the message; the user is its `user`
+    /// group, or the first group if it has no named one
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
+        let regex = Regex::new(pattern).map_err(|e| anyhow!("Invalid user pattern '{}': {}", pattern, e))?;
+        if regex.captures_len() < 2 {
+            return Err(anyhow!("User pattern '{}' has no group capturing the user", pattern));
+        }
+        self.patterns.push(regex);
+        Ok(())
+    }
+
+    pub fn user(&self, entry: &LogEntry) -> Option<String> {
+        let capture = |regex: &Regex| {
+            let caps = regex.captures(&entry.log_entry)?;
+            caps.name("user").or_else(|| caps.get(1))
+                .map(|m| m.as_str().to_string())
+                .filter(|u| !u.is_empty())
+        };
+
+        if let Some(user) = self.patterns.iter().find_map(capture) {
+            return Some(user);
+        }
+        if let Some(user) = entry.attrs.get(USER_ATTR) {
+            return Some(user.clone()).filter(|u| !u.is_empty() && u != "-");
+        }
+
+        let daemon = entry.program();
+        self.builtin.iter()
+            .filter(|(d, _)| d.is_empty() || *d == daemon)
+            .find_map(|(_, regex)| capture(regex))
+    }
+}
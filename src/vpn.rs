@@ -0,0 +1,166 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// A VPN tunnel from connect to disconnect (or to the end of the log)
+#[derive(Debug, Clone)]
+pub struct VpnSession {
+    pub source: &'static str,
+    pub user: String,
+    pub ip: String,
+    pub start: NaiveDateTime,
+    pub end: Option<NaiveDateTime>,
+    pub bytes_received: Option<u64>,
+    pub bytes_sent: Option<u64>,
+}
+
+impl VpnSession {
+    /// Session length in seconds, if the disconnect was seen
+    pub fn duration(&self) -> Option<i64> {
+        self.end.map(|end| (end - self.start).num_seconds())
+    }
+}
+
+pub struct VpnReport {
+    sessions: Vec<VpnSession>,
+}
+
+impl VpnReport {
+    /// Pair OpenVPN client-instance and WireGuard handshake/zeroing lines into sessions
+    pub fn from_log(log: &CrunchLog) -> Self {
+        // OpenVPN: "1.2.3.4:55555 [alice] Peer Connection Initiated with [AF_INET]1.2.3.4:55555"
+        let ovpn_start = Regex::new(r"\[(?P<user>[^\]]+)\] Peer Connection Initiated with \[AF_INET6?\](?P<ip>[0-9a-fA-F.:]+):\d+").unwrap();
+        // OpenVPN: "alice/1.2.3.4:55555 SIGTERM[soft,remote-exit] received, client-instance exiting"
+        let ovpn_end = Regex::new(r"^(?P<user>[^/\s]+)/(?P<ip>[0-9a-fA-F.:]+):\d+ .*client-instance (?:exiting|restarting)").unwrap();
+        // OpenVPN client-disconnect scripts/plugins logging the byte counters
+        let ovpn_bytes = Regex::new(r"^(?P<user>[^/\s]+)/(?P<ip>[0-9a-fA-F.:]+):\d+ .*bytes_received=(?P<rx>\d+).*bytes_sent=(?P<tx>\d+)").unwrap();
+        // WireGuard: handshakes open a session, zeroing out the keys closes it
+        let wg_start = Regex::new(r"^(?:Receiving handshake initiation|Receiving handshake response) from peer (?P<peer>\d+) \((?P<ip>[^)]+?):\d+\)").unwrap();
+        let wg_end = Regex::new(r"^Zeroing out all keys for peer (?P<peer>\d+)").unwrap();
+
+        let mut sessions: Vec<VpnSession> = Vec::new();
+        let mut open: HashMap<(String, String), usize> = HashMap::new();
+
+        for entry in &log.entries {
+            let ts = entry.naive_datetime();
+
+            if let Some(caps) = ovpn_start.captures(&entry.log_entry) {
+                let key = (caps["user"].to_string(), caps["ip"].to_string());
+                open.insert(key.clone(), sessions.len());
+                sessions.push(VpnSession {
+                    source: "openvpn",
+                    user: key.0,
+                    ip: key.1,
+                    start: ts,
+                    end: None,
+                    bytes_received: None,
+                    bytes_sent: None,
+                });
+            } else if let Some(caps) = ovpn_bytes.captures(&entry.log_entry) {
+                let key = (caps["user"].to_string(), caps["ip"].to_string());
+                if let Some(&idx) = open.get(&key) {
+                    sessions[idx].bytes_received = caps["rx"].parse().ok();
+                    sessions[idx].bytes_sent = caps["tx"].parse().ok();
+                }
+            } else if let Some(caps) = ovpn_end.captures(&entry.log_entry) {
+                let key = (caps["user"].to_string(), caps["ip"].to_string());
+                if let Some(idx) = open.remove(&key) {
+                    sessions[idx].end = Some(ts);
+                }
+            } else if entry.daemon.starts_with("wireguard/") {
+                if let Some(caps) = wg_start.captures(&entry.log_entry) {
+                    let user = format!("{} peer {}", &entry.daemon["wireguard/".len()..], &caps["peer"]);
+                    let ip = caps["ip"].trim_matches(|c| c == '[' || c == ']').to_string();
+                    // Handshakes repeat every two minutes and peers roam, so only the
+                    // first handshake per peer opens a session
+                    if let Entry::Vacant(slot) = open.entry((user.clone(), String::new())) {
+                        slot.insert(sessions.len());
+                        sessions.push(VpnSession {
+                            source: "wireguard",
+                            user,
+                            ip,
+                            start: ts,
+                            end: None,
+                            bytes_received: None,
+                            bytes_sent: None,
+                        });
+                    }
+                } else if let Some(caps) = wg_end.captures(&entry.log_entry) {
+                    let user = format!("{} peer {}", &entry.daemon["wireguard/".len()..], &caps["peer"]);
+                    if let Some(idx) = open.remove(&(user, String::new())) {
+                        sessions[idx].end = Some(ts);
+                    }
+                }
+            }
+        }
+
+        Self { sessions }
+    }
+
+    pub fn sessions(&self) -> &[VpnSession] {
+        &self.sessions
+    }
+
+    fn format_duration(seconds: i64) -> String {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.sessions.is_empty() {
+            println!("No VPN sessions found");
+            return;
+        }
+
+        let open = self.sessions.iter().filter(|s| s.end.is_none()).count();
+        println!("VPN sessions: {}\tClosed: {}\tStill open: {}", self.sessions.len(), self.sessions.len() - open, open);
+        println!();
+
+        let mut users: HashMap<String, usize> = HashMap::new();
+        let mut ips: HashMap<String, usize> = HashMap::new();
+        for session in &self.sessions {
+            *users.entry(session.user.clone()).or_insert(0) += 1;
+            *ips.entry(session.ip.clone()).or_insert(0) += 1;
+        }
+        print_top("Sessions per user", users, limit);
+        print_top("Sessions per source IP", ips, limit);
+
+        // Users connecting from several addresses are worth a second look
+        let mut user_ips: HashMap<&str, Vec<&str>> = HashMap::new();
+        for session in &self.sessions {
+            let addresses = user_ips.entry(session.user.as_str()).or_default();
+            if !addresses.contains(&session.ip.as_str()) {
+                addresses.push(session.ip.as_str());
+            }
+        }
+        let mut roaming: Vec<_> = user_ips.into_iter().filter(|(_, ips)| ips.len() > 1).collect();
+        if !roaming.is_empty() {
+            roaming.sort();
+            println!("Users with multiple source IPs:");
+            for (user, addresses) in roaming.iter().take(limit) {
+                println!("{}:\t{}", user, addresses.join(", "));
+            }
+            println!();
+        }
+
+        println!("Sessions:");
+        for session in &self.sessions {
+            let end = session.end
+                .map(|e| e.format("%Y-%m-%dT%H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let duration = session.duration()
+                .map(Self::format_duration)
+                .unwrap_or_else(|| "open".to_string());
+            let bytes = match (session.bytes_received, session.bytes_sent) {
+                (Some(rx), Some(tx)) => format!("\trx {} tx {}", rx, tx),
+                _ => String::new(),
+            };
+            println!("{}\t{}\t{}\t{}\t{}\t{}{}",
+                session.start.format("%Y-%m-%dT%H:%M:%S"), end, duration,
+                session.source, session.user, session.ip, bytes);
+        }
+        println!();
+    }
+}
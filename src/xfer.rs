@@ -0,0 +1,100 @@
+use crate::log_entry::CrunchLog;
+use crate::report::print_top;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+/// One file transfer from an xferlog line
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub timestamp: NaiveDateTime,
+    pub client: String,
+    pub user: String,
+    pub file: String,
+    pub bytes: u64,
+    pub direction: String,
+    pub complete: bool,
+}
+
+pub struct TransferReport {
+    transfers: Vec<Transfer>,
+}
+
+impl TransferReport {
+    /// Collect transfers from xferlog entries (vsftpd, ProFTPD, wu-ftpd)
+    pub fn from_log(log: &CrunchLog) -> Self {
+        let transfers = log.entries.iter()
+            .filter_map(|entry| {
+                let attr = |key: &str| entry.attrs.get(key);
+                Some(Transfer {
+                    timestamp: entry.naive_datetime(),
+                    client: attr("client")?.clone(),
+                    user: attr("user")?.clone(),
+                    file: attr("file")?.clone(),
+                    bytes: attr("bytes")?.parse().ok()?,
+                    direction: attr("direction")?.clone(),
+                    complete: attr("status").map(|s| s == "complete").unwrap_or(true),
+                })
+            })
+            .collect();
+
+        Self { transfers }
+    }
+
+    pub fn transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+
+    fn format_bytes(bytes: u64) -> String {
+        let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < units.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{} B", bytes)
+        } else {
+            format!("{:.1} {}", value, units[unit])
+        }
+    }
+
+    pub fn display(&self, limit: usize) {
+        if self.transfers.is_empty() {
+            println!("No file transfers found");
+            return;
+        }
+
+        let count = |direction: &str| self.transfers.iter().filter(|t| t.direction == direction).count();
+        let incomplete = self.transfers.iter().filter(|t| !t.complete).count();
+        let total: u64 = self.transfers.iter().map(|t| t.bytes).sum();
+        println!("Transfers: {}\tDownloads: {}\tUploads: {}\tDeletes: {}\tIncomplete: {}\tBytes: {}",
+            self.transfers.len(), count("download"), count("upload"), count("delete"), incomplete, Self::format_bytes(total));
+        println!();
+
+        let mut files: HashMap<String, usize> = HashMap::new();
+        let mut users: HashMap<String, usize> = HashMap::new();
+        let mut incomplete_files: HashMap<String, usize> = HashMap::new();
+        let mut client_bytes: HashMap<String, u64> = HashMap::new();
+        for transfer in &self.transfers {
+            *files.entry(format!("{} {}", transfer.direction, transfer.file)).or_insert(0) += 1;
+            *users.entry(transfer.user.clone()).or_insert(0) += 1;
+            if !transfer.complete {
+                *incomplete_files.entry(transfer.file.clone()).or_insert(0) += 1;
+            }
+            *client_bytes.entry(transfer.client.clone()).or_insert(0) += transfer.bytes;
+        }
+        print_top("Top files", files, limit);
+        print_top("Transfers per user", users, limit);
+        print_top("Incomplete transfers", incomplete_files, limit);
+
+        // Byte totals don't fit print_top's counts, so they get their own section
+        let mut clients: Vec<_> = client_bytes.into_iter().collect();
+        clients.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        println!("Bytes per client:");
+        for (client, bytes) in clients.iter().take(limit) {
+            println!("{}:\t{}", Self::format_bytes(*bytes), client);
+        }
+        println!();
+    }
+}